@@ -0,0 +1,154 @@
+use std::{
+    fmt::{self, Debug},
+    fs::Metadata,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::file::{AlternativeFileNaming, CollidingFileBehaviour};
+
+
+/// Signature of a [`DirectoryCollisionResolver`] callback.
+type CollisionResolverCallback =
+    dyn FnMut(&DirectoryCollision) -> DirectoryCollisionDecision + Send;
+
+
+/// A file or symbolic link about to be copied (or moved) onto an existing destination entry,
+/// as passed to a [`DirectoryCollisionResolver`].
+#[derive(Clone, Copy, Debug)]
+pub struct DirectoryCollision<'c> {
+    /// Path of the source file or symbolic link.
+    pub source_path: &'c Path,
+
+    /// Metadata of the source entry.
+    ///
+    /// For files, this is the metadata of the file that would be copied,
+    /// i.e. symbolic links are followed. For symbolic links that would be recreated,
+    /// this is the metadata of the link itself.
+    pub source_metadata: &'c Metadata,
+
+    /// Path of the existing destination entry.
+    pub destination_path: &'c Path,
+
+    /// Metadata of the existing destination entry (symbolic links are not followed).
+    pub destination_metadata: &'c Metadata,
+}
+
+
+/// What to do with a colliding source entry, as decided by a [`DirectoryCollisionResolver`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DirectoryCollisionDecision {
+    /// The source entry is not copied, and the destination entry is left untouched.
+    Skip,
+
+    /// The destination entry is overwritten.
+    Overwrite,
+
+    /// The destination entry is kept, and the source entry is copied
+    /// to an alternative path next to it instead (see [`CollidingFileBehaviour::KeepBoth`]).
+    Rename {
+        /// How to name the alternative path.
+        naming: AlternativeFileNaming,
+    },
+
+    /// The copy (or move) is aborted before anything is copied.
+    Abort,
+
+    /// The provided behaviour is applied to this and all remaining collisions,
+    /// without calling the resolver again.
+    ///
+    /// Symbolic links are overwritten with [`CollidingFileBehaviour::OverwriteIfNewer`]
+    /// and [`CollidingFileBehaviour::OverwriteIfDifferent`].
+    ApplyToAll(CollidingFileBehaviour),
+}
+
+impl DirectoryCollisionDecision {
+    /// Returns the [`CollidingFileBehaviour`] this decision corresponds to.
+    pub(crate) fn to_colliding_file_behaviour(self) -> CollidingFileBehaviour {
+        match self {
+            Self::Skip => CollidingFileBehaviour::Skip,
+            Self::Overwrite => CollidingFileBehaviour::Overwrite,
+            Self::Rename { naming } => CollidingFileBehaviour::KeepBoth { naming },
+            Self::Abort => CollidingFileBehaviour::Abort,
+            Self::ApplyToAll(colliding_file_behaviour) => colliding_file_behaviour,
+        }
+    }
+}
+
+
+/// Decides, one collision at a time, what to do with files and symbolic links
+/// whose destination path already exists when copying or moving a directory.
+///
+/// This allows e.g. an interactive application to ask the user about every conflict,
+/// instead of deciding upfront with a single [`CollidingFileBehaviour`].
+///
+/// The resolver is called **while the copy is being prepared**, in the order
+/// the operations will be executed in, and before anything is copied; the resulting copy
+/// (or [`DirectoryCopyPlan`]) reflects its decisions. If the resolver returns
+/// [`DirectoryCollisionDecision::ApplyToAll`], it is not called again.
+///
+/// As the resolver is only ever called from the thread preparing the copy, one collision at a time,
+/// it can keep state between calls (it is a [`FnMut`]), and does not need to be [`Sync`].
+///
+/// When a resolver is set, it takes precedence over the `colliding_file_behaviour`
+/// of [`DestinationDirectoryRule::AllowNonEmpty`], which must still be used to allow the destination
+/// directory to be non-empty in the first place. Colliding subdirectories are still handled
+/// by its `colliding_subdirectory_behaviour`.
+///
+///
+/// # Example
+/// ```
+/// # use fs_more::directory::{DirectoryCollisionDecision, DirectoryCollisionResolver};
+/// let resolver = DirectoryCollisionResolver::new(|collision| {
+///     if collision.source_metadata.len() == collision.destination_metadata.len() {
+///         DirectoryCollisionDecision::Skip
+///     } else {
+///         DirectoryCollisionDecision::Overwrite
+///     }
+/// });
+/// ```
+///
+///
+/// [`DirectoryCopyPlan`]: super::DirectoryCopyPlan
+/// [`DestinationDirectoryRule::AllowNonEmpty`]: super::DestinationDirectoryRule::AllowNonEmpty
+#[derive(Clone)]
+pub struct DirectoryCollisionResolver {
+    callback: Arc<Mutex<CollisionResolverCallback>>,
+}
+
+impl DirectoryCollisionResolver {
+    /// Creates a resolver that calls `callback` on each collision.
+    ///
+    /// Clones of the resolver share the same `callback` (and its state).
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: FnMut(&DirectoryCollision) -> DirectoryCollisionDecision + Send + 'static,
+    {
+        Self {
+            callback: Arc::new(Mutex::new(callback)),
+        }
+    }
+
+    /// Asks the resolver what to do about the `collision`.
+    pub(crate) fn resolve(&self, collision: &DirectoryCollision) -> DirectoryCollisionDecision {
+        let mut callback = self.callback.lock().unwrap_or_else(PoisonError::into_inner);
+
+        (*callback)(collision)
+    }
+}
+
+impl PartialEq for DirectoryCollisionResolver {
+    /// Two resolvers are equal if they share the same callback.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.callback, &other.callback)
+    }
+}
+
+impl Eq for DirectoryCollisionResolver {}
+
+impl Debug for DirectoryCollisionResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirectoryCollisionResolver")
+            .finish_non_exhaustive()
+    }
+}
@@ -114,6 +114,12 @@ pub enum DestinationDirectoryRule {
         ///
         /// This option has no effect on existing destination files
         /// that don't collide with the ones we're copying or moving.
+        ///
+        /// With [`CollidingFileBehaviour::OverwriteIfNewer`], [`CollidingFileBehaviour::OverwriteIfDifferent`]
+        /// and [`CollidingFileBehaviour::KeepBoth`], colliding files are resolved while preparing
+        /// the copy, before anything is copied. Colliding symbolic links are kept with
+        /// [`CollidingFileBehaviour::KeepBoth`] (the link is created at an alternative path),
+        /// and overwritten with the conditional behaviours.
         colliding_file_behaviour: CollidingFileBehaviour,
 
         /// How to behave when encountering existing (colliding) destination subdirectories.
@@ -132,16 +138,65 @@ impl Default for DestinationDirectoryRule {
 }
 
 impl DestinationDirectoryRule {
+    /// Returns `true` if existing destination files (and symbolic links) may be overwritten.
+    ///
+    /// For the conditional and keep-both behaviours, this is only the case because colliding
+    /// file copies are resolved when planning the copy (see `resolve_collisions_with_rule`).
     pub(crate) fn allows_overwriting_existing_destination_files(&self) -> bool {
         matches!(
             self,
             Self::AllowNonEmpty {
-                colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+                colliding_file_behaviour: CollidingFileBehaviour::Overwrite
+                    | CollidingFileBehaviour::OverwriteIfNewer
+                    | CollidingFileBehaviour::OverwriteIfDifferent
+                    | CollidingFileBehaviour::KeepBoth { .. },
                 ..
             }
         )
     }
 
+    /// Returns the [`CollidingFileBehaviour`] to use when copying individual files under this rule.
+    ///
+    /// Colliding file copies have already been resolved while planning the copy
+    /// for the conditional and keep-both behaviours, so any remaining file copies simply overwrite.
+    pub(crate) fn colliding_file_behaviour(&self) -> CollidingFileBehaviour {
+        match self {
+            Self::DisallowExisting | Self::AllowEmpty => CollidingFileBehaviour::Abort,
+            Self::AllowNonEmpty {
+                colliding_file_behaviour:
+                    CollidingFileBehaviour::OverwriteIfNewer
+                    | CollidingFileBehaviour::OverwriteIfDifferent
+                    | CollidingFileBehaviour::KeepBoth { .. },
+                ..
+            } => CollidingFileBehaviour::Overwrite,
+            Self::AllowNonEmpty {
+                colliding_file_behaviour,
+                ..
+            } => *colliding_file_behaviour,
+        }
+    }
+
+    /// Returns the rule to prepare and execute a copy with when a [`DirectoryCollisionResolver`] is set.
+    ///
+    /// The resolver decides about each colliding file before anything is copied,
+    /// so a non-empty destination directory allows overwriting any files that remain colliding.
+    /// Other rules are returned unchanged.
+    ///
+    ///
+    /// [`DirectoryCollisionResolver`]: super::DirectoryCollisionResolver
+    pub(crate) fn for_collision_resolver(self) -> Self {
+        match self {
+            Self::AllowNonEmpty {
+                colliding_subdirectory_behaviour,
+                ..
+            } => Self::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+                colliding_subdirectory_behaviour,
+            },
+            other_rule => other_rule,
+        }
+    }
+
     pub(crate) fn allows_existing_destination_subdirectories(&self) -> bool {
         matches!(
             self,
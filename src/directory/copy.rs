@@ -1,21 +1,52 @@
-use std::path::{Path, PathBuf};
+use std::{
+    mem,
+    path::{Path, PathBuf},
+};
 
 use_enabled_fs_module!();
 
 use super::{
+    collision::DirectoryCollisionResolver,
     common::DestinationDirectoryRule,
+    filter::DirectoryEntryFilter,
+    parallel::{
+        copy_directory_in_parallel_with_journal,
+        execute_prepared_copy_directory_with_progress_in_parallel,
+        DirectoryCopyParallelism,
+    },
     prepared::{try_exists_without_follow, DirectoryCopyPrepared, QueuedOperation},
+    resume::{DirectoryCopyResumeJournal, ResumeJournalEntry},
+    rollback::{DirectoryCopyJournal, RollbackBehaviour},
 };
 use crate::{
-    error::{CopyDirectoryError, CopyDirectoryExecutionError},
+    error::{
+        CopyDirectoryError,
+        CopyDirectoryExecutionError,
+        CopyDirectoryPreparationError,
+        FileError,
+    },
     file::{
+        apply_preserved_metadata,
+        apply_preserved_ownership,
+        copy_extended_attributes,
         copy_file,
         copy_file_with_progress,
+        sync_directory_to_disk,
         CollidingFileBehaviour,
+        CopyVerification,
+        DurabilityLevel,
+        ExtendedAttributeCopyError,
+        ExtendedAttributeFilter,
         FileCopyOptions,
         FileCopyWithProgressOptions,
         FileProgress,
+        FileProgressPhase,
+        MetadataPreservation,
+        ReflinkBehaviour,
+        ResumeBehaviour,
+        SparseFileBehaviour,
     },
+    ProgressAction,
     DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
     DEFAULT_READ_BUFFER_SIZE,
     DEFAULT_WRITE_BUFFER_SIZE,
@@ -120,8 +151,40 @@ pub enum BrokenSymlinkBehaviour {
 
 
 
-/// Options that influence the [`copy_directory`] function.
+/// How to behave when encountering hard-linked files during directory copies or moves.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HardLinkBehaviour {
+    /// Indicates that each hard link should be copied as an independent file,
+    /// i.e. files that share their contents on the source will not share them on the destination.
+    ///
+    /// This is the default.
+    CopySeparately,
+
+    /// Indicates that hard links between files inside the source directory
+    /// should be recreated on the destination.
+    ///
+    /// While planning the copy, files are identified by their device and inode number.
+    /// Only the first file of each set of hard-linked files is copied; the others
+    /// are then created as hard links to its copy, which means they do not count towards
+    /// the bytes to be copied. Hard links to files outside of the source directory
+    /// can not be preserved, so such files are copied as usual.
+    ///
+    /// Hard links are only detected on Unix; on other platforms, this has the same effect
+    /// as [`HardLinkBehaviour::CopySeparately`].
+    Preserve,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for HardLinkBehaviour {
+    fn default() -> Self {
+        Self::CopySeparately
+    }
+}
+
+
+
+/// Options that influence the [`copy_directory`] function.
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct DirectoryCopyOptions {
     /// Specifies whether you allow the destination directory to exist before copying
     /// and whether it must be empty or not.
@@ -139,27 +202,153 @@ pub struct DirectoryCopyOptions {
 
     /// Sets the behaviour for broken symbolic links when copying a directory.
     pub broken_symlink_behaviour: BrokenSymlinkBehaviour,
+
+    /// Sets whether changes made to the destination are undone if the copy fails midway.
+    ///
+    /// See [`RollbackBehaviour`] for more details.
+    pub rollback_behaviour: RollbackBehaviour,
+
+    /// Decides which entries of the source directory are copied.
+    ///
+    /// Skipped entries are not counted towards the total size of the copy,
+    /// and skipped directories are not descended into.
+    /// See [`DirectoryEntryFilter`] for more details.
+    pub entry_filter: DirectoryEntryFilter,
+
+    /// Sets whether files are copied on multiple threads.
+    ///
+    /// See [`DirectoryCopyParallelism`] for more details.
+    pub parallelism: DirectoryCopyParallelism,
+
+    /// Sets whether files are cloned (i.e. whether copy-on-write reflinks are created)
+    /// instead of having their contents copied.
+    ///
+    /// See [`ReflinkBehaviour`] for more details.
+    pub reflink_behaviour: ReflinkBehaviour,
+
+    /// Which metadata of the source files and directories to apply to their copies.
+    ///
+    /// Directory metadata is applied only after the entire copy has finished,
+    /// so that copying the contents of a directory does not change its timestamps.
+    /// See [`MetadataPreservation`] for more details.
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Which extended attributes of the source files, directories and symbolic links
+    /// to copy to their copies.
+    ///
+    /// See [`ExtendedAttributeFilter`] for more details.
+    pub extended_attributes: ExtendedAttributeFilter,
+
+    /// Whether each copied file is read back and compared to the hash of its source file.
+    ///
+    /// A file that fails verification stops the copy with a [`FileCopyError`] containing
+    /// [`FileError::VerificationFailed`]. See [`CopyVerification`] for more details.
+    ///
+    ///
+    /// [`FileCopyError`]: CopyDirectoryExecutionError::FileCopyError
+    /// [`FileError::VerificationFailed`]: crate::error::FileError::VerificationFailed
+    pub verify: CopyVerification,
+
+    /// Path of a journal file that makes the copy resumable.
+    ///
+    /// If set, every executed operation is recorded in this file. If the copy is interrupted
+    /// (e.g. by an error or a crash), calling the copy again with the same source, destination
+    /// and journal path resumes it: operations whose results are still present on the destination
+    /// are skipped, and a partially copied file is resumed where it stopped
+    /// (see [`ResumeBehaviour::VerifySampled`]). Entries created by the interrupted copy
+    /// do not count as collisions with the [`destination_directory_rule`].
+    /// The journal is removed once the copy has completed successfully.
+    ///
+    /// Each record is synced to disk as soon as it is written, and copied files are synced to disk
+    /// before they are recorded (regardless of the [`durability`] level), so the journal remains
+    /// accurate even after a power loss.
+    ///
+    /// Skipped operations count towards the totals returned in [`DirectoryCopyFinished`].
+    /// The journal should not be placed inside the source or destination directory.
+    ///
+    /// Planned copies can not be resumed: [`plan_directory_copy`] rejects options with a journal path
+    /// with [`CopyDirectoryPreparationError::ResumeJournalNotSupportedForPlans`].
+    ///
+    ///
+    /// [`destination_directory_rule`]: Self::destination_directory_rule
+    /// [`durability`]: Self::durability
+    /// [`plan_directory_copy`]: super::plan_directory_copy
+    pub resume_journal_path: Option<PathBuf>,
+
+    /// Whether each file is copied atomically, i.e. into a hidden temporary file
+    /// that is renamed over the destination file once it has been fully written.
+    ///
+    /// Files that are copied atomically are never left partially copied, so they are never resumed.
+    /// See [`FileCopyOptions::atomic`] for more details.
+    pub atomic: bool,
+
+    /// How much of the copy to sync to disk before returning.
+    ///
+    /// With [`DurabilityLevel::FilesAndDirectories`], every destination directory that an entry
+    /// has been copied into is synced once the entire copy has finished.
+    /// See [`DurabilityLevel`] for more details.
+    pub durability: DurabilityLevel,
+
+    /// Decides what to do with each file or symbolic link whose destination path already exists.
+    ///
+    /// When set, the resolver is asked about every colliding entry while the copy is being prepared,
+    /// instead of applying the `colliding_file_behaviour` of the [`destination_directory_rule`].
+    /// See [`DirectoryCollisionResolver`] for more details.
+    ///
+    ///
+    /// [`destination_directory_rule`]: Self::destination_directory_rule
+    pub collision_resolver: Option<DirectoryCollisionResolver>,
+
+    /// Whether hard links between files inside the source directory are recreated
+    /// on the destination, instead of copying each of them as an independent file.
+    ///
+    /// See [`HardLinkBehaviour`] for more details.
+    pub hard_link_behaviour: HardLinkBehaviour,
 }
 
 impl Default for DirectoryCopyOptions {
     /// Constructs defaults for copying a directory, which are:
     /// - [`DestinationDirectoryRule::AllowEmpty`]: if the destination directory already exists, it must be empty,
     /// - [`DirectoryCopyDepthLimit::Unlimited`]: there is no copy depth limit,
-    /// - [`SymlinkBehaviour::Keep`]: symbolic links are not followed, and
-    /// - [`BrokenSymlinkBehaviour::Keep`]: broken symbolic links are kept as-is, i.e. broken.
+    /// - [`SymlinkBehaviour::Keep`]: symbolic links are not followed,
+    /// - [`BrokenSymlinkBehaviour::Keep`]: broken symbolic links are kept as-is, i.e. broken,
+    /// - [`RollbackBehaviour::Disabled`]: a failed copy is not rolled back,
+    /// - [`DirectoryEntryFilter::new`]: all entries are copied,
+    /// - [`DirectoryCopyParallelism::Sequential`]: everything is copied on the calling thread,
+    /// - [`ReflinkBehaviour::Never`]: files are never cloned,
+    /// - [`MetadataPreservation::NONE`]: no metadata is preserved,
+    /// - [`ExtendedAttributeFilter::NONE`]: no extended attributes are copied,
+    /// - [`CopyVerification::DISABLED`]: copied files are not verified,
+    /// - no resume journal: an interrupted copy can not be resumed,
+    /// - files are written directly into their destination files (they are not copied atomically),
+    /// - [`DurabilityLevel::None`]: nothing is synced to disk,
+    /// - no collision resolver: collisions are handled by the `destination_directory_rule`, and
+    /// - [`HardLinkBehaviour::CopySeparately`]: hard-linked files are copied as independent files.
     fn default() -> Self {
         Self {
             destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
             copy_depth_limit: DirectoryCopyDepthLimit::Unlimited,
             symlink_behaviour: SymlinkBehaviour::Keep,
             broken_symlink_behaviour: BrokenSymlinkBehaviour::Keep,
+            rollback_behaviour: RollbackBehaviour::Disabled,
+            entry_filter: DirectoryEntryFilter::new(),
+            parallelism: DirectoryCopyParallelism::Sequential,
+            reflink_behaviour: ReflinkBehaviour::Never,
+            metadata_preservation: MetadataPreservation::NONE,
+            extended_attributes: ExtendedAttributeFilter::NONE,
+            verify: CopyVerification::DISABLED,
+            resume_journal_path: None,
+            atomic: false,
+            durability: DurabilityLevel::None,
+            collision_resolver: None,
+            hard_link_behaviour: HardLinkBehaviour::CopySeparately,
         }
     }
 }
 
 
 /// Describes a successful directory copy operation.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct DirectoryCopyFinished {
     /// Total number of bytes copied.
     pub total_bytes_copied: u64,
@@ -175,215 +364,545 @@ pub struct DirectoryCopyFinished {
 
     /// Total number of directories created.
     pub directories_created: usize,
+
+    /// Paths of the files (and symbolic links) that were copied to alternative paths next to
+    /// existing destination entries, because [`CollidingFileBehaviour::KeepBoth`] was used
+    /// or a [`DirectoryCollisionResolver`] decided to rename them.
+    ///
+    /// These files are also counted in `files_copied`.
+    ///
+    /// Note that when a copy is resumed (see [`DirectoryCopyOptions::resume_journal_path`]),
+    /// this only includes files copied after resuming.
+    pub files_copied_to_alternative_paths: Vec<PathBuf>,
+
+    /// Total number of hard links created.
+    ///
+    /// Unless the [`DirectoryCopyOptions::hard_link_behaviour`] option is set to
+    /// [`HardLinkBehaviour::Preserve`], this will always be `0`. Files that are created
+    /// as hard links are not counted in `files_copied`.
+    pub hard_links_created: usize,
 }
 
 
 
 /// Perform a copy using prepared data from [`DirectoryCopyPrepared`].
 ///
+/// If rollback is enabled in `options`, changes are undone if the copy fails.
+///
 /// For more details, see [`copy_directory`].
 pub(crate) fn copy_directory_unchecked(
-    prepared_directory_copy: DirectoryCopyPrepared,
+    mut prepared_directory_copy: DirectoryCopyPrepared,
     options: DirectoryCopyOptions,
 ) -> Result<DirectoryCopyFinished, CopyDirectoryExecutionError> {
-    let can_overwrite_files = options
-        .destination_directory_rule
-        .allows_overwriting_existing_destination_files();
+    let mut journal = DirectoryCopyJournal::new(
+        options.rollback_behaviour,
+        prepared_directory_copy.resume_journal.clone(),
+    );
 
-    let can_ignore_existing_sub_directories = options
-        .destination_directory_rule
-        .allows_existing_destination_subdirectories();
+    let entries_to_finish = CreatedEntriesToFinish::collect(
+        &prepared_directory_copy,
+        options.metadata_preservation,
+        options.extended_attributes,
+    );
+
+    let directories_to_sync =
+        DirectoriesToSync::collect(&prepared_directory_copy, options.durability);
+
+    let files_copied_to_alternative_paths =
+        mem::take(&mut prepared_directory_copy.alternative_destination_file_paths);
+
+    let copy_result = match options.parallelism {
+        DirectoryCopyParallelism::Sequential => {
+            copy_directory_unchecked_with_journal(prepared_directory_copy, options, &mut journal)
+        }
+        DirectoryCopyParallelism::Parallel { worker_threads } => {
+            copy_directory_in_parallel_with_journal(
+                prepared_directory_copy,
+                &options,
+                worker_threads,
+                &mut journal,
+            )
+        }
+    }
+    .and_then(|finished_copy| {
+        entries_to_finish.finish()?;
+        directories_to_sync.sync()?;
+
+        Ok(DirectoryCopyFinished {
+            files_copied_to_alternative_paths,
+            ..finished_copy
+        })
+    });
+
+    match copy_result {
+        Ok(finished_copy) => {
+            journal.finish();
+            Ok(finished_copy)
+        }
+        Err(error) => Err(journal.roll_back_after_error(error)),
+    }
+}
+
+
+/// A directory or symbolic link created by a directory copy.
+struct CreatedEntryToFinish {
+    source_path: PathBuf,
+    destination_path: PathBuf,
+    is_symlink: bool,
+}
+
+/// Directories and symbolic links created by a directory copy, whose extended attributes
+/// and metadata are applied only once everything has been copied.
+///
+/// Writing into a directory updates its timestamps (and read-only permissions would prevent
+/// writing into it at all), so this can't be done when the directories are created.
+struct CreatedEntriesToFinish {
+    entries: Vec<CreatedEntryToFinish>,
+    metadata_preservation: MetadataPreservation,
+    extended_attributes: ExtendedAttributeFilter,
+}
+
+impl CreatedEntriesToFinish {
+    /// Collects the directories (and, if extended attributes are to be copied,
+    /// the symbolic links) the copy will create, including the ones created
+    /// by a previous copy that is being resumed.
+    fn collect(
+        prepared_copy: &DirectoryCopyPrepared,
+        metadata_preservation: MetadataPreservation,
+        extended_attributes: ExtendedAttributeFilter,
+    ) -> Self {
+        let finish_directories = !metadata_preservation.is_none() || !extended_attributes.is_none();
+        let finish_symlinks = !extended_attributes.is_none();
+
+        let entries = prepared_copy
+            .resumed_operations
+            .iter()
+            .chain(&prepared_copy.operation_queue)
+            .filter_map(|operation| match operation {
+                QueuedOperation::CreateDirectory {
+                    source_directory_path,
+                    destination_directory_path,
+                    ..
+                } if finish_directories => Some(CreatedEntryToFinish {
+                    source_path: source_directory_path.clone(),
+                    destination_path: destination_directory_path.clone(),
+                    is_symlink: false,
+                }),
+                QueuedOperation::CreateSymlink {
+                    source_symlink_path,
+                    symlink_path,
+                    ..
+                } if finish_symlinks => Some(CreatedEntryToFinish {
+                    source_path: source_symlink_path.clone(),
+                    destination_path: symlink_path.clone(),
+                    is_symlink: true,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            entries,
+            metadata_preservation,
+            extended_attributes,
+        }
+    }
+
+    /// Copies the requested extended attributes and applies the requested metadata
+    /// to the collected entries. Symbolic links never have their metadata changed.
+    ///
+    /// Directories are queued with parents before their children, so we go through them in reverse,
+    /// finishing each directory only after all of its subdirectories.
+    fn finish(&self) -> Result<(), CopyDirectoryExecutionError> {
+        for entry in self.entries.iter().rev() {
+            let map_metadata_error =
+                |error| CopyDirectoryExecutionError::UnableToPreserveMetadata {
+                    directory_path: entry.destination_path.clone(),
+                    error,
+                };
+
+            // Ownership must be applied before extended attributes are copied,
+            // as changing it would remove file capabilities (see `apply_preserved_ownership`).
+            if !entry.is_symlink {
+                apply_preserved_ownership(
+                    &entry.source_path,
+                    &entry.destination_path,
+                    self.metadata_preservation,
+                )
+                .map_err(map_metadata_error)?;
+            }
+
+            copy_extended_attributes(
+                &entry.source_path,
+                &entry.destination_path,
+                self.extended_attributes,
+            )
+            .map_err(
+                |ExtendedAttributeCopyError {
+                     attribute_name,
+                     error,
+                 }| {
+                    CopyDirectoryExecutionError::UnableToCopyExtendedAttribute {
+                        path: entry.destination_path.clone(),
+                        attribute_name,
+                        error,
+                    }
+                },
+            )?;
+
+            if entry.is_symlink {
+                continue;
+            }
 
+            apply_preserved_metadata(
+                &entry.source_path,
+                &entry.destination_path,
+                self.metadata_preservation,
+            )
+            .map_err(map_metadata_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Destination directories whose entries are changed by a directory copy,
+/// which are synced to disk once everything has been copied (see [`DurabilityLevel`]).
+///
+/// Syncing each directory once at the end (instead of after every entry) keeps
+/// the number of syncs proportional to the number of directories.
+struct DirectoriesToSync {
+    directory_paths: Vec<PathBuf>,
+}
+
+impl DirectoriesToSync {
+    /// Collects the directories containing the entries the copy will create,
+    /// or nothing if `durability` does not require syncing directories.
+    fn collect(prepared_copy: &DirectoryCopyPrepared, durability: DurabilityLevel) -> Self {
+        if !durability.syncs_directories() {
+            return Self {
+                directory_paths: Vec::new(),
+            };
+        }
+
+        let mut directory_paths: Vec<PathBuf> = prepared_copy
+            .resumed_operations
+            .iter()
+            .chain(&prepared_copy.operation_queue)
+            .filter_map(|operation| operation.destination_path().parent().map(Path::to_path_buf))
+            .collect();
+
+        directory_paths.sort_unstable();
+        directory_paths.dedup();
+
+        Self { directory_paths }
+    }
 
+    /// Syncs the collected directories to disk.
+    fn sync(&self) -> Result<(), CopyDirectoryExecutionError> {
+        for directory_path in &self.directory_paths {
+            sync_directory_to_disk(directory_path).map_err(|error| {
+                CopyDirectoryExecutionError::UnableToSyncDirectory {
+                    directory_path: directory_path.clone(),
+                    error,
+                }
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Perform a copy using prepared data from [`DirectoryCopyPrepared`],
+/// recording every change to the destination in the provided `journal`.
+fn copy_directory_unchecked_with_journal(
+    prepared_directory_copy: DirectoryCopyPrepared,
+    options: DirectoryCopyOptions,
+    journal: &mut DirectoryCopyJournal,
+) -> Result<DirectoryCopyFinished, CopyDirectoryExecutionError> {
     // We have the entire queue of operations, and we've made sure there are
     // no collisions we should worry about. What's left is performing the file copy
     // and directory creation operations *precisely in the order they have been prepared*.
     // If we ignore the order, we could get into situations where
     // some destination directory doesn't exist yet, but we would try to copy a file into it.
 
-    let mut total_bytes_copied = 0;
-
-    let mut num_files_copied = 0;
-    let mut num_symlinks_recreated = 0;
-    let mut num_directories_created = 0;
+    let mut finished_copy = prepared_directory_copy.resumed_copy();
 
 
     // Execute all queued operations. This means copying files, creating symbolic links,
     // and creating directories in the order the queue specifies.
     for operation in prepared_directory_copy.operation_queue {
-        match operation {
-            QueuedOperation::CopyFile {
-                source_file_path,
-                source_size_bytes,
-                destination_file_path,
-            } => {
-                let destination_file_exists = try_exists_without_follow(&destination_file_path)
-                    .map_err(|error| CopyDirectoryExecutionError::UnableToAccessDestination {
-                        path: destination_file_path.clone(),
-                        error,
-                    })?;
-
-                if destination_file_exists {
-                    let destination_file_metadata = fs::symlink_metadata(&destination_file_path)
-                        .map_err(|error| {
-                            CopyDirectoryExecutionError::UnableToAccessDestination {
-                                path: destination_file_path.clone(),
-                                error,
-                            }
-                        })?;
-
-
-                    if !destination_file_metadata.is_file() {
-                        return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
-                            path: destination_file_path.clone(),
-                        });
-                    }
+        execute_queued_operation(operation, &options, journal, &mut finished_copy)?;
+    }
 
-                    if !can_overwrite_files {
-                        return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
-                            path: destination_file_path.clone(),
-                        });
-                    }
-                }
 
+    Ok(finished_copy)
+}
 
-                copy_file(
-                    source_file_path,
-                    &destination_file_path,
-                    FileCopyOptions {
-                        colliding_file_behaviour: match can_overwrite_files {
-                            true => CollidingFileBehaviour::Overwrite,
-                            false => CollidingFileBehaviour::Abort,
-                        },
-                    },
-                )
-                .map_err(|file_error| {
-                    CopyDirectoryExecutionError::FileCopyError {
-                        file_path: destination_file_path,
-                        error: file_error,
-                    }
+
+/// Executes a single queued operation of a directory copy (without progress reporting),
+/// recording the change in `journal` and updating the counters in `finished_copy`.
+pub(super) fn execute_queued_operation(
+    operation: QueuedOperation,
+    options: &DirectoryCopyOptions,
+    journal: &mut DirectoryCopyJournal,
+    finished_copy: &mut DirectoryCopyFinished,
+) -> Result<(), CopyDirectoryExecutionError> {
+    let can_overwrite_files = options
+        .destination_directory_rule
+        .allows_overwriting_existing_destination_files();
+
+    let can_ignore_existing_sub_directories = options
+        .destination_directory_rule
+        .allows_existing_destination_subdirectories();
+
+
+    match operation {
+        QueuedOperation::CopyFile {
+            source_file_path,
+            source_size_bytes,
+            destination_file_path,
+            source_hard_link_identity: _,
+        } => {
+            let can_overwrite_file =
+                can_overwrite_files || journal.is_left_over_file(&destination_file_path);
+
+            let destination_file_exists =
+                check_destination_file_before_copy(&destination_file_path, can_overwrite_file)?;
+
+            journal
+                .prepare_file_write(&destination_file_path, destination_file_exists)
+                .map_err(|error| CopyDirectoryExecutionError::UnableToAccessDestination {
+                    path: destination_file_path.clone(),
+                    error,
                 })?;
 
+            journal.persist(ResumeJournalEntry::CopyingFile(&destination_file_path))?;
+
+
+            let colliding_file_behaviour = match can_overwrite_file {
+                true => CollidingFileBehaviour::Overwrite,
+                false => CollidingFileBehaviour::Abort,
+            };
+
+            let resume_behaviour =
+                journal.resume_behaviour_for_file(&destination_file_path, destination_file_exists);
+
+            // Only resuming a partially copied file requires a copy with progress.
+            let file_copy_result =
+                if destination_file_exists && resume_behaviour != ResumeBehaviour::Never {
+                    copy_file_with_progress(
+                        source_file_path,
+                        &destination_file_path,
+                        FileCopyWithProgressOptions {
+                            colliding_file_behaviour,
+                            reflink_behaviour: options.reflink_behaviour,
+                            metadata_preservation: options.metadata_preservation,
+                            extended_attributes: options.extended_attributes,
+                            verify: options.verify,
+                            resume_behaviour,
+                            atomic: options.atomic,
+                            durability: journal.durability_for_file(options.durability),
+                            ..Default::default()
+                        },
+                        |_| {},
+                    )
+                    .map(|_| ())
+                } else {
+                    copy_file(
+                        source_file_path,
+                        &destination_file_path,
+                        FileCopyOptions {
+                            colliding_file_behaviour,
+                            reflink_behaviour: options.reflink_behaviour,
+                            metadata_preservation: options.metadata_preservation,
+                            extended_attributes: options.extended_attributes,
+                            verify: options.verify,
+                            atomic: options.atomic,
+                            durability: journal.durability_for_file(options.durability),
+                        },
+                    )
+                    .map(|_| ())
+                };
 
-                num_files_copied += 1;
-                total_bytes_copied += source_size_bytes;
-            }
+            file_copy_result.map_err(|file_error| CopyDirectoryExecutionError::FileCopyError {
+                file_path: destination_file_path.clone(),
+                error: file_error,
+            })?;
 
-            QueuedOperation::CreateDirectory {
-                source_size_bytes,
-                destination_directory_path,
-                create_parent_directories,
-            } => {
-                let destination_directory_exists =
-                    try_exists_without_follow(&destination_directory_path).map_err(|error| {
-                        CopyDirectoryExecutionError::UnableToAccessDestination {
-                            path: destination_directory_path.clone(),
-                            error,
-                        }
-                    })?;
+            journal.persist(ResumeJournalEntry::CopiedFile(&destination_file_path))?;
 
 
-                if destination_directory_exists {
-                    if !destination_directory_path.is_dir() {
-                        return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
-                            path: destination_directory_path.clone(),
-                        });
-                    }
+            finished_copy.files_copied += 1;
+            finished_copy.total_bytes_copied += source_size_bytes;
+        }
 
-                    if !can_ignore_existing_sub_directories {
-                        return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
-                            path: destination_directory_path.clone(),
-                        });
+        QueuedOperation::CreateDirectory {
+            source_directory_path: _,
+            source_size_bytes,
+            destination_directory_path,
+            create_parent_directories,
+        } => {
+            let destination_directory_exists =
+                try_exists_without_follow(&destination_directory_path).map_err(|error| {
+                    CopyDirectoryExecutionError::UnableToAccessDestination {
+                        path: destination_directory_path.clone(),
+                        error,
                     }
-
-                    continue;
-                }
+                })?;
 
 
-                if create_parent_directories {
-                    fs::create_dir_all(&destination_directory_path).map_err(|error| {
-                        CopyDirectoryExecutionError::UnableToCreateDirectory {
-                            directory_path: destination_directory_path,
-                            error,
-                        }
-                    })?;
-                } else {
-                    fs::create_dir(&destination_directory_path).map_err(|error| {
-                        CopyDirectoryExecutionError::UnableToCreateDirectory {
-                            directory_path: destination_directory_path,
-                            error,
-                        }
-                    })?;
+            if destination_directory_exists {
+                if !destination_directory_path.is_dir() {
+                    return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
+                        path: destination_directory_path.clone(),
+                    });
                 }
 
+                if !can_ignore_existing_sub_directories {
+                    return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
+                        path: destination_directory_path.clone(),
+                    });
+                }
 
-                num_directories_created += 1;
-                total_bytes_copied += source_size_bytes;
+                return Ok(());
             }
 
-            #[cfg(windows)]
-            QueuedOperation::CreateSymlink {
-                symlink_path,
-                symlink_destination_type: symlink_type,
-                source_symlink_size_bytes,
-                symlink_destination_path,
-            } => {
-                use crate::directory::prepared::SymlinkType;
-
-                match symlink_type {
-                    SymlinkType::File => {
-                        std::os::windows::fs::symlink_file(
-                            &symlink_destination_path,
-                            &symlink_path,
-                        )
-                        .map_err(|error| {
-                            CopyDirectoryExecutionError::SymlinkCreationError {
-                                symlink_path: symlink_path.clone(),
-                                error,
-                            }
-                        })?;
+
+            journal
+                .record_directory_creation(&destination_directory_path, create_parent_directories)
+                .map_err(|error| CopyDirectoryExecutionError::UnableToAccessDestination {
+                    path: destination_directory_path.clone(),
+                    error,
+                })?;
+
+            journal.persist(ResumeJournalEntry::CreatingDirectory(&destination_directory_path))?;
+
+            if create_parent_directories {
+                fs::create_dir_all(&destination_directory_path).map_err(|error| {
+                    CopyDirectoryExecutionError::UnableToCreateDirectory {
+                        directory_path: destination_directory_path,
+                        error,
                     }
-                    SymlinkType::Directory => {
-                        std::os::windows::fs::symlink_dir(&symlink_destination_path, &symlink_path)
-                            .map_err(|error| CopyDirectoryExecutionError::SymlinkCreationError {
-                                symlink_path: symlink_path.clone(),
-                                error,
-                            })?;
+                })?;
+            } else {
+                fs::create_dir(&destination_directory_path).map_err(|error| {
+                    CopyDirectoryExecutionError::UnableToCreateDirectory {
+                        directory_path: destination_directory_path,
+                        error,
                     }
-                }
+                })?;
+            }
 
 
-                num_symlinks_recreated += 1;
-                total_bytes_copied += source_symlink_size_bytes;
-            }
+            finished_copy.directories_created += 1;
+            finished_copy.total_bytes_copied += source_size_bytes;
+        }
 
-            #[cfg(unix)]
-            QueuedOperation::CreateSymlink {
-                symlink_path,
-                source_symlink_size_bytes,
-                symlink_destination_path,
-            } => {
-                std::os::unix::fs::symlink(&symlink_destination_path, &symlink_path).map_err(
-                    |error| CopyDirectoryExecutionError::SymlinkCreationError {
-                        symlink_path: symlink_path.clone(),
-                        error,
-                    },
-                )?;
+        #[cfg(windows)]
+        QueuedOperation::CreateSymlink {
+            source_symlink_path: _,
+            symlink_path,
+            symlink_destination_type: symlink_type,
+            source_symlink_size_bytes,
+            symlink_destination_path,
+        } => {
+            create_symlink_and_record_in_journal(
+                &SymlinkCreationInfo {
+                    symlink_path,
+                    symlink_destination_path,
+                    symlink_type,
+                    unfollowed_symlink_file_size_bytes: source_symlink_size_bytes,
+                },
+                journal,
+            )?;
+
+            finished_copy.symlinks_created += 1;
+            finished_copy.total_bytes_copied += source_symlink_size_bytes;
+        }
+
+        #[cfg(unix)]
+        QueuedOperation::CreateSymlink {
+            source_symlink_path: _,
+            symlink_path,
+            source_symlink_size_bytes,
+            symlink_destination_path,
+        } => {
+            create_symlink_and_record_in_journal(
+                &SymlinkCreationInfo {
+                    symlink_path,
+                    symlink_destination_path,
+                    unfollowed_symlink_file_size_bytes: source_symlink_size_bytes,
+                },
+                journal,
+            )?;
+
+            finished_copy.symlinks_created += 1;
+            finished_copy.total_bytes_copied += source_symlink_size_bytes;
+        }
+
+        QueuedOperation::CreateHardLink {
+            source_file_path: _,
+            hard_link_path,
+            target_path,
+        } => {
+            let can_overwrite_file =
+                can_overwrite_files || journal.is_left_over_file(&hard_link_path);
+
+            create_hard_link_and_record_in_journal(
+                &hard_link_path,
+                &target_path,
+                can_overwrite_file,
+                journal,
+            )?;
+
+            finished_copy.hard_links_created += 1;
+        }
+    };
+
+    Ok(())
+}
 
 
-                num_symlinks_recreated += 1;
-                total_bytes_copied += source_symlink_size_bytes;
+/// Checks whether a file can be copied to `destination_file_path`,
+/// returning `true` if a file already exists there (and may be overwritten).
+///
+/// Returns an error if the destination exists, but is not a file,
+/// or if it is a file, but `can_overwrite_files` is `false`.
+pub(super) fn check_destination_file_before_copy(
+    destination_file_path: &Path,
+    can_overwrite_files: bool,
+) -> Result<bool, CopyDirectoryExecutionError> {
+    let destination_file_exists =
+        try_exists_without_follow(destination_file_path).map_err(|error| {
+            CopyDirectoryExecutionError::UnableToAccessDestination {
+                path: destination_file_path.to_path_buf(),
+                error,
             }
-        };
+        })?;
+
+    if !destination_file_exists {
+        return Ok(false);
     }
 
 
-    Ok(DirectoryCopyFinished {
-        total_bytes_copied,
-        files_copied: num_files_copied,
-        symlinks_created: num_symlinks_recreated,
-        directories_created: num_directories_created,
-    })
+    let destination_file_metadata =
+        fs::symlink_metadata(destination_file_path).map_err(|error| {
+            CopyDirectoryExecutionError::UnableToAccessDestination {
+                path: destination_file_path.to_path_buf(),
+                error,
+            }
+        })?;
+
+    if !destination_file_metadata.is_file() || !can_overwrite_files {
+        return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
+            path: destination_file_path.to_path_buf(),
+        });
+    }
+
+    Ok(true)
 }
 
 
@@ -436,8 +955,12 @@ pub(crate) fn copy_directory_unchecked(
 ///   the actual copying phase. If an error from this group is returned,
 ///   it is very likely that the destination directory is in an unpredictable state, since
 ///   the error occurred while trying to copy a file or create a directory.
+///   To avoid that, enable rollback with the [`options.rollback_behaviour`] option:
+///   changes are then undone on failure, and
+///   [`CopyDirectoryExecutionError::RolledBack`] is returned.
 ///
 ///
+/// [`options.rollback_behaviour`]: DirectoryCopyOptions::rollback_behaviour
 /// [`options.destination_directory_rule`]: DirectoryCopyOptions::destination_directory_rule
 /// [`options.copy_depth_limit`]: DirectoryCopyOptions::copy_depth_limit
 /// [`symlink_behaviour`]: DirectoryCopyOptions::symlink_behaviour
@@ -450,21 +973,59 @@ pub(crate) fn copy_directory_unchecked(
 pub fn copy_directory<S, T>(
     source_directory_path: S,
     destination_directory_path: T,
-    options: DirectoryCopyOptions,
+    mut options: DirectoryCopyOptions,
 ) -> Result<DirectoryCopyFinished, CopyDirectoryError>
 where
     S: AsRef<Path>,
     T: AsRef<Path>,
 {
+    if options.collision_resolver.is_some() {
+        options.destination_directory_rule =
+            options.destination_directory_rule.for_collision_resolver();
+    }
+
+    let resume_journal = options
+        .resume_journal_path
+        .as_deref()
+        .map(|journal_path| {
+            DirectoryCopyResumeJournal::open(
+                journal_path,
+                source_directory_path.as_ref(),
+                destination_directory_path.as_ref(),
+            )
+        })
+        .transpose()?;
+
     let prepared_copy = DirectoryCopyPrepared::prepare(
         source_directory_path.as_ref(),
         destination_directory_path.as_ref(),
-        options.destination_directory_rule,
+        resume_journal
+            .as_ref()
+            .map_or(options.destination_directory_rule, |resume_journal| {
+                resume_journal
+                    .destination_directory_rule_for_preparation(options.destination_directory_rule)
+            }),
         options.copy_depth_limit,
         options.symlink_behaviour,
         options.broken_symlink_behaviour,
+        &options.entry_filter,
     )?;
 
+    let mut prepared_copy = match resume_journal {
+        Some(resume_journal) => {
+            resume_journal.resume(prepared_copy, options.destination_directory_rule)?
+        }
+        None => prepared_copy,
+    };
+
+    if let Some(collision_resolver) = options.collision_resolver.as_ref() {
+        prepared_copy
+            .resolve_collisions_with_resolver(collision_resolver)
+            .map_err(CopyDirectoryPreparationError::CopyPlanningError)?;
+    }
+
+    prepared_copy.preserve_hard_links(options.hard_link_behaviour);
+
     let finished_copy = copy_directory_unchecked(prepared_copy, options)?;
 
 
@@ -499,6 +1060,12 @@ pub enum DirectoryCopyOperation {
         /// Path to the symlink being created.
         destination_symbolic_link_file_path: PathBuf,
     },
+
+    /// A hard link is being created (see [`HardLinkBehaviour::Preserve`]).
+    CreatingHardLink {
+        /// Path to the hard link being created.
+        destination_hard_link_path: PathBuf,
+    },
 }
 
 
@@ -614,55 +1181,63 @@ pub struct DirectoryCopyProgress {
 
 
 #[derive(Clone, PartialEq, Eq, Debug)]
-struct DirectoryCopyInternalProgress {
+pub(super) struct DirectoryCopyInternalProgress {
     /// Total number of bytes that need to be copied
     /// for the directory copy to be complete.
-    bytes_total: u64,
+    pub(super) bytes_total: u64,
 
     /// Number of bytes that have been copied so far.
-    bytes_finished: u64,
+    pub(super) bytes_finished: u64,
 
     /// Number of files that have been copied so far.
-    files_copied: usize,
+    pub(super) files_copied: usize,
 
     /// Number of symlinks that have been (re)created so far.
     ///
     /// If the [`DirectoryCopyOptions::symlink_behaviour`] option is set to
     /// [`SymlinkBehaviour::Follow`], this will always be `0`.
-    symlinks_created: usize,
+    pub(super) symlinks_created: usize,
 
     /// Number of directories that have been created so far.
-    directories_created: usize,
+    pub(super) directories_created: usize,
+
+    /// Number of hard links that have been created so far.
+    ///
+    /// This is not reported to the progress handler, only in the returned [`DirectoryCopyFinished`].
+    pub(super) hard_links_created: usize,
 
     /// The current operation being performed.
-    current_operation: Option<DirectoryCopyOperation>,
+    pub(super) current_operation: Option<DirectoryCopyOperation>,
 
     /// The index of the current operation.
     ///
     /// Starts at `0`, goes up to (including) `total_operations - 1`.
-    current_operation_index: Option<usize>,
+    pub(super) current_operation_index: Option<usize>,
 
     /// The total number of operations that need to be performed to
     /// copy the requested directory.
     ///
     /// A single operation is either copying a file or creating a directory,
     /// see [`DirectoryCopyOperation`].
-    total_operations: usize,
+    pub(super) total_operations: usize,
 }
 
 impl DirectoryCopyInternalProgress {
     /// Modifies `self` with the provided `FnMut` closure.
     /// Then, the provided progress handler closure is called.
-    fn update_operation_and_emit_progress<M, F>(
+    ///
+    /// Returns the [`ProgressAction`] returned by the progress handler.
+    pub(super) fn update_operation_and_emit_progress<M, F>(
         &mut self,
         mut self_modifier_closure: M,
         progress_handler: &mut F,
-    ) where
+    ) -> ProgressAction
+    where
         M: FnMut(&mut Self),
-        F: FnMut(&DirectoryCopyProgressRef),
+        F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
     {
         self_modifier_closure(self);
-        progress_handler(&self.to_user_facing_progress());
+        progress_handler(&self.to_user_facing_progress())
     }
 
     /// Replaces the current [`current_operation`][Self::current_operation]
@@ -671,13 +1246,15 @@ impl DirectoryCopyInternalProgress {
     /// The [`current_operation_index`][Self::current_operation_index]
     /// is incremented or set to 0, if previously unset.
     ///
-    /// Finally, the provided progress handler closure is called.
-    fn set_next_operation_and_emit_progress<F>(
+    /// Finally, the provided progress handler closure is called,
+    /// and the [`ProgressAction`] it returned is passed through.
+    pub(super) fn set_next_operation_and_emit_progress<F>(
         &mut self,
         operation: DirectoryCopyOperation,
         progress_handler: &mut F,
-    ) where
-        F: FnMut(&DirectoryCopyProgressRef),
+    ) -> ProgressAction
+    where
+        F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
     {
         if let Some(existing_operation_index) = self.current_operation_index.as_mut() {
             *existing_operation_index += 1;
@@ -690,12 +1267,24 @@ impl DirectoryCopyInternalProgress {
         progress_handler(&self.to_user_facing_progress())
     }
 
+    /// Constructs a [`CopyDirectoryExecutionError::Cancelled`] error
+    /// that describes the current state of the copy.
+    pub(super) fn to_cancelled_error(&self) -> CopyDirectoryExecutionError {
+        CopyDirectoryExecutionError::Cancelled {
+            bytes_finished: self.bytes_finished,
+            bytes_total: self.bytes_total,
+            files_copied: self.files_copied,
+            symlinks_created: self.symlinks_created,
+            directories_created: self.directories_created,
+        }
+    }
+
     /// Converts the [`DirectoryCopyInternalProgress`] to a [`DirectoryCopyProgress`],
     /// copying only the small fields, and passing the `current_operation` as a reference.
     ///
     /// # Panics
     /// Panics if the `current_operation` or `current_operation_index` field is `None`.
-    fn to_user_facing_progress(&self) -> DirectoryCopyProgressRef<'_> {
+    pub(super) fn to_user_facing_progress(&self) -> DirectoryCopyProgressRef<'_> {
         let current_operation_reference = self
             .current_operation
             .as_ref()
@@ -724,7 +1313,7 @@ impl DirectoryCopyInternalProgress {
 
 
 /// Options that influence the [`copy_directory_with_progress`] function.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct DirectoryCopyWithProgressOptions {
     /// Specifies whether you allow the destination directory to exist before copying,
     /// and whether you require it to be empty. If you allow a non-empty destination directory,
@@ -762,6 +1351,113 @@ pub struct DirectoryCopyWithProgressOptions {
     ///
     /// [`copy_directory_with_progress`]: copy_directory_with_progress#progress-reporting
     pub progress_update_byte_interval: u64,
+
+    /// Sets whether changes made to the destination are undone if the copy fails midway
+    /// (this includes the copy being cancelled).
+    ///
+    /// See [`RollbackBehaviour`] for more details.
+    pub rollback_behaviour: RollbackBehaviour,
+
+    /// Decides which entries of the source directory are copied.
+    ///
+    /// Skipped entries are not counted towards the total size of the copy
+    /// (and as such, do not appear in progress reports), and skipped directories
+    /// are not descended into. See [`DirectoryEntryFilter`] for more details.
+    pub entry_filter: DirectoryEntryFilter,
+
+    /// Sets whether files are copied on multiple threads.
+    /// The progress handler is always called on the calling thread.
+    ///
+    /// See [`DirectoryCopyParallelism`] for more details.
+    pub parallelism: DirectoryCopyParallelism,
+
+    /// Sets whether files are cloned (i.e. whether copy-on-write reflinks are created)
+    /// instead of having their contents copied.
+    ///
+    /// See [`ReflinkBehaviour`] for more details.
+    pub reflink_behaviour: ReflinkBehaviour,
+
+    /// Which metadata of the source files and directories to apply to their copies.
+    ///
+    /// Directory metadata is applied only after the entire copy has finished,
+    /// so that copying the contents of a directory does not change its timestamps.
+    /// See [`MetadataPreservation`] for more details.
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Which extended attributes of the source files, directories and symbolic links
+    /// to copy to their copies.
+    ///
+    /// See [`ExtendedAttributeFilter`] for more details.
+    pub extended_attributes: ExtendedAttributeFilter,
+
+    /// Sets whether holes in sparse files are preserved.
+    ///
+    /// See [`SparseFileBehaviour`] for more details.
+    pub sparse_behaviour: SparseFileBehaviour,
+
+    /// Whether each copied file is read back and compared to the hash of its source file.
+    ///
+    /// If [`CopyVerification::reports_progress`] is `true`, verification is reported through
+    /// the [`FileProgress::phase`] of the [`DirectoryCopyOperation::CopyingFile`] operation.
+    /// A file that fails verification stops the copy with a [`FileCopyError`] containing
+    /// [`FileError::VerificationFailed`]. See [`CopyVerification`] for more details.
+    ///
+    ///
+    /// [`FileCopyError`]: CopyDirectoryExecutionError::FileCopyError
+    /// [`FileError::VerificationFailed`]: crate::error::FileError::VerificationFailed
+    pub verify: CopyVerification,
+
+    /// Path of a journal file that makes the copy resumable.
+    ///
+    /// If set, every executed operation is recorded in this file. If the copy is interrupted
+    /// (e.g. by an error or a crash), calling the copy again with the same source, destination
+    /// and journal path resumes it: operations whose results are still present on the destination
+    /// are skipped, and a partially copied file is resumed where it stopped
+    /// (see [`ResumeBehaviour::VerifySampled`]). Entries created by the interrupted copy
+    /// do not count as collisions with the [`destination_directory_rule`].
+    /// The journal is removed once the copy has completed successfully.
+    ///
+    /// Each record is synced to disk as soon as it is written, and copied files are synced to disk
+    /// before they are recorded (regardless of the [`durability`] level), so the journal remains
+    /// accurate even after a power loss.
+    ///
+    /// Skipped operations count towards the totals returned in [`DirectoryCopyFinished`].
+    /// The journal should not be placed inside the source or destination directory.
+    ///
+    ///
+    /// [`destination_directory_rule`]: Self::destination_directory_rule
+    /// [`durability`]: Self::durability
+    pub resume_journal_path: Option<PathBuf>,
+
+    /// Whether each file is copied atomically, i.e. into a hidden temporary file
+    /// that is renamed over the destination file once it has been fully written.
+    ///
+    /// Files that are copied atomically are never left partially copied, so they are never resumed.
+    /// See [`FileCopyOptions::atomic`] for more details.
+    pub atomic: bool,
+
+    /// How much of the copy to sync to disk before returning.
+    ///
+    /// With [`DurabilityLevel::FilesAndDirectories`], every destination directory that an entry
+    /// has been copied into is synced once the entire copy has finished.
+    /// See [`DurabilityLevel`] for more details.
+    pub durability: DurabilityLevel,
+
+    /// Decides what to do with each file or symbolic link whose destination path already exists.
+    ///
+    /// When set, the resolver is asked about every colliding entry while the copy is being prepared,
+    /// instead of applying the `colliding_file_behaviour` of the [`destination_directory_rule`].
+    /// See [`DirectoryCollisionResolver`] for more details.
+    ///
+    ///
+    /// [`destination_directory_rule`]: Self::destination_directory_rule
+    pub collision_resolver: Option<DirectoryCollisionResolver>,
+
+    /// Whether hard links between files inside the source directory are recreated
+    /// on the destination, instead of copying each of them as an independent file.
+    ///
+    /// See [`HardLinkBehaviour`] for more details.
+    pub hard_link_behaviour: HardLinkBehaviour,
 }
 
 impl Default for DirectoryCopyWithProgressOptions {
@@ -770,8 +1466,21 @@ impl Default for DirectoryCopyWithProgressOptions {
     /// - [`DirectoryCopyDepthLimit::Unlimited`]: there is no copy depth limit,
     /// - [`SymlinkBehaviour::Keep`]: symbolic links are not followed,
     /// - [`BrokenSymlinkBehaviour::Keep`]: broken symbolic links are kept as-is, i.e. broken,
-    /// - the read and write buffers are 64 KiB large, and
-    /// - the progress reporting closure byte interval is set to 512 KiB.
+    /// - the read and write buffers are 64 KiB large,
+    /// - the progress reporting closure byte interval is set to 512 KiB,
+    /// - [`RollbackBehaviour::Disabled`]: a failed copy is not rolled back,
+    /// - [`DirectoryEntryFilter::new`]: all entries are copied,
+    /// - [`DirectoryCopyParallelism::Sequential`]: everything is copied on the calling thread,
+    /// - [`ReflinkBehaviour::Never`]: files are never cloned,
+    /// - [`MetadataPreservation::NONE`]: no metadata is preserved,
+    /// - [`ExtendedAttributeFilter::NONE`]: no extended attributes are copied,
+    /// - [`SparseFileBehaviour::Never`]: holes in sparse files are not preserved,
+    /// - [`CopyVerification::DISABLED`]: copied files are not verified,
+    /// - no resume journal: an interrupted copy can not be resumed,
+    /// - files are written directly into their destination files (they are not copied atomically),
+    /// - [`DurabilityLevel::None`]: nothing is synced to disk,
+    /// - no collision resolver: collisions are handled by the `destination_directory_rule`, and
+    /// - [`HardLinkBehaviour::CopySeparately`]: hard-linked files are copied as independent files.
     fn default() -> Self {
         Self {
             destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
@@ -781,6 +1490,19 @@ impl Default for DirectoryCopyWithProgressOptions {
             read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
             write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
             progress_update_byte_interval: DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
+            rollback_behaviour: RollbackBehaviour::Disabled,
+            entry_filter: DirectoryEntryFilter::new(),
+            parallelism: DirectoryCopyParallelism::Sequential,
+            reflink_behaviour: ReflinkBehaviour::Never,
+            metadata_preservation: MetadataPreservation::NONE,
+            extended_attributes: ExtendedAttributeFilter::NONE,
+            sparse_behaviour: SparseFileBehaviour::Never,
+            verify: CopyVerification::DISABLED,
+            resume_journal_path: None,
+            atomic: false,
+            durability: DurabilityLevel::None,
+            collision_resolver: None,
+            hard_link_behaviour: HardLinkBehaviour::CopySeparately,
         }
     }
 }
@@ -797,60 +1519,52 @@ fn execute_copy_file_operation_with_progress<F>(
     destination_path: PathBuf,
     options: &DirectoryCopyWithProgressOptions,
     progress: &mut DirectoryCopyInternalProgress,
+    journal: &mut DirectoryCopyJournal,
     progress_handler: &mut F,
 ) -> Result<(), CopyDirectoryExecutionError>
 where
-    F: FnMut(&DirectoryCopyProgressRef),
+    F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
 {
-    let can_overwrite_destination_file = options
-        .destination_directory_rule
-        .allows_overwriting_existing_destination_files();
+    let is_left_over_file = journal.is_left_over_file(&destination_path);
 
+    let can_overwrite_destination_file = is_left_over_file
+        || options
+            .destination_directory_rule
+            .allows_overwriting_existing_destination_files();
 
 
-    let destination_path_exists =
-        try_exists_without_follow(&destination_path).map_err(|error| {
-            CopyDirectoryExecutionError::UnableToAccessDestination {
-                path: destination_path.clone(),
-                error,
-            }
-        })?;
-
-    if destination_path_exists {
-        let destination_path_metadata =
-            fs::symlink_metadata(&destination_path).map_err(|error| {
-                CopyDirectoryExecutionError::UnableToAccessDestination {
-                    path: destination_path.clone(),
-                    error,
-                }
-            })?;
-
-
-        if !destination_path_metadata.is_file() {
-            return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
-                path: destination_path.clone(),
-            });
-        }
 
-        if !can_overwrite_destination_file {
-            return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
-                path: destination_path.clone(),
-            });
-        }
-    }
+    let destination_path_exists =
+        check_destination_file_before_copy(&destination_path, can_overwrite_destination_file)?;
 
 
-    progress.set_next_operation_and_emit_progress(
+    let action = progress.set_next_operation_and_emit_progress(
         DirectoryCopyOperation::CopyingFile {
             destination_file_path: destination_path.clone(),
             progress: FileProgress {
                 bytes_finished: 0,
+                bytes_written: 0,
                 bytes_total: source_size_bytes,
+                phase: FileProgressPhase::Copying,
             },
         },
         progress_handler,
     );
 
+    if action.is_abort() {
+        return Err(progress.to_cancelled_error());
+    }
+
+
+    journal
+        .prepare_file_write(&destination_path, destination_path_exists)
+        .map_err(|error| CopyDirectoryExecutionError::UnableToAccessDestination {
+            path: destination_path.clone(),
+            error,
+        })?;
+
+    journal.persist(ResumeJournalEntry::CopyingFile(&destination_path))?;
+
 
     // Set to `true` when we update our `bytes_total` to the
     // freshly calculated total number of bytes in a file (after the copying starts).
@@ -860,20 +1574,31 @@ where
     let bytes_copied_before = progress.bytes_finished;
 
 
-    copy_file_with_progress(
+    let file_copy_result = copy_file_with_progress(
         source_file_path,
         &destination_path,
         FileCopyWithProgressOptions {
-            colliding_file_behaviour: match options.destination_directory_rule {
-                DestinationDirectoryRule::DisallowExisting => CollidingFileBehaviour::Abort,
-                DestinationDirectoryRule::AllowEmpty => CollidingFileBehaviour::Abort,
-                DestinationDirectoryRule::AllowNonEmpty { colliding_file_behaviour, .. } => colliding_file_behaviour,
+            colliding_file_behaviour: match is_left_over_file {
+                true => CollidingFileBehaviour::Overwrite,
+                false => options
+                    .destination_directory_rule
+                    .colliding_file_behaviour(),
             },
             read_buffer_size: options.read_buffer_size,
             write_buffer_size: options.write_buffer_size,
             progress_update_byte_interval: options.progress_update_byte_interval,
+            reflink_behaviour: options.reflink_behaviour,
+            metadata_preservation: options.metadata_preservation,
+            extended_attributes: options.extended_attributes,
+            sparse_behaviour: options.sparse_behaviour,
+            verify: options.verify,
+            resume_behaviour: journal
+                .resume_behaviour_for_file(&destination_path, destination_path_exists),
+            atomic: options.atomic,
+            durability: journal.durability_for_file(options.durability),
         },
-        |new_file_progress| progress.update_operation_and_emit_progress(
+        |new_file_progress| {
+            progress.update_operation_and_emit_progress(
                 |progress| {
                     let current_operation = progress.current_operation.as_mut()
                         // PANIC SATEFY: The function calls `set_next_operation_and_emit_progress` above,
@@ -896,6 +1621,8 @@ where
                         }
 
                         file_progress.bytes_finished = new_file_progress.bytes_finished;
+                        file_progress.bytes_written = new_file_progress.bytes_written;
+                        file_progress.phase = new_file_progress.phase;
                         progress.bytes_finished =
                             bytes_copied_before + file_progress.bytes_finished;
                     } else {
@@ -909,10 +1636,29 @@ where
                 },
                 progress_handler,
             )
-    )
-        .map_err(|file_error| CopyDirectoryExecutionError::FileCopyError { file_path: destination_path, error: file_error })?;
+        },
+    );
+
+    match file_copy_result {
+        Ok(_) => {}
+        Err(FileError::Cancelled { .. }) => {
+            // The partially copied file has already been removed (or will be resumed),
+            // so it must not count towards the finished bytes.
+            progress.bytes_finished = bytes_copied_before;
+
+            return Err(progress.to_cancelled_error());
+        }
+        Err(file_error) => {
+            return Err(CopyDirectoryExecutionError::FileCopyError {
+                file_path: destination_path,
+                error: file_error,
+            });
+        }
+    };
 
 
+    journal.persist(ResumeJournalEntry::CopiedFile(&destination_path))?;
+
     progress.files_copied += 1;
 
     Ok(())
@@ -927,16 +1673,17 @@ where
 /// (`overwrite_existing_subdirectories`, see [`DestinationDirectoryRule`]).
 ///
 /// If the given path exists, but is not a directory, an error is returned as well.
-fn execute_create_directory_operation_with_progress<F>(
+pub(super) fn execute_create_directory_operation_with_progress<F>(
     destination_directory_path: PathBuf,
     source_size_bytes: u64,
     create_parent_directories: bool,
     options: &DirectoryCopyWithProgressOptions,
     progress: &mut DirectoryCopyInternalProgress,
+    journal: &mut DirectoryCopyJournal,
     progress_handler: &mut F,
 ) -> Result<(), CopyDirectoryExecutionError>
 where
-    F: FnMut(&DirectoryCopyProgressRef),
+    F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
 {
     let destination_directory_exists = try_exists_without_follow(&destination_directory_path)
         .map_err(|error| CopyDirectoryExecutionError::UnableToAccessDestination {
@@ -969,13 +1716,26 @@ where
     }
 
 
-    progress.set_next_operation_and_emit_progress(
+    let action = progress.set_next_operation_and_emit_progress(
         DirectoryCopyOperation::CreatingDirectory {
             destination_directory_path: destination_directory_path.clone(),
         },
         progress_handler,
     );
 
+    if action.is_abort() {
+        return Err(progress.to_cancelled_error());
+    }
+
+
+    journal
+        .record_directory_creation(&destination_directory_path, create_parent_directories)
+        .map_err(|error| CopyDirectoryExecutionError::UnableToAccessDestination {
+            path: destination_directory_path.clone(),
+            error,
+        })?;
+
+    journal.persist(ResumeJournalEntry::CreatingDirectory(&destination_directory_path))?;
 
     if create_parent_directories {
         fs::create_dir_all(&destination_directory_path).map_err(|error| {
@@ -1002,15 +1762,15 @@ where
 
 
 
-struct SymlinkCreationInfo {
-    symlink_path: PathBuf,
+pub(super) struct SymlinkCreationInfo {
+    pub(super) symlink_path: PathBuf,
 
-    symlink_destination_path: PathBuf,
+    pub(super) symlink_destination_path: PathBuf,
 
     #[cfg(windows)]
-    symlink_type: crate::directory::prepared::SymlinkType,
+    pub(super) symlink_type: crate::directory::prepared::SymlinkType,
 
-    unfollowed_symlink_file_size_bytes: u64,
+    pub(super) unfollowed_symlink_file_size_bytes: u64,
 }
 
 
@@ -1018,59 +1778,95 @@ fn execute_create_symlink_operation_with_progress<F>(
     symlink_info: SymlinkCreationInfo,
     options: &DirectoryCopyWithProgressOptions,
     progress: &mut DirectoryCopyInternalProgress,
+    journal: &mut DirectoryCopyJournal,
     progress_handler: &mut F,
 ) -> Result<(), CopyDirectoryExecutionError>
 where
-    F: FnMut(&DirectoryCopyProgressRef),
+    F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
 {
-    let can_overwrite_destination_file = options
-        .destination_directory_rule
-        .allows_overwriting_existing_destination_files();
+    check_destination_symlink_before_creation(
+        &symlink_info.symlink_path,
+        options
+            .destination_directory_rule
+            .allows_overwriting_existing_destination_files(),
+    )?;
 
 
-    let symlink_path_exists =
-        try_exists_without_follow(&symlink_info.symlink_path).map_err(|error| {
-            CopyDirectoryExecutionError::UnableToAccessDestination {
-                path: symlink_info.symlink_path.clone(),
-                error,
-            }
-        })?;
+    let action = progress.set_next_operation_and_emit_progress(
+        DirectoryCopyOperation::CreatingSymbolicLink {
+            destination_symbolic_link_file_path: symlink_info.symlink_path.clone(),
+        },
+        progress_handler,
+    );
 
-    if symlink_path_exists {
-        let symlink_path_metadata =
-            fs::symlink_metadata(&symlink_info.symlink_path).map_err(|error| {
-                CopyDirectoryExecutionError::UnableToAccessDestination {
-                    path: symlink_info.symlink_path.clone(),
-                    error,
-                }
-            })?;
+    if action.is_abort() {
+        return Err(progress.to_cancelled_error());
+    }
 
-        if !symlink_path_metadata.is_symlink() {
-            return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
-                path: symlink_info.symlink_path,
-            });
+
+    create_symlink_and_record_in_journal(&symlink_info, journal)?;
+
+
+    progress.symlinks_created += 1;
+    progress.bytes_finished += symlink_info.unfollowed_symlink_file_size_bytes;
+
+    Ok(())
+}
+
+
+/// Checks whether a symbolic link can be created at `symlink_path`.
+///
+/// Returns an error if the path exists, but is not a symbolic link,
+/// or if it is a symbolic link, but `can_overwrite_files` is `false`.
+pub(super) fn check_destination_symlink_before_creation(
+    symlink_path: &Path,
+    can_overwrite_files: bool,
+) -> Result<(), CopyDirectoryExecutionError> {
+    let symlink_path_exists = try_exists_without_follow(symlink_path).map_err(|error| {
+        CopyDirectoryExecutionError::UnableToAccessDestination {
+            path: symlink_path.to_path_buf(),
+            error,
         }
+    })?;
 
-        if !can_overwrite_destination_file {
-            return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
-                path: symlink_info.symlink_path,
-            });
+    if !symlink_path_exists {
+        return Ok(());
+    }
+
+
+    let symlink_path_metadata = fs::symlink_metadata(symlink_path).map_err(|error| {
+        CopyDirectoryExecutionError::UnableToAccessDestination {
+            path: symlink_path.to_path_buf(),
+            error,
         }
+    })?;
+
+    if !symlink_path_metadata.is_symlink() || !can_overwrite_files {
+        return Err(CopyDirectoryExecutionError::DestinationEntryUnexpected {
+            path: symlink_path.to_path_buf(),
+        });
     }
 
+    Ok(())
+}
 
-    progress.set_next_operation_and_emit_progress(
-        DirectoryCopyOperation::CreatingSymbolicLink {
-            destination_symbolic_link_file_path: symlink_info.symlink_path.clone(),
-        },
-        progress_handler,
-    );
 
+/// Records the symbolic link in `journal`, then creates it.
+pub(super) fn create_symlink_and_record_in_journal(
+    symlink_info: &SymlinkCreationInfo,
+    journal: &mut DirectoryCopyJournal,
+) -> Result<(), CopyDirectoryExecutionError> {
+    journal.persist(ResumeJournalEntry::CreatingSymlink(&symlink_info.symlink_path))?;
 
     #[cfg(windows)]
     {
         use crate::directory::prepared::SymlinkType;
 
+        journal.record_symlink_creation(
+            &symlink_info.symlink_path,
+            matches!(symlink_info.symlink_type, SymlinkType::Directory),
+        );
+
         match symlink_info.symlink_type {
             SymlinkType::File => {
                 std::os::windows::fs::symlink_file(
@@ -1101,6 +1897,8 @@ where
 
     #[cfg(unix)]
     {
+        journal.record_symlink_creation(&symlink_info.symlink_path);
+
         std::os::unix::fs::symlink(
             &symlink_info.symlink_destination_path,
             &symlink_info.symlink_path,
@@ -1111,9 +1909,95 @@ where
         })?;
     }
 
+    Ok(())
+}
 
-    progress.symlinks_created += 1;
-    progress.bytes_finished += symlink_info.unfollowed_symlink_file_size_bytes;
+
+/// Given inner data of [`QueuedOperation::CreateHardLink`], this function
+/// creates the hard link, while reporting progress.
+pub(super) fn execute_create_hard_link_operation_with_progress<F>(
+    hard_link_path: PathBuf,
+    target_path: PathBuf,
+    options: &DirectoryCopyWithProgressOptions,
+    progress: &mut DirectoryCopyInternalProgress,
+    journal: &mut DirectoryCopyJournal,
+    progress_handler: &mut F,
+) -> Result<(), CopyDirectoryExecutionError>
+where
+    F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
+{
+    let action = progress.set_next_operation_and_emit_progress(
+        DirectoryCopyOperation::CreatingHardLink {
+            destination_hard_link_path: hard_link_path.clone(),
+        },
+        progress_handler,
+    );
+
+    if action.is_abort() {
+        return Err(progress.to_cancelled_error());
+    }
+
+
+    let can_overwrite_file = options
+        .destination_directory_rule
+        .allows_overwriting_existing_destination_files()
+        || journal.is_left_over_file(&hard_link_path);
+
+    create_hard_link_and_record_in_journal(
+        &hard_link_path,
+        &target_path,
+        can_overwrite_file,
+        journal,
+    )?;
+
+
+    progress.hard_links_created += 1;
+
+    Ok(())
+}
+
+
+/// Records the hard link in `journal`, then creates it at `hard_link_path`,
+/// pointing to the (already copied) file at `target_path`.
+///
+/// An existing file at `hard_link_path` is replaced, but only if `can_overwrite_files` is `true`
+/// (see [`check_destination_file_before_copy`]).
+pub(super) fn create_hard_link_and_record_in_journal(
+    hard_link_path: &Path,
+    target_path: &Path,
+    can_overwrite_files: bool,
+    journal: &mut DirectoryCopyJournal,
+) -> Result<(), CopyDirectoryExecutionError> {
+    let hard_link_path_exists =
+        check_destination_file_before_copy(hard_link_path, can_overwrite_files)?;
+
+    let map_access_error = |error| CopyDirectoryExecutionError::UnableToAccessDestination {
+        path: hard_link_path.to_path_buf(),
+        error,
+    };
+
+    journal
+        .prepare_file_write(hard_link_path, hard_link_path_exists)
+        .map_err(map_access_error)?;
+
+    journal.persist(ResumeJournalEntry::CreatingHardLink(hard_link_path))?;
+
+
+    // Unlike copying a file, creating a hard link can't overwrite the existing file.
+    // If rollback is enabled, the file has already been moved away as a backup.
+    if hard_link_path_exists
+        && try_exists_without_follow(hard_link_path).map_err(map_access_error)?
+    {
+        fs::remove_file(hard_link_path).map_err(map_access_error)?;
+    }
+
+    fs::hard_link(target_path, hard_link_path).map_err(|error| {
+        CopyDirectoryExecutionError::HardLinkCreationError {
+            hard_link_path: hard_link_path.to_path_buf(),
+            target_path: target_path.to_path_buf(),
+            error,
+        }
+    })?;
 
     Ok(())
 }
@@ -1123,21 +2007,90 @@ where
 
 /// Execute a prepared copy with progress tracking.
 ///
+/// If rollback is enabled in `options`, changes are undone if the copy fails.
+///
 /// For more details, see [`copy_directory_with_progress`].
 pub(crate) fn execute_prepared_copy_directory_with_progress_unchecked<F>(
+    mut prepared_copy: DirectoryCopyPrepared,
+    options: DirectoryCopyWithProgressOptions,
+    progress_handler: F,
+) -> Result<DirectoryCopyFinished, CopyDirectoryExecutionError>
+where
+    F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
+{
+    let mut journal =
+        DirectoryCopyJournal::new(options.rollback_behaviour, prepared_copy.resume_journal.clone());
+
+    let entries_to_finish = CreatedEntriesToFinish::collect(
+        &prepared_copy,
+        options.metadata_preservation,
+        options.extended_attributes,
+    );
+
+    let directories_to_sync = DirectoriesToSync::collect(&prepared_copy, options.durability);
+
+    let files_copied_to_alternative_paths =
+        mem::take(&mut prepared_copy.alternative_destination_file_paths);
+
+    let copy_result = match options.parallelism {
+        DirectoryCopyParallelism::Sequential => {
+            execute_prepared_copy_directory_with_progress_and_journal(
+                prepared_copy,
+                options,
+                &mut journal,
+                progress_handler,
+            )
+        }
+        DirectoryCopyParallelism::Parallel { worker_threads } => {
+            execute_prepared_copy_directory_with_progress_in_parallel(
+                prepared_copy,
+                &options,
+                worker_threads,
+                &mut journal,
+                progress_handler,
+            )
+        }
+    }
+    .and_then(|finished_copy| {
+        entries_to_finish.finish()?;
+        directories_to_sync.sync()?;
+
+        Ok(DirectoryCopyFinished {
+            files_copied_to_alternative_paths,
+            ..finished_copy
+        })
+    });
+
+    match copy_result {
+        Ok(finished_copy) => {
+            journal.finish();
+            Ok(finished_copy)
+        }
+        Err(error) => Err(journal.roll_back_after_error(error)),
+    }
+}
+
+
+/// Execute a prepared copy with progress tracking,
+/// recording every change to the destination in the provided `journal`.
+fn execute_prepared_copy_directory_with_progress_and_journal<F>(
     prepared_copy: DirectoryCopyPrepared,
     options: DirectoryCopyWithProgressOptions,
+    journal: &mut DirectoryCopyJournal,
     mut progress_handler: F,
 ) -> Result<DirectoryCopyFinished, CopyDirectoryExecutionError>
 where
-    F: FnMut(&DirectoryCopyProgressRef),
+    F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
 {
+    let resumed_copy = prepared_copy.resumed_copy();
+
     let mut progress = DirectoryCopyInternalProgress {
         bytes_total: prepared_copy.total_bytes,
-        bytes_finished: 0,
-        files_copied: 0,
-        symlinks_created: 0,
-        directories_created: 0,
+        bytes_finished: resumed_copy.total_bytes_copied,
+        files_copied: resumed_copy.files_copied,
+        symlinks_created: resumed_copy.symlinks_created,
+        directories_created: resumed_copy.directories_created,
+        hard_links_created: resumed_copy.hard_links_created,
         // This is an invisible operation - we don't emit this progress struct at all,
         // but we do need something here before the next operation starts.
         current_operation: None,
@@ -1153,16 +2106,19 @@ where
                 source_file_path: source_path,
                 source_size_bytes,
                 destination_file_path,
+                source_hard_link_identity: _,
             } => execute_copy_file_operation_with_progress(
                 source_path,
                 source_size_bytes,
                 destination_file_path,
                 &options,
                 &mut progress,
+                journal,
                 &mut progress_handler,
             )?,
 
             QueuedOperation::CreateDirectory {
+                source_directory_path: _,
                 source_size_bytes,
                 destination_directory_path,
                 create_parent_directories,
@@ -1172,11 +2128,13 @@ where
                 create_parent_directories,
                 &options,
                 &mut progress,
+                journal,
                 &mut progress_handler,
             )?,
 
             #[cfg(windows)]
             QueuedOperation::CreateSymlink {
+                source_symlink_path: _,
                 symlink_path,
                 symlink_destination_type: symlink_type,
                 source_symlink_size_bytes,
@@ -1190,11 +2148,13 @@ where
                 },
                 &options,
                 &mut progress,
+                journal,
                 &mut progress_handler,
             )?,
 
             #[cfg(unix)]
             QueuedOperation::CreateSymlink {
+                source_symlink_path: _,
                 symlink_path,
                 source_symlink_size_bytes,
                 symlink_destination_path,
@@ -1206,19 +2166,36 @@ where
                 },
                 &options,
                 &mut progress,
+                journal,
+                &mut progress_handler,
+            )?,
+
+            QueuedOperation::CreateHardLink {
+                source_file_path: _,
+                hard_link_path,
+                target_path,
+            } => execute_create_hard_link_operation_with_progress(
+                hard_link_path,
+                target_path,
+                &options,
+                &mut progress,
+                journal,
                 &mut progress_handler,
             )?,
         }
     }
 
-    // One last progress update - everything should be done at this point.
-    progress_handler(&progress.to_user_facing_progress());
+    // One last progress update - everything should be done at this point,
+    // so there is nothing left to cancel.
+    let _ = progress_handler(&progress.to_user_facing_progress());
 
     Ok(DirectoryCopyFinished {
         total_bytes_copied: progress.bytes_finished,
         files_copied: progress.files_copied,
         symlinks_created: progress.symlinks_created,
         directories_created: progress.directories_created,
+        files_copied_to_alternative_paths: Vec::new(),
+        hard_links_created: progress.hard_links_created,
     })
 }
 
@@ -1279,6 +2256,17 @@ where
 /// for [`copy_file`][crate::file::copy_file].
 ///
 ///
+/// ## Cancellation
+/// The `progress_handler` closure can return either `()`, a [`ProgressAction`],
+/// or a [`ControlFlow<()>`](std::ops::ControlFlow).
+/// Returning [`ProgressAction::Abort`] stops the copy as soon as possible (this includes
+/// stopping in the middle of a file copy). Entries that have already been copied are left
+/// in the destination directory, but a partially copied file is removed (unless the
+/// [`options.resume_journal_path`] option is set, in which case it is kept, so that the copy
+/// can be resumed later). The function then returns [`CopyDirectoryExecutionError::Cancelled`],
+/// which describes how far the copy got.
+///
+///
 /// # Errors
 /// If the directory cannot be copied to the destination, a [`CopyDirectoryError`] is returned;
 /// see its documentation for more details.
@@ -1291,8 +2279,13 @@ where
 ///   the actual copying phase. If an error from this group is returned,
 ///   it is very likely that the destination directory is in an unpredictable state, since
 ///   the error occurred while trying to copy a file or create a directory.
+///   To avoid that, enable rollback with the [`options.rollback_behaviour`] option:
+///   changes are then undone on failure (or cancellation), and
+///   [`CopyDirectoryExecutionError::RolledBack`] is returned.
 ///
 ///
+/// [`options.rollback_behaviour`]: DirectoryCopyWithProgressOptions::rollback_behaviour
+/// [`options.resume_journal_path`]: DirectoryCopyWithProgressOptions::resume_journal_path
 /// [`options.progress_update_byte_interval`]: DirectoryCopyWithProgressOptions::progress_update_byte_interval
 /// [`options.destination_directory_rule`]: DirectoryCopyWithProgressOptions::destination_directory_rule
 /// [`options.copy_depth_limit`]: DirectoryCopyWithProgressOptions::copy_depth_limit
@@ -1303,31 +2296,70 @@ where
 /// [`copy_file`]: crate::file::copy_file
 /// [^unix-cp-rd]: Source for coreutils' `cp` is available
 ///     [here](https://github.com/coreutils/coreutils/blob/ccf47cad93bc0b85da0401b0a9d4b652e4c930e4/src/cp.c).
-pub fn copy_directory_with_progress<S, T, F>(
+pub fn copy_directory_with_progress<S, T, F, R>(
     source_directory_path: S,
     destination_directory_path: T,
-    options: DirectoryCopyWithProgressOptions,
-    progress_handler: F,
+    mut options: DirectoryCopyWithProgressOptions,
+    mut progress_handler: F,
 ) -> Result<DirectoryCopyFinished, CopyDirectoryError>
 where
     S: AsRef<Path>,
     T: AsRef<Path>,
-    F: FnMut(&DirectoryCopyProgressRef),
+    F: FnMut(&DirectoryCopyProgressRef) -> R,
+    R: Into<ProgressAction>,
 {
+    if options.collision_resolver.is_some() {
+        options.destination_directory_rule =
+            options.destination_directory_rule.for_collision_resolver();
+    }
+
+    let resume_journal = options
+        .resume_journal_path
+        .as_deref()
+        .map(|journal_path| {
+            DirectoryCopyResumeJournal::open(
+                journal_path,
+                source_directory_path.as_ref(),
+                destination_directory_path.as_ref(),
+            )
+        })
+        .transpose()?;
+
     let prepared_copy = DirectoryCopyPrepared::prepare(
         source_directory_path.as_ref(),
         destination_directory_path.as_ref(),
-        options.destination_directory_rule,
+        resume_journal
+            .as_ref()
+            .map_or(options.destination_directory_rule, |resume_journal| {
+                resume_journal
+                    .destination_directory_rule_for_preparation(options.destination_directory_rule)
+            }),
         options.copy_depth_limit,
         options.symlink_behaviour,
         options.broken_symlink_behaviour,
+        &options.entry_filter,
     )?;
 
+    let mut prepared_copy = match resume_journal {
+        Some(resume_journal) => {
+            resume_journal.resume(prepared_copy, options.destination_directory_rule)?
+        }
+        None => prepared_copy,
+    };
+
+    if let Some(collision_resolver) = options.collision_resolver.as_ref() {
+        prepared_copy
+            .resolve_collisions_with_resolver(collision_resolver)
+            .map_err(CopyDirectoryPreparationError::CopyPlanningError)?;
+    }
+
+    prepared_copy.preserve_hard_links(options.hard_link_behaviour);
+
 
     let finished_copy = execute_prepared_copy_directory_with_progress_unchecked(
         prepared_copy,
         options,
-        progress_handler,
+        |progress| progress_handler(progress).into(),
     )?;
 
     Ok(finished_copy)
@@ -0,0 +1,239 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Debug},
+    fs::Metadata,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::{IgnoreRules, PathMatcher, ScanEntryDepth};
+
+
+/// Signature of a custom [`DirectoryEntryFilter`] predicate.
+type FilterPredicate = dyn Fn(&Path, ScanEntryDepth, &Metadata) -> bool + Send + Sync;
+
+
+/// Decides which entries of a directory tree are processed when scanning,
+/// copying or moving a directory.
+///
+/// A filter consists of:
+/// - an *exclude* [`PathMatcher`]: entries it matches are skipped,
+/// - optionally, `.gitignore` and `.ignore` files (see [`Self::respect_ignore_files`]):
+///   entries ignored by them are skipped,
+/// - an *include* [`PathMatcher`]: if set, files and symbolic links
+///   are skipped unless it matches them, and
+/// - an optional custom predicate (see [`Self::with_predicate`]) that receives the path
+///   of each entry relative to the base directory, its [`ScanEntryDepth`] and its [`Metadata`],
+///   and returns `false` to skip the entry.
+///
+/// An entry is processed only if it passes all of them.
+/// **A skipped directory is never descended into**, meaning its entire subtree is skipped as well.
+/// The include matcher does not apply to directories, as a directory must be descended into
+/// to find the files that match; this means that, when copying, directories
+/// without any included files are still created (empty).
+///
+/// Matchers are evaluated on the path of an entry relative to the base directory,
+/// which is never filtered itself.
+///
+/// The default filter ([`DirectoryEntryFilter::new`]) includes everything.
+///
+///
+/// # Example
+/// ```
+/// # use fs_more::directory::{DirectoryEntryFilter, PathMatcher};
+/// let filter = DirectoryEntryFilter::new()
+///     .exclude(PathMatcher::new([".git", "target/", "*.tmp"]).unwrap());
+/// ```
+#[derive(Clone, Default)]
+pub struct DirectoryEntryFilter {
+    include_matcher: Option<PathMatcher>,
+
+    exclude_matcher: Option<PathMatcher>,
+
+    respects_ignore_files: bool,
+
+    predicate: Option<Arc<FilterPredicate>>,
+}
+
+impl DirectoryEntryFilter {
+    /// Creates a filter that includes every entry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the include matcher, replacing any previously set one.
+    ///
+    /// Files and symbolic links that are not matched by it are skipped.
+    /// Directories are not affected by the include matcher.
+    pub fn include(mut self, matcher: PathMatcher) -> Self {
+        self.include_matcher = Some(matcher);
+
+        self
+    }
+
+    /// Sets the exclude matcher, replacing any previously set one.
+    ///
+    /// Entries that are matched by it are skipped,
+    /// and matching directories are not descended into.
+    pub fn exclude(mut self, matcher: PathMatcher) -> Self {
+        self.exclude_matcher = Some(matcher);
+
+        self
+    }
+
+    /// Enables support for `.gitignore` and `.ignore` files.
+    ///
+    /// When enabled, ignore files are discovered in every directory that is scanned
+    /// (including the base directory), and entries that they ignore are skipped.
+    /// Ignore files follow the [`.gitignore` format](https://git-scm.com/docs/gitignore#_pattern_format),
+    /// which includes negation (`!`) and directory-only (trailing `/`) patterns.
+    /// As in Git:
+    /// - patterns are relative to the directory containing the ignore file,
+    /// - rules from ignore files in deeper directories take precedence over rules
+    ///   of their ancestors, and `.ignore` files take precedence over `.gitignore` files
+    ///   in the same directory,
+    /// - an entry inside an ignored directory cannot be re-included, as ignored directories
+    ///   are not descended into, and
+    /// - invalid patterns are silently skipped.
+    ///
+    /// Ignore files outside of the base directory, `.git/info/exclude` and global
+    /// Git configuration are not taken into account. The ignore files themselves, as well as
+    /// `.git` directories, are not skipped unless they are ignored or excluded.
+    ///
+    /// If an ignore file exists, but cannot be read, the operation fails.
+    pub fn respect_ignore_files(mut self) -> Self {
+        self.respects_ignore_files = true;
+
+        self
+    }
+
+    /// Returns `true` if this filter respects `.gitignore` and `.ignore` files
+    /// (see [`Self::respect_ignore_files`]).
+    pub fn respects_ignore_files(&self) -> bool {
+        self.respects_ignore_files
+    }
+
+    /// Sets a custom predicate, replacing any previously set one.
+    ///
+    /// The predicate is called for each entry that is not skipped by the matchers,
+    /// with the entry's path relative to the base directory, its depth and its metadata.
+    /// Symbolic links are not followed when obtaining the metadata, unless the operation
+    /// itself follows them (e.g. [`DirectoryScanOptions::follow_symbolic_links`]).
+    ///
+    /// Returning `false` skips the entry (and, for directories, their entire subtree).
+    ///
+    ///
+    /// [`DirectoryScanOptions::follow_symbolic_links`]: super::DirectoryScanOptions::follow_symbolic_links
+    pub fn with_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Path, ScanEntryDepth, &Metadata) -> bool + Send + Sync + 'static,
+    {
+        self.predicate = Some(Arc::new(predicate));
+
+        self
+    }
+
+    /// Returns `true` if this filter includes every entry, i.e. if it has
+    /// no matchers, does not respect ignore files and has no custom predicate.
+    pub fn includes_everything(&self) -> bool {
+        self.include_matcher.is_none()
+            && self.exclude_matcher.is_none()
+            && !self.respects_ignore_files
+            && self.predicate.is_none()
+    }
+
+    /// Returns a copy of this filter that additionally skips the entries at `relative_paths`
+    /// (relative to the base directory), on top of whatever this filter already skips.
+    pub(crate) fn excluding_relative_paths(&self, relative_paths: HashSet<PathBuf>) -> Self {
+        if relative_paths.is_empty() {
+            return self.clone();
+        }
+
+        let previous_predicate = self.predicate.clone();
+
+        let mut filter = self.clone();
+        filter.predicate = Some(Arc::new(move |relative_path, depth, metadata| {
+            !relative_paths.contains(relative_path)
+                && previous_predicate
+                    .as_ref()
+                    .map_or(true, |predicate| predicate(relative_path, depth, metadata))
+        }));
+
+        filter
+    }
+
+    /// Returns `true` if the entry at `relative_path` should be processed.
+    ///
+    /// `relative_path` must be relative to the base directory of the operation.
+    /// `ignore_rules` must contain the rules of the directory containing the entry
+    /// (and is ignored if this filter does not respect ignore files).
+    pub(crate) fn is_included(
+        &self,
+        relative_path: &Path,
+        depth: ScanEntryDepth,
+        metadata: &Metadata,
+        ignore_rules: Option<&IgnoreRules>,
+    ) -> bool {
+        if self.includes_everything() {
+            return true;
+        }
+
+
+        let is_directory = metadata.is_dir();
+
+        if let Some(exclude_matcher) = &self.exclude_matcher {
+            if exclude_matcher.is_match(relative_path, is_directory) {
+                return false;
+            }
+        }
+
+        if self.respects_ignore_files {
+            if let Some(ignore_rules) = ignore_rules {
+                if ignore_rules.is_ignored(relative_path, is_directory) {
+                    return false;
+                }
+            }
+        }
+
+        if let Some(include_matcher) = &self.include_matcher {
+            if !is_directory && !include_matcher.is_match(relative_path, is_directory) {
+                return false;
+            }
+        }
+
+        match &self.predicate {
+            Some(predicate) => predicate(relative_path, depth, metadata),
+            None => true,
+        }
+    }
+}
+
+impl PartialEq for DirectoryEntryFilter {
+    /// Two filters are equal if they have the same matchers and ignore file setting,
+    /// and share the same predicate (if any).
+    fn eq(&self, other: &Self) -> bool {
+        let predicates_equal = match (&self.predicate, &other.predicate) {
+            (None, None) => true,
+            (Some(left), Some(right)) => Arc::ptr_eq(left, right),
+            _ => false,
+        };
+
+        self.include_matcher == other.include_matcher
+            && self.exclude_matcher == other.exclude_matcher
+            && self.respects_ignore_files == other.respects_ignore_files
+            && predicates_equal
+    }
+}
+
+impl Eq for DirectoryEntryFilter {}
+
+impl Debug for DirectoryEntryFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirectoryEntryFilter")
+            .field("include_matcher", &self.include_matcher)
+            .field("exclude_matcher", &self.exclude_matcher)
+            .field("respects_ignore_files", &self.respects_ignore_files)
+            .field("has_predicate", &self.predicate.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use_enabled_fs_module!();
+
+use super::{PathMatcher, PathMatcherOptions};
+
+
+/// Names of the ignore files that are respected by [`DirectoryEntryFilter::respect_ignore_files`],
+/// ordered from lowest to highest precedence.
+///
+/// [`DirectoryEntryFilter::respect_ignore_files`]: super::DirectoryEntryFilter::respect_ignore_files
+pub(crate) const IGNORE_FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+
+/// An ignore file that exists, but could not be read.
+pub(crate) struct UnreadableIgnoreFile {
+    /// Path of the ignore file.
+    pub(crate) file_path: PathBuf,
+
+    /// IO error describing why the ignore file could not be read.
+    pub(crate) error: std::io::Error,
+}
+
+
+/// Rules from the ignore files of a single directory, stacked onto the rules of its ancestors.
+///
+/// Rules of deeper directories take precedence over the rules of their ancestors.
+pub(crate) struct IgnoreRules {
+    /// Rules of the closest ancestor directory that has any.
+    parent: Option<Arc<IgnoreRules>>,
+
+    /// Path of the directory containing the ignore files, relative to the base directory.
+    directory_relative_path: PathBuf,
+
+    /// Patterns from all ignore files in the directory, in order of precedence.
+    matcher: PathMatcher,
+}
+
+impl IgnoreRules {
+    /// Reads the ignore files in `directory_path` (if any) and stacks their rules onto `parent`.
+    ///
+    /// If the directory contains no ignore files, `parent` is returned as-is.
+    pub(crate) fn load_for_directory(
+        parent: Option<&Arc<IgnoreRules>>,
+        directory_path: &Path,
+        directory_relative_path: &Path,
+    ) -> Result<Option<Arc<IgnoreRules>>, UnreadableIgnoreFile> {
+        let mut patterns = Vec::new();
+
+        for ignore_file_name in IGNORE_FILE_NAMES {
+            let ignore_file_path = directory_path.join(ignore_file_name);
+
+            let is_file = match fs::metadata(&ignore_file_path) {
+                Ok(metadata) => metadata.is_file(),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => false,
+                Err(error) => {
+                    return Err(UnreadableIgnoreFile {
+                        file_path: ignore_file_path,
+                        error,
+                    })
+                }
+            };
+
+            if !is_file {
+                continue;
+            }
+
+            let contents = fs::read(&ignore_file_path).map_err(|error| UnreadableIgnoreFile {
+                file_path: ignore_file_path.clone(),
+                error,
+            })?;
+
+            patterns.extend(
+                String::from_utf8_lossy(&contents)
+                    .lines()
+                    .filter_map(parse_ignore_file_line),
+            );
+        }
+
+        if patterns.is_empty() {
+            return Ok(parent.cloned());
+        }
+
+
+        Ok(Some(Arc::new(IgnoreRules {
+            parent: parent.cloned(),
+            directory_relative_path: directory_relative_path.to_path_buf(),
+            matcher: PathMatcher::compile_skipping_invalid(patterns, PathMatcherOptions::default()),
+        })))
+    }
+
+    /// Returns `true` if the entry at `relative_path` (relative to the base directory)
+    /// is ignored by these rules or the rules of any ancestor directory.
+    pub(crate) fn is_ignored(&self, relative_path: &Path, is_directory: bool) -> bool {
+        let mut current_rules = Some(self);
+
+        while let Some(rules) = current_rules {
+            if let Ok(path_relative_to_rules) =
+                relative_path.strip_prefix(&rules.directory_relative_path)
+            {
+                if let Some(is_ignored) = rules
+                    .matcher
+                    .last_match(path_relative_to_rules, is_directory)
+                {
+                    return is_ignored;
+                }
+            }
+
+            current_rules = rules.parent.as_deref();
+        }
+
+        false
+    }
+}
+
+
+/// Parses a single line of an ignore file into a glob pattern,
+/// or returns `None` for blank lines and comments.
+fn parse_ignore_file_line(line: &str) -> Option<String> {
+    if line.starts_with('#') {
+        return None;
+    }
+
+    // Trailing spaces are ignored, unless they are escaped with a backslash.
+    let mut pattern = line.trim_end_matches('\r').to_string();
+    while pattern.ends_with(' ') && !pattern.ends_with("\\ ") {
+        pattern.pop();
+    }
+
+    if pattern.is_empty() {
+        return None;
+    }
+
+    Some(pattern)
+}
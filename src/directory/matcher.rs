@@ -0,0 +1,655 @@
+use std::path::{Component, Path};
+
+use crate::error::GlobPatternError;
+
+
+/// Options that influence how a [`PathMatcher`] matches paths.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PathMatcherOptions {
+    /// Whether patterns match paths regardless of letter case.
+    pub case_insensitive: bool,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for PathMatcherOptions {
+    /// Constructs defaults for matching paths, which are:
+    /// - case-sensitive matching.
+    fn default() -> Self {
+        Self {
+            case_insensitive: false,
+        }
+    }
+}
+
+
+
+/// A set of glob patterns, compiled once and matched against relative paths.
+///
+/// Paths are always matched relative to some base directory (e.g. the directory being scanned
+/// or copied), which means patterns behave the same regardless of where that directory is located.
+/// Components of a path are compared using `/` as the separator on all platforms.
+///
+/// See also: [`DirectoryEntryFilter`], which uses path matchers
+/// to include or exclude entries when scanning, copying or moving a directory.
+///
+///
+/// # Pattern syntax
+/// - `*` matches any sequence of characters inside a single path component.
+/// - `?` matches a single character inside a single path component.
+/// - `[abc]`, `[a-z]` match a single character from a set or range of characters.
+///   The set can be negated with `[!abc]` or `[^abc]`.
+/// - `**`, as a full path component, matches any number of path components.
+///   A leading `**/` matches in all directories, and a trailing `/**`
+///   matches everything inside a directory (but not the directory itself).
+/// - `\` escapes the next character, e.g. `\*` matches a literal `*`.
+///
+/// A pattern without a `/` is *unanchored*: it matches an entry with that name at any depth
+/// (e.g. `*.tmp` or `target`). A pattern containing a `/` (other than a trailing one)
+/// is *anchored* to the base directory and must match the full relative path
+/// (e.g. `foo/*.bin` or `/target`).
+///
+/// A trailing `/` makes a pattern match only directories (e.g. `build/`).
+///
+///
+/// # Negation
+/// A pattern starting with `!` is negated: paths it matches are *not* matched by the matcher,
+/// even if an earlier pattern matched them. When multiple patterns match a path,
+/// the last one decides, meaning `["*.log", "!keep.log"]` matches every `.log` file
+/// except `keep.log`. A leading `!` can be escaped as `\!`.
+///
+///
+/// # Example
+/// ```
+/// # use fs_more::directory::PathMatcher;
+/// let matcher = PathMatcher::new(["target/", "*.tmp", "!important.tmp"]).unwrap();
+///
+/// assert!(matcher.is_match("target", true));
+/// assert!(matcher.is_match("foo/bar.tmp", false));
+/// assert!(!matcher.is_match("foo/important.tmp", false));
+/// assert!(!matcher.is_match("src/main.rs", false));
+/// ```
+///
+///
+/// [`DirectoryEntryFilter`]: super::DirectoryEntryFilter
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PathMatcher {
+    patterns: Vec<GlobPattern>,
+
+    options: PathMatcherOptions,
+}
+
+impl PathMatcher {
+    /// Compiles the given glob patterns into a case-sensitive matcher.
+    ///
+    /// See [pattern syntax](Self#pattern-syntax) for the supported syntax.
+    pub fn new<I, G>(patterns: I) -> Result<Self, GlobPatternError>
+    where
+        I: IntoIterator<Item = G>,
+        G: AsRef<str>,
+    {
+        Self::with_options(patterns, PathMatcherOptions::default())
+    }
+
+    /// Compiles the given glob patterns into a matcher with the provided options.
+    ///
+    /// See [pattern syntax](Self#pattern-syntax) for the supported syntax.
+    pub fn with_options<I, G>(
+        patterns: I,
+        options: PathMatcherOptions,
+    ) -> Result<Self, GlobPatternError>
+    where
+        I: IntoIterator<Item = G>,
+        G: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| GlobPattern::compile(pattern.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { patterns, options })
+    }
+
+    /// Compiles the given glob patterns into a matcher, skipping patterns that are invalid
+    /// (this mirrors how Git treats invalid patterns in ignore files).
+    pub(crate) fn compile_skipping_invalid<I, G>(patterns: I, options: PathMatcherOptions) -> Self
+    where
+        I: IntoIterator<Item = G>,
+        G: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .filter_map(|pattern| GlobPattern::compile(pattern.as_ref()).ok())
+            .collect();
+
+        Self { patterns, options }
+    }
+
+    /// Returns the options this matcher was compiled with.
+    pub fn options(&self) -> PathMatcherOptions {
+        self.options
+    }
+
+    /// Returns `true` if the matcher contains no patterns (and as such, never matches).
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns `true` if the path is matched by this matcher.
+    ///
+    /// `relative_path` must be relative to the base directory the patterns were written for.
+    /// `is_directory` indicates whether the path points to a directory,
+    /// which is required to evaluate patterns with a trailing `/`.
+    pub fn is_match<P>(&self, relative_path: P, is_directory: bool) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        self.last_match(relative_path.as_ref(), is_directory)
+            .unwrap_or(false)
+    }
+
+    /// Returns the outcome of the last pattern that matches the path:
+    /// `Some(true)` for a regular pattern, `Some(false)` for a negated pattern,
+    /// or `None` if no pattern matches the path.
+    pub(crate) fn last_match(&self, relative_path: &Path, is_directory: bool) -> Option<bool> {
+        let path_components = relative_path_components(relative_path);
+
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| {
+                pattern.matches(&path_components, is_directory, self.options.case_insensitive)
+            })
+            .map(|pattern| !pattern.is_negated)
+    }
+}
+
+
+
+/// Splits a relative path into its (lossily converted) normal components.
+fn relative_path_components(relative_path: &Path) -> Vec<Vec<char>> {
+    relative_path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(component) => Some(component.to_string_lossy().chars().collect()),
+            _ => None,
+        })
+        .collect()
+}
+
+
+
+/// A single compiled glob pattern (see [`PathMatcher`] for the syntax).
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct GlobPattern {
+    /// Pattern components. Unanchored patterns are compiled with a leading
+    /// [`PatternComponent::AnyComponents`].
+    components: Vec<PatternComponent>,
+
+    /// Whether the pattern started with `!`.
+    is_negated: bool,
+
+    /// Whether the pattern ended with `/`.
+    matches_only_directories: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum PatternComponent {
+    /// `**`, which matches any number of path components (including none).
+    AnyComponents,
+
+    /// A trailing `**`, which matches at least one path component.
+    AnyDescendants,
+
+    /// Any other component, which matches exactly one path component.
+    Segment(Vec<SegmentToken>),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum SegmentToken {
+    Literal(char),
+
+    /// `?`
+    AnyCharacter,
+
+    /// `*`
+    AnyCharacters,
+
+    /// `[...]`
+    CharacterClass {
+        is_negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl GlobPattern {
+    fn compile(pattern: &str) -> Result<Self, GlobPatternError> {
+        let (is_negated, remaining_pattern) = match pattern.strip_prefix('!') {
+            Some(negated_pattern) => (true, negated_pattern),
+            None => (false, pattern),
+        };
+
+        let matches_only_directories = remaining_pattern.ends_with('/');
+        let remaining_pattern = remaining_pattern.trim_end_matches('/');
+
+        let is_anchored = remaining_pattern.contains('/');
+        let remaining_pattern = remaining_pattern.trim_start_matches('/');
+
+
+        let raw_components = remaining_pattern
+            .split('/')
+            .filter(|component| !component.is_empty())
+            .collect::<Vec<_>>();
+
+        if raw_components.is_empty() {
+            return Err(GlobPatternError::EmptyPattern {
+                pattern: pattern.to_string(),
+            });
+        }
+
+
+        let mut components = Vec::with_capacity(raw_components.len() + 1);
+
+        if !is_anchored {
+            components.push(PatternComponent::AnyComponents);
+        }
+
+        let last_component_index = raw_components.len() - 1;
+
+        for (component_index, raw_component) in raw_components.into_iter().enumerate() {
+            if raw_component == "**" {
+                if component_index == last_component_index && component_index > 0 {
+                    components.push(PatternComponent::AnyDescendants);
+                } else {
+                    components.push(PatternComponent::AnyComponents);
+                }
+
+                continue;
+            }
+
+            components.push(PatternComponent::Segment(compile_segment(pattern, raw_component)?));
+        }
+
+
+        Ok(Self {
+            components,
+            is_negated,
+            matches_only_directories,
+        })
+    }
+
+    fn matches(
+        &self,
+        path_components: &[Vec<char>],
+        is_directory: bool,
+        case_insensitive: bool,
+    ) -> bool {
+        if self.matches_only_directories && !is_directory {
+            return false;
+        }
+
+        match_components(&self.components, path_components, case_insensitive)
+    }
+}
+
+
+/// Compiles a single pattern component (that is not `**`) into tokens.
+fn compile_segment(
+    full_pattern: &str,
+    raw_component: &str,
+) -> Result<Vec<SegmentToken>, GlobPatternError> {
+    let mut tokens = Vec::new();
+    let mut characters = raw_component.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        let token = match character {
+            '?' => SegmentToken::AnyCharacter,
+            '*' => {
+                // Consecutive stars inside a component are equivalent to a single one.
+                while characters.next_if_eq(&'*').is_some() {}
+
+                SegmentToken::AnyCharacters
+            }
+            '\\' => match characters.next() {
+                Some(escaped_character) => SegmentToken::Literal(escaped_character),
+                None => {
+                    return Err(GlobPatternError::TrailingEscape {
+                        pattern: full_pattern.to_string(),
+                    })
+                }
+            },
+            '[' => {
+                let is_negated = characters
+                    .next_if(|next| matches!(next, '!' | '^'))
+                    .is_some();
+
+                let mut ranges = Vec::new();
+                let mut is_closed = false;
+                let mut is_first_character = true;
+
+                while let Some(class_character) = characters.next() {
+                    // A `]` immediately after the opening bracket is a literal.
+                    if class_character == ']' && !is_first_character {
+                        is_closed = true;
+                        break;
+                    }
+
+                    is_first_character = false;
+
+                    let range_start = if class_character == '\\' {
+                        characters
+                            .next()
+                            .ok_or_else(|| GlobPatternError::TrailingEscape {
+                                pattern: full_pattern.to_string(),
+                            })?
+                    } else {
+                        class_character
+                    };
+
+                    let is_range = characters.peek() == Some(&'-')
+                        && characters.clone().nth(1).is_some_and(|after| after != ']');
+
+                    if !is_range {
+                        ranges.push((range_start, range_start));
+                        continue;
+                    }
+
+                    // Skip the `-`.
+                    characters.next();
+
+                    let range_end =
+                        match characters.next() {
+                            Some('\\') => characters.next().ok_or_else(|| {
+                                GlobPatternError::TrailingEscape {
+                                    pattern: full_pattern.to_string(),
+                                }
+                            })?,
+                            Some(range_end) => range_end,
+                            None => break,
+                        };
+
+                    if range_start > range_end {
+                        return Err(GlobPatternError::InvalidCharacterRange {
+                            pattern: full_pattern.to_string(),
+                            range_start,
+                            range_end,
+                        });
+                    }
+
+                    ranges.push((range_start, range_end));
+                }
+
+                if !is_closed {
+                    return Err(GlobPatternError::UnclosedCharacterClass {
+                        pattern: full_pattern.to_string(),
+                    });
+                }
+
+                SegmentToken::CharacterClass { is_negated, ranges }
+            }
+            literal_character => SegmentToken::Literal(literal_character),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+
+
+/// Matches pattern components against path components.
+///
+/// This is a greedy wildcard match: on a mismatch, only the most recent `**` is retried
+/// (matching one more path component than before). Earlier `**`s never need to be retried,
+/// as the most recent one can absorb any path components they could have matched,
+/// which keeps matching linear in the number of `**`s instead of exponential.
+fn match_components(
+    pattern_components: &[PatternComponent],
+    path_components: &[Vec<char>],
+    case_insensitive: bool,
+) -> bool {
+    let mut pattern_index = 0;
+    let mut path_index = 0;
+
+    // Index of the most recent `**` and the index of the first path component
+    // it does not match (yet).
+    let mut backtrack_point: Option<(usize, usize)> = None;
+
+    while path_index < path_components.len() {
+        match pattern_components.get(pattern_index) {
+            Some(PatternComponent::AnyComponents) => {
+                backtrack_point = Some((pattern_index, path_index));
+                pattern_index += 1;
+                continue;
+            }
+            // A trailing `**` matches all (at least one) remaining path components.
+            Some(PatternComponent::AnyDescendants) => return true,
+            Some(PatternComponent::Segment(tokens))
+                if match_segment(tokens, &path_components[path_index], case_insensitive) =>
+            {
+                pattern_index += 1;
+                path_index += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some((any_components_index, first_unmatched_path_index)) = backtrack_point else {
+            return false;
+        };
+
+        pattern_index = any_components_index + 1;
+        path_index = first_unmatched_path_index + 1;
+        backtrack_point = Some((any_components_index, path_index));
+    }
+
+    pattern_components[pattern_index..]
+        .iter()
+        .all(|pattern_component| matches!(pattern_component, PatternComponent::AnyComponents))
+}
+
+
+/// Matches a single compiled pattern component against a single path component.
+///
+/// Like [`match_components`], this is a greedy wildcard match
+/// that only ever retries the most recent `*`.
+fn match_segment(tokens: &[SegmentToken], name: &[char], case_insensitive: bool) -> bool {
+    let mut token_index = 0;
+    let mut name_index = 0;
+
+    // Index of the most recent `*` and the index of the first character it does not match (yet).
+    let mut backtrack_point: Option<(usize, usize)> = None;
+
+    while name_index < name.len() {
+        match tokens.get(token_index) {
+            Some(SegmentToken::AnyCharacters) => {
+                backtrack_point = Some((token_index, name_index));
+                token_index += 1;
+                continue;
+            }
+            Some(token) if match_single_character(token, name[name_index], case_insensitive) => {
+                token_index += 1;
+                name_index += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some((any_characters_index, first_unmatched_name_index)) = backtrack_point else {
+            return false;
+        };
+
+        token_index = any_characters_index + 1;
+        name_index = first_unmatched_name_index + 1;
+        backtrack_point = Some((any_characters_index, name_index));
+    }
+
+    tokens[token_index..]
+        .iter()
+        .all(|token| matches!(token, SegmentToken::AnyCharacters))
+}
+
+
+/// Matches a single-character token against a character.
+fn match_single_character(token: &SegmentToken, character: char, case_insensitive: bool) -> bool {
+    match token {
+        SegmentToken::AnyCharacter => true,
+        SegmentToken::AnyCharacters => true,
+        SegmentToken::Literal(expected_character) => {
+            if case_insensitive {
+                characters_equal_ignoring_case(*expected_character, character)
+            } else {
+                *expected_character == character
+            }
+        }
+        SegmentToken::CharacterClass { is_negated, ranges } => {
+            let is_in_class = |character: char| {
+                ranges.iter().any(|(range_start, range_end)| {
+                    (*range_start..=*range_end).contains(&character)
+                })
+            };
+
+            let is_matched = if case_insensitive {
+                is_in_class(character)
+                    || character.to_lowercase().any(is_in_class)
+                    || character.to_uppercase().any(is_in_class)
+            } else {
+                is_in_class(character)
+            };
+
+            is_matched != *is_negated
+        }
+    }
+}
+
+
+fn characters_equal_ignoring_case(first: char, second: char) -> bool {
+    first == second || first.to_lowercase().eq(second.to_lowercase())
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unanchored_patterns_match_at_any_depth() {
+        let matcher = PathMatcher::new(["*.tmp", "target"]).unwrap();
+
+        assert!(matcher.is_match("a.tmp", false));
+        assert!(matcher.is_match("foo/bar/a.tmp", false));
+        assert!(matcher.is_match("target", true));
+        assert!(matcher.is_match("foo/target", true));
+
+        assert!(!matcher.is_match("a.tmp.txt", false));
+        assert!(!matcher.is_match("target/foo.txt", false));
+    }
+
+    #[test]
+    fn anchored_patterns_match_full_relative_path() {
+        let matcher =
+            PathMatcher::new(["/target", "foo/*.bin", "docs/**", "**/cache/*.db"]).unwrap();
+
+        assert!(matcher.is_match("target", true));
+        assert!(!matcher.is_match("foo/target", true));
+
+        assert!(matcher.is_match("foo/a.bin", false));
+        assert!(!matcher.is_match("foo/bar/a.bin", false));
+        assert!(!matcher.is_match("bar/foo/a.bin", false));
+
+        assert!(matcher.is_match("docs/a/b.md", false));
+        assert!(!matcher.is_match("docs", true));
+
+        assert!(matcher.is_match("cache/a.db", false));
+        assert!(matcher.is_match("x/y/cache/a.db", false));
+    }
+
+    #[test]
+    fn character_classes_and_wildcards_match_single_characters() {
+        let matcher = PathMatcher::new(["file-[0-9].txt", "[!a]?.log", "\\*"]).unwrap();
+
+        assert!(matcher.is_match("file-7.txt", false));
+        assert!(!matcher.is_match("file-x.txt", false));
+        assert!(!matcher.is_match("file-10.txt", false));
+
+        assert!(matcher.is_match("bc.log", false));
+        assert!(!matcher.is_match("ac.log", false));
+        assert!(!matcher.is_match("b.log", false));
+
+        assert!(matcher.is_match("*", false));
+        assert!(!matcher.is_match("star", false));
+    }
+
+    #[test]
+    fn last_matching_pattern_decides_and_directory_only_patterns_skip_files() {
+        let matcher = PathMatcher::new(["*.log", "!keep.log", "build/"]).unwrap();
+
+        assert!(matcher.is_match("debug.log", false));
+        assert!(!matcher.is_match("keep.log", false));
+
+        assert!(matcher.is_match("build", true));
+        assert!(!matcher.is_match("build", false));
+    }
+
+    #[test]
+    fn case_insensitive_matching() {
+        let matcher = PathMatcher::with_options(
+            ["*.JPG", "[a-c]*.txt"],
+            PathMatcherOptions {
+                case_insensitive: true,
+            },
+        )
+        .unwrap();
+
+        assert!(matcher.is_match("photo.jpg", false));
+        assert!(matcher.is_match("Photo.Jpg", false));
+        assert!(matcher.is_match("Bar.txt", false));
+        assert!(!matcher.is_match("dog.txt", false));
+
+        let case_sensitive_matcher = PathMatcher::new(["*.JPG"]).unwrap();
+        assert!(!case_sensitive_matcher.is_match("photo.jpg", false));
+    }
+
+    #[test]
+    fn many_wildcards_match_long_names_quickly() {
+        let matcher = PathMatcher::new([
+            "*a*a*a*a*a*a*a*a*a*a*a*a*b",
+            "**/a/**/a/**/a/**/a/**/a/**/b",
+        ])
+        .unwrap();
+
+        let long_name = "a".repeat(1000);
+        let deep_path = vec!["a"; 200].join("/");
+
+        // With exponential backtracking, neither of these would finish in a reasonable time.
+        assert!(!matcher.is_match(&long_name, false));
+        assert!(!matcher.is_match(&deep_path, false));
+
+        assert!(matcher.is_match(format!("{long_name}b"), false));
+        assert!(matcher.is_match(format!("{deep_path}/b"), false));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        assert!(matches!(
+            PathMatcher::new(["foo[ab"]),
+            Err(GlobPatternError::UnclosedCharacterClass { .. })
+        ));
+
+        assert!(matches!(
+            PathMatcher::new(["[z-a]"]),
+            Err(GlobPatternError::InvalidCharacterRange { .. })
+        ));
+
+        assert!(matches!(
+            PathMatcher::new(["foo\\"]),
+            Err(GlobPatternError::TrailingEscape { .. })
+        ));
+
+        assert!(matches!(
+            PathMatcher::new(["!"]),
+            Err(GlobPatternError::EmptyPattern { .. })
+        ));
+    }
+}
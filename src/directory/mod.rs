@@ -1,4 +1,4 @@
-//! Directory copying, moving, scanning and sizing operations.
+//! Directory copying, moving, removal, scanning and sizing operations.
 //! *Includes progress monitoring variants.*
 //!
 //! <br>
@@ -11,29 +11,58 @@
 //! | [`copy_directory_with_progress`] | [`DirectoryCopyWithProgressOptions`] | [`DirectoryCopyFinished`] <br><sup style="text-align: right">(or [`CopyDirectoryError`])</sup> |
 //! | [`move_directory`]               | [`DirectoryMoveOptions`]             | [`DirectoryMoveFinished`] <br><sup style="text-align: right">(or [`MoveDirectoryError`])</sup> |
 //! | [`move_directory_with_progress`] | [`DirectoryMoveWithProgressOptions`] | [`DirectoryMoveFinished`] <br><sup style="text-align: right">(or [`MoveDirectoryError`])</sup> |
+//! | [`plan_directory_copy`]          | [`DirectoryCopyOptions`]             | [`DirectoryCopyPlan`] <br><sup style="text-align: right">(or [`CopyDirectoryPreparationError`])</sup> |
+//! | [`plan_directory_move`]          | [`DirectoryMoveOptions`]             | [`DirectoryMovePlan`] <br><sup style="text-align: right">(or [`MoveDirectoryPreparationError`])</sup> |
+//! | [`sync_directory`]               | [`DirectorySyncOptions`]             | [`DirectorySyncFinished`] <br><sup style="text-align: right">(or [`SyncDirectoryError`])</sup> |
+//! | [`remove_directory`]             | [`DirectoryRemoveOptions`]           | [`DirectoryRemoveFinished`] <br><sup style="text-align: right">(or [`RemoveDirectoryError`])</sup> |
+//! | [`remove_directory_with_progress`] | [`DirectoryRemoveOptions`]         | [`DirectoryRemoveFinished`] <br><sup style="text-align: right">(or [`RemoveDirectoryError`])</sup> |
 //! | [`DirectoryScanner::new`]        | [`DirectoryScanOptions`]             | [`DirectoryScanner`] <br><sup style="text-align: right">(and, eventually, [`BreadthFirstDirectoryIter`])</sup> |
 //! | [`directory_size_in_bytes`]      |                                      | [`u64`] <br><sup style="text-align: right">(or [`DirectorySizeScanError`])</sup> |
+//! | [`directory_size_in_bytes_with_options`] | [`DirectorySizeOptions`]   | [`u64`] <br><sup style="text-align: right">(or [`DirectorySizeScanError`])</sup> |
 //! | [`is_directory_empty`]           |                                      | [`bool`] <br><sup style="text-align: right">(or [`DirectoryEmptinessScanError`])</sup> |
 //!
 //!
 //! [`CopyDirectoryError`]: crate::error::CopyDirectoryError
 //! [`MoveDirectoryError`]: crate::error::MoveDirectoryError
+//! [`CopyDirectoryPreparationError`]: crate::error::CopyDirectoryPreparationError
+//! [`MoveDirectoryPreparationError`]: crate::error::MoveDirectoryPreparationError
+//! [`SyncDirectoryError`]: crate::error::SyncDirectoryError
+//! [`RemoveDirectoryError`]: crate::error::RemoveDirectoryError
 //! [`DirectorySizeScanError`]: crate::error::DirectorySizeScanError
 //! [`DirectoryEmptinessScanError`]: crate::error::DirectoryEmptinessScanError
 //! [`DirectoryScanError`]: crate::error::DirectoryScanError
 
 
+mod collision;
 mod common;
 mod copy;
+mod filter;
+mod ignore;
+mod matcher;
 mod r#move;
+mod parallel;
+mod plan;
 mod prepared;
+mod remove;
+mod resume;
+mod rollback;
 mod scan;
 mod size;
+mod sync;
 
 
+pub use collision::*;
 pub use common::*;
 pub use copy::*;
+pub use filter::*;
+pub(crate) use ignore::*;
+pub use matcher::*;
+pub use parallel::DirectoryCopyParallelism;
+pub use plan::*;
 pub(crate) use prepared::*;
 pub use r#move::*;
+pub use remove::*;
+pub use rollback::*;
 pub use scan::*;
 pub use size::*;
+pub use sync::*;
@@ -1,9 +1,13 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
 use_enabled_fs_module!();
 
 use super::{
     collected::collect_directory_statistics_via_scan,
+    collision::DirectoryCollisionResolver,
     copy_directory_unchecked,
     execute_prepared_copy_directory_with_progress_unchecked,
     prepared::{
@@ -15,17 +19,38 @@ use super::{
         ValidatedDestinationDirectory,
         ValidatedSourceDirectory,
     },
+    remove_directory_unchecked,
     BrokenSymlinkBehaviour,
     DestinationDirectoryRule,
     DirectoryCopyDepthLimit,
     DirectoryCopyOperation,
     DirectoryCopyOptions,
+    DirectoryCopyParallelism,
     DirectoryCopyWithProgressOptions,
+    DirectoryEntryFilter,
+    DirectoryRemoveOptions,
+    DirectoryRemovePrepared,
+    HardLinkBehaviour,
+    RollbackBehaviour,
     SymlinkBehaviour,
 };
 use crate::{
-    error::{MoveDirectoryError, MoveDirectoryExecutionError, MoveDirectoryPreparationError},
-    file::FileProgress,
+    error::{
+        CopyDirectoryExecutionError,
+        MoveDirectoryError,
+        MoveDirectoryExecutionError,
+        MoveDirectoryPreparationError,
+    },
+    file::{
+        CopyVerification,
+        DurabilityLevel,
+        ExtendedAttributeFilter,
+        FileProgress,
+        MetadataPreservation,
+        ReflinkBehaviour,
+        SparseFileBehaviour,
+    },
+    ProgressAction,
     DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
     DEFAULT_READ_BUFFER_SIZE,
     DEFAULT_WRITE_BUFFER_SIZE,
@@ -60,16 +85,115 @@ pub struct DirectoryMoveByCopyOptions {
     ///
     /// [dco-broken_symlink_behaviour]: crate::directory::DirectoryCopyOptions::broken_symlink_behaviour
     pub broken_symlink_behaviour: BrokenSymlinkBehaviour,
+
+    /// Sets whether changes made to the destination are undone if copying fails midway.
+    ///
+    /// Only the copying phase can be rolled back: if the source directory
+    /// cannot be fully removed after it has been copied, the copy is kept.
+    ///
+    /// This has the same impact as the [`rollback_behaviour`][dco-rollback_behaviour]
+    /// option under [`DirectoryCopyOptions`].
+    ///
+    ///
+    /// [dco-rollback_behaviour]: crate::directory::DirectoryCopyOptions::rollback_behaviour
+    pub rollback_behaviour: RollbackBehaviour,
+
+    /// Decides which entries of the source directory are moved.
+    ///
+    /// Entries skipped by the filter are left in the source directory, together with
+    /// the directories that contain them. If the filter skips anything (see
+    /// [`DirectoryEntryFilter::includes_everything`]), the rename strategy is never attempted,
+    /// as it would move the entire directory.
+    ///
+    /// This has the same impact as the [`entry_filter`][dco-entry_filter]
+    /// option under [`DirectoryCopyOptions`].
+    ///
+    ///
+    /// [dco-entry_filter]: crate::directory::DirectoryCopyOptions::entry_filter
+    pub entry_filter: DirectoryEntryFilter,
+
+    /// Sets whether files are copied on multiple threads.
+    ///
+    /// This has the same impact as the [`parallelism`][dco-parallelism]
+    /// option under [`DirectoryCopyOptions`].
+    ///
+    ///
+    /// [dco-parallelism]: crate::directory::DirectoryCopyOptions::parallelism
+    pub parallelism: DirectoryCopyParallelism,
+
+    /// Which metadata of the source files and directories to apply to their copies.
+    /// A renamed directory always keeps all of its metadata.
+    ///
+    /// This has the same impact as the [`metadata_preservation`][dco-metadata_preservation]
+    /// option under [`DirectoryCopyOptions`].
+    ///
+    ///
+    /// [dco-metadata_preservation]: crate::directory::DirectoryCopyOptions::metadata_preservation
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Which extended attributes of the source files, directories and symbolic links
+    /// to copy to their copies. A renamed directory always keeps all of its extended attributes.
+    ///
+    /// This has the same impact as the [`extended_attributes`][dco-extended_attributes]
+    /// option under [`DirectoryCopyOptions`].
+    ///
+    ///
+    /// [dco-extended_attributes]: crate::directory::DirectoryCopyOptions::extended_attributes
+    pub extended_attributes: ExtendedAttributeFilter,
+
+    /// Whether copied files are read back and verified before the source directory is removed.
+    ///
+    /// This has the same impact as the [`verify`][dco-verify] option under [`DirectoryCopyOptions`].
+    ///
+    ///
+    /// [dco-verify]: crate::directory::DirectoryCopyOptions::verify
+    pub verify: CopyVerification,
+
+    /// Decides what to do with each file or symbolic link whose destination path already exists.
+    ///
+    /// This has the same impact as the [`collision_resolver`][dco-collision_resolver] option
+    /// under [`DirectoryCopyOptions`]. Source files that are skipped are kept
+    /// in the source directory.
+    ///
+    ///
+    /// [dco-collision_resolver]: crate::directory::DirectoryCopyOptions::collision_resolver
+    pub collision_resolver: Option<DirectoryCollisionResolver>,
+
+    /// Whether hard links between files inside the source directory are recreated
+    /// on the destination.
+    ///
+    /// This has the same impact as the [`hard_link_behaviour`][dco-hard_link_behaviour] option
+    /// under [`DirectoryCopyOptions`].
+    ///
+    ///
+    /// [dco-hard_link_behaviour]: crate::directory::DirectoryCopyOptions::hard_link_behaviour
+    pub hard_link_behaviour: HardLinkBehaviour,
 }
 
 impl Default for DirectoryMoveByCopyOptions {
     /// Initializes the default options for the copy-and-delete strategy when moving a directory:
-    /// - symbolic links are kept, and
-    /// - broken symbolic links are preserved as-is (i.e. kept broken).
+    /// - symbolic links are kept,
+    /// - broken symbolic links are preserved as-is (i.e. kept broken),
+    /// - a failed copy is not rolled back,
+    /// - all entries are moved,
+    /// - everything is copied on the calling thread,
+    /// - no metadata is preserved,
+    /// - no extended attributes are copied,
+    /// - copied files are not verified,
+    /// - there is no collision resolver, and
+    /// - hard-linked files are moved as independent files.
     fn default() -> Self {
         Self {
             symlink_behaviour: SymlinkBehaviour::Keep,
             broken_symlink_behaviour: BrokenSymlinkBehaviour::Keep,
+            rollback_behaviour: RollbackBehaviour::Disabled,
+            entry_filter: DirectoryEntryFilter::new(),
+            parallelism: DirectoryCopyParallelism::Sequential,
+            metadata_preservation: MetadataPreservation::NONE,
+            extended_attributes: ExtendedAttributeFilter::NONE,
+            verify: CopyVerification::DISABLED,
+            collision_resolver: None,
+            hard_link_behaviour: HardLinkBehaviour::CopySeparately,
         }
     }
 }
@@ -178,6 +302,14 @@ pub struct DirectoryMoveOptions {
     /// Sets the allowed directory move strategies.
     /// Per-strategy options are also configured here.
     pub allowed_strategies: DirectoryMoveAllowedStrategies,
+
+    /// How much of the move to sync to disk before returning.
+    ///
+    /// If the directory is renamed, only [`DurabilityLevel::FilesAndDirectories`] has an effect:
+    /// it syncs the directories that contained the source directory and now contain the destination
+    /// directory. If the directory is copied instead, the copy is synced (see
+    /// [`DirectoryCopyOptions::durability`]) before the source directory is removed.
+    pub durability: DurabilityLevel,
 }
 
 impl Default for DirectoryMoveOptions {
@@ -185,6 +317,7 @@ impl Default for DirectoryMoveOptions {
         Self {
             destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
             allowed_strategies: DirectoryMoveAllowedStrategies::default(),
+            durability: DurabilityLevel::None,
         }
     }
 }
@@ -242,7 +375,7 @@ pub struct DirectoryMoveFinished {
 
 
 /// Summarizes the contents of a directory for internal use.
-struct DirectoryContentDetails {
+pub(super) struct DirectoryContentDetails {
     /// Total size of the directory in bytes.
     pub(crate) total_bytes: u64,
 
@@ -260,10 +393,14 @@ struct DirectoryContentDetails {
 
 /// Scans the provided directory for auxiliary details (without a depth limit).
 /// This includes information like the total number of bytes it contains.
-fn collect_source_directory_details(
+///
+/// Entries skipped by `entry_filter` are not counted.
+pub(super) fn collect_source_directory_details(
     source_directory_path: &Path,
+    entry_filter: &DirectoryEntryFilter,
 ) -> Result<DirectoryContentDetails, MoveDirectoryPreparationError> {
-    let directory_statistics = collect_directory_statistics_via_scan(source_directory_path)?;
+    let directory_statistics =
+        collect_directory_statistics_via_scan(source_directory_path, entry_filter)?;
 
     Ok(DirectoryContentDetails {
         total_bytes: directory_statistics.total_bytes,
@@ -295,10 +432,13 @@ pub(crate) enum DirectoryMoveByRenameAction {
 ///
 /// Returns [`DirectoryMoveByRenameAction`], which indicates whether the move by rename
 /// succeeded or failed due to source and destination being on different mount points or drives.
-fn attempt_directory_move_by_rename(
+/// If the directory has been renamed, the directories containing the source and destination
+/// directory are synced to disk as far as `durability` requires.
+pub(super) fn attempt_directory_move_by_rename(
     validated_source_directory: &ValidatedSourceDirectory,
     source_directory_details: &DirectoryContentDetails,
     validated_destination_directory: &ValidatedDestinationDirectory,
+    durability: DurabilityLevel,
 ) -> Result<DirectoryMoveByRenameAction, MoveDirectoryExecutionError> {
     // We can attempt to simply rename the directory. This is much faster,
     // but will fail if the source and target paths aren't on the same mount point or filesystem
@@ -324,6 +464,12 @@ fn attempt_directory_move_by_rename(
         )
         .is_ok()
         {
+            sync_directories_after_rename(
+                validated_source_directory,
+                validated_destination_directory,
+                durability,
+            )?;
+
             return Ok(DirectoryMoveByRenameAction::Renamed {
                 finished_move: DirectoryMoveFinished {
                     total_bytes_moved: source_directory_details.total_bytes,
@@ -359,6 +505,12 @@ fn attempt_directory_move_by_rename(
             )
             .is_ok()
         {
+            sync_directories_after_rename(
+                validated_source_directory,
+                validated_destination_directory,
+                durability,
+            )?;
+
             return Ok(DirectoryMoveByRenameAction::Renamed {
                 finished_move: DirectoryMoveFinished {
                     total_bytes_moved: source_directory_details.total_bytes,
@@ -384,6 +536,104 @@ fn attempt_directory_move_by_rename(
 
 
 
+/// Syncs the directories that contained the source directory and now contain
+/// the destination directory, if `durability` requires it.
+fn sync_directories_after_rename(
+    validated_source_directory: &ValidatedSourceDirectory,
+    validated_destination_directory: &ValidatedDestinationDirectory,
+    durability: DurabilityLevel,
+) -> Result<(), MoveDirectoryExecutionError> {
+    let source_directory_path = &validated_source_directory.unfollowed_directory_path;
+    let destination_directory_path = &validated_destination_directory.directory_path;
+
+    sync_parent_directory_after_move(destination_directory_path, durability)?;
+
+    if source_directory_path.parent() != destination_directory_path.parent() {
+        sync_parent_directory_after_move(source_directory_path, durability)?;
+    }
+
+    Ok(())
+}
+
+
+/// Syncs the directory containing `path` if `durability` requires it,
+/// e.g. after a directory has been renamed into or removed from it.
+fn sync_parent_directory_after_move(
+    path: &Path,
+    durability: DurabilityLevel,
+) -> Result<(), MoveDirectoryExecutionError> {
+    durability.sync_parent_directory(path).map_err(|error| {
+        MoveDirectoryExecutionError::UnableToSyncDirectory {
+            directory_path: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            error,
+        }
+    })
+}
+
+
+/// Removes the source directory once it has been copied to the destination
+/// as part of a copy-and-delete move.
+///
+/// If the source directory path was a symbolic link to a directory,
+/// only the link is removed, not the directory it points to.
+///
+/// Entries skipped by `entry_filter` or listed in `skipped_source_paths`
+/// (and the directories containing them) are kept.
+pub(super) fn remove_source_directory_after_copy(
+    validated_source_directory: &ValidatedSourceDirectory,
+    source_directory_path: &Path,
+    entry_filter: &DirectoryEntryFilter,
+    skipped_source_paths: &[PathBuf],
+    durability: DurabilityLevel,
+) -> Result<(), MoveDirectoryExecutionError> {
+    let directory_path_to_remove =
+        if validated_source_directory.original_path_was_symlink_to_directory {
+            source_directory_path
+        } else {
+            validated_source_directory.directory_path.as_path()
+        };
+
+    let prepared_removal = DirectoryRemovePrepared::prepare_with_filter(
+        directory_path_to_remove,
+        DirectoryRemoveOptions::default(),
+        &source_removal_filter(
+            entry_filter,
+            &validated_source_directory.directory_path,
+            skipped_source_paths,
+        ),
+    )
+    .map_err(|error| MoveDirectoryExecutionError::SourceRemovalError(error.into()))?;
+
+    remove_directory_unchecked(prepared_removal)
+        .map_err(|error| MoveDirectoryExecutionError::SourceRemovalError(error.into()))?;
+
+    sync_parent_directory_after_move(directory_path_to_remove, durability)
+}
+
+
+/// Returns the filter to remove the source directory with after a copy-and-delete move.
+///
+/// Source entries whose copy was skipped because of a collision
+/// (`skipped_source_paths`) are excluded from the removal, on top of those skipped by `entry_filter`.
+fn source_removal_filter(
+    entry_filter: &DirectoryEntryFilter,
+    source_directory_path: &Path,
+    skipped_source_paths: &[PathBuf],
+) -> DirectoryEntryFilter {
+    let skipped_relative_paths = skipped_source_paths
+        .iter()
+        .filter_map(|skipped_source_path| {
+            skipped_source_path
+                .strip_prefix(source_directory_path)
+                .ok()
+                .map(Path::to_path_buf)
+        })
+        .collect::<HashSet<_>>();
+
+    entry_filter.excluding_relative_paths(skipped_relative_paths)
+}
+
+
 /// Moves a directory from the source to the destination directory.
 ///
 /// `source_directory_path` must point to an existing directory.
@@ -482,15 +732,31 @@ where
     .map_err(MoveDirectoryPreparationError::DestinationDirectoryValidationError)?;
 
 
-    let source_details =
-        collect_source_directory_details(&validated_source_directory.directory_path)?;
+    let allowed_to_rename = options.allowed_strategies.allowed_to_rename();
+    let copy_and_delete_options = options
+        .allowed_strategies
+        .into_options_if_allowed_to_copy_and_delete();
+
+    let entry_filter = copy_and_delete_options
+        .as_ref()
+        .map(|copy_and_delete_options| copy_and_delete_options.entry_filter.clone())
+        .unwrap_or_default();
+
+
+    let source_details = collect_source_directory_details(
+        &validated_source_directory.directory_path,
+        &entry_filter,
+    )?;
 
 
-    if options.allowed_strategies.allowed_to_rename() {
+    // Renaming always moves the entire directory, which is why it is not attempted
+    // if some entries must be left behind.
+    if allowed_to_rename && entry_filter.includes_everything() {
         match attempt_directory_move_by_rename(
             &validated_source_directory,
             &source_details,
             &validated_destination_directory,
+            options.durability,
         )? {
             DirectoryMoveByRenameAction::Renamed { finished_move } => {
                 return Ok(finished_move);
@@ -500,10 +766,7 @@ where
     }
 
 
-    let Some(copy_and_delete_options) = options
-        .allowed_strategies
-        .into_options_if_allowed_to_copy_and_delete()
-    else {
+    let Some(copy_and_delete_options) = copy_and_delete_options else {
         // This branch can execute only when a rename was attempted and failed,
         // and the user disabled the copy-and-delete fallback strategy.
         return Err(MoveDirectoryError::ExecutionError(
@@ -514,41 +777,62 @@ where
 
     // At this point a simple rename was either impossible or failed,
     // but the copy-and-delete fallback is enabled, so we should do that.
-    let prepared_copy = DirectoryCopyPrepared::prepare_with_validated(
+    let destination_directory_rule = match copy_and_delete_options.collision_resolver {
+        Some(_) => options.destination_directory_rule.for_collision_resolver(),
+        None => options.destination_directory_rule,
+    };
+
+    let mut prepared_copy = DirectoryCopyPrepared::prepare_with_validated(
         validated_source_directory.clone(),
         validated_destination_directory,
-        options.destination_directory_rule,
+        destination_directory_rule,
         DirectoryCopyDepthLimit::Unlimited,
         copy_and_delete_options.symlink_behaviour,
         copy_and_delete_options.broken_symlink_behaviour,
+        &entry_filter,
     )
     .map_err(MoveDirectoryPreparationError::CopyPlanningError)?;
 
+    if let Some(collision_resolver) = copy_and_delete_options.collision_resolver.as_ref() {
+        prepared_copy
+            .resolve_collisions_with_resolver(collision_resolver)
+            .map_err(MoveDirectoryPreparationError::CopyPlanningError)?;
+    }
+
+    prepared_copy.preserve_hard_links(copy_and_delete_options.hard_link_behaviour);
+
+    let skipped_source_paths = prepared_copy.skipped_source_paths.clone();
+
     copy_directory_unchecked(
         prepared_copy,
         DirectoryCopyOptions {
-            destination_directory_rule: options.destination_directory_rule,
+            destination_directory_rule,
             copy_depth_limit: DirectoryCopyDepthLimit::Unlimited,
             symlink_behaviour: copy_and_delete_options.symlink_behaviour,
             broken_symlink_behaviour: copy_and_delete_options.broken_symlink_behaviour,
+            rollback_behaviour: copy_and_delete_options.rollback_behaviour,
+            entry_filter: entry_filter.clone(),
+            parallelism: copy_and_delete_options.parallelism,
+            reflink_behaviour: ReflinkBehaviour::Never,
+            metadata_preservation: copy_and_delete_options.metadata_preservation,
+            extended_attributes: copy_and_delete_options.extended_attributes,
+            verify: copy_and_delete_options.verify,
+            resume_journal_path: None,
+            atomic: false,
+            durability: options.durability,
+            collision_resolver: None,
+            hard_link_behaviour: copy_and_delete_options.hard_link_behaviour,
         },
     )
     .map_err(MoveDirectoryExecutionError::CopyDirectoryError)?;
 
-
-    let directory_path_to_remove =
-        if validated_source_directory.original_path_was_symlink_to_directory {
-            source_directory_path.as_ref()
-        } else {
-            validated_source_directory.directory_path.as_path()
-        };
-
-    fs::remove_dir_all(directory_path_to_remove).map_err(|error| {
-        MoveDirectoryExecutionError::UnableToAccessSource {
-            path: validated_source_directory.directory_path,
-            error,
-        }
-    })?;
+    remove_source_directory_after_copy(
+        &validated_source_directory,
+        source_directory_path.as_ref(),
+        &entry_filter,
+        &skipped_source_paths,
+        options.durability,
+    )?;
 
 
     Ok(DirectoryMoveFinished {
@@ -609,6 +893,91 @@ pub struct DirectoryMoveWithProgressByCopyOptions {
     ///
     /// *Note that the real reporting interval can be larger.*
     pub progress_update_byte_interval: u64,
+
+    /// Sets whether changes made to the destination are undone if copying fails midway
+    /// (this includes the move being cancelled while copying).
+    ///
+    /// Only the copying phase can be rolled back: if the source directory
+    /// cannot be fully removed after it has been copied, the copy is kept.
+    ///
+    /// This has the same impact as the [`rollback_behaviour`][dco-rollback_behaviour] option
+    /// under [`DirectoryCopyWithProgressOptions`].
+    ///
+    ///
+    /// [dco-rollback_behaviour]: crate::directory::DirectoryCopyWithProgressOptions::rollback_behaviour
+    pub rollback_behaviour: RollbackBehaviour,
+
+    /// Decides which entries of the source directory are moved.
+    ///
+    /// Entries skipped by the filter are left in the source directory, together with
+    /// the directories that contain them. If the filter skips anything (see
+    /// [`DirectoryEntryFilter::includes_everything`]), the rename strategy is never attempted,
+    /// as it would move the entire directory.
+    ///
+    /// This has the same impact as the [`entry_filter`][dco-entry_filter] option
+    /// under [`DirectoryCopyWithProgressOptions`].
+    ///
+    ///
+    /// [dco-entry_filter]: crate::directory::DirectoryCopyWithProgressOptions::entry_filter
+    pub entry_filter: DirectoryEntryFilter,
+
+    /// Sets whether files are copied on multiple threads.
+    ///
+    /// This has the same impact as the [`parallelism`][dco-parallelism] option
+    /// under [`DirectoryCopyWithProgressOptions`].
+    ///
+    ///
+    /// [dco-parallelism]: crate::directory::DirectoryCopyWithProgressOptions::parallelism
+    pub parallelism: DirectoryCopyParallelism,
+
+    /// Which metadata of the source files and directories to apply to their copies.
+    /// A renamed directory always keeps all of its metadata.
+    ///
+    /// This has the same impact as the [`metadata_preservation`][dco-metadata_preservation] option
+    /// under [`DirectoryCopyWithProgressOptions`].
+    ///
+    ///
+    /// [dco-metadata_preservation]: crate::directory::DirectoryCopyWithProgressOptions::metadata_preservation
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Which extended attributes of the source files, directories and symbolic links
+    /// to copy to their copies. A renamed directory always keeps all of its extended attributes.
+    ///
+    /// This has the same impact as the [`extended_attributes`][dco-extended_attributes] option
+    /// under [`DirectoryCopyWithProgressOptions`].
+    ///
+    ///
+    /// [dco-extended_attributes]: crate::directory::DirectoryCopyWithProgressOptions::extended_attributes
+    pub extended_attributes: ExtendedAttributeFilter,
+
+    /// Whether copied files are read back and verified before the source directory is removed.
+    ///
+    /// This has the same impact as the [`verify`][dco-verify] option
+    /// under [`DirectoryCopyWithProgressOptions`].
+    ///
+    ///
+    /// [dco-verify]: crate::directory::DirectoryCopyWithProgressOptions::verify
+    pub verify: CopyVerification,
+
+    /// Decides what to do with each file or symbolic link whose destination path already exists.
+    ///
+    /// This has the same impact as the [`collision_resolver`][dco-collision_resolver] option
+    /// under [`DirectoryCopyWithProgressOptions`]. Source files that are skipped are kept
+    /// in the source directory.
+    ///
+    ///
+    /// [dco-collision_resolver]: crate::directory::DirectoryCopyWithProgressOptions::collision_resolver
+    pub collision_resolver: Option<DirectoryCollisionResolver>,
+
+    /// Whether hard links between files inside the source directory are recreated
+    /// on the destination.
+    ///
+    /// This has the same impact as the [`hard_link_behaviour`][dco-hard_link_behaviour] option
+    /// under [`DirectoryCopyWithProgressOptions`].
+    ///
+    ///
+    /// [dco-hard_link_behaviour]: crate::directory::DirectoryCopyWithProgressOptions::hard_link_behaviour
+    pub hard_link_behaviour: HardLinkBehaviour,
 }
 
 impl Default for DirectoryMoveWithProgressByCopyOptions {
@@ -619,6 +988,14 @@ impl Default for DirectoryMoveWithProgressByCopyOptions {
             read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
             write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
             progress_update_byte_interval: DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
+            rollback_behaviour: RollbackBehaviour::Disabled,
+            entry_filter: DirectoryEntryFilter::new(),
+            parallelism: DirectoryCopyParallelism::Sequential,
+            metadata_preservation: MetadataPreservation::NONE,
+            extended_attributes: ExtendedAttributeFilter::NONE,
+            verify: CopyVerification::DISABLED,
+            collision_resolver: None,
+            hard_link_behaviour: HardLinkBehaviour::CopySeparately,
         }
     }
 }
@@ -730,6 +1107,14 @@ pub struct DirectoryMoveWithProgressOptions {
     /// Sets the allowed directory move strategies.
     /// Per-strategy options are also configured here.
     pub allowed_strategies: DirectoryMoveWithProgressAllowedStrategies,
+
+    /// How much of the move to sync to disk before returning.
+    ///
+    /// If the directory is renamed, only [`DurabilityLevel::FilesAndDirectories`] has an effect:
+    /// it syncs the directories that contained the source directory and now contain the destination
+    /// directory. If the directory is copied instead, the copy is synced (see
+    /// [`DirectoryCopyWithProgressOptions::durability`]) before the source directory is removed.
+    pub durability: DurabilityLevel,
 }
 
 impl Default for DirectoryMoveWithProgressOptions {
@@ -737,6 +1122,7 @@ impl Default for DirectoryMoveWithProgressOptions {
         Self {
             destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
             allowed_strategies: DirectoryMoveWithProgressAllowedStrategies::default(),
+            durability: DurabilityLevel::None,
         }
     }
 }
@@ -769,6 +1155,12 @@ pub enum DirectoryMoveOperation {
         destination_symbolic_link_file_path: PathBuf,
     },
 
+    /// Describes a hard link being created (see [`HardLinkBehaviour::Preserve`]).
+    CreatingHardLink {
+        /// Path to the hard link being created.
+        destination_hard_link_path: PathBuf,
+    },
+
     /// Describes removal of the source directory.
     /// This happens at the very end when moving a directory.
     RemovingSourceDirectory,
@@ -889,6 +1281,18 @@ pub struct DirectoryMoveProgress {
 /// If the move can be performed by renaming the directory, only one progress report will be emitted.
 ///
 ///
+/// ### Cancellation
+/// The `progress_handler` closure can return either `()`, a [`ProgressAction`],
+/// or a [`ControlFlow<()>`](std::ops::ControlFlow).
+/// If the directory is being moved by copy-and-delete, returning [`ProgressAction::Abort`]
+/// stops the move as soon as possible and returns [`MoveDirectoryExecutionError::Cancelled`].
+/// In that case, the source directory is left untouched; entries that have already been copied
+/// are left in the destination directory, but a partially copied file is removed.
+///
+/// If the move was performed by renaming the directory, the only progress report is emitted
+/// after the move has already completed, so there is nothing left to cancel.
+///
+///
 /// <br>
 ///
 /// #### See also
@@ -903,7 +1307,7 @@ pub struct DirectoryMoveProgress {
 /// [`progress_update_byte_interval`]: DirectoryMoveWithProgressByCopyOptions::progress_update_byte_interval
 /// [`DisallowExisting`]: DestinationDirectoryRule::DisallowExisting
 /// [`AllowEmpty`]: DestinationDirectoryRule::AllowEmpty
-pub fn move_directory_with_progress<S, T, F>(
+pub fn move_directory_with_progress<S, T, F, R>(
     source_directory_path: S,
     target_directory_path: T,
     options: DirectoryMoveWithProgressOptions,
@@ -912,7 +1316,8 @@ pub fn move_directory_with_progress<S, T, F>(
 where
     S: AsRef<Path>,
     T: AsRef<Path>,
-    F: FnMut(&DirectoryMoveProgress),
+    F: FnMut(&DirectoryMoveProgress) -> R,
+    R: Into<ProgressAction>,
 {
     let validated_source_directory = validate_source_directory_path(source_directory_path.as_ref())
         .map_err(MoveDirectoryPreparationError::SourceDirectoryValidationError)?;
@@ -930,20 +1335,35 @@ where
     .map_err(MoveDirectoryPreparationError::DestinationDirectoryValidationError)?;
 
 
-    let source_details =
-        collect_source_directory_details(&validated_source_directory.directory_path)?;
+    let allowed_to_rename = options.allowed_strategies.allowed_to_rename();
+    let copy_and_delete_options = options
+        .allowed_strategies
+        .into_options_if_allowed_to_copy_and_delete();
+
+    let entry_filter = copy_and_delete_options
+        .as_ref()
+        .map(|copy_and_delete_options| copy_and_delete_options.entry_filter.clone())
+        .unwrap_or_default();
+
+
+    let source_details = collect_source_directory_details(
+        &validated_source_directory.directory_path,
+        &entry_filter,
+    )?;
 
 
     // We'll first attempt to move the directory by renaming it.
     // If we don't succeed (e.g. source and target paths are on different drives),
-    // we'll copy and delete instead.
+    // we'll copy and delete instead. Renaming always moves the entire directory,
+    // which is why it is not attempted if some entries must be left behind.
 
 
-    if options.allowed_strategies.allowed_to_rename() {
+    if allowed_to_rename && entry_filter.includes_everything() {
         match attempt_directory_move_by_rename(
             &validated_source_directory,
             &source_details,
             &validated_destination_directory,
+            options.durability,
         )? {
             DirectoryMoveByRenameAction::Renamed { finished_move } => {
                 let final_progress_report = DirectoryMoveProgress {
@@ -959,7 +1379,8 @@ where
                     total_operations: 2,
                 };
 
-                progress_handler(&final_progress_report);
+                // The directory has already been moved, so the returned action is irrelevant.
+                let _ = progress_handler(&final_progress_report);
 
 
                 return Ok(finished_move);
@@ -968,10 +1389,7 @@ where
         };
     }
 
-    let Some(copy_and_delete_options) = options
-        .allowed_strategies
-        .into_options_if_allowed_to_copy_and_delete()
-    else {
+    let Some(copy_and_delete_options) = copy_and_delete_options else {
         // This branch can execute only when a rename was attempted and failed,
         // and the user disabled the copy-and-delete fallback strategy.
         return Err(MoveDirectoryError::ExecutionError(
@@ -983,26 +1401,60 @@ where
     // At this point a simple rename was either impossible or failed.
     // We need to copy and delete instead.
 
+    let destination_directory_rule = match copy_and_delete_options.collision_resolver {
+        Some(_) => options.destination_directory_rule.for_collision_resolver(),
+        None => options.destination_directory_rule,
+    };
+
     let copy_options = DirectoryCopyWithProgressOptions {
-        destination_directory_rule: options.destination_directory_rule,
+        destination_directory_rule,
         read_buffer_size: copy_and_delete_options.read_buffer_size,
         write_buffer_size: copy_and_delete_options.write_buffer_size,
         progress_update_byte_interval: copy_and_delete_options.progress_update_byte_interval,
         copy_depth_limit: DirectoryCopyDepthLimit::Unlimited,
         symlink_behaviour: copy_and_delete_options.symlink_behaviour,
         broken_symlink_behaviour: copy_and_delete_options.broken_symlink_behaviour,
+        rollback_behaviour: copy_and_delete_options.rollback_behaviour,
+        entry_filter: entry_filter.clone(),
+        parallelism: copy_and_delete_options.parallelism,
+        reflink_behaviour: ReflinkBehaviour::Never,
+        metadata_preservation: copy_and_delete_options.metadata_preservation,
+        extended_attributes: copy_and_delete_options.extended_attributes,
+        sparse_behaviour: SparseFileBehaviour::Never,
+        verify: copy_and_delete_options.verify,
+        resume_journal_path: None,
+        atomic: false,
+        durability: options.durability,
+        collision_resolver: None,
+        hard_link_behaviour: copy_and_delete_options.hard_link_behaviour,
     };
 
-    let prepared_copy = DirectoryCopyPrepared::prepare_with_validated(
+    let mut prepared_copy = DirectoryCopyPrepared::prepare_with_validated(
         validated_source_directory.clone(),
         validated_destination_directory,
         copy_options.destination_directory_rule,
         copy_options.copy_depth_limit,
         copy_and_delete_options.symlink_behaviour,
         copy_and_delete_options.broken_symlink_behaviour,
+        &entry_filter,
     )
     .map_err(MoveDirectoryPreparationError::CopyPlanningError)?;
 
+    if let Some(collision_resolver) = copy_and_delete_options.collision_resolver.as_ref() {
+        prepared_copy
+            .resolve_collisions_with_resolver(collision_resolver)
+            .map_err(MoveDirectoryPreparationError::CopyPlanningError)?;
+    }
+
+    prepared_copy.preserve_hard_links(copy_and_delete_options.hard_link_behaviour);
+
+    let skipped_source_paths = prepared_copy.skipped_source_paths.clone();
+
+
+    // The copy-and-delete strategy has one additional operation at the end:
+    // removing the source directory.
+    let total_operations = prepared_copy.operation_queue.len() + 1;
+    let bytes_total = prepared_copy.total_bytes;
 
     let directory_copy_result = execute_prepared_copy_directory_with_progress_unchecked(
         prepared_copy,
@@ -1024,6 +1476,11 @@ where
                 } => DirectoryMoveOperation::CreatingSymbolicLink {
                     destination_symbolic_link_file_path,
                 },
+                DirectoryCopyOperation::CreatingHardLink {
+                    destination_hard_link_path,
+                } => DirectoryMoveOperation::CreatingHardLink {
+                    destination_hard_link_path,
+                },
             };
 
 
@@ -1032,15 +1489,29 @@ where
                 bytes_finished: progress.bytes_finished,
                 current_operation: move_operation,
                 current_operation_index: progress.current_operation_index,
-                total_operations: progress.total_operations,
+                total_operations,
                 files_moved: progress.files_copied,
                 directories_created: progress.directories_created,
             };
 
-            progress_handler(&move_progress)
+            progress_handler(&move_progress).into()
         },
     )
-    .map_err(MoveDirectoryExecutionError::CopyDirectoryError)?;
+    .map_err(|error| match error {
+        CopyDirectoryExecutionError::Cancelled {
+            bytes_finished,
+            bytes_total,
+            files_copied,
+            directories_created,
+            ..
+        } => MoveDirectoryExecutionError::Cancelled {
+            bytes_finished,
+            bytes_total,
+            files_moved: files_copied,
+            directories_created,
+        },
+        other_error => MoveDirectoryExecutionError::CopyDirectoryError(other_error),
+    })?;
 
 
     // Having fully copied the directory to the target, we now
@@ -1052,12 +1523,43 @@ where
             validated_source_directory.directory_path.as_path()
         };
 
-    fs::remove_dir_all(directory_path_to_remove).map_err(|error| {
-        MoveDirectoryExecutionError::UnableToAccessSource {
-            path: validated_source_directory.directory_path,
-            error,
-        }
-    })?;
+    let prepared_removal = DirectoryRemovePrepared::prepare_with_filter(
+        directory_path_to_remove,
+        DirectoryRemoveOptions::default(),
+        &source_removal_filter(
+            &entry_filter,
+            &validated_source_directory.directory_path,
+            &skipped_source_paths,
+        ),
+    )
+    .map_err(|error| MoveDirectoryExecutionError::SourceRemovalError(error.into()))?;
+
+    let action: ProgressAction = progress_handler(&DirectoryMoveProgress {
+        bytes_total,
+        bytes_finished: directory_copy_result.total_bytes_copied,
+        files_moved: directory_copy_result.files_copied,
+        directories_created: directory_copy_result.directories_created,
+        current_operation: DirectoryMoveOperation::RemovingSourceDirectory,
+        current_operation_index: total_operations - 1,
+        total_operations,
+    })
+    .into();
+
+    if action.is_abort() {
+        return Err(MoveDirectoryError::ExecutionError(
+            MoveDirectoryExecutionError::Cancelled {
+                bytes_finished: directory_copy_result.total_bytes_copied,
+                bytes_total,
+                files_moved: directory_copy_result.files_copied,
+                directories_created: directory_copy_result.directories_created,
+            },
+        ));
+    }
+
+    remove_directory_unchecked(prepared_removal)
+        .map_err(|error| MoveDirectoryExecutionError::SourceRemovalError(error.into()))?;
+
+    sync_parent_directory_after_move(directory_path_to_remove, options.durability)?;
 
 
     Ok(DirectoryMoveFinished {
@@ -0,0 +1,774 @@
+use std::{
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+        Mutex,
+        MutexGuard,
+        PoisonError,
+    },
+    thread,
+};
+
+use super::{
+    copy::{
+        check_destination_file_before_copy,
+        check_destination_symlink_before_creation,
+        create_symlink_and_record_in_journal,
+        execute_create_directory_operation_with_progress,
+        execute_create_hard_link_operation_with_progress,
+        execute_queued_operation,
+        DirectoryCopyInternalProgress,
+        SymlinkCreationInfo,
+    },
+    prepared::{DirectoryCopyPrepared, QueuedOperation},
+    resume::ResumeJournalEntry,
+    rollback::DirectoryCopyJournal,
+    DirectoryCopyFinished,
+    DirectoryCopyOperation,
+    DirectoryCopyOptions,
+    DirectoryCopyProgressRef,
+    DirectoryCopyWithProgressOptions,
+};
+use crate::{
+    error::{CopyDirectoryExecutionError, FileError},
+    file::{
+        copy_file_with_progress,
+        CollidingFileBehaviour,
+        FileCopyWithProgressOptions,
+        FileProgress,
+        FileProgressPhase,
+    },
+    ProgressAction,
+};
+
+
+/// How many threads are used to execute a directory copy (or a move by copy-and-delete).
+///
+/// See also: [`DirectoryCopyOptions::parallelism`][crate::directory::DirectoryCopyOptions::parallelism].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DirectoryCopyParallelism {
+    /// All operations are executed one after another on the calling thread.
+    ///
+    /// This is the default.
+    Sequential,
+
+    /// All directories are first created on the calling thread, after which files
+    /// are copied and symbolic links are created by `worker_threads` threads in parallel.
+    /// Hard links (see [`HardLinkBehaviour::Preserve`][super::HardLinkBehaviour::Preserve])
+    /// are created on the calling thread once all files have been copied.
+    ///
+    /// This can significantly speed up copying many small files, especially on fast
+    /// solid-state drives and on network filesystems. Only threads from the standard library are used,
+    /// and they are all joined before the copy function returns.
+    ///
+    /// As soon as one of the workers fails, the other workers stop (interrupting file copies
+    /// in progress when progress reporting is used) and the first error is returned.
+    /// Rollback (see [`RollbackBehaviour`][super::RollbackBehaviour]) is supported as well.
+    ///
+    /// Note that in this mode, the order in which files are copied is not deterministic,
+    /// and progress reports describe the operation of whichever worker
+    /// triggered the report, while byte and entry counts are aggregated across all workers.
+    Parallel {
+        /// Number of worker threads that copy files and create symbolic links.
+        worker_threads: NonZeroUsize,
+    },
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for DirectoryCopyParallelism {
+    fn default() -> Self {
+        Self::Sequential
+    }
+}
+
+
+
+/// State shared between the workers of a parallel directory copy.
+struct SharedWorkerState {
+    /// Operations that have not been picked up by any worker yet.
+    pending_operations: Mutex<std::vec::IntoIter<QueuedOperation>>,
+
+    /// Set once workers should stop picking up new operations
+    /// (and interrupt their current file copy, if possible).
+    should_stop: AtomicBool,
+
+    /// The first error any of the workers encountered.
+    first_error: Mutex<Option<CopyDirectoryExecutionError>>,
+}
+
+impl SharedWorkerState {
+    fn new(operations: Vec<QueuedOperation>) -> Self {
+        Self {
+            pending_operations: Mutex::new(operations.into_iter()),
+            should_stop: AtomicBool::new(false),
+            first_error: Mutex::new(None),
+        }
+    }
+
+    /// Returns the next pending operation, or `None` if there are none left
+    /// or if the workers should stop.
+    fn next_operation(&self) -> Option<QueuedOperation> {
+        if self.should_stop() {
+            return None;
+        }
+
+        lock_ignoring_poison(&self.pending_operations).next()
+    }
+
+    fn should_stop(&self) -> bool {
+        self.should_stop.load(Ordering::Acquire)
+    }
+
+    fn stop(&self) {
+        self.should_stop.store(true, Ordering::Release);
+    }
+
+    /// Stops all workers, keeping the `error` only if no other worker has failed before.
+    fn stop_with_error(&self, error: CopyDirectoryExecutionError) {
+        let mut first_error = lock_ignoring_poison(&self.first_error);
+
+        if first_error.is_none() {
+            *first_error = Some(error);
+        }
+
+        self.stop();
+    }
+
+    fn into_first_error(self) -> Option<CopyDirectoryExecutionError> {
+        self.first_error
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Locks the `mutex`. If a worker panicked while holding the lock, the panic will
+/// be propagated once the worker is joined, so we can keep using the (still consistent) value.
+fn lock_ignoring_poison<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Joins a worker thread, propagating its panic, if any.
+fn join_worker<T>(worker_handle: thread::ScopedJoinHandle<'_, T>) -> T {
+    worker_handle
+        .join()
+        .unwrap_or_else(|panic_payload| std::panic::resume_unwind(panic_payload))
+}
+
+
+/// Splits the operation queue into directory creations and all other operations,
+/// preserving the relative order of both.
+fn split_off_directory_creations(
+    operation_queue: Vec<QueuedOperation>,
+) -> (Vec<QueuedOperation>, Vec<QueuedOperation>) {
+    operation_queue
+        .into_iter()
+        .partition(|operation| matches!(operation, QueuedOperation::CreateDirectory { .. }))
+}
+
+
+/// Splits the operation queue into hard link creations and all other operations,
+/// preserving the relative order of both.
+///
+/// A hard link can only be created once the file it points to has been copied,
+/// which is why hard links are created after all workers have finished.
+fn split_off_hard_link_creations(
+    operation_queue: Vec<QueuedOperation>,
+) -> (Vec<QueuedOperation>, Vec<QueuedOperation>) {
+    operation_queue
+        .into_iter()
+        .partition(|operation| matches!(operation, QueuedOperation::CreateHardLink { .. }))
+}
+
+
+fn add_to_finished_copy(total: &mut DirectoryCopyFinished, worker_total: DirectoryCopyFinished) {
+    total.total_bytes_copied += worker_total.total_bytes_copied;
+    total.files_copied += worker_total.files_copied;
+    total.symlinks_created += worker_total.symlinks_created;
+    total.directories_created += worker_total.directories_created;
+    total.hard_links_created += worker_total.hard_links_created;
+}
+
+
+
+/// Perform a copy using prepared data from [`DirectoryCopyPrepared`], copying files
+/// and creating symbolic links on `worker_threads` threads.
+///
+/// Every change to the destination is recorded in the provided `journal`.
+pub(super) fn copy_directory_in_parallel_with_journal(
+    prepared_directory_copy: DirectoryCopyPrepared,
+    options: &DirectoryCopyOptions,
+    worker_threads: NonZeroUsize,
+    journal: &mut DirectoryCopyJournal,
+) -> Result<DirectoryCopyFinished, CopyDirectoryExecutionError> {
+    let mut finished_copy = prepared_directory_copy.resumed_copy();
+
+    let (directory_operations, remaining_operations) =
+        split_off_directory_creations(prepared_directory_copy.operation_queue);
+    let (hard_link_operations, remaining_operations) =
+        split_off_hard_link_creations(remaining_operations);
+
+
+    // A directory must exist before anything is copied into it, so all directories
+    // are created up front, in the order they were queued in (i.e. parents first).
+    for operation in directory_operations {
+        execute_queued_operation(operation, options, journal, &mut finished_copy)?;
+    }
+
+
+    let shared_state = SharedWorkerState::new(remaining_operations);
+    let main_journal = &*journal;
+
+    let worker_outcomes = thread::scope(|scope| {
+        let worker_handles = (0..worker_threads.get())
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut worker_journal = main_journal.new_for_worker();
+                    let mut worker_finished_copy = DirectoryCopyFinished {
+                        total_bytes_copied: 0,
+                        files_copied: 0,
+                        symlinks_created: 0,
+                        directories_created: 0,
+                        files_copied_to_alternative_paths: Vec::new(),
+                        hard_links_created: 0,
+                    };
+
+                    while let Some(operation) = shared_state.next_operation() {
+                        if let Err(error) = execute_queued_operation(
+                            operation,
+                            options,
+                            &mut worker_journal,
+                            &mut worker_finished_copy,
+                        ) {
+                            shared_state.stop_with_error(error);
+                            break;
+                        }
+                    }
+
+                    (worker_finished_copy, worker_journal)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        worker_handles
+            .into_iter()
+            .map(join_worker)
+            .collect::<Vec<_>>()
+    });
+
+
+    // Changes made by the workers are independent of each other, but depend on
+    // the directories created above, so they are appended (and would be undone first).
+    for (worker_finished_copy, worker_journal) in worker_outcomes {
+        add_to_finished_copy(&mut finished_copy, worker_finished_copy);
+        journal.append(worker_journal);
+    }
+
+    if let Some(error) = shared_state.into_first_error() {
+        return Err(error);
+    }
+
+
+    for operation in hard_link_operations {
+        execute_queued_operation(operation, options, journal, &mut finished_copy)?;
+    }
+
+
+    Ok(finished_copy)
+}
+
+
+
+/// An operation a worker has finished executing.
+enum FinishedWorkerOperation {
+    CopiedFile { bytes_copied: u64 },
+
+    CreatedSymlink { size_bytes: u64 },
+}
+
+
+/// A progress event sent from a worker to the thread that reports progress.
+enum WorkerEvent {
+    /// The worker has started executing an operation.
+    OperationStarted {
+        worker_index: usize,
+        operation: DirectoryCopyOperation,
+    },
+
+    /// The worker has made progress copying a file.
+    FileCopyProgressed {
+        worker_index: usize,
+        file_progress: FileProgress,
+    },
+
+    /// The worker has finished executing an operation.
+    OperationFinished {
+        worker_index: usize,
+        finished_operation: FinishedWorkerOperation,
+    },
+}
+
+
+/// Everything a worker of a parallel copy with progress tracking needs access to.
+struct WorkerContext<'w> {
+    worker_index: usize,
+
+    options: &'w DirectoryCopyWithProgressOptions,
+
+    shared_state: &'w SharedWorkerState,
+
+    /// Where progress events are sent to. The receiver outlives all workers,
+    /// so sending never fails.
+    events: mpsc::Sender<WorkerEvent>,
+}
+
+impl WorkerContext<'_> {
+    fn send_event(&self, event: WorkerEvent) {
+        let _ = self.events.send(event);
+    }
+}
+
+
+/// Copies a file on a worker thread, sending progress events along the way.
+///
+/// Returns `Ok(None)` if the copy was interrupted because the workers should stop.
+fn copy_file_on_worker(
+    context: &WorkerContext,
+    source_file_path: PathBuf,
+    source_size_bytes: u64,
+    destination_file_path: PathBuf,
+    journal: &mut DirectoryCopyJournal,
+) -> Result<Option<FinishedWorkerOperation>, CopyDirectoryExecutionError> {
+    let options = context.options;
+
+    let is_left_over_file = journal.is_left_over_file(&destination_file_path);
+
+    let destination_file_exists = check_destination_file_before_copy(
+        &destination_file_path,
+        is_left_over_file
+            || options
+                .destination_directory_rule
+                .allows_overwriting_existing_destination_files(),
+    )?;
+
+
+    context.send_event(WorkerEvent::OperationStarted {
+        worker_index: context.worker_index,
+        operation: DirectoryCopyOperation::CopyingFile {
+            destination_file_path: destination_file_path.clone(),
+            progress: FileProgress {
+                bytes_finished: 0,
+                bytes_written: 0,
+                bytes_total: source_size_bytes,
+                phase: FileProgressPhase::Copying,
+            },
+        },
+    });
+
+
+    journal
+        .prepare_file_write(&destination_file_path, destination_file_exists)
+        .map_err(|error| CopyDirectoryExecutionError::UnableToAccessDestination {
+            path: destination_file_path.clone(),
+            error,
+        })?;
+
+    journal.persist(ResumeJournalEntry::CopyingFile(&destination_file_path))?;
+
+
+    let mut bytes_copied = 0;
+
+    let file_copy_result = copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            colliding_file_behaviour: match is_left_over_file {
+                true => CollidingFileBehaviour::Overwrite,
+                false => options
+                    .destination_directory_rule
+                    .colliding_file_behaviour(),
+            },
+            read_buffer_size: options.read_buffer_size,
+            write_buffer_size: options.write_buffer_size,
+            progress_update_byte_interval: options.progress_update_byte_interval,
+            reflink_behaviour: options.reflink_behaviour,
+            metadata_preservation: options.metadata_preservation,
+            extended_attributes: options.extended_attributes,
+            sparse_behaviour: options.sparse_behaviour,
+            verify: options.verify,
+            resume_behaviour: journal
+                .resume_behaviour_for_file(&destination_file_path, destination_file_exists),
+            atomic: options.atomic,
+            durability: journal.durability_for_file(options.durability),
+        },
+        |file_progress| {
+            bytes_copied = file_progress.bytes_finished;
+
+            context.send_event(WorkerEvent::FileCopyProgressed {
+                worker_index: context.worker_index,
+                file_progress: file_progress.clone(),
+            });
+
+            match context.shared_state.should_stop() {
+                true => ProgressAction::Abort,
+                false => ProgressAction::Continue,
+            }
+        },
+    );
+
+
+    match file_copy_result {
+        Ok(_) => {
+            journal.persist(ResumeJournalEntry::CopiedFile(&destination_file_path))?;
+
+            Ok(Some(FinishedWorkerOperation::CopiedFile { bytes_copied }))
+        }
+        // The partially copied file has already been removed (or will be resumed).
+        Err(FileError::Cancelled { .. }) => Ok(None),
+        Err(file_error) => Err(CopyDirectoryExecutionError::FileCopyError {
+            file_path: destination_file_path,
+            error: file_error,
+        }),
+    }
+}
+
+
+/// Creates a symbolic link on a worker thread, sending progress events along the way.
+fn create_symlink_on_worker(
+    context: &WorkerContext,
+    symlink_info: SymlinkCreationInfo,
+    journal: &mut DirectoryCopyJournal,
+) -> Result<Option<FinishedWorkerOperation>, CopyDirectoryExecutionError> {
+    check_destination_symlink_before_creation(
+        &symlink_info.symlink_path,
+        context
+            .options
+            .destination_directory_rule
+            .allows_overwriting_existing_destination_files(),
+    )?;
+
+    context.send_event(WorkerEvent::OperationStarted {
+        worker_index: context.worker_index,
+        operation: DirectoryCopyOperation::CreatingSymbolicLink {
+            destination_symbolic_link_file_path: symlink_info.symlink_path.clone(),
+        },
+    });
+
+    create_symlink_and_record_in_journal(&symlink_info, journal)?;
+
+    Ok(Some(FinishedWorkerOperation::CreatedSymlink {
+        size_bytes: symlink_info.unfollowed_symlink_file_size_bytes,
+    }))
+}
+
+
+/// Executes pending operations until there are none left or until the workers should stop,
+/// recording every change in `journal`.
+///
+/// Returns the journal of changes this worker made.
+fn run_worker_with_progress(
+    context: WorkerContext,
+    mut journal: DirectoryCopyJournal,
+) -> DirectoryCopyJournal {
+    while let Some(operation) = context.shared_state.next_operation() {
+        let operation_result = match operation {
+            QueuedOperation::CopyFile {
+                source_file_path,
+                source_size_bytes,
+                destination_file_path,
+                source_hard_link_identity: _,
+            } => copy_file_on_worker(
+                &context,
+                source_file_path,
+                source_size_bytes,
+                destination_file_path,
+                &mut journal,
+            ),
+
+            #[cfg(windows)]
+            QueuedOperation::CreateSymlink {
+                source_symlink_path: _,
+                symlink_path,
+                symlink_destination_type: symlink_type,
+                source_symlink_size_bytes,
+                symlink_destination_path,
+            } => create_symlink_on_worker(
+                &context,
+                SymlinkCreationInfo {
+                    symlink_path,
+                    symlink_destination_path,
+                    symlink_type,
+                    unfollowed_symlink_file_size_bytes: source_symlink_size_bytes,
+                },
+                &mut journal,
+            ),
+
+            #[cfg(unix)]
+            QueuedOperation::CreateSymlink {
+                source_symlink_path: _,
+                symlink_path,
+                source_symlink_size_bytes,
+                symlink_destination_path,
+            } => create_symlink_on_worker(
+                &context,
+                SymlinkCreationInfo {
+                    symlink_path,
+                    symlink_destination_path,
+                    unfollowed_symlink_file_size_bytes: source_symlink_size_bytes,
+                },
+                &mut journal,
+            ),
+
+            // PANIC SAFETY: Directory creations are split off before any worker is started.
+            QueuedOperation::CreateDirectory { .. } => {
+                panic!("BUG: directory creation was queued for a worker")
+            }
+
+            // PANIC SAFETY: Hard link creations are split off before any worker is started.
+            QueuedOperation::CreateHardLink { .. } => {
+                panic!("BUG: hard link creation was queued for a worker")
+            }
+        };
+
+        match operation_result {
+            Ok(Some(finished_operation)) => {
+                context.send_event(WorkerEvent::OperationFinished {
+                    worker_index: context.worker_index,
+                    finished_operation,
+                });
+            }
+            Ok(None) => break,
+            Err(error) => {
+                context.shared_state.stop_with_error(error);
+                break;
+            }
+        }
+    }
+
+    journal
+}
+
+
+
+/// Execute a prepared copy with progress tracking, copying files and creating
+/// symbolic links on `worker_threads` threads.
+///
+/// The `progress_handler` is only ever called from the current thread.
+/// Every change to the destination is recorded in the provided `journal`.
+pub(super) fn execute_prepared_copy_directory_with_progress_in_parallel<F>(
+    prepared_copy: DirectoryCopyPrepared,
+    options: &DirectoryCopyWithProgressOptions,
+    worker_threads: NonZeroUsize,
+    journal: &mut DirectoryCopyJournal,
+    mut progress_handler: F,
+) -> Result<DirectoryCopyFinished, CopyDirectoryExecutionError>
+where
+    F: FnMut(&DirectoryCopyProgressRef) -> ProgressAction,
+{
+    let resumed_copy = prepared_copy.resumed_copy();
+
+    let mut progress = DirectoryCopyInternalProgress {
+        bytes_total: prepared_copy.total_bytes,
+        bytes_finished: resumed_copy.total_bytes_copied,
+        files_copied: resumed_copy.files_copied,
+        symlinks_created: resumed_copy.symlinks_created,
+        directories_created: resumed_copy.directories_created,
+        hard_links_created: resumed_copy.hard_links_created,
+        current_operation: None,
+        current_operation_index: None,
+        total_operations: prepared_copy.operation_queue.len(),
+    };
+
+    let (directory_operations, remaining_operations) =
+        split_off_directory_creations(prepared_copy.operation_queue);
+    let (hard_link_operations, remaining_operations) =
+        split_off_hard_link_creations(remaining_operations);
+
+
+    // A directory must exist before anything is copied into it, so all directories
+    // are created up front, in the order they were queued in (i.e. parents first).
+    for operation in directory_operations {
+        let QueuedOperation::CreateDirectory {
+            source_directory_path: _,
+            source_size_bytes,
+            destination_directory_path,
+            create_parent_directories,
+        } = operation
+        else {
+            // PANIC SAFETY: `split_off_directory_creations` only returns directory creations here.
+            panic!("BUG: expected only directory creations");
+        };
+
+        execute_create_directory_operation_with_progress(
+            destination_directory_path,
+            source_size_bytes,
+            create_parent_directories,
+            options,
+            &mut progress,
+            journal,
+            &mut progress_handler,
+        )?;
+    }
+
+
+    let shared_state = SharedWorkerState::new(remaining_operations);
+    let (event_sender, event_receiver) = mpsc::channel::<WorkerEvent>();
+
+    let mut bytes_finished_by_finished_operations = progress.bytes_finished;
+    let mut was_cancelled = false;
+
+    let worker_journals = thread::scope(|scope| {
+        let worker_handles = (0..worker_threads.get())
+            .map(|worker_index| {
+                let context = WorkerContext {
+                    worker_index,
+                    options,
+                    shared_state: &shared_state,
+                    events: event_sender.clone(),
+                };
+                let worker_journal = journal.new_for_worker();
+
+                scope.spawn(move || run_worker_with_progress(context, worker_journal))
+            })
+            .collect::<Vec<_>>();
+
+        // From now on, only the workers hold senders,
+        // meaning the loop below ends once all of them have finished.
+        drop(event_sender);
+
+
+        let mut operation_by_worker: Vec<Option<DirectoryCopyOperation>> =
+            vec![None; worker_threads.get()];
+        let mut bytes_finished_by_worker = vec![0u64; worker_threads.get()];
+
+        for event in event_receiver {
+            let action = match event {
+                WorkerEvent::OperationStarted {
+                    worker_index,
+                    operation,
+                } => {
+                    bytes_finished_by_worker[worker_index] = 0;
+                    operation_by_worker[worker_index] = Some(operation.clone());
+
+                    progress.set_next_operation_and_emit_progress(operation, &mut progress_handler)
+                }
+
+                WorkerEvent::FileCopyProgressed {
+                    worker_index,
+                    file_progress,
+                } => {
+                    bytes_finished_by_worker[worker_index] = file_progress.bytes_finished;
+
+                    let Some(DirectoryCopyOperation::CopyingFile {
+                        progress: worker_file_progress,
+                        ..
+                    }) = operation_by_worker[worker_index].as_mut()
+                    else {
+                        // PANIC SAFETY: Workers always announce a file copy before reporting its progress.
+                        panic!("BUG: worker reported file progress without copying a file");
+                    };
+
+                    *worker_file_progress = file_progress;
+
+                    let bytes_finished = bytes_finished_by_finished_operations
+                        + bytes_finished_by_worker.iter().sum::<u64>();
+
+                    progress.update_operation_and_emit_progress(
+                        |progress| {
+                            progress.current_operation = operation_by_worker[worker_index].clone();
+                            progress.bytes_finished = bytes_finished;
+                        },
+                        &mut progress_handler,
+                    )
+                }
+
+                WorkerEvent::OperationFinished {
+                    worker_index,
+                    finished_operation,
+                } => {
+                    bytes_finished_by_worker[worker_index] = 0;
+
+                    match finished_operation {
+                        FinishedWorkerOperation::CopiedFile { bytes_copied } => {
+                            progress.files_copied += 1;
+                            bytes_finished_by_finished_operations += bytes_copied;
+                        }
+                        FinishedWorkerOperation::CreatedSymlink { size_bytes } => {
+                            progress.symlinks_created += 1;
+                            bytes_finished_by_finished_operations += size_bytes;
+                        }
+                    }
+
+                    progress.bytes_finished = bytes_finished_by_finished_operations
+                        + bytes_finished_by_worker.iter().sum::<u64>();
+
+                    ProgressAction::Continue
+                }
+            };
+
+            if action.is_abort() && !was_cancelled {
+                was_cancelled = true;
+                shared_state.stop();
+            }
+        }
+
+
+        worker_handles
+            .into_iter()
+            .map(join_worker)
+            .collect::<Vec<_>>()
+    });
+
+
+    for worker_journal in worker_journals {
+        journal.append(worker_journal);
+    }
+
+    // Partially copied files have been removed, so they must not count towards the finished bytes.
+    progress.bytes_finished = bytes_finished_by_finished_operations;
+
+    if let Some(error) = shared_state.into_first_error() {
+        return Err(error);
+    }
+
+    if was_cancelled {
+        return Err(progress.to_cancelled_error());
+    }
+
+
+    for operation in hard_link_operations {
+        let QueuedOperation::CreateHardLink {
+            source_file_path: _,
+            hard_link_path,
+            target_path,
+        } = operation
+        else {
+            // PANIC SAFETY: `split_off_hard_link_creations` only returns hard link creations here.
+            panic!("BUG: expected only hard link creations");
+        };
+
+        execute_create_hard_link_operation_with_progress(
+            hard_link_path,
+            target_path,
+            options,
+            &mut progress,
+            journal,
+            &mut progress_handler,
+        )?;
+    }
+
+
+    // One last progress update - everything should be done at this point,
+    // so there is nothing left to cancel.
+    let _ = progress_handler(&progress.to_user_facing_progress());
+
+    Ok(DirectoryCopyFinished {
+        total_bytes_copied: progress.bytes_finished,
+        files_copied: progress.files_copied,
+        symlinks_created: progress.symlinks_created,
+        directories_created: progress.directories_created,
+        files_copied_to_alternative_paths: Vec::new(),
+        hard_links_created: progress.hard_links_created,
+    })
+}
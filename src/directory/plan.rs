@@ -0,0 +1,971 @@
+use std::path::{Path, PathBuf};
+
+use super::{
+    copy_directory_unchecked,
+    execute_prepared_copy_directory_with_progress_unchecked,
+    prepared::{
+        try_exists_without_follow,
+        validate_destination_directory_path,
+        validate_source_destination_directory_pair,
+        validate_source_directory_path,
+        DestinationDirectoryState,
+        DirectoryCopyPrepared,
+        QueuedOperation,
+        ValidatedDestinationDirectory,
+        ValidatedSourceDirectory,
+    },
+    r#move::{
+        attempt_directory_move_by_rename,
+        collect_source_directory_details,
+        remove_source_directory_after_copy,
+        DirectoryContentDetails,
+        DirectoryMoveByRenameAction,
+    },
+    DirectoryCopyDepthLimit,
+    DirectoryCopyFinished,
+    DirectoryCopyOptions,
+    DirectoryCopyProgressRef,
+    DirectoryCopyWithProgressOptions,
+    DirectoryEntryFilter,
+    DirectoryMoveAllowedStrategies,
+    DirectoryMoveFinished,
+    DirectoryMoveOptions,
+    DirectoryMoveStrategy,
+};
+use crate::{
+    error::{
+        CopyDirectoryError,
+        CopyDirectoryPreparationError,
+        DirectoryExecutionPlanError,
+        MoveDirectoryError,
+        MoveDirectoryExecutionError,
+        MoveDirectoryPreparationError,
+    },
+    file::{ReflinkBehaviour, SparseFileBehaviour},
+    ProgressAction,
+    DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
+    DEFAULT_READ_BUFFER_SIZE,
+    DEFAULT_WRITE_BUFFER_SIZE,
+};
+
+
+/// A single operation of a [`DirectoryCopyPlan`].
+///
+/// Operations are listed in the order they will be executed in: for any file,
+/// the creation of its parent directory appears before it.
+///
+/// See also: [`DirectoryCopyPlan::operations`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlannedDirectoryCopyOperation<'p> {
+    /// Copy a file from `source_file_path` to `destination_file_path`.
+    CopyFile {
+        /// Where the file will be copied from.
+        source_file_path: &'p Path,
+
+        /// Where the file will be copied to.
+        destination_file_path: &'p Path,
+
+        /// Size of the source file in bytes.
+        size_bytes: u64,
+
+        /// Whether a file already existed at `destination_file_path` when planning,
+        /// meaning it will be overwritten.
+        destination_exists: bool,
+    },
+
+    /// Create a directory at `destination_directory_path`.
+    CreateDirectory {
+        /// Directory that will be created.
+        destination_directory_path: &'p Path,
+
+        /// Size of the corresponding source directory in bytes.
+        /// This is the size of the directory "file" itself, not a recursive size scan.
+        size_bytes: u64,
+
+        /// Whether the directory already existed when planning,
+        /// meaning it will be kept and copied into instead of being created.
+        destination_exists: bool,
+    },
+
+    /// Create a symbolic link at `symlink_path`.
+    CreateSymlink {
+        /// Where the symbolic link will be created.
+        symlink_path: &'p Path,
+
+        /// Where the created symbolic link will point to.
+        symlink_destination_path: &'p Path,
+
+        /// Size of the source symbolic link in bytes.
+        size_bytes: u64,
+
+        /// Whether an entry already existed at `symlink_path` when planning.
+        destination_exists: bool,
+    },
+
+    /// Create a hard link at `hard_link_path`, pointing to the file copied to `target_path`.
+    ///
+    /// Only planned with [`HardLinkBehaviour::Preserve`][super::HardLinkBehaviour::Preserve].
+    CreateHardLink {
+        /// Source file that is a hard link to an already copied source file.
+        source_file_path: &'p Path,
+
+        /// Where the hard link will be created.
+        hard_link_path: &'p Path,
+
+        /// Destination file (copied by an earlier operation) the hard link will point to.
+        target_path: &'p Path,
+
+        /// Whether a file already existed at `hard_link_path` when planning,
+        /// meaning it will be overwritten.
+        destination_exists: bool,
+    },
+}
+
+impl<'p> PlannedDirectoryCopyOperation<'p> {
+    fn from_queued_operation(operation: &'p QueuedOperation, destination_exists: bool) -> Self {
+        match operation {
+            QueuedOperation::CopyFile {
+                source_file_path,
+                destination_file_path,
+                source_size_bytes,
+                source_hard_link_identity: _,
+            } => Self::CopyFile {
+                source_file_path,
+                destination_file_path,
+                size_bytes: *source_size_bytes,
+                destination_exists,
+            },
+            QueuedOperation::CreateDirectory {
+                destination_directory_path,
+                source_size_bytes,
+                ..
+            } => Self::CreateDirectory {
+                destination_directory_path,
+                size_bytes: *source_size_bytes,
+                destination_exists,
+            },
+            QueuedOperation::CreateSymlink {
+                symlink_path,
+                symlink_destination_path,
+                source_symlink_size_bytes,
+                ..
+            } => Self::CreateSymlink {
+                symlink_path,
+                symlink_destination_path,
+                size_bytes: *source_symlink_size_bytes,
+                destination_exists,
+            },
+            QueuedOperation::CreateHardLink {
+                source_file_path,
+                hard_link_path,
+                target_path,
+            } => Self::CreateHardLink {
+                source_file_path,
+                hard_link_path,
+                target_path,
+                destination_exists,
+            },
+        }
+    }
+
+    /// Returns the destination path this operation creates (or overwrites).
+    pub fn destination_path(&self) -> &'p Path {
+        match self {
+            Self::CopyFile {
+                destination_file_path,
+                ..
+            } => destination_file_path,
+            Self::CreateDirectory {
+                destination_directory_path,
+                ..
+            } => destination_directory_path,
+            Self::CreateSymlink { symlink_path, .. } => symlink_path,
+            Self::CreateHardLink { hard_link_path, .. } => hard_link_path,
+        }
+    }
+
+    /// Returns the number of bytes this operation contributes to the total copy size.
+    ///
+    /// Hard links contribute nothing, as no data is copied to create them.
+    pub fn size_bytes(&self) -> u64 {
+        match self {
+            Self::CopyFile { size_bytes, .. } => *size_bytes,
+            Self::CreateDirectory { size_bytes, .. } => *size_bytes,
+            Self::CreateSymlink { size_bytes, .. } => *size_bytes,
+            Self::CreateHardLink { .. } => 0,
+        }
+    }
+
+    /// Returns `true` if an entry already existed at the destination path when planning.
+    pub fn destination_exists(&self) -> bool {
+        match self {
+            Self::CopyFile {
+                destination_exists, ..
+            } => *destination_exists,
+            Self::CreateDirectory {
+                destination_exists, ..
+            } => *destination_exists,
+            Self::CreateSymlink {
+                destination_exists, ..
+            } => *destination_exists,
+            Self::CreateHardLink {
+                destination_exists, ..
+            } => *destination_exists,
+        }
+    }
+}
+
+
+/// An iterator over the operations of a [`DirectoryCopyPlan`].
+///
+/// Obtained from [`DirectoryCopyPlan::operations`].
+pub struct DirectoryCopyPlanIter<'p> {
+    operations: std::iter::Zip<std::slice::Iter<'p, QueuedOperation>, std::slice::Iter<'p, bool>>,
+}
+
+impl<'p> Iterator for DirectoryCopyPlanIter<'p> {
+    type Item = PlannedDirectoryCopyOperation<'p>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.operations
+            .next()
+            .map(|(operation, destination_exists)| {
+                PlannedDirectoryCopyOperation::from_queued_operation(operation, *destination_exists)
+            })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.operations.size_hint()
+    }
+}
+
+impl<'p> ExactSizeIterator for DirectoryCopyPlanIter<'p> {}
+
+
+/// Summarizes what a [`DirectoryCopyPlan`] will do when executed.
+///
+/// See also: [`DirectoryCopyPlan::summary`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DirectoryCopyPlanSummary {
+    /// Total number of bytes that will be copied.
+    pub total_bytes: u64,
+
+    /// Number of files that will be copied, including the ones that will overwrite
+    /// existing destination files.
+    pub files_to_copy: usize,
+
+    /// Number of existing destination files that will be overwritten.
+    pub files_to_overwrite: usize,
+
+    /// Number of symbolic links that will be created.
+    pub symlinks_to_create: usize,
+
+    /// Number of hard links that will be created (see [`HardLinkBehaviour::Preserve`][super::HardLinkBehaviour::Preserve]).
+    pub hard_links_to_create: usize,
+
+    /// Number of directories that will be created.
+    pub directories_to_create: usize,
+
+    /// Number of directories that already exist on the destination
+    /// and will be copied into.
+    pub existing_directories: usize,
+}
+
+
+/// Returns, for each queued operation, whether its destination path already exists.
+fn find_existing_destination_entries(
+    operation_queue: &[QueuedOperation],
+    destination_directory_state: DestinationDirectoryState,
+) -> Result<Vec<bool>, DirectoryExecutionPlanError> {
+    // If the destination directory does not exist, nothing inside it can exist either.
+    if destination_directory_state == DestinationDirectoryState::DoesNotExist {
+        return Ok(vec![false; operation_queue.len()]);
+    }
+
+    operation_queue
+        .iter()
+        .map(|operation| {
+            let destination_path = operation.destination_path();
+
+            try_exists_without_follow(destination_path).map_err(|error| {
+                DirectoryExecutionPlanError::UnableToAccess {
+                    path: destination_path.to_path_buf(),
+                    error,
+                }
+            })
+        })
+        .collect()
+}
+
+
+
+/// A reviewed-before-executed directory copy.
+///
+/// A plan is created by [`plan_directory_copy`], which performs the full preparation phase
+/// of a directory copy (validation, scanning the source directory, and checking
+/// for destination collisions) without changing anything on the filesystem.
+/// The plan can then be inspected (see [`Self::operations`] and [`Self::summary`])
+/// and, if acceptable, executed with [`Self::execute`] or [`Self::execute_with_progress`].
+///
+/// # Re-validation on execution
+/// Because time can pass between planning and execution, executing a plan re-validates
+/// everything that could have changed in the meantime, but does not rescan the source directory:
+/// - the source directory must still exist and be a directory,
+/// - the destination directory must still satisfy the [`DestinationDirectoryRule`], and
+/// - no file or symbolic link may have appeared at a destination path that was empty when planning,
+///   otherwise [`DirectoryExecutionPlanError::DestinationChangedAfterPlanning`] is returned.
+///   Directories that appear in the meantime are accepted if the destination directory rule allows
+///   existing sub-directories.
+///
+///
+/// [`DestinationDirectoryRule`]: super::DestinationDirectoryRule
+#[derive(Debug)]
+pub struct DirectoryCopyPlan {
+    /// Source directory path, as provided by the caller.
+    source_directory_path: PathBuf,
+
+    /// Destination directory path, as provided by the caller.
+    destination_directory_path: PathBuf,
+
+    /// State of the destination directory when planning.
+    planned_destination_directory_state: DestinationDirectoryState,
+
+    prepared_copy: DirectoryCopyPrepared,
+
+    /// For each queued operation, whether its destination path existed when planning.
+    existing_destination_entries: Vec<bool>,
+
+    options: DirectoryCopyOptions,
+}
+
+impl DirectoryCopyPlan {
+    fn from_validated(
+        source_directory_path: PathBuf,
+        destination_directory_path: PathBuf,
+        validated_source_directory: ValidatedSourceDirectory,
+        validated_destination_directory: ValidatedDestinationDirectory,
+        mut options: DirectoryCopyOptions,
+    ) -> Result<Self, DirectoryExecutionPlanError> {
+        let planned_destination_directory_state = validated_destination_directory.state;
+
+        if options.collision_resolver.is_some() {
+            options.destination_directory_rule =
+                options.destination_directory_rule.for_collision_resolver();
+        }
+
+        let mut prepared_copy = DirectoryCopyPrepared::prepare_with_validated(
+            validated_source_directory,
+            validated_destination_directory,
+            options.destination_directory_rule,
+            options.copy_depth_limit,
+            options.symlink_behaviour,
+            options.broken_symlink_behaviour,
+            &options.entry_filter,
+        )?;
+
+        if let Some(collision_resolver) = options.collision_resolver.as_ref() {
+            prepared_copy.resolve_collisions_with_resolver(collision_resolver)?;
+        }
+
+        prepared_copy.preserve_hard_links(options.hard_link_behaviour);
+
+        let existing_destination_entries = find_existing_destination_entries(
+            &prepared_copy.operation_queue,
+            planned_destination_directory_state,
+        )?;
+
+
+        Ok(Self {
+            source_directory_path,
+            destination_directory_path,
+            planned_destination_directory_state,
+            prepared_copy,
+            existing_destination_entries,
+            options,
+        })
+    }
+
+    /// Returns the source directory path, as it was provided to [`plan_directory_copy`].
+    pub fn source_directory_path(&self) -> &Path {
+        &self.source_directory_path
+    }
+
+    /// Returns the destination directory path, as it was provided to [`plan_directory_copy`].
+    pub fn destination_directory_path(&self) -> &Path {
+        &self.destination_directory_path
+    }
+
+    /// Returns the options this plan was created with.
+    pub fn options(&self) -> &DirectoryCopyOptions {
+        &self.options
+    }
+
+    /// Returns the total number of bytes that will be copied.
+    pub fn total_bytes(&self) -> u64 {
+        self.prepared_copy.total_bytes
+    }
+
+    /// Returns the number of planned operations.
+    pub fn len(&self) -> usize {
+        self.prepared_copy.operation_queue.len()
+    }
+
+    /// Returns `true` if there is nothing to copy.
+    pub fn is_empty(&self) -> bool {
+        self.prepared_copy.operation_queue.is_empty()
+    }
+
+    /// Returns an iterator over all planned operations, in execution order.
+    pub fn operations(&self) -> DirectoryCopyPlanIter<'_> {
+        DirectoryCopyPlanIter {
+            operations: self
+                .prepared_copy
+                .operation_queue
+                .iter()
+                .zip(self.existing_destination_entries.iter()),
+        }
+    }
+
+    /// Summarizes the planned operations, e.g. how many directories will be created
+    /// and how many files will be overwritten.
+    pub fn summary(&self) -> DirectoryCopyPlanSummary {
+        let mut summary = DirectoryCopyPlanSummary {
+            total_bytes: self.total_bytes(),
+            ..Default::default()
+        };
+
+        for operation in self.operations() {
+            match operation {
+                PlannedDirectoryCopyOperation::CopyFile {
+                    destination_exists, ..
+                } => {
+                    summary.files_to_copy += 1;
+
+                    if destination_exists {
+                        summary.files_to_overwrite += 1;
+                    }
+                }
+                PlannedDirectoryCopyOperation::CreateDirectory {
+                    destination_exists, ..
+                } => {
+                    if destination_exists {
+                        summary.existing_directories += 1;
+                    } else {
+                        summary.directories_to_create += 1;
+                    }
+                }
+                PlannedDirectoryCopyOperation::CreateSymlink { .. } => {
+                    summary.symlinks_to_create += 1;
+                }
+                PlannedDirectoryCopyOperation::CreateHardLink { .. } => {
+                    summary.hard_links_to_create += 1;
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// Re-validates the source and destination directories themselves.
+    fn revalidate_source_and_destination(
+        &self,
+    ) -> Result<
+        (ValidatedSourceDirectory, ValidatedDestinationDirectory),
+        CopyDirectoryPreparationError,
+    > {
+        let validated_source_directory =
+            validate_source_directory_path(&self.source_directory_path)?;
+
+        let validated_destination_directory = validate_destination_directory_path(
+            &self.destination_directory_path,
+            self.options.destination_directory_rule,
+        )?;
+
+        validate_source_destination_directory_pair(
+            &validated_source_directory.directory_path,
+            &validated_destination_directory.directory_path,
+        )?;
+
+        Ok((validated_source_directory, validated_destination_directory))
+    }
+
+    /// Ensures no entry has appeared at a destination path that was free when planning.
+    fn revalidate_destination_entries(
+        &self,
+        current_destination_directory_state: DestinationDirectoryState,
+    ) -> Result<(), DirectoryExecutionPlanError> {
+        // If the destination directory did not exist when planning and still doesn't,
+        // none of the planned destination paths can exist.
+        if self.planned_destination_directory_state == DestinationDirectoryState::DoesNotExist
+            && current_destination_directory_state == DestinationDirectoryState::DoesNotExist
+        {
+            return Ok(());
+        }
+
+        let existing_subdirectories_allowed = self
+            .options
+            .destination_directory_rule
+            .allows_existing_destination_subdirectories();
+
+
+        for (operation, existed_when_planning) in self
+            .prepared_copy
+            .operation_queue
+            .iter()
+            .zip(self.existing_destination_entries.iter())
+        {
+            if *existed_when_planning {
+                continue;
+            }
+
+            let destination_path = operation.destination_path();
+
+            let destination_exists =
+                try_exists_without_follow(destination_path).map_err(|error| {
+                    DirectoryExecutionPlanError::UnableToAccess {
+                        path: destination_path.to_path_buf(),
+                        error,
+                    }
+                })?;
+
+            if !destination_exists {
+                continue;
+            }
+
+            if matches!(operation, QueuedOperation::CreateDirectory { .. }) {
+                if existing_subdirectories_allowed && destination_path.is_dir() {
+                    continue;
+                }
+
+                return Err(DirectoryExecutionPlanError::DestinationItemAlreadyExists {
+                    path: destination_path.to_path_buf(),
+                });
+            }
+
+            return Err(DirectoryExecutionPlanError::DestinationChangedAfterPlanning {
+                path: destination_path.to_path_buf(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Re-validates the plan (see [re-validation on execution][Self#re-validation-on-execution]).
+    fn revalidate(&self) -> Result<(), CopyDirectoryPreparationError> {
+        let (_, validated_destination_directory) = self.revalidate_source_and_destination()?;
+
+        self.revalidate_destination_entries(validated_destination_directory.state)?;
+
+        Ok(())
+    }
+
+    /// Executes the planned copy.
+    ///
+    /// Before anything is copied, the plan is re-validated
+    /// (see [re-validation on execution][Self#re-validation-on-execution]).
+    /// Other than that, this behaves exactly like [`copy_directory`] with the options
+    /// the plan was created with.
+    ///
+    ///
+    /// [`copy_directory`]: super::copy_directory
+    pub fn execute(self) -> Result<DirectoryCopyFinished, CopyDirectoryError> {
+        self.revalidate()?;
+
+        let finished_copy = copy_directory_unchecked(self.prepared_copy, self.options)?;
+
+        Ok(finished_copy)
+    }
+
+    /// Executes the planned copy, reporting progress along the way.
+    ///
+    /// Before anything is copied, the plan is re-validated
+    /// (see [re-validation on execution][Self#re-validation-on-execution]).
+    /// Other than that, this behaves exactly like [`copy_directory_with_progress`]
+    /// with the options the plan was created with, and default buffer sizes
+    /// and progress reporting interval.
+    ///
+    ///
+    /// [`copy_directory_with_progress`]: super::copy_directory_with_progress
+    pub fn execute_with_progress<F, R>(
+        self,
+        mut progress_handler: F,
+    ) -> Result<DirectoryCopyFinished, CopyDirectoryError>
+    where
+        F: FnMut(&DirectoryCopyProgressRef) -> R,
+        R: Into<ProgressAction>,
+    {
+        self.revalidate()?;
+
+        let options_with_progress = DirectoryCopyWithProgressOptions {
+            destination_directory_rule: self.options.destination_directory_rule,
+            copy_depth_limit: self.options.copy_depth_limit,
+            symlink_behaviour: self.options.symlink_behaviour,
+            broken_symlink_behaviour: self.options.broken_symlink_behaviour,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
+            progress_update_byte_interval: DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
+            rollback_behaviour: self.options.rollback_behaviour,
+            entry_filter: self.options.entry_filter,
+            parallelism: self.options.parallelism,
+            reflink_behaviour: self.options.reflink_behaviour,
+            metadata_preservation: self.options.metadata_preservation,
+            extended_attributes: self.options.extended_attributes,
+            sparse_behaviour: SparseFileBehaviour::Never,
+            verify: self.options.verify,
+            resume_journal_path: None,
+            atomic: self.options.atomic,
+            durability: self.options.durability,
+            // Collisions have already been resolved while planning.
+            collision_resolver: None,
+            hard_link_behaviour: self.options.hard_link_behaviour,
+        };
+
+        let finished_copy = execute_prepared_copy_directory_with_progress_unchecked(
+            self.prepared_copy,
+            options_with_progress,
+            |progress| progress_handler(progress).into(),
+        )?;
+
+        Ok(finished_copy)
+    }
+}
+
+impl<'p> IntoIterator for &'p DirectoryCopyPlan {
+    type Item = PlannedDirectoryCopyOperation<'p>;
+    type IntoIter = DirectoryCopyPlanIter<'p>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.operations()
+    }
+}
+
+
+/// Plans a directory copy without performing it (a "dry run").
+///
+/// This performs the same preparation phase as [`copy_directory`] — validating the source
+/// and destination directories, scanning the source directory, and checking for collisions
+/// with the configured [`destination_directory_rule`] — but does not change anything
+/// on the filesystem. The returned [`DirectoryCopyPlan`] can be inspected and later executed.
+///
+///
+/// # Errors
+/// If the copy would fail during preparation, the same [`CopyDirectoryPreparationError`]
+/// that [`copy_directory`] would return is returned here.
+///
+/// Planned copies can not be resumed, so if [`resume_journal_path`] is set,
+/// [`CopyDirectoryPreparationError::ResumeJournalNotSupportedForPlans`] is returned.
+///
+///
+/// [`copy_directory`]: super::copy_directory
+/// [`destination_directory_rule`]: DirectoryCopyOptions::destination_directory_rule
+/// [`resume_journal_path`]: DirectoryCopyOptions::resume_journal_path
+pub fn plan_directory_copy<S, T>(
+    source_directory_path: S,
+    destination_directory_path: T,
+    options: DirectoryCopyOptions,
+) -> Result<DirectoryCopyPlan, CopyDirectoryPreparationError>
+where
+    S: AsRef<Path>,
+    T: AsRef<Path>,
+{
+    if let Some(journal_path) = options.resume_journal_path {
+        return Err(CopyDirectoryPreparationError::ResumeJournalNotSupportedForPlans {
+            journal_path,
+        });
+    }
+
+    let (validated_source_directory, validated_destination_directory) =
+        DirectoryCopyPrepared::validate_source_and_destination(
+            source_directory_path.as_ref(),
+            destination_directory_path.as_ref(),
+            options.destination_directory_rule,
+        )?;
+
+    let plan = DirectoryCopyPlan::from_validated(
+        source_directory_path.as_ref().to_path_buf(),
+        destination_directory_path.as_ref().to_path_buf(),
+        validated_source_directory,
+        validated_destination_directory,
+        options,
+    )?;
+
+    Ok(plan)
+}
+
+
+
+/// Returns `true` if a directory move by rename can be attempted,
+/// given the state of the destination directory.
+fn destination_state_permits_rename(
+    destination_directory_state: DestinationDirectoryState,
+) -> bool {
+    #[cfg(windows)]
+    {
+        // On Windows, renames are only possible with non-existing destinations.
+        matches!(destination_directory_state, DestinationDirectoryState::DoesNotExist)
+    }
+
+    #[cfg(not(windows))]
+    {
+        matches!(
+            destination_directory_state,
+            DestinationDirectoryState::DoesNotExist | DestinationDirectoryState::IsEmpty
+        )
+    }
+}
+
+
+/// A reviewed-before-executed directory move.
+///
+/// A plan is created by [`plan_directory_move`], which performs the full preparation phase
+/// of a directory move without changing anything on the filesystem.
+///
+/// Because a rename can only be attempted (and can fail, e.g. when the source and destination
+/// are on different mount points), a move plan includes the copy-and-delete fallback
+/// as a [`DirectoryCopyPlan`], if that strategy is allowed (see [`Self::copy_plan`]).
+///
+/// Executing the plan re-validates the source and destination directories,
+/// and, if the copy-and-delete fallback is used, the copy plan as described in
+/// [`DirectoryCopyPlan`'s documentation](DirectoryCopyPlan#re-validation-on-execution).
+pub struct DirectoryMovePlan {
+    /// Source directory path, as provided by the caller.
+    source_directory_path: PathBuf,
+
+    /// Destination directory path, as provided by the caller.
+    destination_directory_path: PathBuf,
+
+    /// State of the destination directory when planning.
+    planned_destination_directory_state: DestinationDirectoryState,
+
+    options: DirectoryMoveOptions,
+
+    /// Entry filter of the copy-and-delete strategy (if allowed), or the default filter.
+    entry_filter: DirectoryEntryFilter,
+
+    source_details: DirectoryContentDetails,
+
+    copy_plan: Option<DirectoryCopyPlan>,
+}
+
+impl DirectoryMovePlan {
+    /// Returns the source directory path, as it was provided to [`plan_directory_move`].
+    pub fn source_directory_path(&self) -> &Path {
+        &self.source_directory_path
+    }
+
+    /// Returns the destination directory path, as it was provided to [`plan_directory_move`].
+    pub fn destination_directory_path(&self) -> &Path {
+        &self.destination_directory_path
+    }
+
+    /// Returns the total number of bytes that will be moved.
+    pub fn total_bytes(&self) -> u64 {
+        self.source_details.total_bytes
+    }
+
+    /// Returns `true` if executing the plan will first attempt to move the directory
+    /// by renaming it, which is allowed by the options and possible given the state
+    /// of the destination directory when planning.
+    ///
+    /// A rename is never attempted if the copy-and-delete [`entry_filter`] skips any entries.
+    ///
+    /// Note that the rename can still fail on execution (for example when the source
+    /// and destination directories are on different mount points),
+    /// in which case the [copy-and-delete plan][Self::copy_plan] is used, if available.
+    ///
+    ///
+    /// [`entry_filter`]: super::DirectoryMoveByCopyOptions::entry_filter
+    pub fn will_attempt_rename(&self) -> bool {
+        self.options.allowed_strategies.allowed_to_rename()
+            && self.entry_filter.includes_everything()
+            && destination_state_permits_rename(self.planned_destination_directory_state)
+    }
+
+    /// Returns the plan for the copy phase of the copy-and-delete strategy,
+    /// or `None` if that strategy is not allowed.
+    pub fn copy_plan(&self) -> Option<&DirectoryCopyPlan> {
+        self.copy_plan.as_ref()
+    }
+
+    /// Executes the planned move.
+    ///
+    /// Before anything is moved, the plan is re-validated (see [`DirectoryMovePlan`]).
+    /// Other than that, this behaves exactly like [`move_directory`]
+    /// with the options the plan was created with.
+    ///
+    ///
+    /// [`move_directory`]: super::move_directory
+    pub fn execute(self) -> Result<DirectoryMoveFinished, MoveDirectoryError> {
+        let validated_source_directory =
+            validate_source_directory_path(&self.source_directory_path)
+                .map_err(MoveDirectoryPreparationError::SourceDirectoryValidationError)?;
+
+        let validated_destination_directory = validate_destination_directory_path(
+            &self.destination_directory_path,
+            self.options.destination_directory_rule,
+        )
+        .map_err(MoveDirectoryPreparationError::DestinationDirectoryValidationError)?;
+
+        validate_source_destination_directory_pair(
+            &validated_source_directory.directory_path,
+            &validated_destination_directory.directory_path,
+        )
+        .map_err(MoveDirectoryPreparationError::DestinationDirectoryValidationError)?;
+
+
+        if self.options.allowed_strategies.allowed_to_rename()
+            && self.entry_filter.includes_everything()
+        {
+            match attempt_directory_move_by_rename(
+                &validated_source_directory,
+                &self.source_details,
+                &validated_destination_directory,
+                self.options.durability,
+            )? {
+                DirectoryMoveByRenameAction::Renamed { finished_move } => {
+                    return Ok(finished_move);
+                }
+                DirectoryMoveByRenameAction::FailedOrImpossible => {}
+            };
+        }
+
+
+        let Some(copy_plan) = self.copy_plan else {
+            return Err(MoveDirectoryError::ExecutionError(
+                MoveDirectoryExecutionError::RenameFailedAndNoFallbackStrategy,
+            ));
+        };
+
+        copy_plan
+            .revalidate_destination_entries(validated_destination_directory.state)
+            .map_err(MoveDirectoryPreparationError::CopyPlanningError)?;
+
+        let skipped_source_paths = copy_plan.prepared_copy.skipped_source_paths.clone();
+
+        copy_directory_unchecked(copy_plan.prepared_copy, copy_plan.options)
+            .map_err(MoveDirectoryExecutionError::CopyDirectoryError)?;
+
+        remove_source_directory_after_copy(
+            &validated_source_directory,
+            &self.source_directory_path,
+            &self.entry_filter,
+            &skipped_source_paths,
+            self.options.durability,
+        )?;
+
+
+        Ok(DirectoryMoveFinished {
+            total_bytes_moved: self.source_details.total_bytes,
+            files_moved: self.source_details.total_files,
+            symlinks_moved: self.source_details.total_symlinks,
+            directories_moved: self.source_details.total_directories,
+            strategy_used: DirectoryMoveStrategy::CopyAndDelete,
+        })
+    }
+}
+
+
+/// Plans a directory move without performing it (a "dry run").
+///
+/// This performs the same preparation phase as [`move_directory`] — validating the source
+/// and destination directories, scanning the source directory, and, if the copy-and-delete
+/// strategy is allowed, planning the copy — but does not change anything on the filesystem.
+/// The returned [`DirectoryMovePlan`] can be inspected and later executed.
+///
+///
+/// # Errors
+/// If the move would fail during preparation, a [`MoveDirectoryPreparationError`] is returned.
+///
+/// Note that, unlike [`move_directory`], planning always prepares the copy-and-delete fallback
+/// (if allowed), so a destination collision can be reported here even if
+/// the move would have succeeded by renaming the directory.
+///
+///
+/// [`move_directory`]: super::move_directory
+pub fn plan_directory_move<S, T>(
+    source_directory_path: S,
+    destination_directory_path: T,
+    options: DirectoryMoveOptions,
+) -> Result<DirectoryMovePlan, MoveDirectoryPreparationError>
+where
+    S: AsRef<Path>,
+    T: AsRef<Path>,
+{
+    let validated_source_directory = validate_source_directory_path(source_directory_path.as_ref())
+        .map_err(MoveDirectoryPreparationError::SourceDirectoryValidationError)?;
+
+    let validated_destination_directory = validate_destination_directory_path(
+        destination_directory_path.as_ref(),
+        options.destination_directory_rule,
+    )
+    .map_err(MoveDirectoryPreparationError::DestinationDirectoryValidationError)?;
+
+    validate_source_destination_directory_pair(
+        &validated_source_directory.directory_path,
+        &validated_destination_directory.directory_path,
+    )
+    .map_err(MoveDirectoryPreparationError::DestinationDirectoryValidationError)?;
+
+
+    let entry_filter = match &options.allowed_strategies {
+        DirectoryMoveAllowedStrategies::OnlyRename => DirectoryEntryFilter::default(),
+        DirectoryMoveAllowedStrategies::OnlyCopyAndDelete {
+            options: copy_and_delete_options,
+        }
+        | DirectoryMoveAllowedStrategies::Either {
+            copy_and_delete_options,
+        } => copy_and_delete_options.entry_filter.clone(),
+    };
+
+    let source_details = collect_source_directory_details(
+        &validated_source_directory.directory_path,
+        &entry_filter,
+    )?;
+
+    let planned_destination_directory_state = validated_destination_directory.state;
+
+
+    let copy_plan = match &options.allowed_strategies {
+        DirectoryMoveAllowedStrategies::OnlyRename => None,
+        DirectoryMoveAllowedStrategies::OnlyCopyAndDelete {
+            options: copy_and_delete_options,
+        }
+        | DirectoryMoveAllowedStrategies::Either {
+            copy_and_delete_options,
+        } => Some(DirectoryCopyPlan::from_validated(
+            source_directory_path.as_ref().to_path_buf(),
+            destination_directory_path.as_ref().to_path_buf(),
+            validated_source_directory,
+            validated_destination_directory,
+            DirectoryCopyOptions {
+                destination_directory_rule: options.destination_directory_rule,
+                copy_depth_limit: DirectoryCopyDepthLimit::Unlimited,
+                symlink_behaviour: copy_and_delete_options.symlink_behaviour,
+                broken_symlink_behaviour: copy_and_delete_options.broken_symlink_behaviour,
+                rollback_behaviour: copy_and_delete_options.rollback_behaviour,
+                entry_filter: entry_filter.clone(),
+                parallelism: copy_and_delete_options.parallelism,
+                reflink_behaviour: ReflinkBehaviour::Never,
+                metadata_preservation: copy_and_delete_options.metadata_preservation,
+                extended_attributes: copy_and_delete_options.extended_attributes,
+                verify: copy_and_delete_options.verify,
+                resume_journal_path: None,
+                atomic: false,
+                durability: options.durability,
+                collision_resolver: copy_and_delete_options.collision_resolver.clone(),
+                hard_link_behaviour: copy_and_delete_options.hard_link_behaviour,
+            },
+        )?),
+    };
+
+
+    Ok(DirectoryMovePlan {
+        source_directory_path: source_directory_path.as_ref().to_path_buf(),
+        destination_directory_path: destination_directory_path.as_ref().to_path_buf(),
+        planned_destination_directory_state,
+        options,
+        entry_filter,
+        source_details,
+        copy_plan,
+    })
+}
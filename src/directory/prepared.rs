@@ -1,6 +1,10 @@
 use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fs::Metadata,
     io::{self, ErrorKind},
+    mem,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use_enabled_fs_module!();
@@ -9,8 +13,19 @@ use_enabled_fs_module!();
 use super::{
     common::DestinationDirectoryRule,
     is_directory_empty_unchecked,
+    resume::DirectoryCopyResumeJournal,
     BrokenSymlinkBehaviour,
+    DirectoryCollision,
+    DirectoryCollisionDecision,
+    DirectoryCollisionResolver,
     DirectoryCopyDepthLimit,
+    DirectoryCopyFinished,
+    DirectoryEntries,
+    DirectoryEntryFilter,
+    HardLinkBehaviour,
+    IgnoreRules,
+    ScanEntryDepth,
+    ScanEntryOrdering,
     SymlinkBehaviour,
 };
 use crate::{
@@ -21,6 +36,7 @@ use crate::{
         DirectoryExecutionPlanError,
         SourceDirectoryPathValidationError,
     },
+    file::{CollidingFileBehaviour, FileCollisionResolution},
 };
 
 
@@ -47,10 +63,22 @@ pub(crate) enum QueuedOperation {
 
         /// Size of the `source_file_path` file in bytes.
         source_size_bytes: u64,
+
+        /// Device and inode number of the `source_file_path` file if it has other hard links,
+        /// as recorded while scanning (see [`hard_linked_file_identity`]).
+        ///
+        /// Used to recreate hard links (see [`DirectoryCopyPrepared::preserve_hard_links`]).
+        source_hard_link_identity: Option<(u64, u64)>,
     },
 
     /// Create a directory at `destination_directory_path`.
     CreateDirectory {
+        /// The source directory this directory is a copy of.
+        ///
+        /// Its metadata is applied to the created directory if requested
+        /// (see [`MetadataPreservation`][crate::file::MetadataPreservation]).
+        source_directory_path: PathBuf,
+
         /// Directory to create.
         destination_directory_path: PathBuf,
 
@@ -67,6 +95,12 @@ pub(crate) enum QueuedOperation {
 
     /// Create a symbolic link at `symlink_path`.
     CreateSymlink {
+        /// The source symbolic link this symbolic link is a copy of.
+        ///
+        /// Its extended attributes are copied to the created symbolic link if requested
+        /// (see [`ExtendedAttributeFilter`][crate::file::ExtendedAttributeFilter]).
+        source_symlink_path: PathBuf,
+
         /// Where to create the symbolic link.
         ///
         /// This is a path under the destination directory, but this is not reflected in the
@@ -86,6 +120,101 @@ pub(crate) enum QueuedOperation {
         /// Size of the symbolic link we're "copying".
         source_symlink_size_bytes: u64,
     },
+
+    /// Create a hard link at `hard_link_path` to the file at `target_path`.
+    ///
+    /// This replaces a [`QueuedOperation::CopyFile`] when the source file is a hard link
+    /// to a file that has already been queued for copying (see [`HardLinkBehaviour::Preserve`]).
+    CreateHardLink {
+        /// The source file this hard link is a copy of.
+        source_file_path: PathBuf,
+
+        /// Where to create the hard link.
+        hard_link_path: PathBuf,
+
+        /// The destination file the hard link should point to.
+        ///
+        /// This is the destination of an earlier queued operation, so it will already exist
+        /// by the time this operation is executed.
+        target_path: PathBuf,
+    },
+}
+
+impl QueuedOperation {
+    /// Returns the path this operation will create (or overwrite) on the destination.
+    pub(crate) fn destination_path(&self) -> &Path {
+        match self {
+            QueuedOperation::CopyFile {
+                destination_file_path,
+                ..
+            } => destination_file_path,
+            QueuedOperation::CreateDirectory {
+                destination_directory_path,
+                ..
+            } => destination_directory_path,
+            QueuedOperation::CreateSymlink { symlink_path, .. } => symlink_path,
+            QueuedOperation::CreateHardLink { hard_link_path, .. } => hard_link_path,
+        }
+    }
+
+    /// Returns the number of bytes this operation contributes to the total copy size.
+    ///
+    /// Hard links do not copy any bytes, so they do not contribute to it.
+    pub(crate) fn size_bytes(&self) -> u64 {
+        match self {
+            QueuedOperation::CopyFile {
+                source_size_bytes, ..
+            } => *source_size_bytes,
+            QueuedOperation::CreateDirectory {
+                source_size_bytes, ..
+            } => *source_size_bytes,
+            QueuedOperation::CreateSymlink {
+                source_symlink_size_bytes,
+                ..
+            } => *source_symlink_size_bytes,
+            QueuedOperation::CreateHardLink { .. } => 0,
+        }
+    }
+
+    /// Returns the source path this operation copies (or recreates) an entry from.
+    pub(crate) fn source_path(&self) -> &Path {
+        match self {
+            QueuedOperation::CopyFile {
+                source_file_path, ..
+            } => source_file_path,
+            QueuedOperation::CreateDirectory {
+                source_directory_path,
+                ..
+            } => source_directory_path,
+            QueuedOperation::CreateSymlink {
+                source_symlink_path,
+                ..
+            } => source_symlink_path,
+            QueuedOperation::CreateHardLink {
+                source_file_path, ..
+            } => source_file_path,
+        }
+    }
+
+    /// Changes the path this operation will create (or overwrite) on the destination.
+    fn set_destination_path(&mut self, new_destination_path: PathBuf) {
+        match self {
+            QueuedOperation::CopyFile {
+                destination_file_path,
+                ..
+            } => *destination_file_path = new_destination_path,
+            QueuedOperation::CreateDirectory {
+                destination_directory_path,
+                ..
+            } => *destination_directory_path = new_destination_path,
+            QueuedOperation::CreateSymlink { symlink_path, .. } => {
+                *symlink_path = new_destination_path
+            }
+            QueuedOperation::CreateHardLink { hard_link_path, .. } => {
+                *hard_link_path = new_destination_path
+            }
+        }
+    }
 }
 
 
@@ -106,6 +235,30 @@ pub(crate) fn try_exists_without_follow(path: &Path) -> std::io::Result<bool> {
 }
 
 
+/// Returns the identity of the file described by `metadata` if other hard links to it exist,
+/// i.e. its device and inode number.
+///
+/// Hard links are only detected on Unix, so this always returns `None` on other platforms.
+#[cfg(unix)]
+pub(crate) fn hard_linked_file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    match metadata.nlink() > 1 {
+        true => Some((metadata.dev(), metadata.ino())),
+        false => None,
+    }
+}
+
+/// Returns the identity of the file described by `metadata` if other hard links to it exist,
+/// i.e. its device and inode number.
+///
+/// Hard links are only detected on Unix, so this always returns `None` on other platforms.
+#[cfg(not(unix))]
+pub(crate) fn hard_linked_file_identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+
 
 /// Information about a validated source path (used in copying and moving directories).
 #[derive(Clone, Debug)]
@@ -390,6 +543,7 @@ fn scan_and_plan_directory_copy(
     copy_depth_limit: DirectoryCopyDepthLimit,
     symlink_behaviour: SymlinkBehaviour,
     broken_symlink_behaviour: BrokenSymlinkBehaviour,
+    entry_filter: &DirectoryEntryFilter,
 ) -> Result<Vec<QueuedOperation>, DirectoryExecutionPlanError> {
     let mut operation_queue: Vec<QueuedOperation> = Vec::new();
 
@@ -412,6 +566,7 @@ fn scan_and_plan_directory_copy(
         #[cfg(windows)]
         {
             operation_queue.push(QueuedOperation::CreateSymlink {
+                source_symlink_path: validated_source_directory.unfollowed_directory_path.clone(),
                 symlink_path: validated_destination_directory.directory_path.to_path_buf(),
                 symlink_destination_type: SymlinkType::Directory,
                 source_symlink_size_bytes,
@@ -422,6 +577,7 @@ fn scan_and_plan_directory_copy(
         #[cfg(not(windows))]
         {
             operation_queue.push(QueuedOperation::CreateSymlink {
+                source_symlink_path: validated_source_directory.unfollowed_directory_path.clone(),
                 symlink_path: validated_destination_directory.directory_path.to_path_buf(),
                 source_symlink_size_bytes,
                 symlink_destination_path: validated_source_directory.directory_path.to_path_buf(),
@@ -444,6 +600,7 @@ fn scan_and_plan_directory_copy(
                 .len();
 
         operation_queue.push(QueuedOperation::CreateDirectory {
+            source_directory_path: validated_source_directory.directory_path.to_path_buf(),
             source_size_bytes: source_path_size_bytes,
             destination_directory_path: validated_destination_directory
                 .directory_path
@@ -458,6 +615,8 @@ fn scan_and_plan_directory_copy(
     struct PendingDirectoryScan {
         directory_path: PathBuf,
         directory_path_without_symlink_follows: PathBuf,
+        relative_path: PathBuf,
+        parent_ignore_rules: Option<Arc<IgnoreRules>>,
         depth: usize,
     }
 
@@ -465,6 +624,8 @@ fn scan_and_plan_directory_copy(
     directory_scan_queue.push(PendingDirectoryScan {
         directory_path: validated_source_directory.directory_path.clone(),
         directory_path_without_symlink_follows: validated_source_directory.directory_path.clone(),
+        relative_path: PathBuf::new(),
+        parent_ignore_rules: None,
         depth: 0,
     });
 
@@ -474,13 +635,37 @@ fn scan_and_plan_directory_copy(
     while let Some(next_directory) = directory_scan_queue.pop() {
         // Scan the directory for its files and directories.
         // Files are queued for copying, directories are queued for creation.
-        let directory_iterator = fs::read_dir(&next_directory.directory_path).map_err(|error| {
-            DirectoryExecutionPlanError::UnableToAccess {
-                path: next_directory.directory_path.clone(),
-                error,
-            }
+        //
+        // Entries are sorted by their file name, which makes the order
+        // of queued operations reproducible across platforms and filesystems.
+        let directory_iterator = DirectoryEntries::read(
+            &next_directory.directory_path,
+            &ScanEntryOrdering::ByFileName,
+            ScanEntryDepth::AtDepth {
+                depth: next_directory.depth,
+            },
+        )
+        .map_err(|error| DirectoryExecutionPlanError::UnableToAccess {
+            path: next_directory.directory_path.clone(),
+            error,
         })?;
 
+        let ignore_rules = if entry_filter.respects_ignore_files() {
+            IgnoreRules::load_for_directory(
+                next_directory.parent_ignore_rules.as_ref(),
+                &next_directory.directory_path,
+                &next_directory.relative_path,
+            )
+            .map_err(|unreadable_ignore_file| {
+                DirectoryExecutionPlanError::UnableToAccess {
+                    path: unreadable_ignore_file.file_path,
+                    error: unreadable_ignore_file.error,
+                }
+            })?
+        } else {
+            None
+        };
+
         for directory_item in directory_iterator {
             let directory_item =
                 directory_item.map_err(|error| DirectoryExecutionPlanError::UnableToAccess {
@@ -492,8 +677,7 @@ fn scan_and_plan_directory_copy(
             let directory_item_name = directory_item_source_path.file_name().ok_or_else(|| {
                 DirectoryExecutionPlanError::UnableToAccess {
                     path: directory_item_source_path.clone(),
-                    error: io::Error::new(
-                        ErrorKind::Other,
+                    error: io::Error::other(
                         "ReadDir's iterator generated a path that terminates in \"..\"",
                     ),
                 }
@@ -508,6 +692,9 @@ fn scan_and_plan_directory_copy(
                 .directory_path_without_symlink_follows
                 .join(directory_item_name);
 
+            let directory_item_relative_path =
+                next_directory.relative_path.join(directory_item_name);
+
 
             // Remaps `new_directory_path_without_symlink_follows` (relative to `next_directory.source_directory_path`)
             // onto `destination_directory_path`, preserving directory structure.
@@ -530,6 +717,29 @@ fn scan_and_plan_directory_copy(
             })?;
 
 
+            // Entries that are skipped by the filter are not queued at all, and skipped
+            // directories are never scanned. As such, they are not reflected in the total size.
+            if !entry_filter.includes_everything() {
+                let item_metadata = directory_item.metadata().map_err(|error| {
+                    DirectoryExecutionPlanError::UnableToAccess {
+                        path: directory_item_source_path.clone(),
+                        error,
+                    }
+                })?;
+
+                if !entry_filter.is_included(
+                    &directory_item_relative_path,
+                    ScanEntryDepth::AtDepth {
+                        depth: next_directory.depth,
+                    },
+                    &item_metadata,
+                    ignore_rules.as_deref(),
+                ) {
+                    continue;
+                }
+            }
+
+
             if item_type.is_file() {
                 let file_metadata = directory_item.metadata().map_err(|error| {
                     DirectoryExecutionPlanError::UnableToAccess {
@@ -545,6 +755,7 @@ fn scan_and_plan_directory_copy(
                     source_file_path: directory_item_source_path,
                     source_size_bytes: file_size_in_bytes,
                     destination_file_path: directory_item_destination_path,
+                    source_hard_link_identity: hard_linked_file_identity(&file_metadata),
                 });
             } else if item_type.is_dir() {
                 let directory_metadata = directory_item.metadata().map_err(|error| {
@@ -559,6 +770,7 @@ fn scan_and_plan_directory_copy(
 
 
                 operation_queue.push(QueuedOperation::CreateDirectory {
+                    source_directory_path: directory_item_source_path.clone(),
                     source_size_bytes: directory_size_in_bytes,
                     destination_directory_path: directory_item_destination_path,
                     create_parent_directories: false,
@@ -574,6 +786,8 @@ fn scan_and_plan_directory_copy(
                                 directory_path: directory_item_source_path.clone(),
                                 directory_path_without_symlink_follows:
                                     new_directory_path_without_symlink_follows,
+                                relative_path: directory_item_relative_path,
+                                parent_ignore_rules: ignore_rules.clone(),
                                 depth: next_directory.depth + 1,
                             });
                         }
@@ -583,6 +797,8 @@ fn scan_and_plan_directory_copy(
                             directory_path: directory_item_source_path.clone(),
                             directory_path_without_symlink_follows:
                                 new_directory_path_without_symlink_follows,
+                            relative_path: directory_item_relative_path,
+                            parent_ignore_rules: ignore_rules.clone(),
                             depth: next_directory.depth + 1,
                         });
                     }
@@ -664,6 +880,7 @@ fn scan_and_plan_directory_copy(
 
 
                                 operation_queue.push(QueuedOperation::CreateSymlink {
+                                    source_symlink_path: directory_item_source_path.clone(),
                                     symlink_path: directory_item_destination_path,
                                     symlink_destination_type: symbolic_link_type,
                                     source_symlink_size_bytes: unresolved_symlink_file_size,
@@ -683,6 +900,7 @@ fn scan_and_plan_directory_copy(
                         match broken_symlink_behaviour {
                             BrokenSymlinkBehaviour::Keep => {
                                 operation_queue.push(QueuedOperation::CreateSymlink {
+                                    source_symlink_path: directory_item_source_path.clone(),
                                     symlink_path: directory_item_destination_path,
                                     source_symlink_size_bytes: unresolved_symlink_file_size,
                                     symlink_destination_path: resolved_symlink_path,
@@ -753,6 +971,7 @@ fn scan_and_plan_directory_copy(
 
 
                             operation_queue.push(QueuedOperation::CreateSymlink {
+                                source_symlink_path: directory_item_source_path.clone(),
                                 symlink_path: directory_item_destination_path,
                                 symlink_destination_type: symlink_type,
                                 source_symlink_size_bytes: resolved_symlink_file_size,
@@ -763,6 +982,7 @@ fn scan_and_plan_directory_copy(
                         #[cfg(unix)]
                         {
                             operation_queue.push(QueuedOperation::CreateSymlink {
+                                source_symlink_path: directory_item_source_path.clone(),
                                 symlink_path: directory_item_destination_path,
                                 source_symlink_size_bytes: resolved_symlink_file_size,
                                 symlink_destination_path: resolved_symlink_path,
@@ -785,9 +1005,13 @@ fn scan_and_plan_directory_copy(
                                 source_file_path: resolved_absolute_symlink_path.to_path_buf(),
                                 source_size_bytes: resolved_symlink_file_size,
                                 destination_file_path: directory_item_destination_path,
+                                source_hard_link_identity: hard_linked_file_identity(
+                                    &resolved_symlink_metadata,
+                                ),
                             });
                         } else if resolved_symlink_file_type.is_dir() {
                             operation_queue.push(QueuedOperation::CreateDirectory {
+                                source_directory_path: resolved_absolute_symlink_path.to_path_buf(),
                                 source_size_bytes: resolved_symlink_file_size,
                                 destination_directory_path: directory_item_destination_path,
                                 create_parent_directories: false,
@@ -803,6 +1027,8 @@ fn scan_and_plan_directory_copy(
                                             directory_path: directory_item_source_path.clone(),
                                             directory_path_without_symlink_follows:
                                                 new_directory_path_without_symlink_follows,
+                                            relative_path: directory_item_relative_path,
+                                            parent_ignore_rules: ignore_rules.clone(),
                                             depth: next_directory.depth + 1,
                                         });
                                     }
@@ -812,6 +1038,8 @@ fn scan_and_plan_directory_copy(
                                         directory_path: directory_item_source_path,
                                         directory_path_without_symlink_follows:
                                             new_directory_path_without_symlink_follows,
+                                        relative_path: directory_item_relative_path,
+                                        parent_ignore_rules: ignore_rules.clone(),
                                         depth: next_directory.depth + 1,
                                     });
                                 }
@@ -836,10 +1064,13 @@ fn scan_and_plan_directory_copy(
 
 /// Given a list of references to [`QueuedOperation`]s, this function validates that
 /// the files and directories this queue would process match the provided [`DestinationDirectoryRule`].
-fn check_operation_queue_for_collisions(
-    queue: &[QueuedOperation],
+pub(super) fn check_operation_queue_for_collisions<'q, Q>(
+    queue: Q,
     destination_directory_rules: DestinationDirectoryRule,
-) -> Result<(), DirectoryExecutionPlanError> {
+) -> Result<(), DirectoryExecutionPlanError>
+where
+    Q: IntoIterator<Item = &'q QueuedOperation>,
+{
     let overwriting_existing_destination_files_allowed =
         destination_directory_rules.allows_overwriting_existing_destination_files();
 
@@ -860,6 +1091,10 @@ fn check_operation_queue_for_collisions(
             QueuedOperation::CopyFile {
                 destination_file_path,
                 ..
+            }
+            | QueuedOperation::CreateHardLink {
+                hard_link_path: destination_file_path,
+                ..
             } => {
                 if !overwriting_existing_destination_files_allowed {
                     let destination_file_exists = try_exists_without_follow(destination_file_path)
@@ -921,17 +1156,40 @@ fn check_operation_queue_for_collisions(
 }
 
 
-
 /// An auxiliary struct that contains a set of operations required for a directory copy.
 ///
 /// It can be initialized by calling [`Self::prepare`] or [`Self::prepare_with_validated`].
+#[derive(Debug)]
 pub(crate) struct DirectoryCopyPrepared {
     /// An array of ordered file copy and directory creation operations
     /// that togeher form a requested directory copy.
     pub(crate) operation_queue: Vec<QueuedOperation>,
 
     /// How many bytes will need to be copied (i.e. the source directory size).
+    ///
+    /// This includes the bytes of [`Self::resumed_operations`].
     pub(crate) total_bytes: u64,
+
+    /// The validated destination directory path, which all destination paths
+    /// in the operation queue are under.
+    pub(crate) destination_directory_path: PathBuf,
+
+    /// Operations that have already been completed by a previous, interrupted copy,
+    /// and have been removed from the operation queue.
+    ///
+    /// This is only ever non-empty when resuming a copy (see [`DirectoryCopyResumeJournal`]).
+    pub(crate) resumed_operations: Vec<QueuedOperation>,
+
+    /// The journal to persist executed operations to, if the copy is resumable.
+    pub(crate) resume_journal: Option<Arc<DirectoryCopyResumeJournal>>,
+
+    /// Alternative paths that colliding file copies (or symbolic links) have been redirected to,
+    /// because of [`CollidingFileBehaviour::KeepBoth`] or [`DirectoryCollisionDecision::Rename`].
+    pub(crate) alternative_destination_file_paths: Vec<PathBuf>,
+
+    /// Source paths of the files and symbolic links that have been removed
+    /// from the operation queue because their collisions were resolved by skipping them.
+    pub(crate) skipped_source_paths: Vec<PathBuf>,
 }
 
 
@@ -947,6 +1205,7 @@ impl DirectoryCopyPrepared {
         copy_depth_limit: DirectoryCopyDepthLimit,
         symlink_behaviour: SymlinkBehaviour,
         broken_symlink_behaviour: BrokenSymlinkBehaviour,
+        entry_filter: &DirectoryEntryFilter,
     ) -> Result<Self, CopyDirectoryPreparationError> {
         let (canonical_source_directory_path, validated_destination) =
             Self::validate_source_and_destination(
@@ -962,6 +1221,7 @@ impl DirectoryCopyPrepared {
             copy_depth_limit,
             symlink_behaviour,
             broken_symlink_behaviour,
+            entry_filter,
         )
         .map_err(CopyDirectoryPreparationError::CopyPlanningError)
     }
@@ -976,7 +1236,10 @@ impl DirectoryCopyPrepared {
         copy_depth_limit: DirectoryCopyDepthLimit,
         symlink_behaviour: SymlinkBehaviour,
         broken_symlink_behaviour: BrokenSymlinkBehaviour,
+        entry_filter: &DirectoryEntryFilter,
     ) -> Result<Self, DirectoryExecutionPlanError> {
+        let destination_directory_path = validated_destination_directory.directory_path.clone();
+
         let operations = Self::prepare_directory_operations(
             &validated_source_directory,
             &validated_destination_directory,
@@ -984,36 +1247,315 @@ impl DirectoryCopyPrepared {
             copy_depth_limit,
             symlink_behaviour,
             broken_symlink_behaviour,
+            entry_filter,
         )?;
 
         let bytes_total = Self::calculate_total_bytes_to_be_copied(&operations);
 
 
-        Ok(Self {
+        let mut prepared_copy = Self {
             operation_queue: operations,
             total_bytes: bytes_total,
+            destination_directory_path,
+            resumed_operations: Vec::new(),
+            resume_journal: None,
+            alternative_destination_file_paths: Vec::new(),
+            skipped_source_paths: Vec::new(),
+        };
+
+        prepared_copy.resolve_collisions_with_rule(destination_directory_rule)?;
+
+
+        Ok(prepared_copy)
+    }
+
+    /// Returns the number of entries and bytes that have already been copied
+    /// by a previous, interrupted copy (see [`Self::resumed_operations`]).
+    pub(crate) fn resumed_copy(&self) -> DirectoryCopyFinished {
+        let mut resumed_copy = DirectoryCopyFinished {
+            total_bytes_copied: 0,
+            files_copied: 0,
+            symlinks_created: 0,
+            directories_created: 0,
+            files_copied_to_alternative_paths: Vec::new(),
+            hard_links_created: 0,
+        };
+
+        for operation in &self.resumed_operations {
+            match operation {
+                QueuedOperation::CopyFile { .. } => resumed_copy.files_copied += 1,
+                QueuedOperation::CreateDirectory { .. } => resumed_copy.directories_created += 1,
+                QueuedOperation::CreateSymlink { .. } => resumed_copy.symlinks_created += 1,
+                QueuedOperation::CreateHardLink { .. } => resumed_copy.hard_links_created += 1,
+            }
+
+            resumed_copy.total_bytes_copied += operation.size_bytes();
+        }
+
+        resumed_copy
+    }
+
+    /// Resolves file copies whose destination file already exists according to the
+    /// [`CollidingFileBehaviour`] of `destination_directory_rule`, if it is
+    /// [`CollidingFileBehaviour::OverwriteIfNewer`], [`CollidingFileBehaviour::OverwriteIfDifferent`]
+    /// or [`CollidingFileBehaviour::KeepBoth`] (see [`Self::resolve_collisions`]).
+    ///
+    /// Other behaviours are fully handled by [`check_operation_queue_for_collisions`].
+    /// Colliding symbolic links are kept with [`CollidingFileBehaviour::KeepBoth`],
+    /// and overwritten with the other two behaviours.
+    pub(crate) fn resolve_collisions_with_rule(
+        &mut self,
+        destination_directory_rule: DestinationDirectoryRule,
+    ) -> Result<(), DirectoryExecutionPlanError> {
+        let DestinationDirectoryRule::AllowNonEmpty {
+            colliding_file_behaviour:
+                colliding_file_behaviour @ (CollidingFileBehaviour::OverwriteIfNewer
+                | CollidingFileBehaviour::OverwriteIfDifferent
+                | CollidingFileBehaviour::KeepBoth { .. }),
+            ..
+        } = destination_directory_rule
+        else {
+            return Ok(());
+        };
+
+        self.resolve_collisions(|_, _, _| Ok(Some(colliding_file_behaviour)))
+    }
+
+    /// Resolves file copies and symbolic links whose destination already exists
+    /// by asking the `collision_resolver` what to do with each of them (see [`Self::resolve_collisions`]).
+    ///
+    /// Once the resolver returns [`DirectoryCollisionDecision::ApplyToAll`],
+    /// the behaviour is applied to all remaining collisions without asking it again.
+    pub(crate) fn resolve_collisions_with_resolver(
+        &mut self,
+        collision_resolver: &DirectoryCollisionResolver,
+    ) -> Result<(), DirectoryExecutionPlanError> {
+        let mut behaviour_for_remaining_collisions = None;
+
+        self.resolve_collisions(|source_path, destination_path, is_symlink| {
+            if let Some(colliding_file_behaviour) = behaviour_for_remaining_collisions {
+                return Ok(Some(colliding_file_behaviour));
+            }
+
+            let source_metadata = match is_symlink {
+                true => fs::symlink_metadata(source_path),
+                false => fs::metadata(source_path),
+            }
+            .map_err(|error| DirectoryExecutionPlanError::UnableToAccess {
+                path: source_path.to_path_buf(),
+                error,
+            })?;
+
+            let destination_metadata = fs::symlink_metadata(destination_path).map_err(|error| {
+                DirectoryExecutionPlanError::UnableToAccess {
+                    path: destination_path.to_path_buf(),
+                    error,
+                }
+            })?;
+
+
+            let decision = collision_resolver.resolve(&DirectoryCollision {
+                source_path,
+                source_metadata: &source_metadata,
+                destination_path,
+                destination_metadata: &destination_metadata,
+            });
+
+            if let DirectoryCollisionDecision::ApplyToAll(colliding_file_behaviour) = decision {
+                behaviour_for_remaining_collisions = Some(colliding_file_behaviour);
+            }
+
+            Ok(Some(decision.to_colliding_file_behaviour()))
         })
     }
 
+    /// Resolves file copies and symbolic links whose destination already exists.
+    ///
+    /// For each such operation, `behaviour_for_collision` is called with its source
+    /// and destination path, and whether it is a symbolic link. If it returns a behaviour,
+    /// the collision is resolved accordingly (otherwise, the operation is left as it is):
+    /// - operations that should be skipped are removed from the operation queue,
+    /// - operations that should keep the existing entry are redirected to a free alternative path,
+    ///   which is chosen so that it collides neither with an existing entry
+    ///   nor with any other queued destination path, and
+    /// - if the behaviour is [`CollidingFileBehaviour::Abort`], an error is returned.
+    ///
+    /// Symbolic links are overwritten with [`CollidingFileBehaviour::OverwriteIfNewer`]
+    /// and [`CollidingFileBehaviour::OverwriteIfDifferent`]. Files left over by a previous,
+    /// interrupted copy (see [`Self::resume_journal`]) are never considered collisions.
+    fn resolve_collisions<B>(
+        &mut self,
+        mut behaviour_for_collision: B,
+    ) -> Result<(), DirectoryExecutionPlanError>
+    where
+        B: FnMut(
+            &Path,
+            &Path,
+            bool,
+        ) -> Result<Option<CollidingFileBehaviour>, DirectoryExecutionPlanError>,
+    {
+        let operation_queue = mem::take(&mut self.operation_queue);
+
+        let mut taken_destination_paths = operation_queue
+            .iter()
+            .map(|operation| operation.destination_path().to_path_buf())
+            .collect::<HashSet<_>>();
+
+        let mut resolved_operation_queue = Vec::with_capacity(operation_queue.len());
+
+
+        for mut operation in operation_queue {
+            let is_symlink = match &operation {
+                QueuedOperation::CopyFile { .. } | QueuedOperation::CreateHardLink { .. } => false,
+                QueuedOperation::CreateSymlink { .. } => true,
+                QueuedOperation::CreateDirectory { .. } => {
+                    resolved_operation_queue.push(operation);
+                    continue;
+                }
+            };
+
+            let source_path = operation.source_path();
+            let destination_path = operation.destination_path();
+
+            let is_left_over_file = self
+                .resume_journal
+                .as_ref()
+                .is_some_and(|resume_journal| resume_journal.is_left_over_file(destination_path));
+
+            let map_access_error = |error: io::Error| DirectoryExecutionPlanError::UnableToAccess {
+                path: destination_path.to_path_buf(),
+                error,
+            };
+
+            if is_left_over_file
+                || !try_exists_without_follow(destination_path).map_err(map_access_error)?
+            {
+                resolved_operation_queue.push(operation);
+                continue;
+            }
+
+
+            let Some(colliding_file_behaviour) =
+                behaviour_for_collision(source_path, destination_path, is_symlink)?
+            else {
+                resolved_operation_queue.push(operation);
+                continue;
+            };
+
+            let colliding_file_behaviour = match colliding_file_behaviour {
+                CollidingFileBehaviour::OverwriteIfNewer
+                | CollidingFileBehaviour::OverwriteIfDifferent
+                    if is_symlink =>
+                {
+                    CollidingFileBehaviour::Overwrite
+                }
+                colliding_file_behaviour => colliding_file_behaviour,
+            };
+
+            let collision_resolution = colliding_file_behaviour
+                .resolve_collision(source_path, destination_path, |path| {
+                    Ok(taken_destination_paths.contains(path) || try_exists_without_follow(path)?)
+                })
+                .map_err(map_access_error)?;
+
+
+            match collision_resolution {
+                None => {
+                    return Err(DirectoryExecutionPlanError::DestinationItemAlreadyExists {
+                        path: destination_path.to_path_buf(),
+                    });
+                }
+                Some(FileCollisionResolution::Skip) => {
+                    self.total_bytes -= operation.size_bytes();
+                    self.skipped_source_paths
+                        .push(operation.source_path().to_path_buf());
+                }
+                Some(FileCollisionResolution::Overwrite) => {
+                    resolved_operation_queue.push(operation);
+                }
+                Some(FileCollisionResolution::WriteToAlternativePath(alternative_path)) => {
+                    taken_destination_paths.insert(alternative_path.clone());
+                    self.alternative_destination_file_paths
+                        .push(alternative_path.clone());
+
+                    operation.set_destination_path(alternative_path);
+                    resolved_operation_queue.push(operation);
+                }
+            }
+        }
+
+        self.operation_queue = resolved_operation_queue;
+
+        Ok(())
+    }
+
+    /// Replaces file copies with hard links if `hard_link_behaviour` is
+    /// [`HardLinkBehaviour::Preserve`].
+    ///
+    /// Source files are identified by the device and inode number recorded while scanning,
+    /// so no metadata is read again here. The first queued copy
+    /// of each hard-linked file (including copies completed by a previous, interrupted copy)
+    /// is kept, and every later copy of the same file is replaced
+    /// with a [`QueuedOperation::CreateHardLink`] to its destination.
+    ///
+    /// This must be called once the collisions have been resolved, so that skipped copies
+    /// are not linked to and the final destination paths are known.
+    pub(crate) fn preserve_hard_links(&mut self, hard_link_behaviour: HardLinkBehaviour) {
+        if hard_link_behaviour != HardLinkBehaviour::Preserve {
+            return;
+        }
+
+        let mut copied_file_destinations: HashMap<(u64, u64), PathBuf> = HashMap::new();
+
+        for operation in &self.resumed_operations {
+            if let QueuedOperation::CopyFile {
+                destination_file_path,
+                source_hard_link_identity: Some(file_identity),
+                ..
+            } = operation
+            {
+                copied_file_destinations
+                    .entry(*file_identity)
+                    .or_insert_with(|| destination_file_path.clone());
+            }
+        }
+
+        for operation in &mut self.operation_queue {
+            let QueuedOperation::CopyFile {
+                source_file_path,
+                destination_file_path,
+                source_size_bytes,
+                source_hard_link_identity: Some(file_identity),
+            } = operation
+            else {
+                continue;
+            };
+
+            match copied_file_destinations.entry(*file_identity) {
+                Entry::Vacant(vacant_entry) => {
+                    vacant_entry.insert(destination_file_path.clone());
+                }
+                Entry::Occupied(occupied_entry) => {
+                    self.total_bytes -= *source_size_bytes;
+
+                    *operation = QueuedOperation::CreateHardLink {
+                        source_file_path: mem::take(source_file_path),
+                        hard_link_path: mem::take(destination_file_path),
+                        target_path: occupied_entry.get().clone(),
+                    };
+                }
+            }
+        }
+    }
+
     fn calculate_total_bytes_to_be_copied(queued_operations: &[QueuedOperation]) -> u64 {
         queued_operations
             .iter()
-            .map(|item| match item {
-                QueuedOperation::CopyFile {
-                    source_size_bytes, ..
-                } => *source_size_bytes,
-                QueuedOperation::CreateDirectory {
-                    source_size_bytes, ..
-                } => *source_size_bytes,
-                QueuedOperation::CreateSymlink {
-                    source_symlink_size_bytes: source_size_bytes,
-                    ..
-                } => *source_size_bytes,
-            })
+            .map(QueuedOperation::size_bytes)
             .sum::<u64>()
     }
 
-    fn validate_source_and_destination(
+    pub(super) fn validate_source_and_destination(
         source_directory_path: &Path,
         destination_directory_path: &Path,
         destination_directory_rule: DestinationDirectoryRule,
@@ -1049,10 +1591,14 @@ impl DirectoryCopyPrepared {
     /// It's of course still possible that the destination directory ends up in an unpredictable state,
     /// since a [time-of-check time-of-use](https://en.wikipedia.org/wiki/Time-of-check_to_time-of-use)
     /// race condition is still possible.
-    /// However, those cases should be very rare and are essentially unsolvable,
-    /// unless there was a robust rollback mechanism (but this would require platform-specific implementation).
-    /// For example: Windows
+    /// However, those cases should be very rare. If they matter, callers can opt into
+    /// [`RollbackBehaviour::RollBackOnFailure`], which journals executed operations and undoes
+    /// them on failure. This is a best-effort mechanism, not a filesystem transaction
+    /// (for example, Windows
     /// [cautions against using transactional NTFS](https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-findfirstfiletransacteda)).
+    ///
+    ///
+    /// [`RollbackBehaviour::RollBackOnFailure`]: super::RollbackBehaviour::RollBackOnFailure
     fn prepare_directory_operations(
         validated_source_directory: &ValidatedSourceDirectory,
         validated_destination_directory: &ValidatedDestinationDirectory,
@@ -1060,6 +1606,7 @@ impl DirectoryCopyPrepared {
         copy_depth_limit: DirectoryCopyDepthLimit,
         symlink_behaviour: SymlinkBehaviour,
         broken_symlink_behaviour: BrokenSymlinkBehaviour,
+        entry_filter: &DirectoryEntryFilter,
     ) -> Result<Vec<QueuedOperation>, DirectoryExecutionPlanError> {
         // Initialize a queue of file copy or directory create operations.
         let copy_queue = scan_and_plan_directory_copy(
@@ -1068,6 +1615,7 @@ impl DirectoryCopyPrepared {
             copy_depth_limit,
             symlink_behaviour,
             broken_symlink_behaviour,
+            entry_filter,
         )?;
 
         check_operation_queue_for_collisions(&copy_queue, destination_directory_rule)?;
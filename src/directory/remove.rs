@@ -0,0 +1,769 @@
+use std::path::{Path, PathBuf};
+
+use_enabled_fs_module!();
+
+use super::{
+    is_directory_empty_unchecked,
+    DirectoryEntryFilter,
+    DirectoryScanDepthLimit,
+    DirectoryScanOptions,
+    DirectoryScanner,
+    ScanEntryDepth,
+    ScanEntryOrdering,
+};
+use crate::{
+    error::{RemoveDirectoryError, RemoveDirectoryExecutionError, RemoveDirectoryPreparationError},
+    ProgressAction,
+};
+
+
+/// The maximum depth of a directory removal operation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DirectoryRemoveDepthLimit {
+    /// No depth limit - the entire directory tree will be removed.
+    Unlimited,
+
+    /// Removal depth is limited to `maximum_depth`, where the value refers to
+    /// the maximum depth of the subdirectory whose contents may still be removed.
+    ///
+    /// A directory can only be removed once it is empty, which is why this limit
+    /// acts as a safety net: if any subdirectory *at* the maximum depth is not empty,
+    /// the removal is aborted during preparation, before anything is removed
+    /// (see [`DepthLimitExceeded`]).
+    ///
+    ///
+    /// # Examples
+    /// `maximum_depth = 0` allows removing a directory that only contains files, symbolic links
+    /// and *empty* subdirectories:
+    ///
+    /// ```md
+    /// ~/directory-to-remove
+    ///  |- foo.csv
+    ///  |- foo-2.csv
+    ///  |- bar/
+    ///     (must be empty)
+    /// ```
+    ///
+    ///
+    /// <br>
+    ///
+    /// `maximum_depth = 1` additionally allows one level of files and subdirectories
+    /// inside those subdirectories.
+    ///
+    /// ```md
+    /// ~/directory-to-remove
+    ///  |- foo.csv
+    ///  |- foo-2.csv
+    ///  |- bar/
+    ///     |- hello-world.txt
+    ///     |- bar2/
+    ///        (must be empty)
+    /// ```
+    ///
+    ///
+    /// [`DepthLimitExceeded`]: crate::error::RemoveDirectoryPreparationError::DepthLimitExceeded
+    Limited {
+        /// Maximum removal depth.
+        maximum_depth: usize,
+    },
+}
+
+
+
+/// Options that influence the [`remove_directory`] and
+/// [`remove_directory_with_progress`] functions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DirectoryRemoveOptions {
+    /// Maximum depth of the directory tree to remove.
+    pub remove_depth_limit: DirectoryRemoveDepthLimit,
+}
+
+impl Default for DirectoryRemoveOptions {
+    /// Constructs defaults for removing a directory, which are:
+    /// - [`DirectoryRemoveDepthLimit::Unlimited`]: there is no removal depth limit.
+    #[allow(clippy::derivable_impls)]
+    fn default() -> Self {
+        Self {
+            remove_depth_limit: DirectoryRemoveDepthLimit::Unlimited,
+        }
+    }
+}
+
+
+
+/// Describes a successful directory removal operation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DirectoryRemoveFinished {
+    /// Total number of bytes removed.
+    ///
+    /// This is the sum of sizes of all removed entries,
+    /// i.e. it matches what [`directory_size_in_bytes`] would have returned
+    /// for the same directory before removal.
+    ///
+    ///
+    /// [`directory_size_in_bytes`]: super::directory_size_in_bytes
+    pub total_bytes_removed: u64,
+
+    /// Total number of files removed.
+    pub files_removed: usize,
+
+    /// Total number of symbolic links removed.
+    ///
+    /// Symbolic links are never followed, meaning only the links themselves are removed.
+    pub symlinks_removed: usize,
+
+    /// Total number of directories removed, *including* the base directory.
+    pub directories_removed: usize,
+}
+
+
+
+/// A single removal operation, as planned by [`DirectoryRemovePrepared`].
+pub(crate) enum QueuedRemoval {
+    /// Remove a file.
+    File {
+        /// Path of the file to remove.
+        file_path: PathBuf,
+
+        /// Size of the file in bytes.
+        size_bytes: u64,
+    },
+
+    /// Remove a symbolic link (but not its destination).
+    Symlink {
+        /// Path of the symbolic link to remove.
+        symlink_path: PathBuf,
+
+        /// Whether the symbolic link is a directory symbolic link.
+        /// On Windows, these must be removed as if they were directories.
+        #[cfg(windows)]
+        is_directory_symlink: bool,
+
+        /// Size of the symbolic link itself, in bytes.
+        size_bytes: u64,
+    },
+
+    /// Remove an empty directory.
+    ///
+    /// The planner guarantees that all of the directory's contents
+    /// are removed before this operation is executed.
+    Directory {
+        /// Path of the directory to remove.
+        directory_path: PathBuf,
+
+        /// Size of the directory in bytes (as reported by its metadata).
+        size_bytes: u64,
+
+        /// Whether to keep the directory (instead of failing) if it is not empty
+        /// when it is about to be removed.
+        ///
+        /// This is `true` only for filtered removals, where the contents
+        /// skipped by the filter are kept.
+        keep_if_not_empty: bool,
+    },
+}
+
+
+/// A prepared directory removal, i.e. an ordered queue of removals.
+pub(crate) struct DirectoryRemovePrepared {
+    /// Removal operations, in the order they must be executed.
+    operation_queue: Vec<QueuedRemoval>,
+
+    /// Total number of bytes that will be removed.
+    total_bytes: u64,
+}
+
+impl DirectoryRemovePrepared {
+    /// Scans the directory tree at `directory_path` and prepares an ordered
+    /// list of removals, making sure each directory is removed only after its contents.
+    ///
+    /// Symbolic links are never followed. If `directory_path` is itself a symbolic link
+    /// to a directory, only the link is scheduled for removal.
+    pub(crate) fn prepare(
+        directory_path: &Path,
+        options: DirectoryRemoveOptions,
+    ) -> Result<Self, RemoveDirectoryPreparationError> {
+        Self::prepare_with_filter(directory_path, options, &DirectoryEntryFilter::default())
+    }
+
+    /// Same as [`Self::prepare`], but only schedules the removal of entries
+    /// that are included by `entry_filter`.
+    ///
+    /// Entries that are skipped by the filter (including the entire subtree of a skipped
+    /// directory) are kept, as are their ancestor directories, which will not be empty
+    /// when it comes to removing them.
+    pub(crate) fn prepare_with_filter(
+        directory_path: &Path,
+        options: DirectoryRemoveOptions,
+        entry_filter: &DirectoryEntryFilter,
+    ) -> Result<Self, RemoveDirectoryPreparationError> {
+        let directory_metadata = fs::symlink_metadata(directory_path).map_err(|error| {
+            if error.kind() == std::io::ErrorKind::NotFound {
+                RemoveDirectoryPreparationError::NotFound {
+                    directory_path: directory_path.to_path_buf(),
+                }
+            } else {
+                RemoveDirectoryPreparationError::UnableToAccess {
+                    directory_path: directory_path.to_path_buf(),
+                    error,
+                }
+            }
+        })?;
+
+
+        if directory_metadata.is_symlink() {
+            // We never follow symbolic links, so the only thing we remove is the link itself,
+            // but we do still require it to point to a directory.
+            let points_to_directory = fs::metadata(directory_path)
+                .map(|metadata| metadata.is_dir())
+                .unwrap_or(false);
+
+            if !points_to_directory {
+                return Err(RemoveDirectoryPreparationError::NotADirectory {
+                    path: directory_path.to_path_buf(),
+                });
+            }
+
+            return Ok(Self {
+                operation_queue: vec![QueuedRemoval::Symlink {
+                    symlink_path: directory_path.to_path_buf(),
+                    #[cfg(windows)]
+                    is_directory_symlink: true,
+                    size_bytes: directory_metadata.len(),
+                }],
+                total_bytes: directory_metadata.len(),
+            });
+        }
+
+        if !directory_metadata.is_dir() {
+            return Err(RemoveDirectoryPreparationError::NotADirectory {
+                path: directory_path.to_path_buf(),
+            });
+        }
+
+
+        let maximum_scan_depth = match options.remove_depth_limit {
+            DirectoryRemoveDepthLimit::Unlimited => DirectoryScanDepthLimit::Unlimited,
+            DirectoryRemoveDepthLimit::Limited { maximum_depth } => {
+                DirectoryScanDepthLimit::Limited { maximum_depth }
+            }
+        };
+
+        let scanner = DirectoryScanner::new(
+            directory_path,
+            DirectoryScanOptions {
+                yield_base_directory: true,
+                maximum_scan_depth,
+                follow_symbolic_links: false,
+                follow_base_directory_symbolic_link: false,
+                entry_filter: entry_filter.clone(),
+                entry_ordering: ScanEntryOrdering::Unsorted,
+            },
+        );
+
+        let keep_non_empty_directories = !entry_filter.includes_everything();
+
+
+        let mut operation_queue = Vec::new();
+        let mut total_bytes = 0;
+
+        for scan_entry_result in scanner {
+            let scan_entry = scan_entry_result?;
+
+            let entry_depth = *scan_entry.depth();
+            let (entry_path, entry_metadata) = scan_entry.into_path_and_metadata();
+            let entry_size_bytes = entry_metadata.len();
+            let entry_file_type = entry_metadata.file_type();
+
+            total_bytes += entry_size_bytes;
+
+
+            if entry_file_type.is_symlink() {
+                #[cfg(windows)]
+                let is_directory_symlink = {
+                    use std::os::windows::fs::FileTypeExt;
+
+                    entry_file_type.is_symlink_dir()
+                };
+
+                operation_queue.push(QueuedRemoval::Symlink {
+                    symlink_path: entry_path,
+                    #[cfg(windows)]
+                    is_directory_symlink,
+                    size_bytes: entry_size_bytes,
+                });
+            } else if entry_file_type.is_dir() {
+                // The scanner does not descend into directories at the maximum depth,
+                // which means we can only remove them if they are empty.
+                if let (
+                    DirectoryRemoveDepthLimit::Limited { maximum_depth },
+                    ScanEntryDepth::AtDepth { depth },
+                ) = (options.remove_depth_limit, entry_depth)
+                {
+                    if depth == maximum_depth {
+                        let is_empty =
+                            is_directory_empty_unchecked(&entry_path).map_err(|error| {
+                                RemoveDirectoryPreparationError::UnableToAccess {
+                                    directory_path: entry_path.clone(),
+                                    error,
+                                }
+                            })?;
+
+                        if !is_empty {
+                            return Err(RemoveDirectoryPreparationError::DepthLimitExceeded {
+                                directory_path: entry_path,
+                            });
+                        }
+                    }
+                }
+
+                operation_queue.push(QueuedRemoval::Directory {
+                    directory_path: entry_path,
+                    size_bytes: entry_size_bytes,
+                    keep_if_not_empty: keep_non_empty_directories,
+                });
+            } else {
+                operation_queue.push(QueuedRemoval::File {
+                    file_path: entry_path,
+                    size_bytes: entry_size_bytes,
+                });
+            }
+        }
+
+
+        // The scan is breadth-first, meaning every entry is yielded after all of its ancestors.
+        // Reversing the order therefore guarantees that the contents of each directory
+        // are removed before the directory itself.
+        operation_queue.reverse();
+
+
+        Ok(Self {
+            operation_queue,
+            total_bytes,
+        })
+    }
+}
+
+
+
+/// Removes a single file or symlink, or an empty directory, as described by the [`QueuedRemoval`].
+///
+/// Returns `false` if the entry was intentionally kept
+/// (see [`QueuedRemoval::Directory::keep_if_not_empty`]), `true` otherwise.
+fn execute_queued_removal(
+    operation: &QueuedRemoval,
+) -> Result<bool, RemoveDirectoryExecutionError> {
+    match operation {
+        QueuedRemoval::File { file_path, .. } => {
+            fs::remove_file(file_path).map_err(|error| {
+                RemoveDirectoryExecutionError::UnableToRemoveFile {
+                    file_path: file_path.clone(),
+                    error,
+                }
+            })?;
+        }
+
+        #[cfg(windows)]
+        QueuedRemoval::Symlink {
+            symlink_path,
+            is_directory_symlink,
+            ..
+        } => {
+            let removal_result = if *is_directory_symlink {
+                fs::remove_dir(symlink_path)
+            } else {
+                fs::remove_file(symlink_path)
+            };
+
+            removal_result.map_err(|error| {
+                RemoveDirectoryExecutionError::UnableToRemoveSymbolicLink {
+                    symlink_path: symlink_path.clone(),
+                    error,
+                }
+            })?;
+        }
+
+        #[cfg(unix)]
+        QueuedRemoval::Symlink { symlink_path, .. } => {
+            fs::remove_file(symlink_path).map_err(|error| {
+                RemoveDirectoryExecutionError::UnableToRemoveSymbolicLink {
+                    symlink_path: symlink_path.clone(),
+                    error,
+                }
+            })?;
+        }
+
+        QueuedRemoval::Directory {
+            directory_path,
+            keep_if_not_empty,
+            ..
+        } => {
+            if *keep_if_not_empty {
+                let is_empty = is_directory_empty_unchecked(directory_path).map_err(|error| {
+                    RemoveDirectoryExecutionError::UnableToRemoveDirectory {
+                        directory_path: directory_path.clone(),
+                        error,
+                    }
+                })?;
+
+                if !is_empty {
+                    return Ok(false);
+                }
+            }
+
+            fs::remove_dir(directory_path).map_err(|error| {
+                RemoveDirectoryExecutionError::UnableToRemoveDirectory {
+                    directory_path: directory_path.clone(),
+                    error,
+                }
+            })?;
+        }
+    }
+
+    Ok(true)
+}
+
+
+/// Perform a removal using prepared data from [`DirectoryRemovePrepared`].
+///
+/// For more details, see [`remove_directory`].
+pub(crate) fn remove_directory_unchecked(
+    prepared_removal: DirectoryRemovePrepared,
+) -> Result<DirectoryRemoveFinished, RemoveDirectoryExecutionError> {
+    let mut total_bytes_removed = 0;
+
+    let mut num_files_removed = 0;
+    let mut num_symlinks_removed = 0;
+    let mut num_directories_removed = 0;
+
+
+    for operation in prepared_removal.operation_queue {
+        if !execute_queued_removal(&operation)? {
+            continue;
+        }
+
+        match operation {
+            QueuedRemoval::File { size_bytes, .. } => {
+                num_files_removed += 1;
+                total_bytes_removed += size_bytes;
+            }
+            QueuedRemoval::Symlink { size_bytes, .. } => {
+                num_symlinks_removed += 1;
+                total_bytes_removed += size_bytes;
+            }
+            QueuedRemoval::Directory { size_bytes, .. } => {
+                num_directories_removed += 1;
+                total_bytes_removed += size_bytes;
+            }
+        }
+    }
+
+
+    Ok(DirectoryRemoveFinished {
+        total_bytes_removed,
+        files_removed: num_files_removed,
+        symlinks_removed: num_symlinks_removed,
+        directories_removed: num_directories_removed,
+    })
+}
+
+
+/// Removes a directory, including all of its contents.
+///
+/// `directory_path` must point to an existing directory.
+///
+///
+/// # Symbolic links
+/// Symbolic links are never followed: symbolic links inside the directory tree are removed
+/// as links, and their destinations are left untouched, even if they point inside the tree.
+///
+/// If `directory_path` is itself a symbolic link to a directory, only the link is removed.
+/// This matches the behaviour of [`std::fs::remove_dir_all`].
+///
+///
+/// # Options
+/// See [`DirectoryRemoveOptions`] for the full set of available directory removal options.
+///
+///
+/// # Return value
+/// Upon success, the function returns the number of files, symbolic links and directories
+/// that were removed, as well as the total number of bytes removed; see [`DirectoryRemoveFinished`].
+///
+///
+/// # Errors
+/// If the directory cannot be removed, a [`RemoveDirectoryError`] is returned;
+/// see its documentation for more details.
+///
+/// Errors for this function are split into two main groups:
+/// - Preparation errors ([`RemoveDirectoryError::PreparationError`]) are emitted while
+///   scanning the directory tree. Importantly, if an error from this group is returned,
+///   *nothing has been removed yet*.
+/// - Execution errors ([`RemoveDirectoryError::ExecutionError`]) are emitted during
+///   the actual removal phase. Each of these errors contains the path of the exact entry
+///   that could not be removed; entries removed before the error occurred stay removed.
+///
+///
+/// <br>
+///
+/// #### See also
+/// If you are looking for a directory removal function that reports progress,
+/// see [`remove_directory_with_progress`].
+pub fn remove_directory<P>(
+    directory_path: P,
+    options: DirectoryRemoveOptions,
+) -> Result<DirectoryRemoveFinished, RemoveDirectoryError>
+where
+    P: AsRef<Path>,
+{
+    let prepared_removal = DirectoryRemovePrepared::prepare(directory_path.as_ref(), options)?;
+
+    let finished_removal = remove_directory_unchecked(prepared_removal)?;
+
+
+    Ok(finished_removal)
+}
+
+
+
+/// Describes a directory removal operation.
+///
+/// Used for progress reporting in [`remove_directory_with_progress`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DirectoryRemoveOperation {
+    /// A file is being removed.
+    RemovingFile {
+        /// Path of the file being removed.
+        file_path: PathBuf,
+    },
+
+    /// A symbolic link is being removed.
+    RemovingSymbolicLink {
+        /// Path of the symbolic link being removed.
+        symlink_path: PathBuf,
+    },
+
+    /// An (at this point empty) directory is being removed.
+    RemovingDirectory {
+        /// Path of the directory being removed.
+        directory_path: PathBuf,
+    },
+}
+
+
+/// Directory removal progress.
+///
+/// This struct is used to report progress to a user-provided closure
+/// (see usage in [`remove_directory_with_progress`]).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DirectoryRemoveProgress {
+    /// Total number of bytes that need to be removed
+    /// for the directory removal to be complete.
+    pub bytes_total: u64,
+
+    /// Number of bytes that have been removed so far.
+    pub bytes_finished: u64,
+
+    /// Number of files that have been removed so far.
+    pub files_removed: usize,
+
+    /// Number of symbolic links that have been removed so far.
+    pub symlinks_removed: usize,
+
+    /// Number of directories that have been removed so far.
+    pub directories_removed: usize,
+
+    /// The current operation being performed.
+    pub current_operation: DirectoryRemoveOperation,
+
+    /// The index of the current operation.
+    ///
+    /// Starts at `0`, goes up to (including) `total_operations - 1`.
+    pub current_operation_index: usize,
+
+    /// The total number of operations that need to be performed to
+    /// remove the requested directory.
+    ///
+    /// A single operation is removing a file, a symbolic link or an empty directory,
+    /// see [`DirectoryRemoveOperation`].
+    pub total_operations: usize,
+}
+
+
+/// Perform a removal using prepared data from [`DirectoryRemovePrepared`],
+/// with progress tracking.
+///
+/// For more details, see [`remove_directory_with_progress`].
+pub(crate) fn remove_directory_with_progress_unchecked<F>(
+    prepared_removal: DirectoryRemovePrepared,
+    mut progress_handler: F,
+) -> Result<DirectoryRemoveFinished, RemoveDirectoryExecutionError>
+where
+    F: FnMut(&DirectoryRemoveProgress) -> ProgressAction,
+{
+    let total_operations = prepared_removal.operation_queue.len();
+
+    let mut progress: Option<DirectoryRemoveProgress> = None;
+
+    let mut bytes_finished = 0;
+    let mut num_files_removed = 0;
+    let mut num_symlinks_removed = 0;
+    let mut num_directories_removed = 0;
+
+
+    for (operation_index, operation) in prepared_removal.operation_queue.into_iter().enumerate() {
+        let current_operation = match &operation {
+            QueuedRemoval::File { file_path, .. } => DirectoryRemoveOperation::RemovingFile {
+                file_path: file_path.clone(),
+            },
+            QueuedRemoval::Symlink { symlink_path, .. } => {
+                DirectoryRemoveOperation::RemovingSymbolicLink {
+                    symlink_path: symlink_path.clone(),
+                }
+            }
+            QueuedRemoval::Directory { directory_path, .. } => {
+                DirectoryRemoveOperation::RemovingDirectory {
+                    directory_path: directory_path.clone(),
+                }
+            }
+        };
+
+        let current_progress = progress.insert(DirectoryRemoveProgress {
+            bytes_total: prepared_removal.total_bytes,
+            bytes_finished,
+            files_removed: num_files_removed,
+            symlinks_removed: num_symlinks_removed,
+            directories_removed: num_directories_removed,
+            current_operation,
+            current_operation_index: operation_index,
+            total_operations,
+        });
+
+        if progress_handler(current_progress).is_abort() {
+            return Err(RemoveDirectoryExecutionError::Cancelled {
+                bytes_finished,
+                bytes_total: prepared_removal.total_bytes,
+                files_removed: num_files_removed,
+                symlinks_removed: num_symlinks_removed,
+                directories_removed: num_directories_removed,
+            });
+        }
+
+
+        if !execute_queued_removal(&operation)? {
+            continue;
+        }
+
+        match operation {
+            QueuedRemoval::File { size_bytes, .. } => {
+                num_files_removed += 1;
+                bytes_finished += size_bytes;
+            }
+            QueuedRemoval::Symlink { size_bytes, .. } => {
+                num_symlinks_removed += 1;
+                bytes_finished += size_bytes;
+            }
+            QueuedRemoval::Directory { size_bytes, .. } => {
+                num_directories_removed += 1;
+                bytes_finished += size_bytes;
+            }
+        }
+    }
+
+
+    // One last progress update - everything should be done at this point,
+    // so there is nothing left to cancel.
+    if let Some(mut final_progress) = progress {
+        final_progress.bytes_finished = bytes_finished;
+        final_progress.files_removed = num_files_removed;
+        final_progress.symlinks_removed = num_symlinks_removed;
+        final_progress.directories_removed = num_directories_removed;
+
+        let _ = progress_handler(&final_progress);
+    }
+
+
+    Ok(DirectoryRemoveFinished {
+        total_bytes_removed: bytes_finished,
+        files_removed: num_files_removed,
+        symlinks_removed: num_symlinks_removed,
+        directories_removed: num_directories_removed,
+    })
+}
+
+
+/// Removes a directory, including all of its contents, with progress reporting.
+///
+/// `directory_path` must point to an existing directory.
+///
+///
+/// # Symbolic links
+/// Symbolic links are never followed: symbolic links inside the directory tree are removed
+/// as links, and their destinations are left untouched, even if they point inside the tree.
+///
+/// If `directory_path` is itself a symbolic link to a directory, only the link is removed.
+/// This matches the behaviour of [`std::fs::remove_dir_all`].
+///
+///
+/// # Options
+/// See [`DirectoryRemoveOptions`] for the full set of available directory removal options.
+///
+///
+/// # Return value
+/// Upon success, the function returns the number of files, symbolic links and directories
+/// that were removed, as well as the total number of bytes removed; see [`DirectoryRemoveFinished`].
+///
+///
+/// ## Progress reporting
+/// This function allows you to receive progress reports by passing
+/// a `progress_handler` closure. It will be called with
+/// a reference to [`DirectoryRemoveProgress`] before each entry is removed.
+/// It also guarantees one final progress report, when the state indicates
+/// the removal has been completed.
+///
+///
+/// ## Cancellation
+/// The `progress_handler` closure can return either `()`, a [`ProgressAction`],
+/// or a [`ControlFlow<()>`](std::ops::ControlFlow).
+/// Returning [`ProgressAction::Abort`] stops the removal before the next entry is removed,
+/// and returns [`RemoveDirectoryExecutionError::Cancelled`], which describes how far the removal got.
+/// Entries that have already been removed stay removed.
+///
+///
+/// # Errors
+/// If the directory cannot be removed, a [`RemoveDirectoryError`] is returned;
+/// see its documentation for more details.
+///
+/// Errors for this function are split into two main groups:
+/// - Preparation errors ([`RemoveDirectoryError::PreparationError`]) are emitted while
+///   scanning the directory tree. Importantly, if an error from this group is returned,
+///   *nothing has been removed yet*.
+/// - Execution errors ([`RemoveDirectoryError::ExecutionError`]) are emitted during
+///   the actual removal phase. Each of these errors contains the path of the exact entry
+///   that could not be removed; entries removed before the error occurred stay removed.
+///
+///
+/// <br>
+///
+/// #### See also
+/// If you are looking for a directory removal function that does not report progress,
+/// see [`remove_directory`].
+pub fn remove_directory_with_progress<P, F, R>(
+    directory_path: P,
+    options: DirectoryRemoveOptions,
+    mut progress_handler: F,
+) -> Result<DirectoryRemoveFinished, RemoveDirectoryError>
+where
+    P: AsRef<Path>,
+    F: FnMut(&DirectoryRemoveProgress) -> R,
+    R: Into<ProgressAction>,
+{
+    let prepared_removal = DirectoryRemovePrepared::prepare(directory_path.as_ref(), options)?;
+
+    let finished_removal =
+        remove_directory_with_progress_unchecked(prepared_removal, |progress| {
+            progress_handler(progress).into()
+        })?;
+
+
+    Ok(finished_removal)
+}
@@ -0,0 +1,644 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{self, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+use_enabled_fs_module!();
+
+use super::{
+    prepared::{check_operation_queue_for_collisions, DirectoryCopyPrepared, QueuedOperation},
+    CollidingSubDirectoryBehaviour,
+    DestinationDirectoryRule,
+};
+use crate::{
+    error::{CopyDirectoryPreparationError, DirectoryExecutionPlanError},
+    file::CollidingFileBehaviour,
+};
+
+
+/// First line of every resume journal, identifying the file format and its version.
+const JOURNAL_FORMAT_LINE: &str = "fs-more directory copy journal 1";
+
+
+/// An entry of the destination directory, as recorded in a resume journal
+/// by a previous (interrupted) copy.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PreviousEntry {
+    /// A file was being copied, but the copy had not finished.
+    PartiallyCopiedFile,
+
+    /// A file was fully copied, and had this size and modification time right afterwards.
+    CopiedFile {
+        size_bytes: u64,
+        modified_at: Option<Duration>,
+    },
+
+    /// A directory was created (or was about to be created).
+    CreatedDirectory,
+
+    /// A symbolic link was created (or was about to be created).
+    CreatedSymlink,
+
+    /// A hard link was created (or was about to be created).
+    CreatedHardLink,
+}
+
+
+/// A change to the destination directory that is persisted to a resume journal.
+///
+/// All paths are absolute paths under the destination directory.
+pub(crate) enum ResumeJournalEntry<'p> {
+    /// A file is about to be copied.
+    CopyingFile(&'p Path),
+
+    /// A file has been fully copied.
+    CopiedFile(&'p Path),
+
+    /// A directory is about to be created.
+    CreatingDirectory(&'p Path),
+
+    /// A symbolic link is about to be created.
+    CreatingSymlink(&'p Path),
+
+    /// A hard link is about to be created.
+    CreatingHardLink(&'p Path),
+}
+
+
+/// A persistent journal of the operations a directory copy has executed,
+/// which allows an interrupted copy to be resumed by a later copy with the same journal path.
+///
+/// The journal is a small text file: after a header identifying the source and destination
+/// directory, each line records one operation, with paths relative to the destination directory
+/// (percent-encoded, so that any path fits on a single line). Lines are only ever appended,
+/// and an incomplete last line (e.g. from a crash while writing it) is ignored.
+///
+/// See also: [`DirectoryCopyOptions::resume_journal_path`].
+///
+///
+/// [`DirectoryCopyOptions::resume_journal_path`]: super::DirectoryCopyOptions::resume_journal_path
+#[derive(Debug)]
+pub(crate) struct DirectoryCopyResumeJournal {
+    journal_path: PathBuf,
+
+    journal_file: Mutex<fs::File>,
+
+    /// The (validated) destination directory all journaled paths are relative to.
+    ///
+    /// This is only known once the copy has been prepared (see [`Self::resume`]).
+    destination_directory_path: PathBuf,
+
+    /// Entries recorded by previous copies, keyed by their path relative
+    /// to the destination directory. Later records override earlier ones.
+    previous_entries: HashMap<PathBuf, PreviousEntry>,
+}
+
+impl DirectoryCopyResumeJournal {
+    /// Opens (or creates) the resume journal at `journal_path` for a copy
+    /// from `source_directory_path` to `destination_directory_path`,
+    /// reading the entries recorded by previous copies, if any.
+    ///
+    /// Returns [`CopyDirectoryPreparationError::ResumeJournalMismatch`]
+    /// if the journal belongs to a copy with a different source or destination.
+    pub(crate) fn open(
+        journal_path: &Path,
+        source_directory_path: &Path,
+        destination_directory_path: &Path,
+    ) -> Result<Self, CopyDirectoryPreparationError> {
+        let map_io_error = |error| CopyDirectoryPreparationError::UnableToOpenResumeJournal {
+            journal_path: journal_path.to_path_buf(),
+            error,
+        };
+
+        let header = journal_header(source_directory_path, destination_directory_path)
+            .map_err(map_io_error)?;
+
+        let mut journal_file = fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(journal_path)
+            .map_err(map_io_error)?;
+
+        let mut journal_contents = Vec::new();
+        journal_file
+            .read_to_end(&mut journal_contents)
+            .map_err(map_io_error)?;
+
+
+        // Only complete lines are taken into account: an incomplete last line
+        // is a record whose write was interrupted, and is removed below.
+        let complete_length = journal_contents
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |newline_index| newline_index + 1);
+        let complete_contents = &journal_contents[..complete_length];
+
+        let (previous_entries, retained_length) = if complete_contents
+            .starts_with(header.as_bytes())
+        {
+            let previous_entries =
+                parse_entries(&complete_contents[header.len()..]).map_err(map_io_error)?;
+
+            (previous_entries, complete_length)
+        } else if header.as_bytes().starts_with(&journal_contents) {
+            // The journal is new, or writing its header was interrupted.
+            (HashMap::new(), 0)
+        } else if journal_contents.starts_with(format!("{}\n", JOURNAL_FORMAT_LINE).as_bytes()) {
+            return Err(CopyDirectoryPreparationError::ResumeJournalMismatch {
+                journal_path: journal_path.to_path_buf(),
+            });
+        } else {
+            return Err(map_io_error(io::Error::new(
+                ErrorKind::InvalidData,
+                "file is not a directory copy journal",
+            )));
+        };
+
+
+        if retained_length != journal_contents.len() {
+            journal_file
+                .set_len(retained_length as u64)
+                .map_err(map_io_error)?;
+        }
+
+        if retained_length == 0 {
+            journal_file
+                .write_all(header.as_bytes())
+                .map_err(map_io_error)?;
+        }
+
+
+        Ok(Self {
+            journal_path: journal_path.to_path_buf(),
+            journal_file: Mutex::new(journal_file),
+            destination_directory_path: destination_directory_path.to_path_buf(),
+            previous_entries,
+        })
+    }
+
+    /// Returns the path of the journal file.
+    pub(crate) fn journal_path(&self) -> &Path {
+        &self.journal_path
+    }
+
+    /// Returns the [`DestinationDirectoryRule`] the copy should be prepared with.
+    ///
+    /// If a previous copy has already made changes to the destination, the destination directory
+    /// is expected to be non-empty, so `destination_directory_rule` is relaxed during preparation,
+    /// and enforced by [`Self::resume`] only for entries that were not created by a previous copy.
+    pub(crate) fn destination_directory_rule_for_preparation(
+        &self,
+        destination_directory_rule: DestinationDirectoryRule,
+    ) -> DestinationDirectoryRule {
+        if self.previous_entries.is_empty() {
+            return destination_directory_rule;
+        }
+
+        DestinationDirectoryRule::AllowNonEmpty {
+            colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+        }
+    }
+
+    /// Removes the operations a previous copy has already completed from the `prepared_copy`
+    /// (moving them to its resumed operations), checks and resolves the remaining operations'
+    /// collisions with `destination_directory_rule`, and attaches the journal to the prepared copy.
+    ///
+    /// A file counts as completed if both the source and destination file still have the
+    /// recorded size, and the destination file still has the recorded modification time.
+    /// Directories and symbolic links count as completed if they still exist,
+    /// and hard links if the destination file still has the source file's size.
+    pub(crate) fn resume(
+        mut self,
+        prepared_copy: DirectoryCopyPrepared,
+        destination_directory_rule: DestinationDirectoryRule,
+    ) -> Result<DirectoryCopyPrepared, CopyDirectoryPreparationError> {
+        self.destination_directory_path = prepared_copy.destination_directory_path.clone();
+
+        let mut resumed_operations = Vec::new();
+        let mut remaining_operations = Vec::with_capacity(prepared_copy.operation_queue.len());
+
+        for operation in prepared_copy.operation_queue {
+            let is_completed = self.is_completed(&operation).map_err(|error| {
+                DirectoryExecutionPlanError::UnableToAccess {
+                    path: operation.destination_path().to_path_buf(),
+                    error,
+                }
+            })?;
+
+            if is_completed {
+                resumed_operations.push(operation);
+            } else {
+                remaining_operations.push(operation);
+            }
+        }
+
+
+        // If the destination directory rule was relaxed during preparation, we still need to
+        // check for (and resolve) collisions with entries that weren't created by a previous copy.
+        let rule_was_relaxed = !self.previous_entries.is_empty();
+
+        if rule_was_relaxed {
+            check_operation_queue_for_collisions(
+                remaining_operations
+                    .iter()
+                    .filter(|operation| !self.is_left_over_file(operation.destination_path())),
+                destination_directory_rule,
+            )?;
+        }
+
+        let mut resumed_copy = DirectoryCopyPrepared {
+            operation_queue: remaining_operations,
+            resumed_operations,
+            resume_journal: Some(Arc::new(self)),
+            ..prepared_copy
+        };
+
+        if rule_was_relaxed {
+            resumed_copy.resolve_collisions_with_rule(destination_directory_rule)?;
+        }
+
+
+        Ok(resumed_copy)
+    }
+
+    /// Returns `true` if a previous copy has started or finished copying a file to `file_path`,
+    /// meaning that the file may be overwritten.
+    pub(crate) fn is_left_over_file(&self, file_path: &Path) -> bool {
+        matches!(
+            self.previous_entry(file_path),
+            Some(
+                PreviousEntry::PartiallyCopiedFile
+                    | PreviousEntry::CopiedFile { .. }
+                    | PreviousEntry::CreatedHardLink
+            )
+        )
+    }
+
+    /// Returns `true` if a previous copy has started, but not finished, copying a file to `file_path`,
+    /// meaning that the file copy can be resumed.
+    pub(crate) fn is_partially_copied_file(&self, file_path: &Path) -> bool {
+        self.previous_entry(file_path) == Some(PreviousEntry::PartiallyCopiedFile)
+    }
+
+    /// Appends `entry` to the journal and syncs the journal to disk.
+    ///
+    /// A copied file must already have been synced to disk when it is recorded, so that a crash
+    /// can never leave a recorded file with contents that did not reach the disk
+    /// (see `DirectoryCopyJournal::durability_for_file`).
+    pub(crate) fn persist(&self, entry: ResumeJournalEntry<'_>) -> io::Result<()> {
+        let record = match entry {
+            ResumeJournalEntry::CopyingFile(file_path) => {
+                format!("copying {}\n", self.encode_destination_path(file_path))
+            }
+            ResumeJournalEntry::CopiedFile(file_path) => {
+                let file_metadata = fs::metadata(file_path)?;
+
+                format!(
+                    "copied {} {} {}\n",
+                    file_metadata.len(),
+                    encode_modification_time(modification_time(&file_metadata)),
+                    self.encode_destination_path(file_path)
+                )
+            }
+            ResumeJournalEntry::CreatingDirectory(directory_path) => {
+                format!("directory {}\n", self.encode_destination_path(directory_path))
+            }
+            ResumeJournalEntry::CreatingSymlink(symlink_path) => {
+                format!("symlink {}\n", self.encode_destination_path(symlink_path))
+            }
+            ResumeJournalEntry::CreatingHardLink(hard_link_path) => {
+                format!("hardlink {}\n", self.encode_destination_path(hard_link_path))
+            }
+        };
+
+        // Each record is written with a single call, so that records
+        // of concurrent workers are never interleaved.
+        let mut journal_file = self
+            .journal_file
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        journal_file.write_all(record.as_bytes())?;
+        journal_file.sync_data()
+    }
+
+    /// Removes the journal file after a successful copy.
+    ///
+    /// This is a best-effort cleanup: the copy itself has already succeeded.
+    pub(crate) fn remove(self: Arc<Self>) {
+        // The journal file must be closed before it can be removed on some platforms,
+        // so it is only removed if no other references to the journal remain.
+        if let Ok(journal) = Arc::try_unwrap(self) {
+            drop(journal.journal_file);
+            let _ = fs::remove_file(journal.journal_path);
+        }
+    }
+
+    fn previous_entry(&self, destination_path: &Path) -> Option<PreviousEntry> {
+        let relative_path = destination_path
+            .strip_prefix(&self.destination_directory_path)
+            .ok()?;
+
+        self.previous_entries.get(relative_path).copied()
+    }
+
+    fn is_completed(&self, operation: &QueuedOperation) -> io::Result<bool> {
+        let Some(previous_entry) = self.previous_entry(operation.destination_path()) else {
+            return Ok(false);
+        };
+
+        let destination_metadata = match fs::symlink_metadata(operation.destination_path()) {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(error) => return Err(error),
+        };
+
+        match (operation, previous_entry) {
+            (
+                QueuedOperation::CopyFile {
+                    source_size_bytes, ..
+                },
+                PreviousEntry::CopiedFile {
+                    size_bytes,
+                    modified_at,
+                },
+            ) => Ok(*source_size_bytes == size_bytes
+                && destination_metadata.is_file()
+                && destination_metadata.len() == size_bytes
+                && modification_time(&destination_metadata) == modified_at),
+
+            (QueuedOperation::CreateDirectory { .. }, PreviousEntry::CreatedDirectory) => {
+                Ok(destination_metadata.is_dir())
+            }
+
+            (
+                QueuedOperation::CreateSymlink {
+                    symlink_path,
+                    symlink_destination_path,
+                    ..
+                },
+                PreviousEntry::CreatedSymlink,
+            ) => Ok(destination_metadata.is_symlink()
+                && fs::read_link(symlink_path)? == *symlink_destination_path),
+
+            // Hard links are only recreated after the copy has been prepared,
+            // so the operation that created one is still queued as a file copy.
+            (
+                QueuedOperation::CopyFile {
+                    source_size_bytes, ..
+                },
+                PreviousEntry::CreatedHardLink,
+            ) => {
+                Ok(destination_metadata.is_file()
+                    && destination_metadata.len() == *source_size_bytes)
+            }
+
+            _ => Ok(false),
+        }
+    }
+
+    fn encode_destination_path(&self, destination_path: &Path) -> String {
+        encode_path(
+            destination_path
+                .strip_prefix(&self.destination_directory_path)
+                .unwrap_or(destination_path),
+        )
+    }
+}
+
+
+
+/// Returns the header of a journal for a copy from `source_directory_path`
+/// to `destination_directory_path`, including the trailing newline.
+fn journal_header(
+    source_directory_path: &Path,
+    destination_directory_path: &Path,
+) -> io::Result<String> {
+    Ok(format!(
+        "{}\nsource {}\ndestination {}\n",
+        JOURNAL_FORMAT_LINE,
+        encode_path(&absolute_path(source_directory_path)?),
+        encode_path(&absolute_path(destination_directory_path)?)
+    ))
+}
+
+/// Returns `path` as an absolute path, without resolving symbolic links (unlike [`fs::canonicalize`]),
+/// since the destination directory does not need to exist.
+fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+
+    Ok(std::env::current_dir()?.join(path))
+}
+
+
+/// Parses the records following the journal header.
+///
+/// `records` must consist of complete (newline-terminated) lines.
+fn parse_entries(records: &[u8]) -> io::Result<HashMap<PathBuf, PreviousEntry>> {
+    let invalid_record = || io::Error::new(ErrorKind::InvalidData, "invalid journal record");
+
+    let mut previous_entries = HashMap::new();
+
+    for record in records.split(|byte| *byte == b'\n') {
+        if record.is_empty() {
+            continue;
+        }
+
+        let record = std::str::from_utf8(record).map_err(|_| invalid_record())?;
+
+        let (relative_path, previous_entry) = match record.split_once(' ') {
+            Some(("copying", path)) => (path, PreviousEntry::PartiallyCopiedFile),
+            Some(("copied", size_time_and_path)) => {
+                let mut fields = size_time_and_path.splitn(3, ' ');
+
+                let (Some(size_bytes), Some(modified_at), Some(path)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    return Err(invalid_record());
+                };
+
+                (
+                    path,
+                    PreviousEntry::CopiedFile {
+                        size_bytes: size_bytes.parse().map_err(|_| invalid_record())?,
+                        modified_at: decode_modification_time(modified_at)
+                            .ok_or_else(invalid_record)?,
+                    },
+                )
+            }
+            Some(("directory", path)) => (path, PreviousEntry::CreatedDirectory),
+            Some(("symlink", path)) => (path, PreviousEntry::CreatedSymlink),
+            Some(("hardlink", path)) => (path, PreviousEntry::CreatedHardLink),
+            _ => return Err(invalid_record()),
+        };
+
+        previous_entries
+            .insert(decode_path(relative_path).ok_or_else(invalid_record)?, previous_entry);
+    }
+
+    Ok(previous_entries)
+}
+
+
+/// Returns the modification time of a file as a duration since the Unix epoch,
+/// or `None` if it is not available on this platform.
+fn modification_time(metadata: &std::fs::Metadata) -> Option<Duration> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified_at| modified_at.duration_since(SystemTime::UNIX_EPOCH).ok())
+}
+
+fn encode_modification_time(modified_at: Option<Duration>) -> String {
+    match modified_at {
+        Some(modified_at) => format!("{}.{:09}", modified_at.as_secs(), modified_at.subsec_nanos()),
+        None => String::from("-"),
+    }
+}
+
+/// Decodes a modification time encoded by [`encode_modification_time`].
+///
+/// The outer `Option` is `None` if `encoded` is invalid.
+fn decode_modification_time(encoded: &str) -> Option<Option<Duration>> {
+    if encoded == "-" {
+        return Some(None);
+    }
+
+    let (seconds, nanoseconds) = encoded.split_once('.')?;
+
+    Some(Some(Duration::new(seconds.parse().ok()?, nanoseconds.parse().ok()?)))
+}
+
+
+/// Percent-encodes a path, so that it contains no spaces or line breaks.
+///
+/// On Unix, the raw bytes of the path are encoded. Elsewhere, paths that are not valid Unicode
+/// are encoded lossily, which means that such entries will not be resumed.
+fn encode_path(path: &Path) -> String {
+    #[cfg(unix)]
+    let path_bytes = {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes()
+    };
+
+    #[cfg(not(unix))]
+    let lossy_path = path.to_string_lossy();
+    #[cfg(not(unix))]
+    let path_bytes = lossy_path.as_bytes();
+
+
+    let mut encoded_path = String::with_capacity(path_bytes.len());
+
+    for byte in path_bytes {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(byte) {
+            encoded_path.push(*byte as char);
+        } else {
+            // PANIC SAFETY: Writing to a `String` never fails.
+            write!(encoded_path, "%{:02X}", byte).expect("writing to a String to succeed");
+        }
+    }
+
+    encoded_path
+}
+
+/// Decodes a path encoded by [`encode_path`], returning `None` if `encoded_path` is invalid.
+fn decode_path(encoded_path: &str) -> Option<PathBuf> {
+    let mut path_bytes = Vec::with_capacity(encoded_path.len());
+    let mut encoded_bytes = encoded_path.bytes();
+
+    while let Some(byte) = encoded_bytes.next() {
+        if byte != b'%' {
+            path_bytes.push(byte);
+            continue;
+        }
+
+        let hex_digits = [encoded_bytes.next()?, encoded_bytes.next()?];
+        path_bytes.push(u8::from_str_radix(std::str::from_utf8(&hex_digits).ok()?, 16).ok()?);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(std::ffi::OsString::from_vec(path_bytes)))
+    }
+
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(path_bytes).ok().map(PathBuf::from)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoded_paths_round_trip() {
+        for path in [
+            "",
+            "foo/bar.txt",
+            "with space/and\nnewline",
+            "percent%20sign",
+            "unicode/čšž",
+        ] {
+            let encoded_path = encode_path(Path::new(path));
+
+            assert!(!encoded_path.contains([' ', '\n']));
+            assert_eq!(decode_path(&encoded_path), Some(PathBuf::from(path)));
+        }
+    }
+
+    #[test]
+    fn records_are_parsed_with_later_records_taking_precedence() {
+        let previous_entries = parse_entries(
+            b"directory \n\
+              directory sub%20dir\n\
+              copying sub%20dir/a.bin\n\
+              copying b.bin\n\
+              copied 42 1700000000.000000123 sub%20dir/a.bin\n\
+              symlink link\n\
+              hardlink sub%20dir/a-link.bin\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            previous_entries.get(Path::new("")),
+            Some(&PreviousEntry::CreatedDirectory)
+        );
+        assert_eq!(
+            previous_entries.get(Path::new("sub dir/a.bin")),
+            Some(&PreviousEntry::CopiedFile {
+                size_bytes: 42,
+                modified_at: Some(Duration::new(1_700_000_000, 123)),
+            })
+        );
+        assert_eq!(
+            previous_entries.get(Path::new("b.bin")),
+            Some(&PreviousEntry::PartiallyCopiedFile)
+        );
+        assert_eq!(
+            previous_entries.get(Path::new("link")),
+            Some(&PreviousEntry::CreatedSymlink)
+        );
+        assert_eq!(
+            previous_entries.get(Path::new("sub dir/a-link.bin")),
+            Some(&PreviousEntry::CreatedHardLink)
+        );
+    }
+
+    #[test]
+    fn invalid_records_are_rejected() {
+        assert!(parse_entries(b"copied 42 sub/a.bin\n").is_err());
+        assert!(parse_entries(b"deleted a.bin\n").is_err());
+        assert!(parse_entries(b"copying a%2\n").is_err());
+    }
+}
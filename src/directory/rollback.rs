@@ -0,0 +1,484 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use_enabled_fs_module!();
+
+use super::{
+    resume::{DirectoryCopyResumeJournal, ResumeJournalEntry},
+    try_exists_without_follow,
+};
+use crate::{
+    error::CopyDirectoryExecutionError,
+    file::{DurabilityLevel, ResumeBehaviour},
+};
+
+
+/// Whether to undo a partially executed directory copy (or move by copy-and-delete)
+/// when it fails.
+///
+/// See also: [`DirectoryCopyOptions::rollback_behaviour`][crate::directory::DirectoryCopyOptions::rollback_behaviour].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RollbackBehaviour {
+    /// Nothing is undone when the copy fails midway,
+    /// meaning the destination directory can be left in a partially copied state.
+    ///
+    /// This is the default.
+    Disabled,
+
+    /// Every change made to the destination is journaled while copying, and if the copy fails
+    /// (or is cancelled), the changes are undone in reverse order. This means that:
+    /// - created files and symbolic links are removed,
+    /// - created directories are removed, and
+    /// - files that were overwritten are restored from backups.
+    ///
+    /// Overwriting a file in this mode works by first renaming the existing destination file
+    /// to a hidden backup next to it (e.g. `.foo.txt.fs-more-backup`). Backups are removed
+    /// once the copy has completed successfully.
+    ///
+    /// If the copy fails, the returned error will be
+    /// [`CopyDirectoryExecutionError::RolledBack`], which contains
+    /// both the original error and a [`DirectoryRollbackReport`].
+    RollBackOnFailure,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for RollbackBehaviour {
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
+
+
+
+/// Describes a single step of a rollback that could not be performed.
+///
+/// See also: [`DirectoryRollbackReport`].
+#[derive(Debug)]
+pub struct DirectoryRollbackFailure {
+    /// Path of the entry that could not be removed or restored.
+    pub path: PathBuf,
+
+    /// IO error describing why the entry could not be removed or restored.
+    pub error: std::io::Error,
+}
+
+
+/// Describes what was undone when a failed directory copy was rolled back.
+///
+/// This is part of [`CopyDirectoryExecutionError::RolledBack`].
+#[derive(Debug, Default)]
+pub struct DirectoryRollbackReport {
+    /// Number of files created by the copy that were removed.
+    pub files_removed: usize,
+
+    /// Number of symbolic links created by the copy that were removed.
+    pub symlinks_removed: usize,
+
+    /// Number of directories created by the copy that were removed.
+    pub directories_removed: usize,
+
+    /// Number of overwritten files that were restored from their backups.
+    pub files_restored: usize,
+
+    /// Rollback steps that could not be performed.
+    ///
+    /// If this is empty, the destination was fully restored
+    /// to the state it was in before the copy started.
+    pub failures: Vec<DirectoryRollbackFailure>,
+}
+
+impl DirectoryRollbackReport {
+    /// Returns `true` if every change made by the copy was successfully undone.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+
+
+/// A single change to the destination, as recorded by [`DirectoryCopyJournal`].
+enum JournalEntry {
+    /// A directory was created.
+    CreatedDirectory { directory_path: PathBuf },
+
+    /// A file was created (or is about to be created).
+    CreatedFile { file_path: PathBuf },
+
+    /// A symbolic link was created (or is about to be created).
+    CreatedSymlink {
+        symlink_path: PathBuf,
+
+        #[cfg(windows)]
+        is_directory_symlink: bool,
+    },
+
+    /// An existing file was moved to `backup_path` so it could be overwritten.
+    BackedUpFile {
+        file_path: PathBuf,
+        backup_path: PathBuf,
+    },
+}
+
+
+/// Records changes made to the destination during a directory copy,
+/// so they can be undone if the copy fails.
+///
+/// When constructed with [`RollbackBehaviour::Disabled`], nothing is recorded
+/// and all methods are no-ops.
+///
+/// If the copy is resumable, executed operations are additionally persisted
+/// to its resume journal (see [`Self::persist`]).
+pub(crate) struct DirectoryCopyJournal {
+    enabled: bool,
+    entries: Vec<JournalEntry>,
+    resume_journal: Option<Arc<DirectoryCopyResumeJournal>>,
+}
+
+impl DirectoryCopyJournal {
+    pub(crate) fn new(
+        rollback_behaviour: RollbackBehaviour,
+        resume_journal: Option<Arc<DirectoryCopyResumeJournal>>,
+    ) -> Self {
+        Self {
+            enabled: rollback_behaviour == RollbackBehaviour::RollBackOnFailure,
+            entries: Vec::new(),
+            resume_journal,
+        }
+    }
+
+    /// Creates an empty journal with the same configuration,
+    /// to be used by a worker thread and later [appended][Self::append] to this one.
+    pub(crate) fn new_for_worker(&self) -> Self {
+        Self {
+            enabled: self.enabled,
+            entries: Vec::new(),
+            resume_journal: self.resume_journal.clone(),
+        }
+    }
+
+    /// Persists `entry` to the resume journal, if the copy is resumable.
+    pub(crate) fn persist(
+        &self,
+        entry: ResumeJournalEntry<'_>,
+    ) -> Result<(), CopyDirectoryExecutionError> {
+        let Some(resume_journal) = &self.resume_journal else {
+            return Ok(());
+        };
+
+        resume_journal.persist(entry).map_err(|error| {
+            CopyDirectoryExecutionError::UnableToWriteResumeJournal {
+                journal_path: resume_journal.journal_path().to_path_buf(),
+                error,
+            }
+        })
+    }
+
+    /// Returns `true` if the file at `file_path` was (partially) copied there by a previous,
+    /// interrupted copy, meaning it may be overwritten regardless of the destination directory rule.
+    pub(crate) fn is_left_over_file(&self, file_path: &Path) -> bool {
+        self.resume_journal
+            .as_ref()
+            .is_some_and(|resume_journal| resume_journal.is_left_over_file(file_path))
+    }
+
+    /// Returns the [`DurabilityLevel`] to copy a file with, given the `durability` of the directory copy.
+    ///
+    /// If the copy is resumable, files are always synced to disk while they are being copied,
+    /// as they must have reached the disk before being recorded as copied (see [`Self::persist`]).
+    pub(crate) fn durability_for_file(&self, durability: DurabilityLevel) -> DurabilityLevel {
+        if self.resume_journal.is_some() {
+            DurabilityLevel::Files
+        } else {
+            durability.without_directories()
+        }
+    }
+
+    /// Returns the [`ResumeBehaviour`] to copy a file to `file_path` with.
+    ///
+    /// If the copy is resumable, files partially copied by a previous copy are resumed,
+    /// and files that did not exist before are kept if the copy is cancelled,
+    /// so that they can be resumed later. Other existing files are never resumed.
+    pub(crate) fn resume_behaviour_for_file(
+        &self,
+        file_path: &Path,
+        file_exists: bool,
+    ) -> ResumeBehaviour {
+        let Some(resume_journal) = &self.resume_journal else {
+            return ResumeBehaviour::Never;
+        };
+
+        if !file_exists || resume_journal.is_partially_copied_file(file_path) {
+            ResumeBehaviour::VerifySampled
+        } else {
+            ResumeBehaviour::Never
+        }
+    }
+
+    /// Records a directory that is about to be created.
+    ///
+    /// If `create_parent_directories` is `true`, all missing ancestors
+    /// of the directory are recorded as well.
+    ///
+    /// This must be called *before* creating the directory.
+    pub(crate) fn record_directory_creation(
+        &mut self,
+        directory_path: &Path,
+        create_parent_directories: bool,
+    ) -> std::io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let mut directories_to_be_created = vec![directory_path.to_path_buf()];
+
+        if create_parent_directories {
+            let mut current_ancestor = directory_path.parent();
+
+            while let Some(ancestor_path) = current_ancestor {
+                if ancestor_path.as_os_str().is_empty() || try_exists_without_follow(ancestor_path)?
+                {
+                    break;
+                }
+
+                directories_to_be_created.push(ancestor_path.to_path_buf());
+                current_ancestor = ancestor_path.parent();
+            }
+        }
+
+        // Ancestors must be recorded first, so they are removed last.
+        for directory_path in directories_to_be_created.into_iter().rev() {
+            self.entries
+                .push(JournalEntry::CreatedDirectory { directory_path });
+        }
+
+        Ok(())
+    }
+
+    /// Records a file that is about to be created.
+    ///
+    /// This must be called *before* creating the file, so that a partially
+    /// written file is removed as well.
+    pub(crate) fn record_file_creation(&mut self, file_path: &Path) {
+        if !self.enabled {
+            return;
+        }
+
+        self.entries.push(JournalEntry::CreatedFile {
+            file_path: file_path.to_path_buf(),
+        });
+    }
+
+    /// Records a symbolic link that is about to be created.
+    pub(crate) fn record_symlink_creation(
+        &mut self,
+        symlink_path: &Path,
+        #[cfg(windows)] is_directory_symlink: bool,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        self.entries.push(JournalEntry::CreatedSymlink {
+            symlink_path: symlink_path.to_path_buf(),
+            #[cfg(windows)]
+            is_directory_symlink,
+        });
+    }
+
+    /// Moves an existing file out of the way (into a backup file next to it)
+    /// so that it can be overwritten, and records the backup.
+    ///
+    /// This must be called *before* overwriting the file.
+    pub(crate) fn back_up_file_before_overwrite(
+        &mut self,
+        file_path: &Path,
+    ) -> std::io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let backup_path = find_unused_backup_path(file_path)?;
+        fs::rename(file_path, &backup_path)?;
+
+        self.entries.push(JournalEntry::BackedUpFile {
+            file_path: file_path.to_path_buf(),
+            backup_path,
+        });
+
+        Ok(())
+    }
+
+    /// Prepares for writing a file to `file_path`: if a file already exists there
+    /// (`file_exists`), it is backed up (see [`Self::back_up_file_before_overwrite`]),
+    /// otherwise the file creation is recorded (see [`Self::record_file_creation`]).
+    ///
+    /// This must be called *before* writing the file.
+    pub(crate) fn prepare_file_write(
+        &mut self,
+        file_path: &Path,
+        file_exists: bool,
+    ) -> std::io::Result<()> {
+        if file_exists {
+            self.back_up_file_before_overwrite(file_path)
+        } else {
+            self.record_file_creation(file_path);
+            Ok(())
+        }
+    }
+
+    /// Appends all changes recorded in `other` after the changes recorded in this journal,
+    /// meaning they will be undone first.
+    ///
+    /// This is used to combine the journals of worker threads in a parallel copy.
+    pub(crate) fn append(&mut self, other: Self) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Finalizes a successful copy by removing all backups of overwritten files,
+    /// as well as the resume journal, if any.
+    ///
+    /// Removing them is a best-effort cleanup: the copy itself has already succeeded.
+    pub(crate) fn finish(self) {
+        for entry in self.entries {
+            if let JournalEntry::BackedUpFile { backup_path, .. } = entry {
+                let _ = fs::remove_file(backup_path);
+            }
+        }
+
+        if let Some(resume_journal) = self.resume_journal {
+            resume_journal.remove();
+        }
+    }
+
+    /// If rollback is enabled, undoes all recorded changes in reverse order and wraps
+    /// the given `error` into [`CopyDirectoryExecutionError::RolledBack`].
+    /// Otherwise, the error is returned unchanged.
+    pub(crate) fn roll_back_after_error(
+        self,
+        error: CopyDirectoryExecutionError,
+    ) -> CopyDirectoryExecutionError {
+        if !self.enabled {
+            return error;
+        }
+
+        let rollback_report = self.roll_back();
+
+        CopyDirectoryExecutionError::RolledBack {
+            error: Box::new(error),
+            rollback_report,
+        }
+    }
+
+    /// Undoes all recorded changes in reverse order.
+    fn roll_back(self) -> DirectoryRollbackReport {
+        let mut report = DirectoryRollbackReport::default();
+
+        for entry in self.entries.into_iter().rev() {
+            match entry {
+                JournalEntry::CreatedDirectory { directory_path } => {
+                    match ignore_not_found(fs::remove_dir(&directory_path)) {
+                        Ok(true) => report.directories_removed += 1,
+                        Ok(false) => {}
+                        Err(error) => report.failures.push(DirectoryRollbackFailure {
+                            path: directory_path,
+                            error,
+                        }),
+                    }
+                }
+                JournalEntry::CreatedFile { file_path } => {
+                    match ignore_not_found(fs::remove_file(&file_path)) {
+                        Ok(true) => report.files_removed += 1,
+                        Ok(false) => {}
+                        Err(error) => report.failures.push(DirectoryRollbackFailure {
+                            path: file_path,
+                            error,
+                        }),
+                    }
+                }
+                JournalEntry::CreatedSymlink {
+                    symlink_path,
+                    #[cfg(windows)]
+                    is_directory_symlink,
+                } => {
+                    #[cfg(windows)]
+                    let removal_result = if is_directory_symlink {
+                        fs::remove_dir(&symlink_path)
+                    } else {
+                        fs::remove_file(&symlink_path)
+                    };
+
+                    #[cfg(not(windows))]
+                    let removal_result = fs::remove_file(&symlink_path);
+
+                    match ignore_not_found(removal_result) {
+                        Ok(true) => report.symlinks_removed += 1,
+                        Ok(false) => {}
+                        Err(error) => report.failures.push(DirectoryRollbackFailure {
+                            path: symlink_path,
+                            error,
+                        }),
+                    }
+                }
+                JournalEntry::BackedUpFile {
+                    file_path,
+                    backup_path,
+                } => {
+                    // The (possibly partially written) new file must be removed
+                    // before the backup can be moved back into place.
+                    let restore_result = ignore_not_found(fs::remove_file(&file_path))
+                        .and_then(|_| fs::rename(&backup_path, &file_path));
+
+                    match restore_result {
+                        Ok(()) => report.files_restored += 1,
+                        Err(error) => report.failures.push(DirectoryRollbackFailure {
+                            path: file_path,
+                            error,
+                        }),
+                    }
+                }
+            }
+        }
+
+        report
+    }
+}
+
+
+/// Maps a `NotFound` error to `Ok(false)` (there was nothing to undo)
+/// and a success to `Ok(true)`.
+fn ignore_not_found(result: std::io::Result<()>) -> std::io::Result<bool> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+
+/// Finds a path next to `file_path` that is not in use and can hold a backup of it.
+///
+/// For `foo.txt`, this is `.foo.txt.fs-more-backup`, or `.foo.txt.fs-more-backup-{n}`
+/// if the former already exists.
+fn find_unused_backup_path(file_path: &Path) -> std::io::Result<PathBuf> {
+    let file_name = file_path.file_name().unwrap_or_default();
+
+    let mut backup_file_name = OsString::from(".");
+    backup_file_name.push(file_name);
+    backup_file_name.push(".fs-more-backup");
+
+    let mut backup_path = file_path.with_file_name(&backup_file_name);
+    let mut attempt = 1;
+
+    while try_exists_without_follow(&backup_path)? {
+        let mut numbered_backup_file_name = backup_file_name.clone();
+        numbered_backup_file_name.push(format!("-{}", attempt));
+
+        backup_path = file_path.with_file_name(numbered_backup_file_name);
+        attempt += 1;
+    }
+
+    Ok(backup_path)
+}
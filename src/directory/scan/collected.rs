@@ -1,6 +1,12 @@
 use std::path::Path;
 
-use super::{DirectoryScanDepthLimit, DirectoryScanOptions, DirectoryScanner};
+use super::{
+    DirectoryEntryFilter,
+    DirectoryScanDepthLimit,
+    DirectoryScanOptions,
+    DirectoryScanner,
+    ScanEntryOrdering,
+};
 use crate::error::DirectoryScanError;
 
 pub(crate) struct DirectoryStatistics {
@@ -10,8 +16,11 @@ pub(crate) struct DirectoryStatistics {
     pub(crate) total_directories: usize,
 }
 
+/// Scans the directory (without following symbolic links inside it)
+/// and collects statistics about the entries included by `entry_filter`.
 pub(crate) fn collect_directory_statistics_via_scan(
     directory_path: &Path,
+    entry_filter: &DirectoryEntryFilter,
 ) -> Result<DirectoryStatistics, DirectoryScanError> {
     let scanner = DirectoryScanner::new(
         directory_path,
@@ -20,6 +29,8 @@ pub(crate) fn collect_directory_statistics_via_scan(
             maximum_scan_depth: DirectoryScanDepthLimit::Unlimited,
             follow_symbolic_links: false,
             follow_base_directory_symbolic_link: true,
+            entry_filter: entry_filter.clone(),
+            entry_ordering: ScanEntryOrdering::Unsorted,
         },
     )
     .into_iter();
@@ -0,0 +1,478 @@
+use std::{
+    fs::Metadata,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use_enabled_fs_module!();
+
+use super::{DirectoryEntries, DirectoryScanDepthLimit, DirectoryScanOptions, ScanEntry};
+use crate::{
+    directory::{IgnoreRules, ScanEntryDepth},
+    error::DirectoryScanError,
+};
+
+
+/// The order in which a [`DepthFirstDirectoryIter`] yields directories
+/// relative to their contents.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DepthFirstOrder {
+    /// Each directory is yielded *before* its contents.
+    ///
+    /// This is useful e.g. for rendering a directory tree or writing an archive.
+    PreOrder,
+
+    /// Each directory is yielded *after* its contents.
+    ///
+    /// This is useful e.g. for removing a directory tree, as every directory
+    /// is yielded only once all of its contents have been yielded.
+    PostOrder,
+}
+
+
+/// State of the directory reader of a [`DirectoryFrame`].
+enum DirectoryReader {
+    /// The directory has not been opened for reading yet.
+    NotOpened,
+
+    /// The directory is open and is being read.
+    Open(DirectoryEntries),
+
+    /// All entries of the directory have been read (or skipped).
+    Exhausted,
+}
+
+
+/// A directory on the current path from the base directory to the entry being scanned.
+struct DirectoryFrame {
+    /// Path of the directory. If symbolic links are followed, this is never a symlink.
+    directory_path: PathBuf,
+
+    /// Depth of the directory, relative to the root of the scan tree.
+    directory_depth: ScanEntryDepth,
+
+    /// Path of the directory relative to the root of the scan tree,
+    /// constructed without following symbolic links.
+    relative_path: PathBuf,
+
+    /// Ignore file rules of the directory's parent.
+    parent_ignore_rules: Option<Arc<IgnoreRules>>,
+
+    /// Ignore file rules that apply to entries of this directory. Loaded when the directory is opened.
+    ignore_rules: Option<Arc<IgnoreRules>>,
+
+    reader: DirectoryReader,
+
+    /// When iterating in post-order, the scan entry of the directory itself,
+    /// which is yielded once the directory has been exhausted.
+    post_order_entry: Option<ScanEntry>,
+}
+
+
+
+/// A recursive depth-first directory iterator.
+///
+/// Obtained from calling [`DirectoryScanner::into_depth_first_iter`].
+/// All [`DirectoryScanOptions`] behave the same as with [`BreadthFirstDirectoryIter`],
+/// including the depth limit, entry filtering and symbolic link cycle detection.
+///
+/// Unlike the breadth-first iterator, this iterator keeps only the directories
+/// on the path from the base directory to the current entry open.
+///
+///
+/// # Skipping directories
+/// Calling [`Self::skip_current_directory`] inside the iteration loop
+/// skips the rest of the directory that is currently being iterated:
+///
+/// ```no_run
+/// # use fs_more::directory::{DepthFirstOrder, DirectoryScanner, DirectoryScanOptions};
+/// let mut scan = DirectoryScanner::new("./my-project", DirectoryScanOptions::default())
+///     .into_depth_first_iter(DepthFirstOrder::PreOrder);
+///
+/// while let Some(entry) = scan.next() {
+///     let entry = entry.unwrap();
+///
+///     if entry.metadata().is_dir() && entry.path().ends_with("node_modules") {
+///         // The contents of `node_modules` will not be read.
+///         scan.skip_current_directory();
+///         continue;
+///     }
+///
+///     println!("{}", entry.path().display());
+/// }
+/// ```
+///
+///
+/// [`DirectoryScanner::into_depth_first_iter`]: super::DirectoryScanner::into_depth_first_iter
+/// [`BreadthFirstDirectoryIter`]: super::BreadthFirstDirectoryIter
+pub struct DepthFirstDirectoryIter {
+    /// Path of the directory the scan started at.
+    base_directory: PathBuf,
+
+    /// Directory scanning options.
+    options: DirectoryScanOptions,
+
+    /// Whether directories are yielded before or after their contents.
+    order: DepthFirstOrder,
+
+    /// Whether the base directory has been processed yet (see [`Self::process_base_directory`]).
+    has_processed_base_directory: bool,
+
+    /// Directories from the base directory (first) to the directory currently being read (last).
+    ///
+    /// When following symbolic links, these are also the ancestors used for cycle detection.
+    directory_stack: Vec<DirectoryFrame>,
+}
+
+impl DepthFirstDirectoryIter {
+    pub(super) fn new<P>(
+        base_directory: P,
+        options: DirectoryScanOptions,
+        order: DepthFirstOrder,
+    ) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            base_directory: base_directory.into(),
+            options,
+            order,
+            has_processed_base_directory: false,
+            directory_stack: Vec::new(),
+        }
+    }
+
+    /// Skips the rest of the directory that is currently being iterated.
+    ///
+    /// If the last yielded entry is a directory that would be descended into (which is only
+    /// possible in [pre-order][DepthFirstOrder::PreOrder]), its contents are skipped.
+    /// Otherwise, the remaining entries of the directory containing the last yielded entry
+    /// are skipped; in [post-order][DepthFirstOrder::PostOrder], that directory is still yielded.
+    ///
+    /// Calling this right after the base directory has been yielded (in pre-order) skips the entire scan.
+    /// Calling this before the first call to [`Self::next`] has no effect.
+    pub fn skip_current_directory(&mut self) {
+        if let Some(current_directory) = self.directory_stack.last_mut() {
+            current_directory.reader = DirectoryReader::Exhausted;
+        }
+    }
+
+    /// Validates the base directory and pushes it onto the directory stack,
+    /// following it if it is a symbolic link (and if configured to do so).
+    ///
+    /// Returns the base directory entry if it should be yielded immediately.
+    fn process_base_directory(&mut self) -> Result<Option<ScanEntry>, DirectoryScanError> {
+        let base_directory_metadata =
+            fs::symlink_metadata(&self.base_directory).map_err(|error| {
+                DirectoryScanError::UnableToReadDirectory {
+                    directory_path: self.base_directory.clone(),
+                    error,
+                }
+            })?;
+
+        if !base_directory_metadata.is_symlink() && !base_directory_metadata.is_dir() {
+            return Err(DirectoryScanError::NotADirectory {
+                path: self.base_directory.clone(),
+            });
+        }
+
+
+        let mut should_scan_base_directory = true;
+
+        if base_directory_metadata.is_symlink() {
+            if !self.options.yield_base_directory {
+                // Nothing to follow, nothing to yield - the iterator will have no elements.
+                return Ok(None);
+            }
+
+            if self.options.follow_base_directory_symbolic_link {
+                let symlink_destination = fs::read_link(&self.base_directory).map_err(|error| {
+                    DirectoryScanError::UnableToReadDirectory {
+                        directory_path: self.base_directory.clone(),
+                        error,
+                    }
+                })?;
+
+                let symlink_destination_metadata = fs::symlink_metadata(&symlink_destination)
+                    .map_err(|error| DirectoryScanError::UnableToReadDirectory {
+                        directory_path: self.base_directory.clone(),
+                        error,
+                    })?;
+
+                if !symlink_destination_metadata.is_dir() {
+                    return Err(DirectoryScanError::NotADirectory {
+                        path: self.base_directory.clone(),
+                    });
+                }
+
+                self.base_directory = symlink_destination;
+            } else {
+                // The base directory (which is a symlink) will be yielded,
+                // but no further elements will be returned.
+                should_scan_base_directory = false;
+            }
+        }
+
+
+        let base_directory_entry = self.options.yield_base_directory.then(|| {
+            ScanEntry::new(
+                self.base_directory.clone(),
+                base_directory_metadata,
+                ScanEntryDepth::BaseDirectory,
+            )
+        });
+
+        if !should_scan_base_directory {
+            return Ok(base_directory_entry);
+        }
+
+
+        let (entry_to_yield_now, post_order_entry) = match self.order {
+            DepthFirstOrder::PreOrder => (base_directory_entry, None),
+            DepthFirstOrder::PostOrder => (None, base_directory_entry),
+        };
+
+        self.directory_stack.push(DirectoryFrame {
+            directory_path: self.base_directory.clone(),
+            directory_depth: ScanEntryDepth::BaseDirectory,
+            relative_path: PathBuf::new(),
+            parent_ignore_rules: None,
+            ignore_rules: None,
+            reader: DirectoryReader::NotOpened,
+            post_order_entry,
+        });
+
+        Ok(entry_to_yield_now)
+    }
+
+    /// Returns a [`SymlinkCycleEncountered`] error if the provided `directory_path`
+    /// is already on the directory stack, i.e. if descending into it would lead to a cycle.
+    ///
+    ///
+    /// [`SymlinkCycleEncountered`]: DirectoryScanError::SymlinkCycleEncountered
+    fn ensure_directory_path_does_not_lead_to_a_tree_cycle(
+        &self,
+        directory_path: &Path,
+    ) -> Result<(), DirectoryScanError> {
+        if self
+            .directory_stack
+            .iter()
+            .any(|ancestor| ancestor.directory_path == directory_path)
+        {
+            return Err(DirectoryScanError::SymlinkCycleEncountered {
+                directory_path: directory_path.to_path_buf(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if a directory at the given depth should be descended into.
+    fn should_descend_into_directory_at(&self, depth: ScanEntryDepth) -> bool {
+        match (self.options.maximum_scan_depth, depth) {
+            (DirectoryScanDepthLimit::Unlimited, _) => true,
+            (_, ScanEntryDepth::BaseDirectory) => true,
+            (
+                DirectoryScanDepthLimit::Limited { maximum_depth },
+                ScanEntryDepth::AtDepth { depth },
+            ) => depth < maximum_depth,
+        }
+    }
+
+    /// Reads the next raw entry of the directory at the top of the stack,
+    /// opening the directory first if needed.
+    ///
+    /// Returns `Ok(None)` once the directory is exhausted.
+    fn next_raw_entry_of_current_directory(
+        &mut self,
+    ) -> Result<Option<fs::DirEntry>, DirectoryScanError> {
+        let respects_ignore_files = self.options.entry_filter.respects_ignore_files();
+
+        let current_directory = self
+            .directory_stack
+            .last_mut()
+            // PANIC SAFETY: The caller ensures the stack is not empty.
+            .expect("expected the directory stack to be non-empty");
+
+        if matches!(current_directory.reader, DirectoryReader::NotOpened) {
+            let directory_iterator = DirectoryEntries::read(
+                &current_directory.directory_path,
+                &self.options.entry_ordering,
+                current_directory.directory_depth.plus_one_level(),
+            )
+            .map_err(|error| DirectoryScanError::UnableToReadDirectory {
+                directory_path: current_directory.directory_path.clone(),
+                error,
+            })?;
+
+            if respects_ignore_files {
+                current_directory.ignore_rules = IgnoreRules::load_for_directory(
+                    current_directory.parent_ignore_rules.as_ref(),
+                    &current_directory.directory_path,
+                    &current_directory.relative_path,
+                )
+                .map_err(|unreadable_ignore_file| DirectoryScanError::UnableToReadIgnoreFile {
+                    file_path: unreadable_ignore_file.file_path,
+                    error: unreadable_ignore_file.error,
+                })?;
+            }
+
+            current_directory.reader = DirectoryReader::Open(directory_iterator);
+        }
+
+        let DirectoryReader::Open(directory_iterator) = &mut current_directory.reader else {
+            return Ok(None);
+        };
+
+        match directory_iterator.next() {
+            Some(raw_entry_result) => raw_entry_result.map(Some).map_err(|error| {
+                DirectoryScanError::UnableToReadDirectoryEntry {
+                    directory_path: current_directory.directory_path.clone(),
+                    error,
+                }
+            }),
+            None => {
+                current_directory.reader = DirectoryReader::Exhausted;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the path and metadata of a raw directory entry, following it if it is
+    /// a symbolic link and following symbolic links is enabled.
+    fn resolve_raw_entry(
+        &self,
+        raw_entry: &fs::DirEntry,
+    ) -> Result<(PathBuf, Metadata), DirectoryScanError> {
+        let current_directory_path = &self
+            .directory_stack
+            .last()
+            // PANIC SAFETY: The caller ensures the stack is not empty.
+            .expect("expected the directory stack to be non-empty")
+            .directory_path;
+
+        let map_entry_error = |error| DirectoryScanError::UnableToReadDirectoryEntry {
+            directory_path: current_directory_path.clone(),
+            error,
+        };
+
+
+        let raw_entry_metadata = raw_entry.metadata().map_err(map_entry_error)?;
+
+        if !self.options.follow_symbolic_links || !raw_entry_metadata.is_symlink() {
+            return Ok((raw_entry.path(), raw_entry_metadata));
+        }
+
+
+        let resolved_raw_entry_path = fs::read_link(raw_entry.path()).map_err(map_entry_error)?;
+
+        let raw_entry_metadata_followed =
+            fs::symlink_metadata(&resolved_raw_entry_path).map_err(map_entry_error)?;
+
+        if raw_entry_metadata_followed.is_dir() {
+            self.ensure_directory_path_does_not_lead_to_a_tree_cycle(&resolved_raw_entry_path)?;
+        }
+
+        Ok((resolved_raw_entry_path, raw_entry_metadata_followed))
+    }
+
+    /// Returns the next entry, or `Ok(None)` once the iterator is exhausted.
+    fn next_entry(&mut self) -> Result<Option<ScanEntry>, DirectoryScanError> {
+        if !self.has_processed_base_directory {
+            self.has_processed_base_directory = true;
+
+            if let Some(base_directory_entry) = self.process_base_directory()? {
+                return Ok(Some(base_directory_entry));
+            }
+        }
+
+
+        loop {
+            let Some(current_directory) = self.directory_stack.last() else {
+                return Ok(None);
+            };
+
+            let current_directory_depth = current_directory.directory_depth;
+
+
+            let Some(raw_entry) = self.next_raw_entry_of_current_directory()? else {
+                let exhausted_directory = self
+                    .directory_stack
+                    .pop()
+                    // PANIC SAFETY: We just looked at the last element of the stack.
+                    .expect("expected the directory stack to be non-empty");
+
+                if let Some(directory_entry) = exhausted_directory.post_order_entry {
+                    return Ok(Some(directory_entry));
+                }
+
+                continue;
+            };
+
+
+            let (entry_path, entry_metadata) = self.resolve_raw_entry(&raw_entry)?;
+            let entry_depth = current_directory_depth.plus_one_level();
+
+            // PANIC SAFETY: `next_raw_entry_of_current_directory` never pops the stack.
+            let current_directory = self
+                .directory_stack
+                .last()
+                .expect("expected the directory stack to be non-empty");
+
+            let entry_relative_path = current_directory.relative_path.join(raw_entry.file_name());
+
+            let current_ignore_rules = current_directory.ignore_rules.clone();
+
+
+            // Entries rejected by the filter are skipped. For directories, this also means
+            // they are never descended into.
+            if !self.options.entry_filter.is_included(
+                &entry_relative_path,
+                entry_depth,
+                &entry_metadata,
+                current_ignore_rules.as_deref(),
+            ) {
+                continue;
+            }
+
+
+            let entry = ScanEntry::new(entry_path, entry_metadata, entry_depth);
+
+            if !entry.metadata().is_dir() || !self.should_descend_into_directory_at(entry_depth) {
+                return Ok(Some(entry));
+            }
+
+
+            let directory_path = entry.path().to_path_buf();
+
+            let (entry_to_yield_now, post_order_entry) = match self.order {
+                DepthFirstOrder::PreOrder => (Some(entry), None),
+                DepthFirstOrder::PostOrder => (None, Some(entry)),
+            };
+
+            // The directory is opened lazily, so that skipping it
+            // (see `skip_current_directory`) avoids reading it altogether.
+            self.directory_stack.push(DirectoryFrame {
+                directory_path,
+                directory_depth: entry_depth,
+                relative_path: entry_relative_path,
+                parent_ignore_rules: current_ignore_rules,
+                ignore_rules: None,
+                reader: DirectoryReader::NotOpened,
+                post_order_entry,
+            });
+
+            if let Some(entry) = entry_to_yield_now {
+                return Ok(Some(entry));
+            }
+        }
+    }
+}
+
+impl Iterator for DepthFirstDirectoryIter {
+    type Item = Result<ScanEntry, DirectoryScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
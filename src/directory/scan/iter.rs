@@ -2,12 +2,16 @@ use std::{
     collections::VecDeque,
     fs::Metadata,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use_enabled_fs_module!();
 
-use super::{DirectoryScanDepthLimit, DirectoryScanOptions, ScanEntry};
-use crate::{directory::ScanEntryDepth, error::DirectoryScanError};
+use super::{DirectoryEntries, DirectoryScanDepthLimit, DirectoryScanOptions, ScanEntry};
+use crate::{
+    directory::{IgnoreRules, ScanEntryDepth},
+    error::DirectoryScanError,
+};
 
 
 /// A currently open directory that is being iterated over (scanned).
@@ -18,8 +22,16 @@ struct OpenDirectory {
     /// Depth of the directory, relative to the root of the scan tree.
     directory_depth: ScanEntryDepth,
 
-    /// The actual directory iterator from the standard library.
-    iterator: fs::ReadDir,
+    /// Path of the directory relative to the root of the scan tree,
+    /// constructed without following symbolic links.
+    relative_path: PathBuf,
+
+    /// Ignore file rules that apply to entries of this directory
+    /// (including the rules of the directory's own ignore files).
+    ignore_rules: Option<Arc<IgnoreRules>>,
+
+    /// The directory iterator, sorted according to [`DirectoryScanOptions::entry_ordering`].
+    iterator: DirectoryEntries,
 }
 
 
@@ -30,6 +42,14 @@ struct PendingDirectory {
 
     /// Depth of the directory, relative to the root of the scan tree.
     directory_depth: ScanEntryDepth,
+
+    /// Path of the directory relative to the root of the scan tree,
+    /// constructed without following symbolic links.
+    relative_path: PathBuf,
+
+    /// Ignore file rules of the directory's parent. Rules from the directory's
+    /// own ignore files are added when it is opened.
+    parent_ignore_rules: Option<Arc<IgnoreRules>>,
 }
 
 
@@ -59,6 +79,13 @@ struct NextEntryInfo {
 
     /// Depth of the entry, relative to the root of the scan tree.
     depth: ScanEntryDepth,
+
+    /// Path of the entry relative to the root of the scan tree,
+    /// constructed without following symbolic links.
+    relative_path: PathBuf,
+
+    /// Ignore file rules of the directory containing the entry.
+    ignore_rules: Option<Arc<IgnoreRules>>,
 }
 
 
@@ -98,6 +125,12 @@ pub struct BreadthFirstDirectoryIter {
     /// Fresh entries are added to the back, and the next entry is taken from the front (FIFO order).
     pending_directory_stack: VecDeque<PendingDirectory>,
 
+    /// The most recently yielded directory, if it is to be scanned.
+    ///
+    /// It is queued for scanning on the next call to [`Self::next`],
+    /// unless [`Self::skip_current_directory`] is called before that.
+    last_yielded_directory: Option<PendingDirectory>,
+
     /// If `Some`, this field contains the currently active directory "reader" (iterator),
     /// along with information about the directory path we're scanning, and its depth in the scan tree.
     currently_open_directory: Option<OpenDirectory>,
@@ -130,24 +163,58 @@ impl BreadthFirstDirectoryIter {
             options,
             currently_open_directory: None,
             pending_directory_stack: VecDeque::new(),
+            last_yielded_directory: None,
             current_directory_ancestors: vec![],
         }
     }
 
 
-    /// Returns a reference to the currently active (open) directory iterator, if any,
-    /// `None` otherwise.
-    fn current_directory_handle(&self) -> Option<&OpenDirectory> {
-        if self.currently_open_directory.is_some() {
-            let handle = self
-                .currently_open_directory
-                .as_ref()
-                .expect("currently_open_directory should be Some");
+    /// Skips the rest of the directory that is currently being iterated.
+    ///
+    /// If the last yielded entry is a directory that would be descended into,
+    /// it is never queued for scanning, meaning none of its contents are read.
+    /// Otherwise, the remaining entries of the directory containing the last yielded entry are skipped.
+    ///
+    /// Calling this right after the base directory has been yielded skips the entire scan.
+    /// Calling this before the first call to [`Self::next`] has no effect.
+    ///
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use fs_more::directory::{DirectoryScanner, DirectoryScanOptions};
+    /// let mut scan = DirectoryScanner::new("./my-project", DirectoryScanOptions::default())
+    ///     .into_iter();
+    ///
+    /// while let Some(entry) = scan.next() {
+    ///     let entry = entry.unwrap();
+    ///
+    ///     if entry.metadata().is_dir() && entry.path().ends_with("node_modules") {
+    ///         // The contents of `node_modules` will not be read.
+    ///         scan.skip_current_directory();
+    ///         continue;
+    ///     }
+    ///
+    ///     println!("{}", entry.path().display());
+    /// }
+    /// ```
+    pub fn skip_current_directory(&mut self) {
+        if self.last_yielded_directory.take().is_some() {
+            return;
+        }
 
-            return Some(handle);
+        if self.has_processed_base_directory && !self.has_scanned_base_directory {
+            // The base directory has just been yielded, but has not been opened yet.
+            self.has_scanned_base_directory = true;
+            return;
         }
 
-        None
+        let _ = self.close_current_directory_handle();
+    }
+
+    /// Returns a reference to the currently active (open) directory iterator, if any,
+    /// `None` otherwise.
+    fn current_directory_handle(&self) -> Option<&OpenDirectory> {
+        self.currently_open_directory.as_ref()
     }
 
     /// Returns the directory path of the currently active (open) directory iterator.
@@ -183,12 +250,7 @@ impl BreadthFirstDirectoryIter {
         &mut self,
     ) -> Result<Option<&mut OpenDirectory>, DirectoryScanError> {
         if self.currently_open_directory.is_some() {
-            let handle = self
-                .currently_open_directory
-                .as_mut()
-                .expect("currently_open_directory should be Some");
-
-            return Ok(Some(handle));
+            return Ok(self.currently_open_directory.as_mut());
         }
 
         self.open_next_directory_handle()
@@ -222,6 +284,26 @@ impl BreadthFirstDirectoryIter {
         Ok(())
     }
 
+    /// Loads the ignore file rules for a directory that is about to be opened,
+    /// if the entry filter respects ignore files. Otherwise, `None` is returned.
+    fn load_ignore_rules(
+        &self,
+        parent_ignore_rules: Option<&Arc<IgnoreRules>>,
+        directory_path: &Path,
+        relative_path: &Path,
+    ) -> Result<Option<Arc<IgnoreRules>>, DirectoryScanError> {
+        if !self.options.entry_filter.respects_ignore_files() {
+            return Ok(None);
+        }
+
+        IgnoreRules::load_for_directory(parent_ignore_rules, directory_path, relative_path).map_err(
+            |unreadable_ignore_file| DirectoryScanError::UnableToReadIgnoreFile {
+                file_path: unreadable_ignore_file.file_path,
+                error: unreadable_ignore_file.error,
+            },
+        )
+    }
+
     /// Attempts to open the next pending directory for iteration.
     ///
     /// On success, a `Ok(Some(&mut`[`OpenDirectory`]`))` is returned,
@@ -241,17 +323,24 @@ impl BreadthFirstDirectoryIter {
             // We've just started, perhaps having just yielded the base directory.
             // As such, we should open the base directory.
 
-            let base_dir_iterator = fs::read_dir(&self.base_directory).map_err(|io_error| {
-                DirectoryScanError::UnableToReadDirectory {
-                    directory_path: self.base_directory.clone(),
-                    error: io_error,
-                }
+            let base_dir_iterator = DirectoryEntries::read(
+                &self.base_directory,
+                &self.options.entry_ordering,
+                ScanEntryDepth::BaseDirectory.plus_one_level(),
+            )
+            .map_err(|io_error| DirectoryScanError::UnableToReadDirectory {
+                directory_path: self.base_directory.clone(),
+                error: io_error,
             })?;
 
 
+            let ignore_rules = self.load_ignore_rules(None, &self.base_directory, Path::new(""))?;
+
             let active_reader_entry = OpenDirectory {
                 directory_path: self.base_directory.clone(),
                 directory_depth: ScanEntryDepth::BaseDirectory,
+                relative_path: PathBuf::new(),
+                ignore_rules,
                 iterator: base_dir_iterator,
             };
 
@@ -297,17 +386,27 @@ impl BreadthFirstDirectoryIter {
         };
 
 
-        let directory_iterator =
-            fs::read_dir(&next_pending_directory.directory_path).map_err(|io_error| {
-                DirectoryScanError::UnableToReadDirectory {
-                    directory_path: next_pending_directory.directory_path.clone(),
-                    error: io_error,
-                }
-            })?;
+        let directory_iterator = DirectoryEntries::read(
+            &next_pending_directory.directory_path,
+            &self.options.entry_ordering,
+            next_pending_directory.directory_depth.plus_one_level(),
+        )
+        .map_err(|io_error| DirectoryScanError::UnableToReadDirectory {
+            directory_path: next_pending_directory.directory_path.clone(),
+            error: io_error,
+        })?;
+
+        let ignore_rules = self.load_ignore_rules(
+            next_pending_directory.parent_ignore_rules.as_ref(),
+            &next_pending_directory.directory_path,
+            &next_pending_directory.relative_path,
+        )?;
 
         let active_reader_entry = OpenDirectory {
             directory_path: next_pending_directory.directory_path.clone(),
             directory_depth: next_pending_directory.directory_depth,
+            relative_path: next_pending_directory.relative_path,
+            ignore_rules,
             iterator: directory_iterator,
         };
 
@@ -381,15 +480,12 @@ impl BreadthFirstDirectoryIter {
         Ok(())
     }
 
-    /// Pushes a directory onto the pending directory scan queue.
+    /// Pushes the most recently yielded directory (if any) onto the pending directory scan queue.
     /// As this is a breadth-first iterator, the new directory will be placed last.
-    fn queue_directory_for_scanning(&mut self, directory_path: PathBuf, depth: ScanEntryDepth) {
-        let pending_dir_entry = PendingDirectory {
-            directory_path,
-            directory_depth: depth,
-        };
-
-        self.pending_directory_stack.push_back(pending_dir_entry);
+    fn queue_last_yielded_directory_for_scanning(&mut self) {
+        if let Some(pending_dir_entry) = self.last_yielded_directory.take() {
+            self.pending_directory_stack.push_back(pending_dir_entry);
+        }
     }
 
     /// Returns the next directory scan entry. This will automatically manage
@@ -446,6 +542,19 @@ impl BreadthFirstDirectoryIter {
             })?;
 
 
+            // PANIC SAFETY: between the call to `current_or_next_directory_handle_mut` and this point,
+            // we never call `close_current_directory_handle`.
+            let current_directory_handle = self
+                .current_directory_handle()
+                .expect("expected a directory handle to be open");
+
+            let raw_entry_relative_path = current_directory_handle
+                .relative_path
+                .join(raw_entry.file_name());
+
+            let raw_entry_ignore_rules = current_directory_handle.ignore_rules.clone();
+
+
             let (raw_entry_path, raw_entry_metadata) =
                 if follow_symbolic_links && raw_entry_metadata.is_symlink() {
                     let resolved_raw_entry_path =
@@ -494,6 +603,8 @@ impl BreadthFirstDirectoryIter {
                 depth: self
                     .current_directory_handle_depth_unchecked()
                     .plus_one_level(),
+                relative_path: raw_entry_relative_path,
+                ignore_rules: raw_entry_ignore_rules,
             }));
         }
     }
@@ -576,7 +687,10 @@ impl Iterator for BreadthFirstDirectoryIter {
         }
 
 
-        let next_entry = {
+        self.queue_last_yielded_directory_for_scanning();
+
+
+        let next_entry = loop {
             let Some(next_entry_info) = try_some!(self.next_entry()) else {
                 // No further entries, the iterator has concluded. Once this is reached,
                 // all subsequent calls to `next` will also hit this branch, returning `None`.
@@ -584,6 +698,18 @@ impl Iterator for BreadthFirstDirectoryIter {
             };
 
 
+            // Entries rejected by the filter are skipped. For directories, this also means
+            // they are never queued, i.e. their contents are never scanned.
+            if !self.options.entry_filter.is_included(
+                &next_entry_info.relative_path,
+                next_entry_info.depth,
+                &next_entry_info.metadata,
+                next_entry_info.ignore_rules.as_deref(),
+            ) {
+                continue;
+            }
+
+
             if next_entry_info.metadata.is_dir() {
                 let ScanEntryDepth::AtDepth {
                     depth: current_dir_depth,
@@ -594,26 +720,31 @@ impl Iterator for BreadthFirstDirectoryIter {
                     panic!("expected the next entry's depth to be 0+, not base directory");
                 };
 
-                match self.options.maximum_scan_depth {
-                    DirectoryScanDepthLimit::Unlimited => {
-                        self.queue_directory_for_scanning(
-                            next_entry_info.path.clone(),
-                            next_entry_info.depth,
-                        );
-                    }
+                let should_scan_directory = match self.options.maximum_scan_depth {
+                    DirectoryScanDepthLimit::Unlimited => true,
                     DirectoryScanDepthLimit::Limited { maximum_depth } => {
-                        if current_dir_depth < maximum_depth {
-                            self.queue_directory_for_scanning(
-                                next_entry_info.path.clone(),
-                                next_entry_info.depth,
-                            );
-                        }
+                        current_dir_depth < maximum_depth
                     }
+                };
+
+                if should_scan_directory {
+                    // The directory is queued on the next call, which allows the caller
+                    // to skip it beforehand (see `skip_current_directory`).
+                    self.last_yielded_directory = Some(PendingDirectory {
+                        directory_path: next_entry_info.path.clone(),
+                        directory_depth: next_entry_info.depth,
+                        relative_path: next_entry_info.relative_path,
+                        parent_ignore_rules: next_entry_info.ignore_rules,
+                    });
                 }
             }
 
 
-            ScanEntry::new(next_entry_info.path, next_entry_info.metadata, next_entry_info.depth)
+            break ScanEntry::new(
+                next_entry_info.path,
+                next_entry_info.metadata,
+                next_entry_info.depth,
+            );
         };
 
 
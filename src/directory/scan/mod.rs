@@ -7,11 +7,16 @@ use std::{
 
 use_enabled_fs_module!();
 
+use super::DirectoryEntryFilter;
 use crate::error::{DirectoryEmptinessScanError, DirectoryScanError};
 
 pub(crate) mod collected;
+mod depth_first;
 mod iter;
+mod ordering;
+pub use depth_first::*;
 pub use iter::*;
+pub use ordering::*;
 
 
 
@@ -101,6 +106,21 @@ pub struct DirectoryScanOptions {
     ///
     /// [`next`]: BreadthFirstDirectoryIter::next
     pub follow_base_directory_symbolic_link: bool,
+
+    /// Decides which entries are yielded. Directories that are skipped
+    /// by the filter are not descended into.
+    ///
+    /// This is also where support for `.gitignore` and `.ignore` files is enabled
+    /// (see [`DirectoryEntryFilter::respect_ignore_files`]).
+    ///
+    /// See [`DirectoryEntryFilter`] for more details.
+    pub entry_filter: DirectoryEntryFilter,
+
+    /// The order in which entries of each directory are yielded, see [`ScanEntryOrdering`].
+    ///
+    /// Use any ordering other than [`ScanEntryOrdering::Unsorted`]
+    /// if you need the scan output to be reproducible across platforms and filesystems.
+    pub entry_ordering: ScanEntryOrdering,
 }
 
 impl DirectoryScanOptions {
@@ -117,6 +137,8 @@ impl Default for DirectoryScanOptions {
             maximum_scan_depth: DirectoryScanDepthLimit::Unlimited,
             follow_symbolic_links: false,
             follow_base_directory_symbolic_link: false,
+            entry_filter: DirectoryEntryFilter::default(),
+            entry_ordering: ScanEntryOrdering::Unsorted,
         }
     }
 }
@@ -244,6 +266,7 @@ impl DirectoryScanner {
     ///
     /// This call will not interact with the filesystem yet. To turn this scanner struct into
     /// a breadth-first recursive iterator, call its [`into_iter`][`Self::into_iter`] method.
+    /// For a depth-first iterator, call [`into_depth_first_iter`][`Self::into_depth_first_iter`].
     pub fn new<P>(base_directory_path: P, options: DirectoryScanOptions) -> Self
     where
         P: Into<PathBuf>,
@@ -253,6 +276,14 @@ impl DirectoryScanner {
             options,
         }
     }
+
+    /// Turns this scanner into a depth-first recursive iterator,
+    /// yielding directories before or after their contents, depending on `order`.
+    ///
+    /// See [`DepthFirstDirectoryIter`] for more information.
+    pub fn into_depth_first_iter(self, order: DepthFirstOrder) -> DepthFirstDirectoryIter {
+        DepthFirstDirectoryIter::new(self.base_path, self.options, order)
+    }
 }
 
 impl IntoIterator for DirectoryScanner {
@@ -0,0 +1,263 @@
+use std::{
+    cmp::Ordering,
+    ffi::OsStr,
+    fmt::{self, Debug},
+    path::Path,
+    sync::Arc,
+};
+
+use_enabled_fs_module!();
+
+use super::{ScanEntry, ScanEntryDepth};
+
+
+/// Signature of a custom [`ScanEntryOrdering`] comparator.
+type ScanEntryComparator = dyn Fn(&ScanEntry, &ScanEntry) -> Ordering + Send + Sync;
+
+
+/// The order in which entries of each scanned directory are yielded.
+///
+/// Sorting is performed per directory: when a directory is opened, all of its entries
+/// are read and sorted before the first one is yielded. The traversal order
+/// (e.g. breadth-first or depth-first) is not affected.
+///
+/// Entries are compared as they are listed in their directory,
+/// i.e. **without following symbolic links**, even if the scan follows them.
+///
+/// The default is [`ScanEntryOrdering::Unsorted`].
+#[derive(Clone, Default)]
+pub enum ScanEntryOrdering {
+    /// Entries are yielded in the order they are read from the directory,
+    /// which depends on the platform and filesystem.
+    ///
+    /// This is the fastest option, as entries do not need to be read all at once.
+    #[default]
+    Unsorted,
+
+    /// Entries are sorted by their file name, comparing the raw names.
+    ///
+    /// For example, `file10` will come before `file2`.
+    ByFileName,
+
+    /// Entries are sorted by their file name, comparing runs of digits by their numeric value.
+    ///
+    /// For example, `file2` will come before `file10`.
+    NaturalByFileName,
+
+    /// Directories come before all other entries; both groups are sorted by their file name
+    /// (as with [`ScanEntryOrdering::ByFileName`]).
+    DirectoriesFirst,
+
+    /// Entries are sorted by a custom comparator (see [`ScanEntryOrdering::custom`]).
+    Custom(Arc<ScanEntryComparator>),
+}
+
+impl ScanEntryOrdering {
+    /// Creates an ordering that sorts entries by the provided comparator.
+    ///
+    /// The comparator receives entries of a single directory. Their paths are not resolved
+    /// and their metadata is obtained without following symbolic links.
+    pub fn custom<F>(comparator: F) -> Self
+    where
+        F: Fn(&ScanEntry, &ScanEntry) -> Ordering + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(comparator))
+    }
+
+    /// Returns `true` if this is [`ScanEntryOrdering::Unsorted`].
+    pub fn is_unsorted(&self) -> bool {
+        matches!(self, Self::Unsorted)
+    }
+
+    /// Compares two entries of the same directory.
+    fn compare(&self, first: &ScanEntry, second: &ScanEntry) -> Ordering {
+        match self {
+            Self::Unsorted => Ordering::Equal,
+            Self::ByFileName => file_name_of(first).cmp(file_name_of(second)),
+            Self::NaturalByFileName => {
+                compare_file_names_naturally(file_name_of(first), file_name_of(second))
+            }
+            Self::DirectoriesFirst => second
+                .metadata()
+                .is_dir()
+                .cmp(&first.metadata().is_dir())
+                .then_with(|| file_name_of(first).cmp(file_name_of(second))),
+            Self::Custom(comparator) => comparator(first, second),
+        }
+    }
+}
+
+impl PartialEq for ScanEntryOrdering {
+    /// Two custom orderings are equal only if they share the same comparator.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Custom(left), Self::Custom(right)) => Arc::ptr_eq(left, right),
+            (Self::Custom(_), _) | (_, Self::Custom(_)) => false,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for ScanEntryOrdering {}
+
+impl Debug for ScanEntryOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsorted => write!(f, "Unsorted"),
+            Self::ByFileName => write!(f, "ByFileName"),
+            Self::NaturalByFileName => write!(f, "NaturalByFileName"),
+            Self::DirectoriesFirst => write!(f, "DirectoriesFirst"),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+
+fn file_name_of(entry: &ScanEntry) -> &OsStr {
+    entry.path().file_name().unwrap_or_default()
+}
+
+
+/// Compares two file names, treating runs of ASCII digits as numbers.
+///
+/// Numbers that are equal in value are ordered by their number of leading zeros (fewer first).
+/// Names that are still equal are ordered by their raw value.
+fn compare_file_names_naturally(first: &OsStr, second: &OsStr) -> Ordering {
+    let first_lossy = first.to_string_lossy();
+    let second_lossy = second.to_string_lossy();
+
+    let mut first_remaining = first_lossy.as_ref();
+    let mut second_remaining = second_lossy.as_ref();
+
+    loop {
+        let (first_chunk, first_rest) = split_first_chunk(first_remaining);
+        let (second_chunk, second_rest) = split_first_chunk(second_remaining);
+
+        if first_chunk.is_empty() || second_chunk.is_empty() {
+            return first_chunk
+                .len()
+                .cmp(&second_chunk.len())
+                .then_with(|| first.cmp(second));
+        }
+
+        let first_is_number = first_chunk.starts_with(|c: char| c.is_ascii_digit());
+        let second_is_number = second_chunk.starts_with(|c: char| c.is_ascii_digit());
+
+        let chunk_ordering = if first_is_number && second_is_number {
+            let first_digits = first_chunk.trim_start_matches('0');
+            let second_digits = second_chunk.trim_start_matches('0');
+
+            first_digits
+                .len()
+                .cmp(&second_digits.len())
+                .then_with(|| first_digits.cmp(second_digits))
+                .then_with(|| first_chunk.len().cmp(&second_chunk.len()))
+        } else {
+            first_chunk.cmp(second_chunk)
+        };
+
+        if chunk_ordering != Ordering::Equal {
+            return chunk_ordering;
+        }
+
+        first_remaining = first_rest;
+        second_remaining = second_rest;
+    }
+}
+
+/// Splits off the leading run of either ASCII digits or non-digits.
+fn split_first_chunk(value: &str) -> (&str, &str) {
+    let starts_with_digit = value.starts_with(|c: char| c.is_ascii_digit());
+
+    let chunk_length = value
+        .find(|c: char| c.is_ascii_digit() != starts_with_digit)
+        .unwrap_or(value.len());
+
+    value.split_at(chunk_length)
+}
+
+
+
+/// Entries of a single directory, either read lazily or sorted up front.
+pub(crate) enum DirectoryEntries {
+    Unsorted(fs::ReadDir),
+
+    Sorted(std::vec::IntoIter<fs::DirEntry>),
+}
+
+impl DirectoryEntries {
+    /// Opens a directory for reading. Unless `ordering` is [`ScanEntryOrdering::Unsorted`],
+    /// all entries are read and sorted right away.
+    ///
+    /// `entry_depth` is the depth of the directory's entries, as seen by a custom comparator.
+    pub(crate) fn read(
+        directory_path: &Path,
+        ordering: &ScanEntryOrdering,
+        entry_depth: ScanEntryDepth,
+    ) -> std::io::Result<Self> {
+        let directory_iterator = fs::read_dir(directory_path)?;
+
+        if ordering.is_unsorted() {
+            return Ok(Self::Unsorted(directory_iterator));
+        }
+
+
+        let mut entries = directory_iterator
+            .map(|entry_result| {
+                let entry = entry_result?;
+                let comparable_entry = ScanEntry::new(entry.path(), entry.metadata()?, entry_depth);
+
+                Ok((entry, comparable_entry))
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        entries.sort_by(|(_, first), (_, second)| ordering.compare(first, second));
+
+        Ok(Self::Sorted(
+            entries
+                .into_iter()
+                .map(|(entry, _)| entry)
+                .collect::<Vec<_>>()
+                .into_iter(),
+        ))
+    }
+}
+
+impl Iterator for DirectoryEntries {
+    type Item = std::io::Result<fs::DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Unsorted(directory_iterator) => directory_iterator.next(),
+            Self::Sorted(sorted_entries) => sorted_entries.next().map(Ok),
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn natural(first: &str, second: &str) -> Ordering {
+        compare_file_names_naturally(OsStr::new(first), OsStr::new(second))
+    }
+
+    #[test]
+    fn natural_ordering_compares_numbers_by_value() {
+        assert_eq!(natural("file2", "file10"), Ordering::Less);
+        assert_eq!(natural("file10", "file2"), Ordering::Greater);
+        assert_eq!(natural("a1b2", "a1b10"), Ordering::Less);
+        assert_eq!(natural("10", "9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn natural_ordering_handles_prefixes_and_leading_zeros() {
+        assert_eq!(natural("file", "file1"), Ordering::Less);
+        assert_eq!(natural("file1", "file01"), Ordering::Less);
+        assert_eq!(natural("file01", "file2"), Ordering::Less);
+        assert_eq!(natural("same", "same"), Ordering::Equal);
+        assert_eq!(natural("abc", "abd"), Ordering::Less);
+    }
+}
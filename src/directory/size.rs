@@ -1,9 +1,27 @@
 use std::path::PathBuf;
 
-use super::{DirectoryScanDepthLimit, DirectoryScanOptions, DirectoryScanner};
+use super::{
+    DirectoryEntryFilter,
+    DirectoryScanDepthLimit,
+    DirectoryScanOptions,
+    DirectoryScanner,
+    ScanEntryOrdering,
+};
 use crate::error::DirectorySizeScanError;
 
 
+/// Options that influence the [`directory_size_in_bytes_with_options`] function.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct DirectorySizeOptions {
+    /// Decides which entries of the directory are counted.
+    ///
+    /// Skipped directories are not descended into, meaning their contents are not counted either.
+    /// See [`DirectoryEntryFilter`] for more details.
+    pub entry_filter: DirectoryEntryFilter,
+}
+
+
+
 /// Returns the size of the directory, including all of its files and subdirectories, in bytes.
 ///
 /// There is no limit to the depth of this scan; the directory tree is traversed as deep as needed.
@@ -11,7 +29,49 @@ use crate::error::DirectorySizeScanError;
 ///
 /// This function is essentially a shortcut for initializing
 /// a [`DirectoryScanner`] with unlimited scan depth and summing entries' sizes.
+///
+/// To skip some entries (e.g. with a [`PathMatcher`]),
+/// see [`directory_size_in_bytes_with_options`].
+///
+///
+/// [`PathMatcher`]: super::PathMatcher
 pub fn directory_size_in_bytes<P>(directory_path: P) -> Result<u64, DirectorySizeScanError>
+where
+    P: Into<PathBuf>,
+{
+    directory_size_in_bytes_with_options(directory_path, DirectorySizeOptions::default())
+}
+
+
+/// Returns the size of the directory, including all of its (non-skipped) files
+/// and subdirectories, in bytes.
+///
+/// This behaves exactly like [`directory_size_in_bytes`], except that entries can be skipped
+/// with [`options.entry_filter`]. For example, to ignore build artifacts:
+///
+/// ```no_run
+/// # use fs_more::directory::{
+/// #     directory_size_in_bytes_with_options,
+/// #     DirectoryEntryFilter,
+/// #     DirectorySizeOptions,
+/// #     PathMatcher,
+/// # };
+/// let size_without_artifacts = directory_size_in_bytes_with_options(
+///     "./my-project",
+///     DirectorySizeOptions {
+///         entry_filter: DirectoryEntryFilter::new()
+///             .exclude(PathMatcher::new(["/target"]).unwrap()),
+///     },
+/// )
+/// .unwrap();
+/// ```
+///
+///
+/// [`options.entry_filter`]: DirectorySizeOptions::entry_filter
+pub fn directory_size_in_bytes_with_options<P>(
+    directory_path: P,
+    options: DirectorySizeOptions,
+) -> Result<u64, DirectorySizeScanError>
 where
     P: Into<PathBuf>,
 {
@@ -25,6 +85,8 @@ where
             maximum_scan_depth: DirectoryScanDepthLimit::Unlimited,
             follow_symbolic_links: false,
             follow_base_directory_symbolic_link: true,
+            entry_filter: options.entry_filter,
+            entry_ordering: ScanEntryOrdering::Unsorted,
         },
     );
 
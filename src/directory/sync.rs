@@ -0,0 +1,709 @@
+use std::{
+    collections::HashSet,
+    fs::Metadata,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use_enabled_fs_module!();
+
+use super::{
+    copy_directory_unchecked,
+    prepared::{DirectoryCopyPrepared, QueuedOperation},
+    BrokenSymlinkBehaviour,
+    CollidingSubDirectoryBehaviour,
+    DestinationDirectoryRule,
+    DirectoryCopyDepthLimit,
+    DirectoryCopyOptions,
+    DirectoryEntries,
+    DirectoryEntryFilter,
+    IgnoreRules,
+    ScanEntryDepth,
+    ScanEntryOrdering,
+    SymlinkBehaviour,
+};
+use crate::{
+    error::{CopyDirectoryExecutionError, CopyDirectoryPreparationError, SyncDirectoryError},
+    file::{
+        have_same_contents,
+        sync_directory_to_disk,
+        CollidingFileBehaviour,
+        DurabilityLevel,
+        FileComparisonError,
+        MetadataPreservation,
+    },
+};
+
+
+/// How [`sync_directory`] decides whether an existing destination file is up to date.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DirectorySyncComparison {
+    /// A destination file is up to date if its size and last modification time
+    /// match the ones of its source file. File contents are never read.
+    ///
+    /// This is fast, and is what `rsync` does by default, but it relies on the modification times
+    /// of copied files being preserved (see [`DirectorySyncOptions::metadata_preservation`]).
+    SizeAndModificationTime,
+
+    /// A destination file is up to date if its size and contents match the ones of its source file.
+    ///
+    /// The contents of files with matching sizes are compared byte for byte, chunk by chunk.
+    /// Reading stops at the first mismatching chunk, so only up-to-date files are read in full.
+    Contents,
+}
+
+
+
+/// Options that influence the [`sync_directory`] function.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DirectorySyncOptions {
+    /// How to decide whether an existing destination file is up to date
+    /// (and can be left as-is) or must be copied again.
+    pub comparison: DirectorySyncComparison,
+
+    /// Whether to delete destination entries that are absent from the source directory
+    /// (like `rsync --delete`).
+    ///
+    /// Entries excluded by the [`entry_filter`] and entries deeper than the [`copy_depth_limit`]
+    /// are never deleted. This also allows replacing destination entries whose type
+    /// differs from their source counterparts (e.g. a directory where the source has a file),
+    /// which otherwise results in [`SyncDirectoryError::DestinationEntryTypeMismatch`].
+    ///
+    ///
+    /// [`entry_filter`]: Self::entry_filter
+    /// [`copy_depth_limit`]: Self::copy_depth_limit
+    pub delete_extraneous_entries: bool,
+
+    /// Maximum depth of the source directory to sync to the destination.
+    pub copy_depth_limit: DirectoryCopyDepthLimit,
+
+    /// Sets the behaviour for symbolic links when syncing a directory.
+    pub symlink_behaviour: SymlinkBehaviour,
+
+    /// Sets the behaviour for broken symbolic links when syncing a directory.
+    pub broken_symlink_behaviour: BrokenSymlinkBehaviour,
+
+    /// Decides which entries of the source directory are synced.
+    ///
+    /// See [`DirectoryEntryFilter`] for more details.
+    pub entry_filter: DirectoryEntryFilter,
+
+    /// Which metadata of the source files and directories to apply to their copies.
+    ///
+    /// Unchanged files are left as they are, including their metadata.
+    /// See [`MetadataPreservation`] for more details.
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Whether each new or changed file is copied atomically, i.e. into a hidden temporary file
+    /// that is renamed over the destination file once it has been fully written.
+    ///
+    /// See [`FileCopyOptions::atomic`] for more details.
+    ///
+    ///
+    /// [`FileCopyOptions::atomic`]: crate::file::FileCopyOptions::atomic
+    pub atomic: bool,
+
+    /// How much of the sync to flush to disk before returning.
+    ///
+    /// See [`DurabilityLevel`] for more details.
+    pub durability: DurabilityLevel,
+}
+
+impl Default for DirectorySyncOptions {
+    /// Constructs defaults for syncing a directory, which are:
+    /// - [`DirectorySyncComparison::SizeAndModificationTime`]: files are compared by their size and modification time,
+    /// - destination entries absent from the source directory are kept,
+    /// - [`DirectoryCopyDepthLimit::Unlimited`]: there is no sync depth limit,
+    /// - [`SymlinkBehaviour::Keep`]: symbolic links are not followed,
+    /// - [`BrokenSymlinkBehaviour::Keep`]: broken symbolic links are kept as-is, i.e. broken,
+    /// - [`DirectoryEntryFilter::new`]: all entries are synced,
+    /// - only timestamps are preserved (see [`MetadataPreservation::timestamps`]),
+    /// - files are written directly into their destination files (they are not copied atomically), and
+    /// - [`DurabilityLevel::None`]: nothing is synced to disk.
+    fn default() -> Self {
+        Self {
+            comparison: DirectorySyncComparison::SizeAndModificationTime,
+            delete_extraneous_entries: false,
+            copy_depth_limit: DirectoryCopyDepthLimit::Unlimited,
+            symlink_behaviour: SymlinkBehaviour::Keep,
+            broken_symlink_behaviour: BrokenSymlinkBehaviour::Keep,
+            entry_filter: DirectoryEntryFilter::new(),
+            metadata_preservation: MetadataPreservation {
+                timestamps: true,
+                ..MetadataPreservation::NONE
+            },
+            atomic: false,
+            durability: DurabilityLevel::None,
+        }
+    }
+}
+
+
+
+/// Describes a successful directory sync operation.
+///
+/// All paths are relative to the destination directory.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DirectorySyncFinished {
+    /// Entries that did not exist on the destination and have been copied from the source.
+    pub added: Vec<PathBuf>,
+
+    /// Entries that existed on the destination, but differed from the source,
+    /// and have been copied from the source again.
+    ///
+    /// This includes destination entries that have been replaced because their type
+    /// differed from their source counterparts.
+    pub updated: Vec<PathBuf>,
+
+    /// Destination entries that were absent from the source and have been deleted
+    /// (see [`DirectorySyncOptions::delete_extraneous_entries`]).
+    ///
+    /// The contents of deleted directories are not listed.
+    pub deleted: Vec<PathBuf>,
+
+    /// Entries that were already up to date on the destination and have been left untouched.
+    pub unchanged: Vec<PathBuf>,
+
+    /// Total number of bytes copied.
+    pub total_bytes_copied: u64,
+}
+
+
+
+/// State of a destination entry, compared with the source entry of a [`QueuedOperation`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum DestinationEntryState {
+    /// The destination entry does not exist.
+    Missing,
+
+    /// The destination entry exists and matches the source entry.
+    UpToDate,
+
+    /// The destination entry exists and is of the same type as the source entry,
+    /// but differs from it (e.g. a file with different contents).
+    Outdated,
+
+    /// The destination entry exists, but is of a different type than the source entry.
+    OfDifferentType,
+}
+
+
+/// Compares the destination entry of `operation` with its source entry.
+fn compare_with_destination(
+    operation: &QueuedOperation,
+    comparison: DirectorySyncComparison,
+) -> Result<DestinationEntryState, SyncDirectoryError> {
+    let destination_path = operation.destination_path();
+
+    let destination_metadata = match fs::symlink_metadata(destination_path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(DestinationEntryState::Missing);
+        }
+        Err(error) => {
+            return Err(SyncDirectoryError::UnableToAccessDestination {
+                path: destination_path.to_path_buf(),
+                error,
+            });
+        }
+    };
+
+
+    match operation {
+        QueuedOperation::CopyFile {
+            source_file_path,
+            source_size_bytes,
+            destination_file_path,
+            source_hard_link_identity: _,
+        } => {
+            if !destination_metadata.is_file() {
+                return Ok(DestinationEntryState::OfDifferentType);
+            }
+
+            if destination_metadata.len() != *source_size_bytes {
+                return Ok(DestinationEntryState::Outdated);
+            }
+
+            let is_up_to_date = match comparison {
+                DirectorySyncComparison::SizeAndModificationTime => have_same_modification_time(
+                    source_file_path,
+                    destination_file_path,
+                    &destination_metadata,
+                )?,
+                DirectorySyncComparison::Contents => {
+                    have_same_contents(source_file_path, destination_file_path).map_err(
+                        |error| match error {
+                            FileComparisonError::UnableToReadFirstFile(error) => {
+                                SyncDirectoryError::UnableToAccessSource {
+                                    path: source_file_path.clone(),
+                                    error,
+                                }
+                            }
+                            FileComparisonError::UnableToReadSecondFile(error) => {
+                                SyncDirectoryError::UnableToAccessDestination {
+                                    path: destination_file_path.clone(),
+                                    error,
+                                }
+                            }
+                        },
+                    )?
+                }
+            };
+
+            match is_up_to_date {
+                true => Ok(DestinationEntryState::UpToDate),
+                false => Ok(DestinationEntryState::Outdated),
+            }
+        }
+
+        // PANIC SAFETY: Syncs never preserve hard links, so no hard link creations are queued.
+        QueuedOperation::CreateHardLink { .. } => {
+            panic!("BUG: hard link creation was queued for a sync")
+        }
+
+        QueuedOperation::CreateDirectory { .. } => match destination_metadata.is_dir() {
+            true => Ok(DestinationEntryState::UpToDate),
+            false => Ok(DestinationEntryState::OfDifferentType),
+        },
+
+        QueuedOperation::CreateSymlink {
+            symlink_path,
+            symlink_destination_path,
+            ..
+        } => {
+            if !destination_metadata.is_symlink() {
+                return Ok(DestinationEntryState::OfDifferentType);
+            }
+
+            let destination_symlink_target = fs::read_link(symlink_path).map_err(|error| {
+                SyncDirectoryError::UnableToAccessDestination {
+                    path: symlink_path.clone(),
+                    error,
+                }
+            })?;
+
+            match destination_symlink_target.eq(symlink_destination_path) {
+                true => Ok(DestinationEntryState::UpToDate),
+                false => Ok(DestinationEntryState::Outdated),
+            }
+        }
+    }
+}
+
+
+/// Returns `true` if the source and destination file have the same last modification time.
+fn have_same_modification_time(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    destination_metadata: &Metadata,
+) -> Result<bool, SyncDirectoryError> {
+    let source_modification_time = fs::metadata(source_file_path)
+        .and_then(|source_metadata| source_metadata.modified())
+        .map_err(|error| SyncDirectoryError::UnableToAccessSource {
+            path: source_file_path.to_path_buf(),
+            error,
+        })?;
+
+    let destination_modification_time = destination_metadata.modified().map_err(|error| {
+        SyncDirectoryError::UnableToAccessDestination {
+            path: destination_file_path.to_path_buf(),
+            error,
+        }
+    })?;
+
+    Ok(source_modification_time == destination_modification_time)
+}
+
+
+/// Removes the destination entry at `path`, including all of its contents if it is a directory.
+///
+/// Symbolic links are never followed, meaning only the links themselves are removed.
+fn remove_destination_entry(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+
+    if metadata.is_dir() {
+        return fs::remove_dir_all(path);
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::FileTypeExt;
+
+        // On Windows, directory symbolic links must be removed as if they were directories.
+        if metadata.file_type().is_symlink_dir() {
+            return fs::remove_dir(path);
+        }
+    }
+
+    fs::remove_file(path)
+}
+
+
+
+/// A destination directory whose entries are checked for ones that are absent from the source.
+struct PendingDestinationScan {
+    directory_path: PathBuf,
+    source_directory_path: PathBuf,
+    relative_path: PathBuf,
+    parent_ignore_rules: Option<Arc<IgnoreRules>>,
+    depth: usize,
+}
+
+/// Scans the destination directory for entries that are not covered by the synced source entries
+/// (`synced_paths`), and returns their paths.
+///
+/// Only directories that have been synced from the source (`synced_directory_paths`) are descended into,
+/// and only up to the depth limit. Entries excluded by `entry_filter` are never returned.
+fn collect_extraneous_destination_entries(
+    source_directory_path: &Path,
+    destination_directory_path: &Path,
+    synced_paths: &HashSet<PathBuf>,
+    synced_directory_paths: &HashSet<PathBuf>,
+    options: &DirectorySyncOptions,
+) -> Result<Vec<PathBuf>, SyncDirectoryError> {
+    let mut extraneous_entry_paths = Vec::new();
+
+    if !destination_directory_path.is_dir() {
+        return Ok(extraneous_entry_paths);
+    }
+
+
+    let mut destination_scan_queue = vec![PendingDestinationScan {
+        directory_path: destination_directory_path.to_path_buf(),
+        source_directory_path: source_directory_path.to_path_buf(),
+        relative_path: PathBuf::new(),
+        parent_ignore_rules: None,
+        depth: 0,
+    }];
+
+    while let Some(next_directory) = destination_scan_queue.pop() {
+        let directory_entries = DirectoryEntries::read(
+            &next_directory.directory_path,
+            &ScanEntryOrdering::ByFileName,
+            ScanEntryDepth::AtDepth {
+                depth: next_directory.depth,
+            },
+        )
+        .map_err(|error| SyncDirectoryError::UnableToAccessDestination {
+            path: next_directory.directory_path.clone(),
+            error,
+        })?;
+
+        // Ignore files are read from the source directory, just like when planning the copy,
+        // so that the entries they ignore are protected from deletion.
+        let ignore_rules = if options.entry_filter.respects_ignore_files() {
+            IgnoreRules::load_for_directory(
+                next_directory.parent_ignore_rules.as_ref(),
+                &next_directory.source_directory_path,
+                &next_directory.relative_path,
+            )
+            .map_err(|unreadable_ignore_file| {
+                SyncDirectoryError::UnableToAccessSource {
+                    path: unreadable_ignore_file.file_path,
+                    error: unreadable_ignore_file.error,
+                }
+            })?
+        } else {
+            None
+        };
+
+        for directory_entry in directory_entries {
+            let directory_entry =
+                directory_entry.map_err(|error| SyncDirectoryError::UnableToAccessDestination {
+                    path: next_directory.directory_path.clone(),
+                    error,
+                })?;
+
+            let entry_path = directory_entry.path();
+            let entry_name = directory_entry.file_name();
+            let entry_relative_path = next_directory.relative_path.join(&entry_name);
+
+
+            if synced_paths.contains(&entry_path) {
+                let may_descend = match options.copy_depth_limit {
+                    DirectoryCopyDepthLimit::Limited { maximum_depth } => {
+                        next_directory.depth < maximum_depth
+                    }
+                    DirectoryCopyDepthLimit::Unlimited => true,
+                };
+
+                if may_descend && synced_directory_paths.contains(&entry_path) {
+                    destination_scan_queue.push(PendingDestinationScan {
+                        directory_path: entry_path,
+                        source_directory_path: next_directory
+                            .source_directory_path
+                            .join(&entry_name),
+                        relative_path: entry_relative_path,
+                        parent_ignore_rules: ignore_rules.clone(),
+                        depth: next_directory.depth + 1,
+                    });
+                }
+
+                continue;
+            }
+
+
+            if !options.entry_filter.includes_everything() {
+                let entry_metadata = directory_entry.metadata().map_err(|error| {
+                    SyncDirectoryError::UnableToAccessDestination {
+                        path: entry_path.clone(),
+                        error,
+                    }
+                })?;
+
+                if !options.entry_filter.is_included(
+                    &entry_relative_path,
+                    ScanEntryDepth::AtDepth {
+                        depth: next_directory.depth,
+                    },
+                    &entry_metadata,
+                    ignore_rules.as_deref(),
+                ) {
+                    continue;
+                }
+            }
+
+            extraneous_entry_paths.push(entry_path);
+        }
+    }
+
+    Ok(extraneous_entry_paths)
+}
+
+
+
+/// Syncs the destination directory with the source directory, like `rsync` does.
+///
+/// The source directory is scanned and each of its entries is compared with the corresponding
+/// destination entry (see [`DirectorySyncComparison`]). Only new and changed files, symbolic links and
+/// directories are copied; entries that are already up to date are left untouched.
+/// If [`options.delete_extraneous_entries`] is enabled, destination entries that are absent
+/// from the source directory are deleted, making the destination an exact mirror of the source.
+///
+/// The destination directory is created if it does not exist. It may contain other entries,
+/// as a sync always merges the source directory into it.
+///
+///
+/// # Symbolic links
+/// Symbolic links inside the source directory are handled according to the [`symlink_behaviour`] option,
+/// just like in [`copy_directory`]. A destination symbolic link is up to date
+/// if it points to the same path as its source counterpart.
+///
+///
+/// # Return value
+/// Upon success, the function returns the entries that were added, updated, deleted and left unchanged,
+/// as well as the total number of bytes copied; see [`DirectorySyncFinished`].
+///
+///
+/// # Errors
+/// If the directory cannot be synced, a [`SyncDirectoryError`] is returned;
+/// see its documentation for more details.
+///
+/// All entries are compared, and all destination entries to delete are collected,
+/// before the destination directory is changed in any way. This means that if a preparation error
+/// ([`SyncDirectoryError::PreparationError`]) or a comparison error is returned,
+/// the destination directory is left untouched.
+///
+///
+/// [`options.delete_extraneous_entries`]: DirectorySyncOptions::delete_extraneous_entries
+/// [`symlink_behaviour`]: DirectorySyncOptions::symlink_behaviour
+/// [`copy_directory`]: super::copy_directory
+pub fn sync_directory<S, T>(
+    source_directory_path: S,
+    destination_directory_path: T,
+    options: DirectorySyncOptions,
+) -> Result<DirectorySyncFinished, SyncDirectoryError>
+where
+    S: AsRef<Path>,
+    T: AsRef<Path>,
+{
+    let destination_directory_rule = DestinationDirectoryRule::AllowNonEmpty {
+        colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+        colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+    };
+
+    let (validated_source_directory, validated_destination_directory) =
+        DirectoryCopyPrepared::validate_source_and_destination(
+            source_directory_path.as_ref(),
+            destination_directory_path.as_ref(),
+            destination_directory_rule,
+        )?;
+
+    let source_directory_path = validated_source_directory.directory_path.clone();
+
+    let mut prepared_copy = DirectoryCopyPrepared::prepare_with_validated(
+        validated_source_directory,
+        validated_destination_directory,
+        destination_directory_rule,
+        options.copy_depth_limit,
+        options.symlink_behaviour,
+        options.broken_symlink_behaviour,
+        &options.entry_filter,
+    )
+    .map_err(CopyDirectoryPreparationError::CopyPlanningError)?;
+
+    let destination_directory_path = prepared_copy.destination_directory_path.clone();
+    let relative_to_destination = |path: &Path| {
+        path.strip_prefix(&destination_directory_path)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| path.to_path_buf())
+    };
+
+
+    let mut finished_sync = DirectorySyncFinished {
+        added: Vec::new(),
+        updated: Vec::new(),
+        deleted: Vec::new(),
+        unchanged: Vec::new(),
+        total_bytes_copied: 0,
+    };
+
+    let mut synced_paths = HashSet::new();
+    let mut synced_directory_paths = HashSet::new();
+    let mut entries_to_replace = Vec::new();
+    let mut entries_of_different_type = HashSet::new();
+    let mut operations_to_execute = Vec::new();
+
+    for operation in prepared_copy.operation_queue.drain(..) {
+        let destination_path = operation.destination_path().to_path_buf();
+
+        // Nothing exists inside a destination entry that is about to be replaced
+        // (e.g. a file or a symbolic link where the source has a directory),
+        // and looking inside it would fail or follow the symbolic link.
+        let is_inside_entry_of_different_type = destination_path
+            .ancestors()
+            .skip(1)
+            .any(|ancestor_path| entries_of_different_type.contains(ancestor_path));
+
+        let destination_entry_state = match is_inside_entry_of_different_type {
+            true => DestinationEntryState::Missing,
+            false => compare_with_destination(&operation, options.comparison)?,
+        };
+
+        let is_base_directory = destination_path == destination_directory_path;
+        let relative_destination_path = relative_to_destination(&destination_path);
+
+        match destination_entry_state {
+            DestinationEntryState::Missing => {
+                if !is_base_directory {
+                    finished_sync.added.push(relative_destination_path);
+                }
+            }
+            DestinationEntryState::UpToDate => {
+                if !is_base_directory {
+                    finished_sync.unchanged.push(relative_destination_path);
+                }
+            }
+            DestinationEntryState::Outdated => {
+                // Existing files are overwritten, but symbolic links must be created anew.
+                if matches!(operation, QueuedOperation::CreateSymlink { .. }) {
+                    entries_to_replace.push(destination_path.clone());
+                }
+
+                finished_sync.updated.push(relative_destination_path);
+            }
+            DestinationEntryState::OfDifferentType => {
+                if is_base_directory || !options.delete_extraneous_entries {
+                    return Err(SyncDirectoryError::DestinationEntryTypeMismatch {
+                        path: destination_path,
+                    });
+                }
+
+                entries_to_replace.push(destination_path.clone());
+                entries_of_different_type.insert(destination_path.clone());
+                finished_sync.updated.push(relative_destination_path);
+            }
+        }
+
+
+        let is_directory = matches!(operation, QueuedOperation::CreateDirectory { .. });
+
+        // Existing directories are kept in the queue, so that their metadata is still applied.
+        if destination_entry_state != DestinationEntryState::UpToDate || is_directory {
+            operations_to_execute.push(operation);
+        }
+
+        // Destination entries of a different type are removed, so they must not be
+        // descended into when looking for extraneous entries.
+        if is_directory && destination_entry_state != DestinationEntryState::OfDifferentType {
+            synced_directory_paths.insert(destination_path.clone());
+        }
+
+        synced_paths.insert(destination_path);
+    }
+
+    let extraneous_entry_paths = if options.delete_extraneous_entries {
+        collect_extraneous_destination_entries(
+            &source_directory_path,
+            &destination_directory_path,
+            &synced_paths,
+            &synced_directory_paths,
+            &options,
+        )?
+    } else {
+        Vec::new()
+    };
+
+
+    for entry_path in entries_to_replace.iter().chain(&extraneous_entry_paths) {
+        remove_destination_entry(entry_path).map_err(|error| {
+            SyncDirectoryError::UnableToRemoveDestinationEntry {
+                path: entry_path.clone(),
+                error,
+            }
+        })?;
+    }
+
+    // The directories that entries have been deleted from are not touched by the copy below,
+    // so they must be synced here.
+    if options.durability.syncs_directories() {
+        let mut parent_directory_paths = extraneous_entry_paths
+            .iter()
+            .filter_map(|entry_path| entry_path.parent())
+            .collect::<Vec<_>>();
+
+        parent_directory_paths.sort_unstable();
+        parent_directory_paths.dedup();
+
+        for parent_directory_path in parent_directory_paths {
+            sync_directory_to_disk(parent_directory_path).map_err(|error| {
+                CopyDirectoryExecutionError::UnableToSyncDirectory {
+                    directory_path: parent_directory_path.to_path_buf(),
+                    error,
+                }
+            })?;
+        }
+    }
+
+    finished_sync.deleted = extraneous_entry_paths
+        .iter()
+        .map(|entry_path| relative_to_destination(entry_path))
+        .collect();
+
+
+    prepared_copy.total_bytes = operations_to_execute
+        .iter()
+        .map(QueuedOperation::size_bytes)
+        .sum();
+    prepared_copy.operation_queue = operations_to_execute;
+
+    let finished_copy = copy_directory_unchecked(
+        prepared_copy,
+        DirectoryCopyOptions {
+            destination_directory_rule,
+            copy_depth_limit: options.copy_depth_limit,
+            symlink_behaviour: options.symlink_behaviour,
+            broken_symlink_behaviour: options.broken_symlink_behaviour,
+            metadata_preservation: options.metadata_preservation,
+            atomic: options.atomic,
+            durability: options.durability,
+            ..Default::default()
+        },
+    )?;
+
+    finished_sync.total_bytes_copied = finished_copy.total_bytes_copied;
+
+
+    Ok(finished_sync)
+}
@@ -1,9 +1,9 @@
-use std::path::PathBuf;
+use std::{ffi::OsString, path::PathBuf};
 
 use thiserror::Error;
 
 use super::FileError;
-use crate::directory::DestinationDirectoryRule;
+use crate::directory::{DestinationDirectoryRule, DirectoryRollbackReport};
 
 
 /// Source directory path validation error.
@@ -184,6 +184,21 @@ pub enum DirectoryExecutionPlanError {
         path: PathBuf,
     },
 
+    /// A destination file or symbolic link that did not exist when a
+    /// [`DirectoryCopyPlan`] (or [`DirectoryMovePlan`]) was created has appeared since.
+    ///
+    /// Executing the plan would overwrite an entry that was not part of the reviewed plan,
+    /// so execution is refused instead. Create a new plan to proceed.
+    ///
+    ///
+    /// [`DirectoryCopyPlan`]: crate::directory::DirectoryCopyPlan
+    /// [`DirectoryMovePlan`]: crate::directory::DirectoryMovePlan
+    #[error("destination entry appeared after the plan was created: {}", .path.display())]
+    DestinationChangedAfterPlanning {
+        /// Path of the destination entry that appeared after planning.
+        path: PathBuf,
+    },
+
     /// A broken symbolic link has been encountered inside the source directory.
     ///
     /// This error can occur only when `broken_symlink_behaviour` is set to
@@ -240,6 +255,54 @@ pub enum CopyDirectoryPreparationError {
     /// A directory copy planning error.
     #[error(transparent)]
     CopyPlanningError(#[from] DirectoryExecutionPlanError),
+
+    /// The resume journal could not be opened, read or created,
+    /// or it is not a valid directory copy journal.
+    ///
+    /// See [`DirectoryCopyOptions::resume_journal_path`].
+    ///
+    ///
+    /// [`DirectoryCopyOptions::resume_journal_path`]: crate::directory::DirectoryCopyOptions::resume_journal_path
+    #[error("unable to open resume journal: {}", .journal_path.display())]
+    UnableToOpenResumeJournal {
+        /// Path of the resume journal.
+        journal_path: PathBuf,
+
+        /// IO error describing why the resume journal could not be opened.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// The resume journal was written by a copy with a different
+    /// source or destination directory, so it can not be used to resume this copy.
+    ///
+    /// See [`DirectoryCopyOptions::resume_journal_path`].
+    ///
+    ///
+    /// [`DirectoryCopyOptions::resume_journal_path`]: crate::directory::DirectoryCopyOptions::resume_journal_path
+    #[error(
+        "resume journal belongs to a copy with a different source or destination: {}",
+        .journal_path.display()
+    )]
+    ResumeJournalMismatch {
+        /// Path of the resume journal.
+        journal_path: PathBuf,
+    },
+
+    /// A resume journal was requested when planning a copy, but planned copies can not be resumed.
+    ///
+    /// See [`DirectoryCopyOptions::resume_journal_path`].
+    ///
+    ///
+    /// [`DirectoryCopyOptions::resume_journal_path`]: crate::directory::DirectoryCopyOptions::resume_journal_path
+    #[error(
+        "planned directory copies can not be resumed, but a resume journal was provided: {}",
+        .journal_path.display()
+    )]
+    ResumeJournalNotSupportedForPlans {
+        /// Path of the resume journal.
+        journal_path: PathBuf,
+    },
 }
 
 
@@ -301,6 +364,90 @@ pub enum CopyDirectoryExecutionError {
         error: std::io::Error,
     },
 
+    /// An error occurred while trying to create a hard link at the destination.
+    ///
+    /// This can only happen when hard links are preserved
+    /// (see [`HardLinkBehaviour::Preserve`]).
+    ///
+    ///
+    /// [`HardLinkBehaviour::Preserve`]: crate::directory::HardLinkBehaviour::Preserve
+    #[error(
+        "failed while creating a hard link at {} to {}",
+        .hard_link_path.display(),
+        .target_path.display()
+    )]
+    HardLinkCreationError {
+        /// The path to the hard link that could not be created.
+        hard_link_path: PathBuf,
+
+        /// The (already copied) destination file the hard link should have pointed to.
+        target_path: PathBuf,
+
+        /// The underlying hard link creation error.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// Everything has been copied, but the metadata requested by
+    /// [`MetadataPreservation`] could not be applied to one of the created directories.
+    ///
+    /// Errors applying metadata to files are reported as [`Self::FileCopyError`] instead.
+    ///
+    ///
+    /// [`MetadataPreservation`]: crate::file::MetadataPreservation
+    #[error("unable to preserve metadata on directory: {}", .directory_path.display())]
+    UnableToPreserveMetadata {
+        /// Destination directory whose metadata could not be set.
+        directory_path: PathBuf,
+
+        /// IO error describing why the metadata could not be set.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// Everything has been copied, but one of the extended attributes requested by
+    /// [`ExtendedAttributeFilter`] could not be copied to one of the created
+    /// directories or symbolic links.
+    ///
+    /// Errors copying extended attributes of files are reported as [`Self::FileCopyError`] instead.
+    ///
+    ///
+    /// [`ExtendedAttributeFilter`]: crate::file::ExtendedAttributeFilter
+    #[error(
+        "unable to copy extended attribute {:?} to {}",
+        .attribute_name,
+        .path.display()
+    )]
+    UnableToCopyExtendedAttribute {
+        /// Destination directory or symbolic link the extended attribute could not be copied to.
+        path: PathBuf,
+
+        /// Name of the extended attribute that could not be copied,
+        /// or `None` if the extended attributes of the source could not be listed.
+        attribute_name: Option<OsString>,
+
+        /// IO error describing why the extended attribute could not be copied.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// Everything has been copied, but one of the destination directories
+    /// could not be synced to disk, as requested by [`DurabilityLevel::FilesAndDirectories`].
+    ///
+    /// Errors syncing files are reported as [`Self::FileCopyError`] instead.
+    ///
+    ///
+    /// [`DurabilityLevel::FilesAndDirectories`]: crate::file::DurabilityLevel::FilesAndDirectories
+    #[error("unable to sync directory to disk: {}", .directory_path.display())]
+    UnableToSyncDirectory {
+        /// Destination directory that could not be synced.
+        directory_path: PathBuf,
+
+        /// IO error describing why the directory could not be synced.
+        #[source]
+        error: std::io::Error,
+    },
+
     /// A destination directory, a file, or a sub-directory inside it
     /// has changed since the preparation phase of the directory copy.
     ///
@@ -318,6 +465,77 @@ pub enum CopyDirectoryExecutionError {
         /// The path of the target directory or file that already exists.
         path: PathBuf,
     },
+
+    /// The copy was cancelled by the progress handler returning [`ProgressAction::Abort`].
+    ///
+    /// Entries that had been fully copied before the copy was cancelled are left
+    /// in the destination directory, but a partially copied file is removed
+    /// (unless the copy is resumable, see [`DirectoryCopyWithProgressOptions::resume_journal_path`]).
+    ///
+    ///
+    /// [`ProgressAction::Abort`]: crate::ProgressAction::Abort
+    /// [`DirectoryCopyWithProgressOptions::resume_journal_path`]: crate::directory::DirectoryCopyWithProgressOptions::resume_journal_path
+    #[error(
+        "directory copy was cancelled after {} out of {} bytes",
+        .bytes_finished,
+        .bytes_total
+    )]
+    Cancelled {
+        /// Number of bytes that had been copied before the copy was cancelled.
+        bytes_finished: u64,
+
+        /// Total number of bytes that would have needed to be copied
+        /// to complete the copy.
+        bytes_total: u64,
+
+        /// Number of files that had been fully copied before the copy was cancelled.
+        files_copied: usize,
+
+        /// Number of symlinks that had been created before the copy was cancelled.
+        symlinks_created: usize,
+
+        /// Number of directories that had been created before the copy was cancelled.
+        directories_created: usize,
+    },
+
+    /// An executed operation could not be recorded in the resume journal.
+    ///
+    /// See [`DirectoryCopyOptions::resume_journal_path`].
+    ///
+    ///
+    /// [`DirectoryCopyOptions::resume_journal_path`]: crate::directory::DirectoryCopyOptions::resume_journal_path
+    #[error("unable to write to resume journal: {}", .journal_path.display())]
+    UnableToWriteResumeJournal {
+        /// Path of the resume journal.
+        journal_path: PathBuf,
+
+        /// IO error describing why the resume journal could not be written to.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// The copy failed, and the changes it had made to the destination were rolled back.
+    ///
+    /// This can only be returned when rollback is enabled (see [`RollbackBehaviour`]).
+    ///
+    /// The rollback itself may have been incomplete; check
+    /// [`DirectoryRollbackReport::is_complete`] and its `failures` field.
+    ///
+    ///
+    /// [`RollbackBehaviour`]: crate::directory::RollbackBehaviour
+    /// [`DirectoryRollbackReport::is_complete`]: crate::directory::DirectoryRollbackReport::is_complete
+    #[error(
+        "directory copy failed and was rolled back ({} rollback step(s) failed)",
+        .rollback_report.failures.len()
+    )]
+    RolledBack {
+        /// The error that caused the copy to fail.
+        #[source]
+        error: Box<CopyDirectoryExecutionError>,
+
+        /// Describes what was undone during the rollback.
+        rollback_report: DirectoryRollbackReport,
+    },
 }
 
 
@@ -434,6 +652,61 @@ pub enum MoveDirectoryExecutionError {
     )]
     RenameFailedAndNoFallbackStrategy,
 
+    /// Source directory removal error.
+    ///
+    /// These errors can happen after a copy-and-delete has successfully copied
+    /// the source directory to the destination, but the source directory
+    /// (or some of its contents) could not be removed afterwards.
+    #[error(transparent)]
+    SourceRemovalError(#[from] RemoveDirectoryError),
+
+    /// The directory has been moved, but a directory containing the source or destination
+    /// directory could not be synced to disk, as requested by [`DurabilityLevel::FilesAndDirectories`].
+    ///
+    ///
+    /// [`DurabilityLevel::FilesAndDirectories`]: crate::file::DurabilityLevel::FilesAndDirectories
+    #[error("unable to sync directory to disk: {}", .directory_path.display())]
+    UnableToSyncDirectory {
+        /// Directory that could not be synced.
+        directory_path: PathBuf,
+
+        /// IO error describing why the directory could not be synced.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// The move was cancelled by the progress handler returning [`ProgressAction::Abort`].
+    ///
+    /// This can only happen when the directory is being moved by copy-and-delete.
+    /// The source directory is left untouched. Entries that had been fully copied
+    /// before the move was cancelled are left in the destination directory,
+    /// but a partially copied file is removed.
+    ///
+    ///
+    /// [`ProgressAction::Abort`]: crate::ProgressAction::Abort
+    #[error(
+        "directory move was cancelled after {} out of {} bytes",
+        .bytes_finished,
+        .bytes_total
+    )]
+    Cancelled {
+        /// Number of bytes that had been copied to the destination
+        /// before the move was cancelled.
+        bytes_finished: u64,
+
+        /// Total number of bytes that would have needed to be moved
+        /// to complete the move.
+        bytes_total: u64,
+
+        /// Number of files that had been fully copied to the destination
+        /// before the move was cancelled.
+        files_moved: usize,
+
+        /// Number of directories that had been created in the destination
+        /// before the move was cancelled.
+        directories_created: usize,
+    },
+
     /// An uncategorized unrecoverable IO error.
     /// See `error` field for more information.
     #[error("uncategorized std::io::Error")]
@@ -464,6 +737,235 @@ pub enum MoveDirectoryError {
 
 
 
+/// Directory syncing error (see [`sync_directory`]).
+///
+///
+/// [`sync_directory`]: crate::directory::sync_directory
+#[derive(Error, Debug)]
+pub enum SyncDirectoryError {
+    /// Directory sync preparation error, i.e. an error that occurred while validating
+    /// the source and destination directory paths or while scanning the source directory.
+    ///
+    /// If an error from this group is returned, the destination directory hasn't been changed yet.
+    #[error(transparent)]
+    PreparationError(#[from] CopyDirectoryPreparationError),
+
+    /// A file inside the source directory could not be read
+    /// while comparing it with its destination counterpart.
+    #[error("unable to access source path: {}", .path.display())]
+    UnableToAccessSource {
+        /// The path we were unable to access.
+        path: PathBuf,
+
+        /// IO error describing why the path could not be accessed.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// An entry inside the destination directory could not be read
+    /// while comparing it with its source counterpart.
+    #[error("unable to access destination path: {}", .path.display())]
+    UnableToAccessDestination {
+        /// The path we were unable to access.
+        path: PathBuf,
+
+        /// IO error describing why the path could not be accessed.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A destination entry is of a different type than its source counterpart
+    /// (e.g. the source entry is a file, but the destination entry is a directory).
+    ///
+    /// Such entries are only replaced if deleting destination entries is allowed
+    /// (see [`DirectorySyncOptions::delete_extraneous_entries`]).
+    ///
+    ///
+    /// [`DirectorySyncOptions::delete_extraneous_entries`]: crate::directory::DirectorySyncOptions::delete_extraneous_entries
+    #[error(
+        "destination entry is of a different type than its source counterpart: {}",
+        .path.display()
+    )]
+    DestinationEntryTypeMismatch {
+        /// Path of the destination entry.
+        path: PathBuf,
+    },
+
+    /// A destination entry that is absent from the source directory (or that had to be replaced)
+    /// could not be removed.
+    #[error("unable to remove destination entry: {}", .path.display())]
+    UnableToRemoveDestinationEntry {
+        /// Path of the destination entry we were unable to remove.
+        path: PathBuf,
+
+        /// IO error describing why the entry could not be removed.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// An error occurred while copying new or changed entries to the destination.
+    #[error(transparent)]
+    ExecutionError(#[from] CopyDirectoryExecutionError),
+}
+
+
+
+/// Directory removal preparation error.
+#[derive(Error, Debug)]
+pub enum RemoveDirectoryPreparationError {
+    /// The provided directory path does not exist.
+    #[error("directory path does not exist: {}", .directory_path.display())]
+    NotFound {
+        /// The directory path that does not exist.
+        directory_path: PathBuf,
+    },
+
+    /// The provided path exists, but is neither a directory nor a symlink to one.
+    #[error(
+        "path exists, but is not a directory nor a symlink to one: {}",
+        .path.display()
+    )]
+    NotADirectory {
+        /// The path that was supposed to be a directory.
+        path: PathBuf,
+    },
+
+    /// The provided directory path (or its metadata) could not be read.
+    ///
+    /// Among other things, this can happen due to missing read permissions.
+    ///
+    /// The inner [`std::io::Error`] will likely describe a more precise cause of this error.
+    #[error("unable to access directory: {}", .directory_path.display())]
+    UnableToAccess {
+        /// The exact path we are unable to access.
+        directory_path: PathBuf,
+
+        /// IO error describing why the directory could not be accessed.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A subdirectory at the maximum configured depth is not empty.
+    ///
+    /// Removing it would mean removing entries deeper than the configured
+    /// [`remove_depth_limit`] allows, which is why the removal was aborted
+    /// before anything was removed.
+    ///
+    ///
+    /// [`remove_depth_limit`]: crate::directory::DirectoryRemoveOptions::remove_depth_limit
+    #[error(
+        "directory at the maximum removal depth is not empty: {}",
+        .directory_path.display()
+    )]
+    DepthLimitExceeded {
+        /// Path of the non-empty directory at the maximum removal depth.
+        directory_path: PathBuf,
+    },
+
+    /// An error occurred while scanning the directory tree.
+    #[error(transparent)]
+    DirectoryScanError(#[from] DirectoryScanError),
+}
+
+
+
+/// Directory removal execution error.
+#[derive(Error, Debug)]
+pub enum RemoveDirectoryExecutionError {
+    /// A file inside the directory tree could not be removed.
+    ///
+    /// The inner [`std::io::Error`] will likely describe a more precise cause of this error.
+    #[error("unable to remove file: {}", .file_path.display())]
+    UnableToRemoveFile {
+        /// Path of the file that could not be removed.
+        file_path: PathBuf,
+
+        /// IO error describing why the file could not be removed.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A symbolic link inside the directory tree (or the base directory path itself,
+    /// if it is a symlink) could not be removed.
+    ///
+    /// The inner [`std::io::Error`] will likely describe a more precise cause of this error.
+    #[error("unable to remove symbolic link: {}", .symlink_path.display())]
+    UnableToRemoveSymbolicLink {
+        /// Path of the symbolic link that could not be removed.
+        symlink_path: PathBuf,
+
+        /// IO error describing why the symbolic link could not be removed.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A directory inside the directory tree (or the base directory itself)
+    /// could not be removed.
+    ///
+    /// This can also happen if new entries appeared in the directory
+    /// after the removal was prepared.
+    ///
+    /// The inner [`std::io::Error`] will likely describe a more precise cause of this error.
+    #[error("unable to remove directory: {}", .directory_path.display())]
+    UnableToRemoveDirectory {
+        /// Path of the directory that could not be removed.
+        directory_path: PathBuf,
+
+        /// IO error describing why the directory could not be removed.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// The removal was cancelled by the progress handler returning [`ProgressAction::Abort`].
+    ///
+    /// Entries that had been removed before the removal was cancelled stay removed.
+    ///
+    ///
+    /// [`ProgressAction::Abort`]: crate::ProgressAction::Abort
+    #[error(
+        "directory removal was cancelled after {} out of {} bytes",
+        .bytes_finished,
+        .bytes_total
+    )]
+    Cancelled {
+        /// Number of bytes that had been removed before the removal was cancelled.
+        bytes_finished: u64,
+
+        /// Total number of bytes that would have needed to be removed
+        /// to complete the removal.
+        bytes_total: u64,
+
+        /// Number of files that had been removed before the removal was cancelled.
+        files_removed: usize,
+
+        /// Number of symbolic links that had been removed before the removal was cancelled.
+        symlinks_removed: usize,
+
+        /// Number of directories that had been removed before the removal was cancelled.
+        directories_removed: usize,
+    },
+}
+
+
+
+/// Directory removal error (see [`remove_directory`] / [`remove_directory_with_progress`]).
+///
+///
+/// [`remove_directory`]: crate::directory::remove_directory
+/// [`remove_directory_with_progress`]: crate::directory::remove_directory_with_progress
+#[derive(Error, Debug)]
+pub enum RemoveDirectoryError {
+    /// Directory removal preparation error.
+    #[error(transparent)]
+    PreparationError(#[from] RemoveDirectoryPreparationError),
+
+    /// Directory removal execution error.
+    #[error(transparent)]
+    ExecutionError(#[from] RemoveDirectoryExecutionError),
+}
+
+
+
 /// An error that can occur when querying the size of a directory
 /// (see [`directory_size_in_bytes`]).
 ///
@@ -595,4 +1097,70 @@ pub enum DirectoryScanError {
         /// The directory path at which the cycle loops around (i.e. where the cycle was detected).
         directory_path: PathBuf,
     },
+
+    /// An ignore file (e.g. `.gitignore`) exists, but could not be read.
+    ///
+    /// This can only happen when the entry filter respects ignore files (see
+    /// [`DirectoryEntryFilter::respect_ignore_files`]).
+    ///
+    /// The inner [`std::io::Error`] will likely describe a more precise cause of this error.
+    ///
+    ///
+    /// [`DirectoryEntryFilter::respect_ignore_files`]: crate::directory::DirectoryEntryFilter::respect_ignore_files
+    #[error("unable to read ignore file: {}", .file_path.display())]
+    UnableToReadIgnoreFile {
+        /// Path of the ignore file that could not be read.
+        file_path: PathBuf,
+
+        /// IO error describing why the ignore file could not be read.
+        #[source]
+        error: std::io::Error,
+    },
+}
+
+
+
+
+/// An error that can occur when compiling glob patterns into a [`PathMatcher`].
+///
+///
+/// [`PathMatcher`]: crate::directory::PathMatcher
+#[derive(Error, Debug)]
+pub enum GlobPatternError {
+    /// The pattern does not contain anything to match
+    /// (e.g. it is empty, or consists only of `!` or `/`).
+    #[error("glob pattern is empty: \"{pattern}\"")]
+    EmptyPattern {
+        /// The invalid pattern.
+        pattern: String,
+    },
+
+    /// The pattern contains a `[` that is not closed by a matching `]`.
+    #[error("glob pattern contains an unclosed character class: \"{pattern}\"")]
+    UnclosedCharacterClass {
+        /// The invalid pattern.
+        pattern: String,
+    },
+
+    /// The pattern contains a character range whose start is after its end (e.g. `[z-a]`).
+    #[error(
+        "glob pattern contains an invalid character range {range_start}-{range_end}: \"{pattern}\""
+    )]
+    InvalidCharacterRange {
+        /// The invalid pattern.
+        pattern: String,
+
+        /// Start of the invalid range.
+        range_start: char,
+
+        /// End of the invalid range.
+        range_end: char,
+    },
+
+    /// The pattern ends with an escape character (`\`) that has nothing to escape.
+    #[error("glob pattern ends with an unfinished escape sequence: \"{pattern}\"")]
+    TrailingEscape {
+        /// The invalid pattern.
+        pattern: String,
+    },
 }
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{ffi::OsString, path::PathBuf};
 
 use thiserror::Error;
 
@@ -73,6 +73,103 @@ pub enum FileError {
         path: PathBuf,
     },
 
+    /// The file could not be cloned (i.e. a copy-on-write reflink could not be created),
+    /// even though [`ReflinkBehaviour::Always`] was requested.
+    ///
+    /// This happens when the platform or filesystem does not support cloning files,
+    /// or when the source and destination are on different filesystems.
+    /// A destination file that did not exist before the operation has been removed,
+    /// and an existing one has been left unchanged.
+    ///
+    ///
+    /// [`ReflinkBehaviour::Always`]: crate::file::ReflinkBehaviour::Always
+    #[error("unable to clone file to destination: {}", .path.display())]
+    UnableToReflink {
+        /// Destination file path the file could not be cloned to.
+        path: PathBuf,
+
+        /// Underlying IO error describing why the file could not be cloned.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// The file has been copied, but the metadata requested by [`MetadataPreservation`]
+    /// (permissions, timestamps or ownership) could not be applied to the destination file.
+    ///
+    /// The copied file is left in place.
+    ///
+    ///
+    /// [`MetadataPreservation`]: crate::file::MetadataPreservation
+    #[error("unable to preserve metadata on destination file: {}", .path.display())]
+    UnableToPreserveMetadata {
+        /// Destination file path whose metadata could not be set.
+        path: PathBuf,
+
+        /// Underlying IO error describing why the metadata could not be set.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// The file has been copied, but one of the extended attributes requested by
+    /// [`ExtendedAttributeFilter`] could not be copied to the destination file.
+    ///
+    /// The copied file is left in place.
+    ///
+    ///
+    /// [`ExtendedAttributeFilter`]: crate::file::ExtendedAttributeFilter
+    #[error(
+        "unable to copy extended attribute {:?} to destination file: {}",
+        .attribute_name,
+        .path.display()
+    )]
+    UnableToCopyExtendedAttribute {
+        /// Destination file path the extended attribute could not be copied to.
+        path: PathBuf,
+
+        /// Name of the extended attribute that could not be copied,
+        /// or `None` if the extended attributes of the source file could not be listed.
+        attribute_name: Option<OsString>,
+
+        /// Underlying IO error describing why the extended attribute could not be copied.
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// The operation was cancelled by the progress handler returning [`ProgressAction::Abort`].
+    ///
+    /// The partially written destination file has been removed.
+    /// If the operation was a move, the source file has been left untouched.
+    ///
+    ///
+    /// [`ProgressAction::Abort`]: crate::ProgressAction::Abort
+    #[error(
+        "operation was cancelled after {} out of {} bytes",
+        .bytes_finished,
+        .bytes_total
+    )]
+    Cancelled {
+        /// Number of bytes that had been written to the destination
+        /// before the operation was cancelled.
+        bytes_finished: u64,
+
+        /// Total number of bytes that would have needed to be written
+        /// to complete the operation.
+        bytes_total: u64,
+    },
+
+    /// The file has been copied, but the destination file did not match the source file
+    /// when it was read back to verify it (see [`CopyVerification`]).
+    ///
+    /// The destination file is removed. When moving a file, the source file is left untouched.
+    ///
+    ///
+    /// [`CopyVerification`]: crate::file::CopyVerification
+    #[error("destination file does not match the source file after copying: {}", .path.display())]
+    VerificationFailed {
+        /// Destination file path that failed verification.
+        path: PathBuf,
+    },
+
     /// Some other [`std::io::Error`] was encountered.
     #[error("uncategorized std::io::Error")]
     OtherIoError {
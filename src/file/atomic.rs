@@ -0,0 +1,130 @@
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+};
+
+use_enabled_fs_module!();
+
+use super::DurabilityLevel;
+use crate::error::FileError;
+
+
+/// Suffix of the hidden temporary files that atomic copies are written into.
+const TEMPORARY_FILE_SUFFIX: &str = ".fs-more-tmp";
+
+
+/// Calls `write` with the path that the destination file should be written to,
+/// then syncs the directory containing it to disk as far as `durability` requires.
+///
+/// If `atomic` is `false`, that path is simply `destination_file_path`. Otherwise, `write`
+/// receives the path of a new hidden temporary file in the destination directory, which is
+/// renamed over `destination_file_path` once `write` succeeds.
+/// If anything fails, the temporary file is removed and the destination file is left untouched.
+///
+/// `write` itself must sync the written file to disk if `atomic` is `true` or `durability`
+/// requires it, and do so *before* applying any preserved metadata: the file may be read-only
+/// afterwards, and Windows requires write access to sync a file.
+pub(super) fn write_destination_file<T, F>(
+    destination_file_path: &Path,
+    atomic: bool,
+    durability: DurabilityLevel,
+    write: F,
+) -> Result<T, FileError>
+where
+    F: FnOnce(&Path) -> Result<T, FileError>,
+{
+    if !atomic {
+        let value = write(destination_file_path)?;
+
+        durability
+            .sync_parent_directory(destination_file_path)
+            .map_err(|error| FileError::OtherIoError { error })?;
+
+        return Ok(value);
+    }
+
+
+    let temporary_file_path =
+        create_temporary_file_next_to(destination_file_path).map_err(|error| {
+            FileError::UnableToAccessDestinationFile {
+                path: destination_file_path.to_path_buf(),
+                error,
+            }
+        })?;
+
+    let result = write(&temporary_file_path)
+        .map_err(|error| refer_to_destination_file(error, destination_file_path))
+        .and_then(|value| {
+            fs::rename(&temporary_file_path, destination_file_path)
+                .map_err(|error| FileError::OtherIoError { error })?;
+
+            Ok(value)
+        });
+
+    if result.is_err() {
+        // Removing the temporary file is a best-effort cleanup; the error is what we must report.
+        let _ = fs::remove_file(&temporary_file_path);
+    }
+
+    // The renamed file is only guaranteed to be found after a power loss
+    // once the directory containing it has been synced.
+    let value = result?;
+
+    durability
+        .sync_parent_directory(destination_file_path)
+        .map_err(|error| FileError::OtherIoError { error })?;
+
+    Ok(value)
+}
+
+
+/// Replaces the path of the temporary file in errors about the destination file
+/// with `destination_file_path`, which is the path the caller actually knows about.
+fn refer_to_destination_file(mut error: FileError, destination_file_path: &Path) -> FileError {
+    match &mut error {
+        FileError::UnableToReflink { path, .. }
+        | FileError::UnableToPreserveMetadata { path, .. }
+        | FileError::UnableToCopyExtendedAttribute { path, .. }
+        | FileError::VerificationFailed { path, .. } => {
+            *path = destination_file_path.to_path_buf();
+        }
+        _ => {}
+    }
+
+    error
+}
+
+
+/// Creates a new, empty, hidden file next to `file_path` (e.g. `.file.txt.fs-more-tmp`)
+/// and returns its path.
+///
+/// The file is created exclusively, so concurrent copies never end up sharing a temporary file.
+fn create_temporary_file_next_to(file_path: &Path) -> io::Result<PathBuf> {
+    let file_name = file_path.file_name().unwrap_or_default();
+
+    let mut temporary_file_name = OsString::from(".");
+    temporary_file_name.push(file_name);
+    temporary_file_name.push(TEMPORARY_FILE_SUFFIX);
+
+    let mut temporary_file_path = file_path.with_file_name(&temporary_file_name);
+    let mut attempt = 1;
+
+    loop {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temporary_file_path)
+        {
+            Ok(_) => return Ok(temporary_file_path),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(error) => return Err(error),
+        }
+
+        let mut numbered_temporary_file_name = temporary_file_name.clone();
+        numbered_temporary_file_name.push(format!("-{}", attempt));
+
+        temporary_file_path = file_path.with_file_name(numbered_temporary_file_name);
+        attempt += 1;
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+};
+
+use_enabled_fs_module!();
+
+use super::{have_same_contents, CollidingFileBehaviour};
+
+
+/// How to name the alternative path a file is written to
+/// when using [`CollidingFileBehaviour::KeepBoth`].
+///
+/// The lowest number whose path isn't taken yet is used, starting at `1`.
+/// The number is inserted before the file extension, if there is one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlternativeFileNaming {
+    /// Appends the number in parentheses, e.g. `report.pdf` becomes `report (1).pdf`.
+    ///
+    /// This is the default.
+    NumberInParentheses,
+
+    /// Appends the number after a hyphen, e.g. `report.pdf` becomes `report-1.pdf`.
+    NumberAfterHyphen,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for AlternativeFileNaming {
+    fn default() -> Self {
+        Self::NumberInParentheses
+    }
+}
+
+impl AlternativeFileNaming {
+    /// Returns the `number`-th alternative path for `file_path`.
+    ///
+    /// The alternative path is always located in the same directory as `file_path`.
+    pub(crate) fn alternative_path(self, file_path: &Path, number: u64) -> PathBuf {
+        let file_stem = file_path.file_stem().unwrap_or_default();
+
+        let mut alternative_file_name = OsString::from(file_stem);
+        match self {
+            AlternativeFileNaming::NumberInParentheses => {
+                alternative_file_name.push(format!(" ({})", number));
+            }
+            AlternativeFileNaming::NumberAfterHyphen => {
+                alternative_file_name.push(format!("-{}", number));
+            }
+        }
+
+        if let Some(extension) = file_path.extension() {
+            alternative_file_name.push(".");
+            alternative_file_name.push(extension);
+        }
+
+        file_path.with_file_name(alternative_file_name)
+    }
+
+    /// Returns the first alternative path for `file_path` for which `is_path_taken` returns `false`.
+    pub(crate) fn first_free_alternative_path<F>(
+        self,
+        file_path: &Path,
+        mut is_path_taken: F,
+    ) -> io::Result<PathBuf>
+    where
+        F: FnMut(&Path) -> io::Result<bool>,
+    {
+        let mut number = 1;
+
+        loop {
+            let alternative_path = self.alternative_path(file_path, number);
+
+            if !is_path_taken(&alternative_path)? {
+                return Ok(alternative_path);
+            }
+
+            number += 1;
+        }
+    }
+}
+
+
+
+/// Describes what to do with a source file whose destination path already exists.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum FileCollisionResolution {
+    /// The existing destination file should be overwritten.
+    Overwrite,
+
+    /// The source file should not be copied or moved.
+    Skip,
+
+    /// The source file should be copied or moved to the contained (free) alternative path.
+    WriteToAlternativePath(PathBuf),
+}
+
+impl CollidingFileBehaviour {
+    /// Decides what to do with the source file at `source_file_path`,
+    /// given that `destination_file_path` already exists.
+    ///
+    /// `is_path_taken` is used to find a free alternative path for [`Self::KeepBoth`].
+    ///
+    /// Returns `None` if the behaviour is [`Self::Abort`], i.e. if the collision
+    /// should be reported as an error.
+    pub(crate) fn resolve_collision<F>(
+        self,
+        source_file_path: &Path,
+        destination_file_path: &Path,
+        is_path_taken: F,
+    ) -> io::Result<Option<FileCollisionResolution>>
+    where
+        F: FnMut(&Path) -> io::Result<bool>,
+    {
+        let resolution = match self {
+            CollidingFileBehaviour::Abort => return Ok(None),
+            CollidingFileBehaviour::Skip => FileCollisionResolution::Skip,
+            CollidingFileBehaviour::Overwrite => FileCollisionResolution::Overwrite,
+            CollidingFileBehaviour::OverwriteIfNewer => {
+                let source_modified_at = fs::metadata(source_file_path)?.modified()?;
+                let destination_modified_at = fs::metadata(destination_file_path)?.modified()?;
+
+                if source_modified_at > destination_modified_at {
+                    FileCollisionResolution::Overwrite
+                } else {
+                    FileCollisionResolution::Skip
+                }
+            }
+            CollidingFileBehaviour::OverwriteIfDifferent => {
+                if files_differ(source_file_path, destination_file_path)? {
+                    FileCollisionResolution::Overwrite
+                } else {
+                    FileCollisionResolution::Skip
+                }
+            }
+            CollidingFileBehaviour::KeepBoth { naming } => {
+                FileCollisionResolution::WriteToAlternativePath(
+                    naming.first_free_alternative_path(destination_file_path, is_path_taken)?,
+                )
+            }
+        };
+
+        Ok(Some(resolution))
+    }
+}
+
+
+/// Returns `true` if the two files differ in size or contents.
+fn files_differ(first_file_path: &Path, second_file_path: &Path) -> io::Result<bool> {
+    let first_file_size = fs::metadata(first_file_path)?.len();
+    let second_file_size = fs::metadata(second_file_path)?.len();
+
+    if first_file_size != second_file_size {
+        return Ok(true);
+    }
+
+    Ok(!have_same_contents(first_file_path, second_file_path)?)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternative_path_is_numbered_before_the_extension() {
+        let file_path = Path::new("backups/report.tar.gz");
+
+        assert_eq!(
+            AlternativeFileNaming::NumberInParentheses.alternative_path(file_path, 1),
+            Path::new("backups/report.tar (1).gz")
+        );
+        assert_eq!(
+            AlternativeFileNaming::NumberAfterHyphen.alternative_path(file_path, 12),
+            Path::new("backups/report.tar-12.gz")
+        );
+    }
+
+    #[test]
+    fn alternative_path_handles_files_without_extensions() {
+        assert_eq!(
+            AlternativeFileNaming::NumberInParentheses.alternative_path(Path::new("README"), 2),
+            Path::new("README (2)")
+        );
+        assert_eq!(
+            AlternativeFileNaming::NumberAfterHyphen
+                .alternative_path(Path::new("/home/.bashrc"), 1),
+            Path::new("/home/.bashrc-1")
+        );
+    }
+
+    #[test]
+    fn first_free_alternative_path_skips_taken_paths() {
+        let taken_paths = [
+            PathBuf::from("notes (1).txt"),
+            PathBuf::from("notes (2).txt"),
+        ];
+
+        let free_path = AlternativeFileNaming::NumberInParentheses
+            .first_free_alternative_path(Path::new("notes.txt"), |path| {
+                Ok(taken_paths.iter().any(|taken_path| taken_path == path))
+            })
+            .unwrap();
+
+        assert_eq!(free_path, Path::new("notes (3).txt"));
+    }
+}
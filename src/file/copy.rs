@@ -1,21 +1,37 @@
 use std::{
-    io::{BufReader, BufWriter, Write},
-    path::Path,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
 };
 
 use_enabled_fs_module!();
 
+#[cfg(target_os = "linux")]
+use super::linux::AcceleratedCopyOutcome;
 use super::{
-    progress::{FileProgress, ProgressWriter},
+    atomic::write_destination_file,
+    durability::sync_file_to_disk,
+    metadata::{apply_preserved_metadata, apply_preserved_ownership},
+    progress::{FileProgress, FileProgressPhase, ProgressWriter},
+    resume::resumable_prefix_length,
+    sparse::{copy_file_contents_sparsely_with_progress, SparseCopyOutcome},
     validate_destination_file_path,
     validate_source_file_path,
+    verify::{hash_reader, Hasher, HashingReader},
+    xattr::{copy_extended_attributes, ExtendedAttributeCopyError},
     CollidingFileBehaviour,
+    CopyVerification,
     DestinationValidationAction,
+    DurabilityLevel,
+    ExtendedAttributeFilter,
+    MetadataPreservation,
+    ResumeBehaviour,
+    SparseFileBehaviour,
     ValidatedDestinationFilePath,
     ValidatedSourceFilePath,
 };
 use crate::{
     error::FileError,
+    ProgressAction,
     DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
     DEFAULT_READ_BUFFER_SIZE,
     DEFAULT_WRITE_BUFFER_SIZE,
@@ -28,6 +44,53 @@ use crate::{
 pub struct FileCopyOptions {
     /// How to behave when the destination file already exists.
     pub colliding_file_behaviour: CollidingFileBehaviour,
+
+    /// Whether to clone the file (i.e. create a copy-on-write reflink) instead of copying its contents.
+    ///
+    /// See [`ReflinkBehaviour`] for more details.
+    ///
+    /// Defaults to [`ReflinkBehaviour::Never`].
+    pub reflink_behaviour: ReflinkBehaviour,
+
+    /// Which metadata of the source file to apply to the destination file once it has been copied.
+    ///
+    /// See [`MetadataPreservation`] for more details.
+    ///
+    /// Defaults to [`MetadataPreservation::NONE`].
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Which extended attributes of the source file to copy to the destination file.
+    ///
+    /// See [`ExtendedAttributeFilter`] for more details.
+    ///
+    /// Defaults to [`ExtendedAttributeFilter::NONE`].
+    pub extended_attributes: ExtendedAttributeFilter,
+
+    /// Whether to read the destination file back once it has been copied,
+    /// and compare its hash to the hash of the source file.
+    ///
+    /// See [`CopyVerification`] for more details.
+    ///
+    /// Defaults to [`CopyVerification::DISABLED`].
+    pub verify: CopyVerification,
+
+    /// Whether to copy the file into a hidden temporary file in the destination directory,
+    /// which is synced to disk and then renamed over the destination file.
+    ///
+    /// This way, the destination path never contains a partially written file:
+    /// it contains either the previous destination file (if any), or the complete copy.
+    /// If the copy fails or is interrupted, the destination file is left untouched,
+    /// and the temporary file is removed (unless the process itself is interrupted).
+    ///
+    /// Defaults to `false`.
+    pub atomic: bool,
+
+    /// How much of the copy to sync to disk before returning.
+    ///
+    /// See [`DurabilityLevel`] for more details.
+    ///
+    /// Defaults to [`DurabilityLevel::None`].
+    pub durability: DurabilityLevel,
 }
 
 
@@ -36,33 +99,117 @@ impl Default for FileCopyOptions {
     fn default() -> Self {
         Self {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            reflink_behaviour: ReflinkBehaviour::Never,
+            metadata_preservation: MetadataPreservation::NONE,
+            extended_attributes: ExtendedAttributeFilter::NONE,
+            verify: CopyVerification::DISABLED,
+            atomic: false,
+            durability: DurabilityLevel::None,
         }
     }
 }
 
 
+/// Controls whether files are cloned (i.e. whether copy-on-write reflinks are created)
+/// instead of having their contents copied.
+///
+/// A cloned file shares its data with the source file until either of them is modified,
+/// which makes cloning near instantaneous and means no additional disk space is used.
+/// Cloning is only possible on Linux, on filesystems that support it (e.g. Btrfs or XFS),
+/// and when the source and destination are on the same filesystem.
+///
+/// Whether a file has been cloned is reported as [`FileCopyMethod::Reflink`] in [`FileCopyFinished`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReflinkBehaviour {
+    /// Files are never cloned; their contents are always copied.
+    ///
+    /// This is the default.
+    Never,
+
+    /// Files are cloned if possible; otherwise, their contents are copied as usual.
+    Auto,
+
+    /// Files are always cloned. If a file can not be cloned,
+    /// [`FileError::UnableToReflink`] is returned and the destination is left unchanged.
+    ///
+    /// Use this if you need to be sure that no data has been duplicated.
+    Always,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ReflinkBehaviour {
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+
 /// Results of a successful file copy operation.
 ///
 /// Returned from: [`copy_file`] and [`copy_file_with_progress`].
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum FileCopyFinished {
     /// The destination file did not exist prior to the operation.
     /// The file was freshly created and written to.
     Created {
         /// Number of bytes written to the file.
         bytes_copied: u64,
+
+        /// How the file contents were copied.
+        method: FileCopyMethod,
     },
 
     /// The destination file already existed, and was overwritten by the copy operation.
     Overwritten {
         /// Number of bytes written to the file.
         bytes_copied: u64,
+
+        /// How the file contents were copied.
+        method: FileCopyMethod,
+    },
+
+    /// The destination file already existed and was kept, so the file was freshly
+    /// created and written to at an alternative path next to it instead.
+    ///
+    /// This can only be returned when existing destination file behaviour
+    /// is set to [`CollidingFileBehaviour::KeepBoth`].
+    CreatedAtAlternativePath {
+        /// Path of the file that was created.
+        destination_file_path: PathBuf,
+
+        /// Number of bytes written to the file.
+        bytes_copied: u64,
+
+        /// How the file contents were copied.
+        method: FileCopyMethod,
+    },
+
+    /// The destination file already existed as a partial copy of the source file,
+    /// and the copy operation was resumed from where it left off.
+    ///
+    /// This can only be returned from [`copy_file_with_progress`] when
+    /// [`options.resume_behaviour`] is not [`ResumeBehaviour::Never`].
+    ///
+    ///
+    /// [`options.resume_behaviour`]: FileCopyWithProgressOptions::resume_behaviour
+    Resumed {
+        /// Number of bytes written to the file by this operation,
+        /// i.e. excluding the bytes that had already been copied.
+        bytes_copied: u64,
+
+        /// Number of bytes that had already been copied before the operation was resumed.
+        resumed_from: u64,
+
+        /// How the rest of the file contents were copied.
+        method: FileCopyMethod,
     },
 
     /// The destination file already existed, and the copy operation was skipped.
     ///
     /// This can only be returned when existing destination file behaviour
-    /// is set to [`CollidingFileBehaviour::Skip`].
+    /// is set to [`CollidingFileBehaviour::Skip`], or to [`CollidingFileBehaviour::OverwriteIfNewer`]
+    /// or [`CollidingFileBehaviour::OverwriteIfDifferent`] and the destination file
+    /// was not overwritten.
     ///
     ///
     /// [`options.colliding_file_behaviour`]: FileCopyOptions::colliding_file_behaviour
@@ -70,6 +217,58 @@ pub enum FileCopyFinished {
 }
 
 
+/// A method used for copying the contents of a file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FileCopyMethod {
+    /// The file was cloned (a copy-on-write reflink was created), meaning no data was copied.
+    ///
+    /// Only possible on Linux, and only if cloning was requested
+    /// (see [`ReflinkBehaviour`]).
+    Reflink,
+
+    /// The file was copied by the kernel using `copy_file_range` (Linux only).
+    ///
+    /// Depending on the filesystem, this may perform a server-side or copy-on-write copy.
+    CopyFileRange,
+
+    /// The file was copied by the kernel using `sendfile` (Linux only).
+    SendFile,
+
+    /// The file was copied by reading it into (and writing it from) buffers in user space.
+    Buffered,
+
+    /// The file was copied through buffers in user space, skipping holes,
+    /// so the destination file is sparse.
+    ///
+    /// Only possible if sparse copying was requested (see [`SparseFileBehaviour`]).
+    Sparse,
+
+    /// Copying was delegated to [`std::fs::copy`], which uses the fastest method
+    /// available on the platform.
+    StandardLibrary,
+}
+
+
+/// Results of [`copy_file_with_progress_unchecked`].
+pub(crate) struct CopiedFileContents {
+    /// Number of bytes written to the destination file.
+    ///
+    /// If the copy has been resumed, this excludes the first `resumed_from` bytes.
+    pub(crate) bytes_copied: u64,
+
+    /// Number of bytes that had already been copied before the copy was resumed,
+    /// or zero if the copy started from the beginning.
+    pub(crate) resumed_from: u64,
+
+    /// Number of bytes physically written to the destination file
+    /// (see [`FileProgress::bytes_written`]).
+    pub(crate) bytes_written: u64,
+
+    /// How the file contents were copied.
+    pub(crate) method: FileCopyMethod,
+}
+
+
 
 /// Copies a single file from the source to the destination path.
 ///
@@ -91,6 +290,13 @@ pub enum FileCopyFinished {
 /// See [`FileCopyOptions`] for available file copying options.
 ///
 ///
+/// # Atomic copies
+/// If [`options.atomic`] is enabled, the file is first copied into a hidden temporary file
+/// in the destination directory (e.g. `.file.txt.fs-more-tmp`), which is synced to disk
+/// and then renamed over the destination path. If anything fails, the temporary file is removed
+/// and the destination path is left untouched.
+///
+///
 /// # Return value
 /// If the copy succeeds, the function returns [`FileCopyFinished`],
 /// which contains information about whether the file was created,
@@ -112,6 +318,10 @@ pub enum FileCopyFinished {
 ///   then copying will be aborted with [`SourceAndDestinationAreTheSame`].
 /// - If the destination path has other issues (is a directory, does not have the correct permissions, etc.),
 ///   [`UnableToAccessDestinationFile`] will be returned.
+/// - If [`options.reflink_behaviour`] is set to [`ReflinkBehaviour::Always`]
+///   and the file can not be cloned, [`UnableToReflink`] will be returned.
+/// - If [`options.verify`] is enabled and the destination file does not match
+///   the source file after copying, [`VerificationFailed`] will be returned.
 ///
 /// There do exist other failure points, mostly due to unavoidable
 /// [time-of-check time-of-use](https://en.wikipedia.org/wiki/Time-of-check_to_time-of-use)
@@ -141,16 +351,27 @@ pub enum FileCopyFinished {
 /// or [`fs_err::copy`](https://docs.rs/fs-err/latest/fs_err/fn.copy.html)
 /// if the `fs-err` feature flag is enabled.
 ///
+/// Unless [`options.reflink_behaviour`] is [`ReflinkBehaviour::Never`],
+/// a `FICLONE` reflink is attempted first (on Linux).
+///
+/// If [`options.verify`] is enabled, both the source and the destination file
+/// are read again after copying in order to hash them.
+///
 /// </details>
 ///
 ///
 /// [`options.colliding_file_behaviour`]: FileCopyOptions::colliding_file_behaviour
+/// [`options.reflink_behaviour`]: FileCopyOptions::reflink_behaviour
+/// [`options.verify`]: FileCopyOptions::verify
+/// [`options.atomic`]: FileCopyOptions::atomic
 /// [`SourceFileNotFound`]: FileError::SourceFileNotFound
 /// [`SourcePathNotAFile`]: FileError::SourcePathNotAFile
 /// [`UnableToAccessSourceFile`]: FileError::UnableToAccessSourceFile
 /// [`DestinationPathAlreadyExists`]: FileError::DestinationPathAlreadyExists
 /// [`UnableToAccessDestinationFile`]: FileError::UnableToAccessDestinationFile
 /// [`SourceAndDestinationAreTheSame`]: FileError::SourceAndDestinationAreTheSame
+/// [`UnableToReflink`]: FileError::UnableToReflink
+/// [`VerificationFailed`]: FileError::VerificationFailed
 /// [`OtherIoError`]: FileError::OtherIoError
 /// [^unix-cp]: Source for coreutils' `cp` is available
 ///     [here](https://github.com/coreutils/coreutils/blob/ccf47cad93bc0b85da0401b0a9d4b652e4c930e4/src/cp.c).
@@ -172,6 +393,7 @@ where
     let ValidatedDestinationFilePath {
         destination_file_path,
         exists: destination_file_exists,
+        is_alternative_path,
     } = match validate_destination_file_path(
         &validated_source_file_path,
         destination_file_path,
@@ -188,18 +410,486 @@ where
     } = validated_source_file_path;
 
 
-    // All checks have passed, pass the copying onto Rust's standard library.
-    // Note that a time-of-check time-of-use errors are certainly possible
-    // (hence [`FileError::OtherIoError`], though there may be other reasons for it as well).
+    let (bytes_copied, method) = write_destination_file(
+        &destination_file_path,
+        options.atomic,
+        options.durability,
+        |file_path_to_write| {
+            copy_file_to_path(
+                &source_file_path,
+                file_path_to_write,
+                destination_file_exists,
+                options,
+            )
+        },
+    )?;
 
-    let bytes_copied = fs::copy(source_file_path, destination_file_path)
-        .map_err(|error| FileError::OtherIoError { error })?;
 
+    Ok(finished_copy(
+        destination_file_exists,
+        is_alternative_path.then_some(destination_file_path),
+        bytes_copied,
+        method,
+    ))
+}
 
 
-    match destination_file_exists {
-        true => Ok(FileCopyFinished::Overwritten { bytes_copied }),
-        false => Ok(FileCopyFinished::Created { bytes_copied }),
+/// Copies the (already validated) source file to `destination_file_path`, see [`copy_file`].
+///
+/// When copying atomically, `destination_file_path` is the path of the temporary file.
+fn copy_file_to_path(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    destination_file_existed: bool,
+    options: FileCopyOptions,
+) -> Result<(u64, FileCopyMethod), FileError> {
+    let reflinked_bytes = if options.reflink_behaviour != ReflinkBehaviour::Never {
+        try_reflink_file(source_file_path, destination_file_path, options.reflink_behaviour)
+            .map_err(|error| {
+                remove_destination_file_after_failed_reflink(
+                    &error,
+                    destination_file_path,
+                    destination_file_existed,
+                );
+                error
+            })?
+    } else {
+        None
+    };
+
+
+    let sync_to_disk = options.atomic || options.durability.syncs_files();
+
+    let (bytes_copied, method) = match reflinked_bytes {
+        Some(bytes_copied) => (bytes_copied, FileCopyMethod::Reflink),
+        None => {
+            let bytes_copied = copy_and_verify_file(
+                source_file_path,
+                destination_file_path,
+                options.verify,
+                sync_to_disk,
+            )?;
+
+            (bytes_copied, FileCopyMethod::StandardLibrary)
+        }
+    };
+
+    // A cloned file's contents never pass through user space, so its source
+    // has to be read separately in order to verify it.
+    if method == FileCopyMethod::Reflink {
+        verify_copied_file(source_file_path, destination_file_path, options.verify)?;
+
+        if sync_to_disk {
+            sync_file_to_disk(destination_file_path)
+                .map_err(|error| FileError::OtherIoError { error })?;
+        }
+    }
+
+    finish_copied_file(
+        source_file_path,
+        destination_file_path,
+        options.extended_attributes,
+        options.metadata_preservation,
+    )?;
+
+    Ok((bytes_copied, method))
+}
+
+
+/// Copies the requested extended attributes and applies the requested metadata
+/// of the source file to the (already copied) destination file.
+///
+/// Ownership is applied before extended attributes are copied, as changing it
+/// would remove file capabilities (see [`apply_preserved_ownership`]).
+fn finish_copied_file(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    extended_attributes: ExtendedAttributeFilter,
+    metadata_preservation: MetadataPreservation,
+) -> Result<(), FileError> {
+    let map_metadata_error = |error| FileError::UnableToPreserveMetadata {
+        path: destination_file_path.to_path_buf(),
+        error,
+    };
+
+    apply_preserved_ownership(source_file_path, destination_file_path, metadata_preservation)
+        .map_err(map_metadata_error)?;
+
+    copy_extended_attributes(source_file_path, destination_file_path, extended_attributes)
+        .map_err(
+            |ExtendedAttributeCopyError {
+                 attribute_name,
+                 error,
+             }| FileError::UnableToCopyExtendedAttribute {
+                path: destination_file_path.to_path_buf(),
+                attribute_name,
+                error,
+            },
+        )?;
+
+    apply_preserved_metadata(source_file_path, destination_file_path, metadata_preservation)
+        .map_err(map_metadata_error)
+}
+
+
+/// Copies the contents and permissions of the source file to `destination_file_path`
+/// (like [`fs::copy`] does), and verifies the copy if `verification` is enabled.
+///
+/// When verifying, the source file is hashed while it is being copied, so it is only read once;
+/// this means the kernel-accelerated copy [`fs::copy`] would use is not available.
+/// If the hashes differ, the destination file is removed and [`FileError::VerificationFailed`] is returned.
+///
+/// If `sync_to_disk` is `true`, the destination file is synced to disk through the handle it was
+/// written with, as the copied permissions may have made it read-only (and Windows requires
+/// write access to sync a file). [`fs::copy`] is not used in that case either.
+///
+/// Returns the number of bytes copied.
+pub(super) fn copy_and_verify_file(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    verification: CopyVerification,
+    sync_to_disk: bool,
+) -> Result<u64, FileError> {
+    let mut source_hasher = verification.new_hasher();
+
+    if source_hasher.is_none() && !sync_to_disk {
+        // All checks have passed, pass the copying onto Rust's standard library.
+        // Note that a time-of-check time-of-use errors are certainly possible
+        // (hence [`FileError::OtherIoError`], though there may be other reasons for it as well).
+        return fs::copy(source_file_path, destination_file_path)
+            .map_err(|error| FileError::OtherIoError { error });
+    }
+
+    let map_source_error = |error| FileError::UnableToAccessSourceFile {
+        path: source_file_path.to_path_buf(),
+        error,
+    };
+    let map_destination_error = |error| FileError::UnableToAccessDestinationFile {
+        path: destination_file_path.to_path_buf(),
+        error,
+    };
+
+
+    let mut source_file = fs::File::open(source_file_path).map_err(map_source_error)?;
+    let source_permissions = source_file
+        .metadata()
+        .map_err(map_source_error)?
+        .permissions();
+
+    let mut destination_file =
+        fs::File::create(destination_file_path).map_err(map_destination_error)?;
+
+    let mut buffer = vec![0u8; DEFAULT_READ_BUFFER_SIZE];
+    let mut bytes_copied = 0;
+
+    loop {
+        let bytes_read = match source_file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(bytes_read) => bytes_read,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(map_source_error(error)),
+        };
+
+        if let Some(source_hasher) = source_hasher.as_mut() {
+            source_hasher.update(&buffer[..bytes_read]);
+        }
+
+        destination_file
+            .write_all(&buffer[..bytes_read])
+            .map_err(map_destination_error)?;
+
+        bytes_copied += bytes_read as u64;
+    }
+
+    destination_file
+        .set_permissions(source_permissions)
+        .map_err(map_destination_error)?;
+
+    if sync_to_disk {
+        destination_file.sync_all().map_err(map_destination_error)?;
+    }
+
+    drop(destination_file);
+
+
+    let Some(source_hasher) = source_hasher else {
+        return Ok(bytes_copied);
+    };
+
+    let outcome = compare_destination_file_hash(
+        destination_file_path,
+        source_hasher.finish(),
+        verification,
+        DEFAULT_READ_BUFFER_SIZE,
+        u64::MAX,
+        |_| true,
+    )
+    .map_err(map_destination_error)?;
+
+    handle_verification_outcome(outcome, destination_file_path, bytes_copied)?;
+
+    Ok(bytes_copied)
+}
+
+
+/// Reads both files (if `verification` is enabled) and compares their hashes.
+///
+/// This is only used for copies whose contents never passed through user space (i.e. cloned files);
+/// other copies are hashed while copying (see [`copy_and_verify_file`]).
+///
+/// If the hashes differ, the destination file is removed and [`FileError::VerificationFailed`] is returned.
+fn verify_copied_file(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    verification: CopyVerification,
+) -> Result<(), FileError> {
+    let Some(mut source_hasher) = verification.new_hasher() else {
+        return Ok(());
+    };
+
+    let map_source_error = |error| FileError::UnableToAccessSourceFile {
+        path: source_file_path.to_path_buf(),
+        error,
+    };
+
+    let mut source_file = fs::File::open(source_file_path).map_err(map_source_error)?;
+
+    let bytes_total = hash_reader(
+        &mut source_file,
+        source_hasher.as_mut(),
+        DEFAULT_READ_BUFFER_SIZE,
+        u64::MAX,
+        |_| true,
+    )
+    .map_err(map_source_error)?
+    .unwrap_or_default();
+
+    let outcome = compare_destination_file_hash(
+        destination_file_path,
+        source_hasher.finish(),
+        verification,
+        DEFAULT_READ_BUFFER_SIZE,
+        u64::MAX,
+        |_| true,
+    )
+    .map_err(|error| FileError::UnableToAccessDestinationFile {
+        path: destination_file_path.to_path_buf(),
+        error,
+    })?;
+
+    handle_verification_outcome(outcome, destination_file_path, bytes_total)
+}
+
+
+/// Outcome of [`compare_destination_file_hash`].
+enum VerificationOutcome {
+    /// The hashes match.
+    Verified,
+
+    /// The hashes do not match.
+    Mismatched,
+
+    /// The progress handler cancelled the verification.
+    Cancelled,
+}
+
+
+/// Reads and hashes the destination file, then compares its hash to `source_digest`.
+///
+/// `progress_handler` is called with the number of bytes verified so far whenever at least
+/// `progress_update_byte_interval` bytes have been hashed since the last call;
+/// returning `false` cancels the verification.
+fn compare_destination_file_hash<F>(
+    destination_file_path: &Path,
+    source_digest: Vec<u8>,
+    verification: CopyVerification,
+    read_buffer_size: usize,
+    progress_update_byte_interval: u64,
+    progress_handler: F,
+) -> std::io::Result<VerificationOutcome>
+where
+    F: FnMut(u64) -> bool,
+{
+    // PANIC SAFETY: Callers only verify files if verification is enabled.
+    let mut destination_hasher = verification
+        .new_hasher()
+        .expect("verification to be enabled");
+
+    let mut destination_file = fs::File::open(destination_file_path)?;
+
+    let bytes_hashed = hash_reader(
+        &mut destination_file,
+        destination_hasher.as_mut(),
+        read_buffer_size,
+        progress_update_byte_interval,
+        progress_handler,
+    )?;
+
+    if bytes_hashed.is_none() {
+        return Ok(VerificationOutcome::Cancelled);
+    }
+
+    match destination_hasher.finish() == source_digest {
+        true => Ok(VerificationOutcome::Verified),
+        false => Ok(VerificationOutcome::Mismatched),
+    }
+}
+
+
+/// Removes the destination file if verification did not succeed, and returns the matching error.
+fn handle_verification_outcome(
+    outcome: VerificationOutcome,
+    destination_file_path: &Path,
+    bytes_total: u64,
+) -> Result<(), FileError> {
+    let error = match outcome {
+        VerificationOutcome::Verified => return Ok(()),
+        VerificationOutcome::Mismatched => FileError::VerificationFailed {
+            path: destination_file_path.to_path_buf(),
+        },
+        VerificationOutcome::Cancelled => FileError::Cancelled {
+            bytes_finished: bytes_total,
+            bytes_total,
+        },
+    };
+
+    // Removing the unverified file is a best-effort cleanup; the error is what we must report.
+    let _ = fs::remove_file(destination_file_path);
+
+    Err(error)
+}
+
+
+fn finished_copy(
+    destination_file_existed: bool,
+    alternative_destination_file_path: Option<PathBuf>,
+    bytes_copied: u64,
+    method: FileCopyMethod,
+) -> FileCopyFinished {
+    if let Some(destination_file_path) = alternative_destination_file_path {
+        return FileCopyFinished::CreatedAtAlternativePath {
+            destination_file_path,
+            bytes_copied,
+            method,
+        };
+    }
+
+    match destination_file_existed {
+        true => FileCopyFinished::Overwritten {
+            bytes_copied,
+            method,
+        },
+        false => FileCopyFinished::Created {
+            bytes_copied,
+            method,
+        },
+    }
+}
+
+
+/// Attempts to clone the source file to the destination path.
+///
+/// Returns `Ok(Some(bytes_copied))` if the file has been cloned, and `Ok(None)` if it could not be
+/// (but `reflink_behaviour` allows falling back to copying it as usual).
+fn try_reflink_file(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    reflink_behaviour: ReflinkBehaviour,
+) -> Result<Option<u64>, FileError> {
+    let source_file =
+        fs::File::open(source_file_path).map_err(|error| FileError::OtherIoError { error })?;
+
+    let bytes_total = source_file
+        .metadata()
+        .map_err(|error| FileError::OtherIoError { error })?
+        .len();
+
+    let destination_file = open_destination_file_without_truncating(destination_file_path)?;
+
+
+    match reflink_if_requested(
+        &source_file,
+        &destination_file,
+        bytes_total,
+        reflink_behaviour,
+        destination_file_path,
+    )? {
+        true => Ok(Some(bytes_total)),
+        false => Ok(None),
+    }
+}
+
+
+/// Opens (or creates) the destination file for writing.
+///
+/// The file is not truncated yet, so that an existing file is left unchanged if cloning fails.
+fn open_destination_file_without_truncating(
+    destination_file_path: &Path,
+) -> Result<fs::File, FileError> {
+    fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(destination_file_path)
+        .map_err(|error| FileError::OtherIoError { error })
+}
+
+
+/// Clones `source_file` into `destination_file`, unless `reflink_behaviour` is [`ReflinkBehaviour::Never`].
+///
+/// Returns `Ok(true)` if the file has been cloned. If it hasn't been, `Ok(false)` is returned and
+/// the destination file is left unchanged, unless `reflink_behaviour` is [`ReflinkBehaviour::Always`],
+/// in which case [`FileError::UnableToReflink`] is returned instead.
+fn reflink_if_requested(
+    source_file: &fs::File,
+    destination_file: &fs::File,
+    bytes_total: u64,
+    reflink_behaviour: ReflinkBehaviour,
+    destination_file_path: &Path,
+) -> Result<bool, FileError> {
+    if reflink_behaviour == ReflinkBehaviour::Never {
+        return Ok(false);
+    }
+
+
+    // A clone does not shrink the destination file, so a longer existing file must be truncated.
+    #[cfg(target_os = "linux")]
+    let reflink_result = super::linux::clone_file_contents(source_file, destination_file)
+        .and_then(|_| destination_file.set_len(bytes_total));
+
+    #[cfg(not(target_os = "linux"))]
+    let reflink_result = {
+        let _ = (source_file, destination_file, bytes_total);
+
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "cloning files is only supported on Linux",
+        ))
+    };
+
+
+    match reflink_result {
+        Ok(()) => Ok(true),
+        Err(error) if reflink_behaviour == ReflinkBehaviour::Always => {
+            Err(FileError::UnableToReflink {
+                path: destination_file_path.to_path_buf(),
+                error,
+            })
+        }
+        Err(_) => Ok(false),
+    }
+}
+
+
+/// If cloning the file failed, removes the destination file, but only if it was created by us.
+fn remove_destination_file_after_failed_reflink(
+    error: &FileError,
+    destination_file_path: &Path,
+    destination_file_existed: bool,
+) {
+    if matches!(error, FileError::UnableToReflink { .. }) && !destination_file_existed {
+        // This is a best-effort cleanup; the reflink error is what we must report.
+        let _ = fs::remove_file(destination_file_path);
     }
 }
 
@@ -233,19 +923,108 @@ pub struct FileCopyWithProgressOptions {
     ///
     /// Defaults to 512 KiB.
     pub progress_update_byte_interval: u64,
+
+    /// Whether to clone the file (i.e. create a copy-on-write reflink) instead of copying its contents.
+    ///
+    /// See [`ReflinkBehaviour`] for more details.
+    ///
+    /// Defaults to [`ReflinkBehaviour::Never`].
+    pub reflink_behaviour: ReflinkBehaviour,
+
+    /// Which metadata of the source file to apply to the destination file once it has been copied.
+    ///
+    /// See [`MetadataPreservation`] for more details.
+    ///
+    /// Defaults to [`MetadataPreservation::NONE`].
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Which extended attributes of the source file to copy to the destination file.
+    ///
+    /// See [`ExtendedAttributeFilter`] for more details.
+    ///
+    /// Defaults to [`ExtendedAttributeFilter::NONE`].
+    pub extended_attributes: ExtendedAttributeFilter,
+
+    /// Whether to preserve holes when copying sparse files.
+    ///
+    /// See [`SparseFileBehaviour`] for more details.
+    ///
+    /// Defaults to [`SparseFileBehaviour::Never`].
+    pub sparse_behaviour: SparseFileBehaviour,
+
+    /// Whether to read the destination file back once it has been copied,
+    /// and compare its hash to the hash of the source file computed while copying it.
+    ///
+    /// See [`CopyVerification`] for more details.
+    ///
+    /// Defaults to [`CopyVerification::DISABLED`].
+    pub verify: CopyVerification,
+
+    /// Whether to resume copying if the destination file is a partial copy of the source file.
+    ///
+    /// Resuming writes to the existing destination file, so it is only attempted if
+    /// [`colliding_file_behaviour`] allows that file to be overwritten. For example,
+    /// with [`CollidingFileBehaviour::Abort`] an existing destination file is never resumed,
+    /// even if it is a partial copy of the source file (an empty file always is).
+    /// To resume over existing files, use [`CollidingFileBehaviour::Overwrite`].
+    ///
+    /// See [`ResumeBehaviour`] for more details.
+    ///
+    /// Defaults to [`ResumeBehaviour::Never`].
+    ///
+    ///
+    /// [`colliding_file_behaviour`]: Self::colliding_file_behaviour
+    pub resume_behaviour: ResumeBehaviour,
+
+    /// Whether to copy the file into a hidden temporary file in the destination directory,
+    /// which is synced to disk and then renamed over the destination file.
+    ///
+    /// This way, the destination path never contains a partially written file:
+    /// it contains either the previous destination file (if any), or the complete copy.
+    /// If the copy fails or is cancelled, the destination file is left untouched,
+    /// and the temporary file is removed (unless the process itself is interrupted).
+    ///
+    /// As an atomic copy never leaves a partially copied destination file behind,
+    /// [`resume_behaviour`](Self::resume_behaviour) is ignored.
+    ///
+    /// Defaults to `false`.
+    pub atomic: bool,
+
+    /// How much of the copy to sync to disk before returning.
+    ///
+    /// See [`DurabilityLevel`] for more details.
+    ///
+    /// Defaults to [`DurabilityLevel::None`].
+    pub durability: DurabilityLevel,
 }
 
 impl Default for FileCopyWithProgressOptions {
     /// Constructs relatively safe defaults for copying a file:
     /// - aborts if there is an existing destination file ([`CollidingFileBehaviour::Abort`]),
-    /// - sets buffer size for reading and writing to 64 KiB, and
-    /// - sets the progress update closure call interval to 512 KiB.
+    /// - sets buffer size for reading and writing to 64 KiB,
+    /// - sets the progress update closure call interval to 512 KiB,
+    /// - never clones the file ([`ReflinkBehaviour::Never`]),
+    /// - preserves no metadata ([`MetadataPreservation::NONE`]),
+    /// - copies no extended attributes ([`ExtendedAttributeFilter::NONE`]),
+    /// - does not preserve holes in sparse files ([`SparseFileBehaviour::Never`]),
+    /// - does not verify the copy ([`CopyVerification::DISABLED`]),
+    /// - never resumes partially copied files ([`ResumeBehaviour::Never`]),
+    /// - writes directly into the destination file (the copy is not atomic), and
+    /// - does not sync anything to disk ([`DurabilityLevel::None`]).
     fn default() -> Self {
         Self {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
             read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
             write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
             progress_update_byte_interval: DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
+            reflink_behaviour: ReflinkBehaviour::Never,
+            metadata_preservation: MetadataPreservation::NONE,
+            extended_attributes: ExtendedAttributeFilter::NONE,
+            sparse_behaviour: SparseFileBehaviour::Never,
+            verify: CopyVerification::DISABLED,
+            resume_behaviour: ResumeBehaviour::Never,
+            atomic: false,
+            durability: DurabilityLevel::None,
         }
     }
 }
@@ -254,9 +1033,23 @@ impl Default for FileCopyWithProgressOptions {
 /// Copies the specified file from the source to the destination using the provided options
 /// and progress reporting closure.
 ///
-/// This is done by opening two file handles (one for reading, another for writing),
-/// wrapping them in buffered readers and writers, plus our progress tracker intermediary,
-/// and then finally using the [`std::io::copy`] function to copy the entire file.
+/// This is done by opening two file handles (one for reading, another for writing).
+/// On Linux, we first try to have the kernel copy the file (see [`super::linux`]).
+/// Otherwise, the handles are wrapped in buffered readers and writers, plus our progress
+/// tracker intermediary, and the [`std::io::copy`] function is used to copy the entire file.
+/// If sparse copying was requested, holes are skipped instead (see [`SparseFileBehaviour`]).
+///
+/// If the progress handler returns [`ProgressAction::Abort`], copying stops,
+/// the partially written destination file is removed, and [`FileError::Cancelled`] is returned.
+///
+/// If [`options.verify`][FileCopyWithProgressOptions::verify] is enabled, the source file
+/// is hashed while it is being copied (which is why the kernel is not asked to copy it),
+/// after which the destination file is read back and hashed as well; see [`CopyVerification`].
+///
+/// Once the file has been copied, the extended attributes requested by
+/// [`options.extended_attributes`][FileCopyWithProgressOptions::extended_attributes]
+/// are copied and the metadata requested by
+/// [`options.metadata_preservation`][FileCopyWithProgressOptions::metadata_preservation] is applied.
 ///
 ///
 /// # Invariants
@@ -268,9 +1061,148 @@ pub(crate) fn copy_file_with_progress_unchecked<F>(
     destination_file_path: &Path,
     options: FileCopyWithProgressOptions,
     progress_handler: F,
-) -> Result<u64, FileError>
+) -> Result<CopiedFileContents, FileError>
+where
+    F: FnMut(&FileProgress) -> ProgressAction,
+{
+    resume_file_copy_with_progress_unchecked(
+        source_file_path,
+        destination_file_path,
+        options,
+        0,
+        progress_handler,
+    )
+}
+
+
+/// Copies the specified file from the source to the destination, like [`copy_file_with_progress_unchecked`],
+/// but assumes the first `resume_from` bytes of the destination file have already been copied
+/// (see [`ResumeBehaviour`]). If `resume_from` is zero, the file is copied from the beginning.
+///
+/// The rest of the file is copied through buffers in user space, and progress reports
+/// start at `resume_from` bytes.
+///
+///
+/// # Invariants
+/// **Be warned:** no path validation or other checks are performed before copying.
+/// Apart from validating the paths, it is up to the caller to ensure that the first `resume_from` bytes
+/// of the destination file match the source file, e.g. using [`resumable_prefix_length`].
+pub(crate) fn resume_file_copy_with_progress_unchecked<F>(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    options: FileCopyWithProgressOptions,
+    resume_from: u64,
+    mut progress_handler: F,
+) -> Result<CopiedFileContents, FileError>
+where
+    F: FnMut(&FileProgress) -> ProgressAction,
+{
+    let mut source_hasher = options.verify.new_hasher();
+
+    let copied_contents = copy_file_contents_with_progress_unchecked(
+        source_file_path,
+        destination_file_path,
+        options,
+        resume_from,
+        source_hasher.as_mut(),
+        &mut progress_handler,
+    )?;
+
+    if let Some(source_hasher) = source_hasher {
+        verify_copied_file_with_progress(
+            destination_file_path,
+            source_hasher.finish(),
+            &options,
+            &copied_contents,
+            &mut progress_handler,
+        )?;
+    }
+
+    // The file is synced before the preserved metadata is applied, as that may make it read-only,
+    // and Windows requires write access to sync a file.
+    if options.atomic || options.durability.syncs_files() {
+        sync_file_to_disk(destination_file_path)
+            .map_err(|error| FileError::OtherIoError { error })?;
+    }
+
+    finish_copied_file(
+        source_file_path,
+        destination_file_path,
+        options.extended_attributes,
+        options.metadata_preservation,
+    )?;
+
+    Ok(copied_contents)
+}
+
+
+/// Reads back and hashes the copied destination file, then compares its hash to `source_digest`.
+///
+/// Verification is reported as [`FileProgressPhase::Verifying`] if the options request it.
+fn verify_copied_file_with_progress<F>(
+    destination_file_path: &Path,
+    source_digest: Vec<u8>,
+    options: &FileCopyWithProgressOptions,
+    copied_contents: &CopiedFileContents,
+    progress_handler: &mut F,
+) -> Result<(), FileError>
+where
+    F: FnMut(&FileProgress) -> ProgressAction,
+{
+    let report_progress = options.verify.reports_progress();
+    let bytes_total = copied_contents.resumed_from + copied_contents.bytes_copied;
+
+    let mut progress = FileProgress {
+        bytes_finished: bytes_total,
+        bytes_written: copied_contents.bytes_written,
+        bytes_total,
+        phase: FileProgressPhase::Verifying { bytes_verified: 0 },
+    };
+
+    let outcome = compare_destination_file_hash(
+        destination_file_path,
+        source_digest,
+        options.verify,
+        options.read_buffer_size,
+        options.progress_update_byte_interval,
+        |bytes_verified| {
+            if !report_progress {
+                return true;
+            }
+
+            progress.phase = FileProgressPhase::Verifying { bytes_verified };
+            !progress_handler(&progress).is_abort()
+        },
+    )
+    .map_err(|error| FileError::OtherIoError { error })?;
+
+    if report_progress && matches!(outcome, VerificationOutcome::Verified) {
+        // The file has been fully verified, so the returned action is irrelevant.
+        progress.phase = FileProgressPhase::Verifying {
+            bytes_verified: bytes_total,
+        };
+        let _ = progress_handler(&progress);
+    }
+
+    handle_verification_outcome(outcome, destination_file_path, bytes_total)
+}
+
+
+/// Copies the contents of the source file to the destination file,
+/// see [`resume_file_copy_with_progress_unchecked`].
+///
+/// If a `source_hasher` is provided, the contents of the source file
+/// (including the first `resume_from` bytes) are fed into it.
+fn copy_file_contents_with_progress_unchecked<F>(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    options: FileCopyWithProgressOptions,
+    resume_from: u64,
+    source_hasher: Option<&mut Box<dyn Hasher>>,
+    progress_handler: &mut F,
+) -> Result<CopiedFileContents, FileError>
 where
-    F: FnMut(&FileProgress),
+    F: FnMut(&FileProgress) -> ProgressAction,
 {
     let bytes_total = fs::metadata(source_file_path)
         .map_err(|error| FileError::OtherIoError { error })?
@@ -278,40 +1210,302 @@ where
 
     // Open a file for reading and a file for writing,
     // wrap them in buffers and progress monitors, then copy the file.
-    let input_file = fs::OpenOptions::new()
+    let mut input_file = fs::OpenOptions::new()
         .read(true)
         .open(source_file_path)
         .map_err(|error| FileError::OtherIoError { error })?;
 
-    let mut input_file_buffered = BufReader::with_capacity(options.read_buffer_size, input_file);
+    if resume_from > 0 {
+        return resume_file_contents_with_progress(
+            input_file,
+            destination_file_path,
+            bytes_total,
+            resume_from,
+            &options,
+            source_hasher,
+            progress_handler,
+        );
+    }
 
+    let mut output_file = open_destination_file_without_truncating(destination_file_path)?;
 
-    let output_file = fs::OpenOptions::new()
-        .create(true)
+    if reflink_if_requested(
+        &input_file,
+        &output_file,
+        bytes_total,
+        options.reflink_behaviour,
+        destination_file_path,
+    )? {
+        // The contents never passed through our hands, so we have to read them to hash them.
+        if let Some(source_hasher) = source_hasher {
+            hash_reader(
+                &mut input_file,
+                source_hasher.as_mut(),
+                options.read_buffer_size,
+                u64::MAX,
+                |_| true,
+            )
+            .map_err(|error| FileError::OtherIoError { error })?;
+        }
+
+        // The file has already been copied, so the returned action is irrelevant.
+        let _ = progress_handler(&FileProgress {
+            bytes_finished: bytes_total,
+            bytes_written: 0,
+            bytes_total,
+            phase: FileProgressPhase::Copying,
+        });
+
+        return Ok(CopiedFileContents {
+            bytes_copied: bytes_total,
+            resumed_from: 0,
+            bytes_written: 0,
+            method: FileCopyMethod::Reflink,
+        });
+    }
+
+    output_file
+        .set_len(0)
+        .map_err(|error| FileError::OtherIoError { error })?;
+
+
+    // The kernel-accelerated copies below would fill in holes, so sparse files are copied separately.
+    if options.sparse_behaviour != SparseFileBehaviour::Never {
+        return match copy_file_contents_sparsely_with_progress(
+            &mut input_file,
+            &mut output_file,
+            bytes_total,
+            &options,
+            source_hasher,
+            progress_handler,
+        )
+        .map_err(|error| FileError::OtherIoError { error })?
+        {
+            SparseCopyOutcome::Finished {
+                bytes_copied,
+                bytes_written,
+            } => Ok(CopiedFileContents {
+                bytes_copied,
+                resumed_from: 0,
+                bytes_written,
+                method: FileCopyMethod::Sparse,
+            }),
+            SparseCopyOutcome::Cancelled { bytes_finished } => {
+                drop(output_file);
+
+                remove_destination_file_after_cancelled_copy(
+                    destination_file_path,
+                    options.resume_behaviour,
+                );
+
+                Err(FileError::Cancelled {
+                    bytes_finished,
+                    bytes_total,
+                })
+            }
+        };
+    }
+
+
+    // The data never passes through user space when the kernel copies it,
+    // so we can't do that if we need to hash it.
+    #[cfg(target_os = "linux")]
+    if source_hasher.is_none() {
+        match super::linux::copy_file_contents_with_progress(
+            &input_file,
+            &output_file,
+            bytes_total,
+            options.progress_update_byte_interval,
+            progress_handler,
+        )
+        .map_err(|error| FileError::OtherIoError { error })?
+        {
+            AcceleratedCopyOutcome::Finished {
+                bytes_copied,
+                method,
+            } => {
+                return Ok(CopiedFileContents {
+                    bytes_copied,
+                    resumed_from: 0,
+                    bytes_written: bytes_copied,
+                    method,
+                });
+            }
+            AcceleratedCopyOutcome::Cancelled { bytes_finished } => {
+                drop(output_file);
+
+                remove_destination_file_after_cancelled_copy(
+                    destination_file_path,
+                    options.resume_behaviour,
+                );
+
+                return Err(FileError::Cancelled {
+                    bytes_finished,
+                    bytes_total,
+                });
+            }
+            AcceleratedCopyOutcome::Unsupported => {}
+        }
+    }
+
+
+    copy_remaining_file_contents_buffered(
+        input_file,
+        output_file,
+        bytes_total,
+        0,
+        &options,
+        source_hasher,
+        progress_handler,
+    )
+    .map_err(|error| {
+        if matches!(error, FileError::Cancelled { .. }) {
+            remove_destination_file_after_cancelled_copy(
+                destination_file_path,
+                options.resume_behaviour,
+            );
+        }
+
+        error
+    })
+}
+
+
+/// Copies the rest of the source file to a destination file
+/// whose first `resume_from` bytes have already been copied.
+///
+/// If a `source_hasher` is provided, the already copied bytes of the source file are fed into it
+/// before the rest of the file is copied (see [`copy_remaining_file_contents_buffered`]).
+fn resume_file_contents_with_progress<F>(
+    mut input_file: fs::File,
+    destination_file_path: &Path,
+    bytes_total: u64,
+    resume_from: u64,
+    options: &FileCopyWithProgressOptions,
+    source_hasher: Option<&mut Box<dyn Hasher>>,
+    progress_handler: &mut F,
+) -> Result<CopiedFileContents, FileError>
+where
+    F: FnMut(&FileProgress) -> ProgressAction,
+{
+    let mut output_file = fs::OpenOptions::new()
         .write(true)
-        .truncate(true)
         .open(destination_file_path)
         .map_err(|error| FileError::OtherIoError { error })?;
 
+    // Anything past the already copied bytes is discarded before we continue.
+    output_file
+        .set_len(resume_from)
+        .and_then(|_| output_file.seek(SeekFrom::Start(resume_from)))
+        .map_err(|error| FileError::OtherIoError { error })?;
+
+
+    // Hashing the already copied bytes conveniently leaves the source file
+    // positioned where we need to resume from.
+    let source_hasher = match source_hasher {
+        Some(source_hasher) => {
+            hash_reader(
+                &mut (&mut input_file).take(resume_from),
+                source_hasher.as_mut(),
+                options.read_buffer_size,
+                u64::MAX,
+                |_| true,
+            )
+            .map_err(|error| FileError::OtherIoError { error })?;
+
+            Some(source_hasher)
+        }
+        None => {
+            input_file
+                .seek(SeekFrom::Start(resume_from))
+                .map_err(|error| FileError::OtherIoError { error })?;
+
+            None
+        }
+    };
+
+
+    copy_remaining_file_contents_buffered(
+        input_file,
+        output_file,
+        bytes_total,
+        resume_from,
+        options,
+        source_hasher,
+        progress_handler,
+    )
+    .map_err(|error| {
+        if matches!(error, FileError::Cancelled { .. }) {
+            remove_destination_file_after_cancelled_copy(
+                destination_file_path,
+                options.resume_behaviour,
+            );
+        }
+
+        error
+    })
+}
+
+
+/// Copies the source file to the destination file through buffers in user space,
+/// starting at `resume_from` bytes (both files must already be positioned there).
+///
+/// If the progress handler cancels the copy, the destination file is closed
+/// (but not removed) and [`FileError::Cancelled`] is returned.
+fn copy_remaining_file_contents_buffered<F>(
+    input_file: fs::File,
+    output_file: fs::File,
+    bytes_total: u64,
+    resume_from: u64,
+    options: &FileCopyWithProgressOptions,
+    source_hasher: Option<&mut Box<dyn Hasher>>,
+    progress_handler: &mut F,
+) -> Result<CopiedFileContents, FileError>
+where
+    F: FnMut(&FileProgress) -> ProgressAction,
+{
+    let mut input_file_buffered = BufReader::with_capacity(
+        options.read_buffer_size,
+        HashingReader::new(input_file, source_hasher),
+    );
+
     let output_file_progress_monitored = ProgressWriter::new(
         output_file,
         progress_handler,
         options.progress_update_byte_interval,
         bytes_total,
-    );
+    )
+    .resuming_from(resume_from);
     let mut output_file_buffered =
         BufWriter::with_capacity(options.write_buffer_size, output_file_progress_monitored);
 
 
 
+    // We flush the buffered writer explicitly (instead of relying on `BufWriter::into_inner`
+    // to do it) so the progress handler can cancel the copy while the last chunk is being written.
+    let copy_result = std::io::copy(&mut input_file_buffered, &mut output_file_buffered)
+        .and_then(|bytes_copied| output_file_buffered.flush().map(|_| bytes_copied));
+
+    if output_file_buffered.get_ref().is_cancelled() {
+        let bytes_finished = output_file_buffered.get_ref().progress().bytes_finished;
+
+        // Discard any buffered data (there is no point in writing it) and close the file.
+        let (progress_writer, _) = output_file_buffered.into_parts();
+        drop(progress_writer);
+
+        return Err(FileError::Cancelled {
+            bytes_finished,
+            bytes_total,
+        });
+    }
+
     let final_number_of_bytes_copied =
-        std::io::copy(&mut input_file_buffered, &mut output_file_buffered)
-            .map_err(|error| FileError::OtherIoError { error })?;
+        copy_result.map_err(|error| FileError::OtherIoError { error })?;
 
 
 
     // Unwrap writers and flush any remaining output.
-    let (mut output_file, mut copy_progress, mut progress_handler) = output_file_buffered
+    let (mut output_file, mut copy_progress, progress_handler) = output_file_buffered
         .into_inner()
         .map_err(|error| FileError::OtherIoError {
             error: error.into_error(),
@@ -322,11 +1516,32 @@ where
         .flush()
         .map_err(|error| FileError::OtherIoError { error })?;
 
-    // Perform one last progress update.
-    copy_progress.bytes_finished = final_number_of_bytes_copied;
+    // Perform one last progress update. At this point the file has been fully copied,
+    // so there is nothing left to cancel.
+    copy_progress.bytes_finished = resume_from + final_number_of_bytes_copied;
+    copy_progress.bytes_written = final_number_of_bytes_copied;
     progress_handler(&copy_progress);
 
-    Ok(final_number_of_bytes_copied)
+    Ok(CopiedFileContents {
+        bytes_copied: final_number_of_bytes_copied,
+        resumed_from: resume_from,
+        bytes_written: final_number_of_bytes_copied,
+        method: FileCopyMethod::Buffered,
+    })
+}
+
+
+/// Removes the partially written destination file after the copy has been cancelled,
+/// unless it is kept so that the copy can be resumed later (see [`ResumeBehaviour`]).
+fn remove_destination_file_after_cancelled_copy(
+    destination_file_path: &Path,
+    resume_behaviour: ResumeBehaviour,
+) {
+    if resume_behaviour == ResumeBehaviour::Never {
+        // Removing the partially written file is a best-effort cleanup; the user asked
+        // us to stop, and that is the most important thing to report.
+        let _ = fs::remove_file(destination_file_path);
+    }
 }
 
 
@@ -353,7 +1568,7 @@ where
 /// # Return value
 /// If the copy succeeds, the function returns [`FileCopyFinished`],
 /// which contains information about whether the file was created,
-/// overwritten or skipped. The struct includes the number of bytes copied,
+/// overwritten, resumed or skipped. The struct includes the number of bytes copied,
 /// if relevant.
 ///
 ///
@@ -374,6 +1589,43 @@ where
 /// In most cases though, the number of calls to the closure will be near the expected number,
 /// which is `file_size / progress_update_byte_interval`.
 ///
+/// If [`options.verify`] is enabled and set to report progress, the copy is followed
+/// by reports in the [`FileProgressPhase::Verifying`] phase, while the destination file is read back.
+///
+/// If the copy is resumed, progress reports start at the number of bytes that had already been copied.
+///
+///
+/// # Resuming
+/// Unless [`options.resume_behaviour`] is [`ResumeBehaviour::Never`], a destination file that is
+/// a partial copy of the source file (e.g. because an earlier copy was interrupted or cancelled)
+/// is not copied again from the start. Instead, the start of the destination file is compared
+/// to the source file, and if it matches, the rest of the file is copied and
+/// [`FileCopyFinished::Resumed`] is returned. If it does not match, the file is overwritten.
+///
+/// Only destination files that [`options.colliding_file_behaviour`] allows us to overwrite
+/// are resumed: it is applied as usual before anything is resumed, so e.g.
+/// [`CollidingFileBehaviour::Abort`] still returns [`DestinationPathAlreadyExists`].
+///
+///
+/// # Atomic copies
+/// If [`options.atomic`] is enabled, the file is first copied into a hidden temporary file
+/// in the destination directory (e.g. `.file.txt.fs-more-tmp`), which is synced to disk
+/// and then renamed over the destination path. If anything fails (or the copy is cancelled),
+/// the temporary file is removed and the destination path is left untouched.
+/// Atomic copies are never resumed.
+///
+///
+/// # Cancellation
+/// The `progress_handler` closure can return either `()`, a [`ProgressAction`],
+/// or a [`ControlFlow<()>`](std::ops::ControlFlow).
+/// Returning [`ProgressAction::Abort`] (or [`ControlFlow::Break`](std::ops::ControlFlow::Break))
+/// stops the copy as soon as possible: the partially written destination file is removed
+/// and [`FileError::Cancelled`] is returned, containing the number of bytes
+/// that had been written before the copy was cancelled.
+///
+/// If [`options.resume_behaviour`] is not [`ResumeBehaviour::Never`], the partially written
+/// destination file is kept instead, so that the copy can be resumed later.
+///
 ///
 /// # Errors
 /// If the file cannot be copied to the destination, a [`FileError`] is returned;
@@ -382,13 +1634,18 @@ where
 /// - If the source path has issues (does not exist, does not have the correct permissions, etc.), one of
 ///   [`SourceFileNotFound`], [`SourcePathNotAFile`], or [`UnableToAccessSourceFile`]
 ///   variants will be returned.
-/// - If the destination already exists, and [`options.colliding_file_behaviour`]
+/// - If the destination already exists and [`options.colliding_file_behaviour`]
 ///   is set to [`CollidingFileBehaviour::Abort`], then a [`DestinationPathAlreadyExists`]
-///   will be returned.
+///   will be returned (even if the destination file could be resumed).
 /// - If the source and destination paths are canonically actually the same file,
 ///   then copying will be aborted with [`SourceAndDestinationAreTheSame`].
 /// - If the destination path has other issues (is a directory, does not have the correct permissions, etc.),
 ///   [`UnableToAccessDestinationFile`] will be returned.
+/// - If [`options.reflink_behaviour`] is set to [`ReflinkBehaviour::Always`]
+///   and the file can not be cloned, [`UnableToReflink`] will be returned.
+/// - If [`options.verify`] is enabled and the destination file does not match
+///   the source file after copying, [`VerificationFailed`] will be returned.
+/// - If the progress handler cancels the copy, [`Cancelled`] will be returned.
 ///
 /// There do exist other failure points, mostly due to unavoidable
 /// [time-of-check time-of-use](https://en.wikipedia.org/wiki/Time-of-check_to_time-of-use)
@@ -415,42 +1672,73 @@ where
 /// <br>
 ///
 /// Unlike [`copy_file`], this function handles copying itself by opening file handles for
-/// both the source and destination file, then buffering reads and writes.
+/// both the source and destination file.
+///
+/// On Linux, the file is first copied by the kernel in chunks using `copy_file_range`
+/// (falling back to `sendfile`), reporting progress between chunks. Unless
+/// [`options.reflink_behaviour`] is [`ReflinkBehaviour::Never`], a `FICLONE` reflink
+/// is attempted before that. If none of these are supported, or on other platforms,
+/// reads and writes are buffered in user space.
+///
+/// Unless [`options.sparse_behaviour`] is [`SparseFileBehaviour::Never`], the kernel is not
+/// asked to copy the file. Instead, reads and writes are buffered in user space and holes
+/// are skipped by seeking past them in the destination file. The same goes for
+/// [`options.verify`], as the source file is hashed while it is being copied,
+/// and for resumed copies.
 ///
 /// </details>
 ///
 ///
 /// [`options.progress_update_byte_interval`]: FileCopyWithProgressOptions::progress_update_byte_interval
+/// [`options.reflink_behaviour`]: FileCopyWithProgressOptions::reflink_behaviour
+/// [`options.sparse_behaviour`]: FileCopyWithProgressOptions::sparse_behaviour
+/// [`options.verify`]: FileCopyWithProgressOptions::verify
+/// [`options.resume_behaviour`]: FileCopyWithProgressOptions::resume_behaviour
 /// [`options.colliding_file_behaviour`]: FileCopyOptions::colliding_file_behaviour
+/// [`options.atomic`]: FileCopyWithProgressOptions::atomic
 /// [`SourceFileNotFound`]: FileError::SourceFileNotFound
 /// [`SourcePathNotAFile`]: FileError::SourcePathNotAFile
 /// [`UnableToAccessSourceFile`]: FileError::UnableToAccessSourceFile
 /// [`DestinationPathAlreadyExists`]: FileError::DestinationPathAlreadyExists
 /// [`UnableToAccessDestinationFile`]: FileError::UnableToAccessDestinationFile
 /// [`SourceAndDestinationAreTheSame`]: FileError::SourceAndDestinationAreTheSame
+/// [`UnableToReflink`]: FileError::UnableToReflink
+/// [`VerificationFailed`]: FileError::VerificationFailed
+/// [`Cancelled`]: FileError::Cancelled
 /// [`OtherIoError`]: FileError::OtherIoError
 /// [^unix-cp]: Source for coreutils' `cp` is available
 ///     [here](https://github.com/coreutils/coreutils/blob/ccf47cad93bc0b85da0401b0a9d4b652e4c930e4/src/cp.c).
-pub fn copy_file_with_progress<P, T, F>(
+pub fn copy_file_with_progress<P, T, F, R>(
     source_file_path: P,
     destination_file_path: T,
     options: FileCopyWithProgressOptions,
-    progress_handler: F,
+    mut progress_handler: F,
 ) -> Result<FileCopyFinished, FileError>
 where
     P: AsRef<Path>,
     T: AsRef<Path>,
-    F: FnMut(&FileProgress),
+    F: FnMut(&FileProgress) -> R,
+    R: Into<ProgressAction>,
 {
     let source_file_path = source_file_path.as_ref();
     let destination_file_path = destination_file_path.as_ref();
 
+    // An atomic copy is written into a fresh temporary file, so there is nothing to resume.
+    let options = match options.atomic {
+        true => FileCopyWithProgressOptions {
+            resume_behaviour: ResumeBehaviour::Never,
+            ..options
+        },
+        false => options,
+    };
+
 
     let validated_source_file_path = validate_source_file_path(source_file_path)?;
 
     let ValidatedDestinationFilePath {
         destination_file_path,
         exists: destination_file_exists,
+        is_alternative_path,
     } = match validate_destination_file_path(
         &validated_source_file_path,
         destination_file_path,
@@ -467,20 +1755,59 @@ where
     } = validated_source_file_path;
 
 
+    // Only a destination file that the colliding file behaviour allows us
+    // to overwrite can be resumed instead.
+    let resume_from = if destination_file_exists {
+        resumable_prefix_length(&source_file_path, &destination_file_path, options.resume_behaviour)
+            .map_err(|error| FileError::OtherIoError { error })?
+    } else {
+        None
+    };
+
+
     // All checks have passed, we must now copy the file.
     // Unlike in the `copy_file` function, we must copy the file ourselves, as we
-    // can't report progress otherwise. This is delegated to the `copy_file_with_progress_unchecked`
+    // can't report progress otherwise. This is delegated to the `resume_file_copy_with_progress_unchecked`
     // function which is used in other parts of the library as well.
 
-    let bytes_copied = copy_file_with_progress_unchecked(
-        &source_file_path,
+    let CopiedFileContents {
+        bytes_copied,
+        method,
+        ..
+    } = write_destination_file(
         &destination_file_path,
-        options,
-        progress_handler,
+        options.atomic,
+        options.durability,
+        |file_path_to_write| {
+            resume_file_copy_with_progress_unchecked(
+                &source_file_path,
+                file_path_to_write,
+                options,
+                resume_from.unwrap_or(0),
+                |progress| progress_handler(progress).into(),
+            )
+            .map_err(|error| {
+                remove_destination_file_after_failed_reflink(
+                    &error,
+                    file_path_to_write,
+                    destination_file_exists,
+                );
+                error
+            })
+        },
     )?;
 
-    match destination_file_exists {
-        true => Ok(FileCopyFinished::Overwritten { bytes_copied }),
-        false => Ok(FileCopyFinished::Created { bytes_copied }),
+    match resume_from {
+        Some(resumed_from) => Ok(FileCopyFinished::Resumed {
+            bytes_copied,
+            resumed_from,
+            method,
+        }),
+        None => Ok(finished_copy(
+            destination_file_exists,
+            is_alternative_path.then_some(destination_file_path),
+            bytes_copied,
+            method,
+        )),
     }
 }
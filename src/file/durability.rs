@@ -0,0 +1,115 @@
+use std::{io, path::Path};
+
+use_enabled_fs_module!();
+
+
+/// Controls how much of a copy or move is flushed to disk (i.e. `fsync`-ed)
+/// before the corresponding function returns.
+///
+/// Without syncing, written data may still be sitting in the operating system's caches
+/// when a function returns, meaning it can be lost (or end up only partially written)
+/// if the system loses power or crashes shortly afterwards.
+///
+/// Syncing makes copies and moves considerably slower, especially with many small files.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DurabilityLevel {
+    /// Nothing is synced; the operating system decides when to write the data to disk.
+    ///
+    /// This is the default.
+    None,
+
+    /// The contents and metadata of every written file are synced to disk.
+    ///
+    /// Files are synced right after their contents (and permissions) have been written,
+    /// before the rest of the preserved metadata (e.g. timestamps) is applied.
+    ///
+    /// Note that this does not guarantee that newly created or renamed files can be found
+    /// after a power loss, as that depends on the directories containing them being synced.
+    Files,
+
+    /// Like [`DurabilityLevel::Files`], but the directories containing the files
+    /// (and other entries) that were created, renamed or removed are synced to disk as well.
+    ///
+    /// Directories are only synced on Unix; on other platforms, this is the same as
+    /// [`DurabilityLevel::Files`].
+    FilesAndDirectories,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for DurabilityLevel {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl DurabilityLevel {
+    /// Returns `true` if written files should be synced to disk.
+    #[inline]
+    pub(crate) const fn syncs_files(self) -> bool {
+        matches!(self, Self::Files | Self::FilesAndDirectories)
+    }
+
+    /// Returns `true` if directories whose entries have changed should be synced to disk.
+    #[inline]
+    pub(crate) const fn syncs_directories(self) -> bool {
+        matches!(self, Self::FilesAndDirectories)
+    }
+
+    /// Returns the level to use for the individual files of a directory copy,
+    /// whose directories are synced separately, once the entire copy has finished.
+    #[inline]
+    pub(crate) const fn without_directories(self) -> Self {
+        match self {
+            Self::None => Self::None,
+            Self::Files | Self::FilesAndDirectories => Self::Files,
+        }
+    }
+
+    /// Syncs the directory containing `path` if this level requires it,
+    /// e.g. after the entry at `path` has been created, renamed or removed.
+    pub(crate) fn sync_parent_directory(self, path: &Path) -> io::Result<()> {
+        if !self.syncs_directories() {
+            return Ok(());
+        }
+
+        match path.parent() {
+            Some(parent_directory_path) if !parent_directory_path.as_os_str().is_empty() => {
+                sync_directory_to_disk(parent_directory_path)
+            }
+            _ => sync_directory_to_disk(Path::new(".")),
+        }
+    }
+}
+
+
+/// Flushes the contents and metadata of the file at `file_path` to disk.
+///
+/// On Windows, this requires write access to the file, so it must not be called
+/// after the preserved permissions may have made the file read-only.
+pub(crate) fn sync_file_to_disk(file_path: &Path) -> io::Result<()> {
+    // `fsync` works on read-only file descriptors; Windows, however, requires write access to flush a file.
+    #[cfg(unix)]
+    let file = fs::File::open(file_path)?;
+
+    #[cfg(not(unix))]
+    let file = fs::OpenOptions::new().write(true).open(file_path)?;
+
+    file.sync_all()
+}
+
+
+/// Flushes the entries of the directory at `directory_path` to disk.
+///
+/// This is a no-op on platforms other than Unix, where directories can not be synced.
+pub(crate) fn sync_directory_to_disk(directory_path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        fs::File::open(directory_path)?.sync_all()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = directory_path;
+        Ok(())
+    }
+}
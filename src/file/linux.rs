@@ -0,0 +1,232 @@
+//! Kernel-accelerated file copying on Linux.
+//!
+//! The fast paths implemented here copy data without it ever passing through user space
+//! (and, in the case of reflinks, without copying any data at all).
+//! If none of them are supported for the given pair of files, the caller is expected
+//! to fall back to buffered copying.
+
+use std::{
+    io,
+    os::fd::{AsRawFd, RawFd},
+};
+
+use super::{FileCopyMethod, FileProgress, FileProgressPhase};
+use crate::ProgressAction;
+
+
+/// The `FICLONE` ioctl request (`_IOW(0x94, 9, int)`), whose encoding depends on the architecture.
+#[cfg(any(
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "sparc",
+    target_arch = "sparc64"
+))]
+const FICLONE: u32 = 0x80049409;
+
+/// The `FICLONE` ioctl request (`_IOW(0x94, 9, int)`), whose encoding depends on the architecture.
+#[cfg(not(any(
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "sparc",
+    target_arch = "sparc64"
+)))]
+const FICLONE: u32 = 0x40049409;
+
+/// Copies up to the given number of bytes from the first to the second file descriptor,
+/// returning the number of bytes copied (`0` at the end of the source file).
+type CopyChunkFn = fn(RawFd, RawFd, usize) -> io::Result<usize>;
+
+/// The largest number of bytes we ask the kernel to copy in a single system call.
+const MAXIMUM_CHUNK_SIZE: u64 = 1024 * 1024 * 1024;
+
+
+/// Outcome of [`copy_file_contents_with_progress`].
+pub(super) enum AcceleratedCopyOutcome {
+    /// The file has been fully copied.
+    Finished {
+        /// Number of bytes copied.
+        bytes_copied: u64,
+
+        /// Which of the fast paths was used.
+        method: FileCopyMethod,
+    },
+
+    /// The progress handler cancelled the copy.
+    ///
+    /// The destination file is left as-is; removing it is up to the caller.
+    Cancelled {
+        /// Number of bytes copied before the copy was cancelled.
+        bytes_finished: u64,
+    },
+
+    /// None of the fast paths are supported for this pair of files.
+    /// Nothing has been written to the destination file.
+    Unsupported,
+}
+
+
+/// Attempts to clone the contents of `source_file` into `destination_file` (`FICLONE`).
+///
+/// This only succeeds on filesystems with copy-on-write support (e.g. Btrfs or XFS),
+/// and only if both files are on the same filesystem. Note that the destination file
+/// is not truncated if it was larger than the source file.
+pub(super) fn clone_file_contents<S, D>(source_file: &S, destination_file: &D) -> io::Result<()>
+where
+    S: AsRawFd,
+    D: AsRawFd,
+{
+    // SAFETY: Both file descriptors are valid for the duration of the call,
+    // and `FICLONE` takes the source descriptor by value.
+    let result =
+        unsafe { libc::ioctl(destination_file.as_raw_fd(), FICLONE as _, source_file.as_raw_fd()) };
+
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+
+/// Copies the contents of `source_file` into `destination_file` using `copy_file_range`,
+/// falling back to `sendfile` if the former is not supported. Both files are read and written
+/// starting at their current offsets.
+///
+/// Data is copied in chunks of (roughly) `progress_update_byte_interval` bytes,
+/// and `progress_handler` is called after each chunk and once more after the file has been copied.
+pub(super) fn copy_file_contents_with_progress<S, D, F>(
+    source_file: &S,
+    destination_file: &D,
+    bytes_total: u64,
+    progress_update_byte_interval: u64,
+    progress_handler: &mut F,
+) -> io::Result<AcceleratedCopyOutcome>
+where
+    S: AsRawFd,
+    D: AsRawFd,
+    F: FnMut(&FileProgress) -> ProgressAction,
+{
+    let chunk_size = progress_update_byte_interval.clamp(1, MAXIMUM_CHUNK_SIZE) as usize;
+
+    let fast_paths: [(FileCopyMethod, CopyChunkFn); 2] = [
+        (FileCopyMethod::CopyFileRange, copy_chunk_with_copy_file_range),
+        (FileCopyMethod::SendFile, copy_chunk_with_sendfile),
+    ];
+
+    for (method, copy_chunk) in fast_paths {
+        let mut progress = FileProgress {
+            bytes_finished: 0,
+            bytes_written: 0,
+            bytes_total,
+            phase: FileProgressPhase::Copying,
+        };
+
+        loop {
+            let bytes_copied =
+                match copy_chunk(source_file.as_raw_fd(), destination_file.as_raw_fd(), chunk_size)
+                {
+                    Ok(bytes_copied) => bytes_copied as u64,
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(error) if progress.bytes_finished == 0 && is_unsupported_error(&error) => {
+                        break;
+                    }
+                    Err(error) => return Err(error),
+                };
+
+            if bytes_copied == 0 {
+                // Some filesystems (e.g. procfs) report a size, but their files can't be copied
+                // by the kernel; an immediate end of file means we should try another method.
+                if progress.bytes_finished == 0 && progress.bytes_total > 0 {
+                    break;
+                }
+
+                // We've reached the end of the source file.
+                let _ = progress_handler(&progress);
+
+                return Ok(AcceleratedCopyOutcome::Finished {
+                    bytes_copied: progress.bytes_finished,
+                    method,
+                });
+            }
+
+            progress.bytes_finished += bytes_copied;
+            progress.bytes_written += bytes_copied;
+
+            if progress.bytes_finished < progress.bytes_total
+                && progress_handler(&progress).is_abort()
+            {
+                return Ok(AcceleratedCopyOutcome::Cancelled {
+                    bytes_finished: progress.bytes_finished,
+                });
+            }
+        }
+    }
+
+    Ok(AcceleratedCopyOutcome::Unsupported)
+}
+
+
+fn copy_chunk_with_copy_file_range(
+    source_fd: RawFd,
+    destination_fd: RawFd,
+    chunk_size: usize,
+) -> io::Result<usize> {
+    // SAFETY: Both file descriptors are valid for the duration of the call.
+    // Null offsets mean the kernel uses (and advances) the file offsets of both files.
+    let result = unsafe {
+        libc::copy_file_range(
+            source_fd,
+            std::ptr::null_mut(),
+            destination_fd,
+            std::ptr::null_mut(),
+            chunk_size,
+            0,
+        )
+    };
+
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(result as usize)
+}
+
+fn copy_chunk_with_sendfile(
+    source_fd: RawFd,
+    destination_fd: RawFd,
+    chunk_size: usize,
+) -> io::Result<usize> {
+    // SAFETY: Both file descriptors are valid for the duration of the call.
+    // A null offset means the kernel uses (and advances) the file offset of the source file.
+    let result =
+        unsafe { libc::sendfile(destination_fd, source_fd, std::ptr::null_mut(), chunk_size) };
+
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(result as usize)
+}
+
+
+/// Returns `true` if the error indicates that a system call can not be used for the given files
+/// (e.g. because the kernel is too old, the files are on different filesystems,
+/// or a seccomp filter forbids it), in which case we should try another method.
+fn is_unsupported_error(error: &io::Error) -> bool {
+    matches!(
+        error.raw_os_error(),
+        Some(
+            libc::ENOSYS
+                | libc::EXDEV
+                | libc::EINVAL
+                | libc::EOPNOTSUPP
+                | libc::EPERM
+                | libc::ENOTTY
+                | libc::EBADF
+        )
+    )
+}
@@ -0,0 +1,248 @@
+use std::{io, path::Path};
+
+use_enabled_fs_module!();
+
+
+/// Controls which metadata of a source file or directory is applied to its copy.
+///
+/// Metadata is applied after the contents of an entry have been fully written;
+/// in directory copies, this means directory metadata is applied only once
+/// everything inside the directory has been copied (otherwise copying the contents
+/// would change the timestamps again, or be denied by read-only permissions).
+///
+/// Symbolic links created by directory copies never have their metadata changed.
+///
+/// The default preserves nothing. Use [`MetadataPreservation::ALL`] to preserve
+/// permissions, timestamps and ownership, like `cp -a` does on Unix.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MetadataPreservation {
+    /// Whether to preserve permissions.
+    ///
+    /// On Unix, these are the permission bits (including the executable, setuid, setgid
+    /// and sticky bits). On Windows, this is the read-only attribute.
+    ///
+    /// Note that [`copy_file`] always copies file permissions, regardless of this option
+    /// (because [`std::fs::copy`] does so).
+    ///
+    ///
+    /// [`copy_file`]: super::copy_file
+    pub permissions: bool,
+
+    /// Whether to preserve the last access and last modification timestamps.
+    pub timestamps: bool,
+
+    /// Whether to preserve the owner and group.
+    ///
+    /// Changing the owner requires elevated privileges. If we are not permitted to change it,
+    /// we attempt to change only the group, and if that is not permitted either,
+    /// ownership is silently left as it is (which matches the behaviour of `cp -a`).
+    ///
+    /// This is only supported on Unix; on other platforms, this has no effect.
+    pub ownership: bool,
+}
+
+impl MetadataPreservation {
+    /// Preserves no metadata. This is the default.
+    pub const NONE: Self = Self {
+        permissions: false,
+        timestamps: false,
+        ownership: false,
+    };
+
+    /// Preserves permissions, timestamps and ownership.
+    pub const ALL: Self = Self {
+        permissions: true,
+        timestamps: true,
+        ownership: true,
+    };
+
+    /// Returns `true` if no metadata is to be preserved.
+    pub(crate) const fn is_none(&self) -> bool {
+        !self.permissions && !self.timestamps && !self.ownership
+    }
+}
+
+impl Default for MetadataPreservation {
+    /// Constructs [`MetadataPreservation::NONE`]: no metadata is preserved.
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+
+
+/// Applies the owner and group of `source_path` to `destination_path`, if requested.
+///
+/// This must be called before extended attributes are copied and before
+/// [`apply_preserved_metadata`]: on Linux, changing the owner always removes the
+/// `security.capability` attribute (even if the owner stays the same),
+/// and it can clear the setuid and setgid bits. This matches the order `cp -a` uses.
+///
+/// Both paths are followed if they are symbolic links.
+#[cfg(unix)]
+pub(crate) fn apply_preserved_ownership(
+    source_path: &Path,
+    destination_path: &Path,
+    preservation: MetadataPreservation,
+) -> io::Result<()> {
+    if !preservation.ownership {
+        return Ok(());
+    }
+
+    unix::apply_ownership(&fs::metadata(source_path)?, destination_path)
+}
+
+/// Applies the owner and group of `source_path` to `destination_path`, if requested.
+///
+/// Ownership is only preserved on Unix, so this does nothing on other platforms.
+#[cfg(not(unix))]
+pub(crate) fn apply_preserved_ownership(
+    _source_path: &Path,
+    _destination_path: &Path,
+    _preservation: MetadataPreservation,
+) -> io::Result<()> {
+    Ok(())
+}
+
+
+/// Applies the requested permissions and timestamps of `source_path` to `destination_path`.
+///
+/// Ownership is applied separately by [`apply_preserved_ownership`], which must be called first,
+/// followed by copying extended attributes. Timestamps are applied last, so that nothing
+/// changes them afterwards.
+///
+/// Both paths are followed if they are symbolic links.
+pub(crate) fn apply_preserved_metadata(
+    source_path: &Path,
+    destination_path: &Path,
+    preservation: MetadataPreservation,
+) -> io::Result<()> {
+    if !preservation.permissions && !preservation.timestamps {
+        return Ok(());
+    }
+
+    let source_metadata = fs::metadata(source_path)?;
+
+    if preservation.permissions {
+        fs::set_permissions(destination_path, source_metadata.permissions())?;
+    }
+
+    #[cfg(unix)]
+    if preservation.timestamps {
+        unix::apply_timestamps(&source_metadata, destination_path)?;
+    }
+
+    #[cfg(windows)]
+    if preservation.timestamps {
+        windows::apply_timestamps(&source_metadata, destination_path)?;
+    }
+
+    Ok(())
+}
+
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        ffi::CString,
+        fs::Metadata,
+        io,
+        os::unix::{
+            ffi::OsStrExt,
+            fs::{chown, MetadataExt},
+        },
+        path::Path,
+    };
+
+    pub(super) fn apply_ownership(
+        source_metadata: &Metadata,
+        destination_path: &Path,
+    ) -> io::Result<()> {
+        let owner_result = chown(
+            destination_path,
+            Some(source_metadata.uid()),
+            Some(source_metadata.gid()),
+        );
+
+        match owner_result {
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {}
+            other => return other,
+        }
+
+        // We are not privileged enough to change the owner, but we may still
+        // be able to change the group (if we are a member of it).
+        match chown(destination_path, None, Some(source_metadata.gid())) {
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => Ok(()),
+            other => other,
+        }
+    }
+
+    pub(super) fn apply_timestamps(
+        source_metadata: &Metadata,
+        destination_path: &Path,
+    ) -> io::Result<()> {
+        let times = [
+            timespec(source_metadata.atime(), source_metadata.atime_nsec()),
+            timespec(source_metadata.mtime(), source_metadata.mtime_nsec()),
+        ];
+
+        let destination_path = CString::new(destination_path.as_os_str().as_bytes())?;
+
+        // SAFETY: The path is a valid NUL-terminated string and `times` contains
+        // exactly the two elements `utimensat` expects; both outlive the call.
+        let result = unsafe {
+            libc::utimensat(libc::AT_FDCWD, destination_path.as_ptr(), times.as_ptr(), 0)
+        };
+
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    fn timespec(seconds: i64, nanoseconds: i64) -> libc::timespec {
+        // Some platforms have private padding fields in `timespec`,
+        // so we can't use a struct literal here.
+        // SAFETY: `timespec` is a plain C struct, for which all zeroes is a valid value.
+        let mut timespec: libc::timespec = unsafe { std::mem::zeroed() };
+
+        timespec.tv_sec = seconds as libc::time_t;
+        timespec.tv_nsec = nanoseconds as _;
+
+        timespec
+    }
+}
+
+
+#[cfg(windows)]
+mod windows {
+    use std::{
+        fs::{FileTimes, Metadata, OpenOptions},
+        io,
+        os::windows::fs::OpenOptionsExt,
+        path::Path,
+    };
+
+    /// Allows opening directories, not only files.
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+
+    /// Allows changing the timestamps (even of read-only files) without write access to the contents.
+    const FILE_WRITE_ATTRIBUTES: u32 = 0x0100;
+
+    pub(super) fn apply_timestamps(
+        source_metadata: &Metadata,
+        destination_path: &Path,
+    ) -> io::Result<()> {
+        let times = FileTimes::new()
+            .set_accessed(source_metadata.accessed()?)
+            .set_modified(source_metadata.modified()?);
+
+        // Permissions are applied before timestamps, so the destination may already be read-only.
+        OpenOptions::new()
+            .access_mode(FILE_WRITE_ATTRIBUTES)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+            .open(destination_path)?
+            .set_times(times)
+    }
+}
@@ -23,17 +23,34 @@ use std::path::{Path, PathBuf};
 
 use_enabled_fs_module!();
 
+mod atomic;
+mod collision;
 mod copy;
+mod durability;
+#[cfg(target_os = "linux")]
+mod linux;
+mod metadata;
 mod r#move;
 mod progress;
 mod remove;
+mod resume;
 mod size;
+mod sparse;
+mod verify;
+mod xattr;
 
+pub use collision::*;
 pub use copy::*;
+pub use durability::*;
+pub use metadata::*;
 pub use progress::*;
 pub use r#move::*;
 pub use remove::*;
+pub use resume::*;
 pub use size::*;
+pub use sparse::*;
+pub use verify::*;
+pub use xattr::*;
 
 use crate::{directory::try_exists_without_follow, error::FileError};
 
@@ -57,6 +74,39 @@ pub enum CollidingFileBehaviour {
     /// Ensures that an existing destination file *can* be overwritten
     /// by the corresponding copying or moving function.
     Overwrite,
+
+    /// Overwrites an existing destination file only if the source file has been modified
+    /// more recently than it (i.e. if the source file has a later last modification time).
+    ///
+    /// Otherwise, the file is skipped silently, just like with [`Self::Skip`].
+    ///
+    /// When moving a directory by copy-and-delete, the skipped source files
+    /// are kept in the source directory.
+    OverwriteIfNewer,
+
+    /// Overwrites an existing destination file only if it differs from the source file,
+    /// i.e. if their sizes or contents differ. Contents of files with matching sizes
+    /// are compared byte for byte, chunk by chunk, until the first difference.
+    ///
+    /// Otherwise, the file is skipped silently, just like with [`Self::Skip`].
+    ///
+    /// When moving a directory by copy-and-delete, the skipped source files
+    /// are kept in the source directory.
+    OverwriteIfDifferent,
+
+    /// Keeps an existing destination file, and copies or moves the file to an alternative
+    /// path next to it instead, e.g. `report (1).pdf` (see [`AlternativeFileNaming`]).
+    ///
+    /// The path the file has ended up at is reported in [`FileCopyFinished::CreatedAtAlternativePath`]
+    /// and [`FileMoveFinished::CreatedAtAlternativePath`] (or, when copying a directory,
+    /// in [`DirectoryCopyFinished::files_copied_to_alternative_paths`]).
+    ///
+    ///
+    /// [`DirectoryCopyFinished::files_copied_to_alternative_paths`]: crate::directory::DirectoryCopyFinished::files_copied_to_alternative_paths
+    KeepBoth {
+        /// How to name the alternative path.
+        naming: AlternativeFileNaming,
+    },
 }
 
 
@@ -156,12 +206,17 @@ pub(crate) struct ValidatedDestinationFilePath {
 
     /// Whether the destination already exists.
     pub(crate) exists: bool,
+
+    /// Whether `destination_file_path` is a free alternative path next to the original
+    /// destination file path, chosen because of [`CollidingFileBehaviour::KeepBoth`].
+    pub(crate) is_alternative_path: bool,
 }
 
 pub(crate) enum DestinationValidationAction {
     /// The validation logic concluded that no action should be taken
     /// (the file should not be copied or moved) since the destination file already exists,
-    /// and `colliding_file_behaviour` is set to [`CollidingFileBehaviour::Skip`].
+    /// and `colliding_file_behaviour` is set to [`CollidingFileBehaviour::Skip`]
+    /// (or to one of the conditional overwrite behaviours, whose condition did not hold).
     SkipCopyOrMove,
 
     /// The validation logic found no path validation errors.
@@ -227,30 +282,41 @@ fn validate_destination_file_path(
 
 
         // Ensure we respect the [`CollidingFileBehaviour`] option if
-        // the destination file already exists.
-        if destination_file_exists {
-            match colliding_file_behaviour {
-                CollidingFileBehaviour::Abort => {
-                    return Err(FileError::DestinationPathAlreadyExists {
-                        path: destination_file_path.to_path_buf(),
-                    })
-                }
-                CollidingFileBehaviour::Skip => {
-                    return Ok(DestinationValidationAction::SkipCopyOrMove);
-                }
-                CollidingFileBehaviour::Overwrite => {}
-            };
+        // the destination file already exists. Note that any alternative path
+        // is placed next to the path we were given, not next to the file it may be a symlink to.
+        let collision_resolution = colliding_file_behaviour
+            .resolve_collision(
+                &validated_source_file_path.source_file_path,
+                destination_file_path,
+                try_exists_without_follow,
+            )
+            .map_err(|error| FileError::OtherIoError { error })?;
+
+        match collision_resolution {
+            None => Err(FileError::DestinationPathAlreadyExists {
+                path: destination_file_path.to_path_buf(),
+            }),
+            Some(FileCollisionResolution::Skip) => Ok(DestinationValidationAction::SkipCopyOrMove),
+            Some(FileCollisionResolution::Overwrite) => {
+                Ok(DestinationValidationAction::Continue(ValidatedDestinationFilePath {
+                    destination_file_path: canonical_destination_path,
+                    exists: true,
+                    is_alternative_path: false,
+                }))
+            }
+            Some(FileCollisionResolution::WriteToAlternativePath(alternative_file_path)) => {
+                Ok(DestinationValidationAction::Continue(ValidatedDestinationFilePath {
+                    destination_file_path: alternative_file_path,
+                    exists: false,
+                    is_alternative_path: true,
+                }))
+            }
         }
-
-
-        Ok(DestinationValidationAction::Continue(ValidatedDestinationFilePath {
-            destination_file_path: canonical_destination_path,
-            exists: true,
-        }))
     } else {
         Ok(DestinationValidationAction::Continue(ValidatedDestinationFilePath {
             destination_file_path: destination_file_path.to_path_buf(),
             exists: false,
+            is_alternative_path: false,
         }))
     }
 }
@@ -1,19 +1,29 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use_enabled_fs_module!();
 
 use super::{
-    copy::copy_file_with_progress_unchecked,
+    copy::{copy_and_verify_file, copy_file_with_progress_unchecked, CopiedFileContents},
+    metadata::{apply_preserved_metadata, apply_preserved_ownership},
     validate_destination_file_path,
     validate_source_file_path,
     CollidingFileBehaviour,
+    CopyVerification,
     DestinationValidationAction,
+    DurabilityLevel,
+    ExtendedAttributeFilter,
     FileCopyWithProgressOptions,
     FileProgress,
+    FileProgressPhase,
+    MetadataPreservation,
+    ReflinkBehaviour,
+    ResumeBehaviour,
+    SparseFileBehaviour,
 };
 use crate::{
     error::{FileError, FileRemoveError},
     file::ValidatedSourceFilePath,
+    ProgressAction,
     DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
     DEFAULT_READ_BUFFER_SIZE,
     DEFAULT_WRITE_BUFFER_SIZE,
@@ -25,15 +35,51 @@ use crate::{
 pub struct FileMoveOptions {
     /// How to behave when the destination file already exists.
     pub colliding_file_behaviour: CollidingFileBehaviour,
+
+    /// Which metadata of the source file to apply to the destination file
+    /// if the file has to be copied (see [`FileMoveMethod::CopyAndDelete`]).
+    /// A renamed file always keeps all of its metadata.
+    ///
+    /// See [`MetadataPreservation`] for more details.
+    ///
+    /// Defaults to [`MetadataPreservation::NONE`].
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Whether to verify the destination file if the file has to be copied
+    /// (see [`FileMoveMethod::CopyAndDelete`]). The source file is only removed
+    /// once the copy has been verified.
+    ///
+    /// See [`CopyVerification`] for more details.
+    ///
+    /// Defaults to [`CopyVerification::DISABLED`].
+    pub verify: CopyVerification,
+
+    /// How much of the move to sync to disk before returning.
+    ///
+    /// If the file has to be copied, the copy is synced before the source file is removed.
+    /// A renamed file has no new contents to sync, so renames are only affected by
+    /// [`DurabilityLevel::FilesAndDirectories`], which syncs the directories
+    /// that contained the source file and now contain the destination file.
+    ///
+    /// See [`DurabilityLevel`] for more details.
+    ///
+    /// Defaults to [`DurabilityLevel::None`].
+    pub durability: DurabilityLevel,
 }
 
 #[allow(clippy::derivable_impls)]
 impl Default for FileMoveOptions {
     /// Constructs a default [`FileMoveOptions`]:
-    /// - existing destination files will not be overwritten, and will cause an error ([`CollidingFileBehaviour::Abort`]).
+    /// - existing destination files will not be overwritten, and will cause an error ([`CollidingFileBehaviour::Abort`]),
+    /// - no metadata is preserved if the file has to be copied ([`MetadataPreservation::NONE`]),
+    /// - the file is not verified if it has to be copied ([`CopyVerification::DISABLED`]),
+    /// - nothing is synced to disk ([`DurabilityLevel::None`]).
     fn default() -> Self {
         Self {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            metadata_preservation: MetadataPreservation::NONE,
+            verify: CopyVerification::DISABLED,
+            durability: DurabilityLevel::None,
         }
     }
 }
@@ -43,7 +89,7 @@ impl Default for FileMoveOptions {
 /// Information about a successful file move operation.
 ///
 /// See also: [`move_file`].
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum FileMoveFinished {
     /// Destination file was freshly created and the contents of the source
     /// file were moved. `method` will describe how the move was made.
@@ -65,10 +111,28 @@ pub enum FileMoveFinished {
         method: FileMoveMethod,
     },
 
+    /// Destination file existed and was kept, so the source file was moved
+    /// to a freshly created alternative path next to it instead.
+    ///
+    /// This can be returned by [`move_file`] or [`move_file_with_progress`]
+    /// if `options.colliding_file_behaviour` is set to [`CollidingFileBehaviour::KeepBoth`].
+    CreatedAtAlternativePath {
+        /// Path the file was moved to.
+        destination_file_path: PathBuf,
+
+        /// The number of bytes transferred in the move (i.e. the file size).
+        bytes_copied: u64,
+
+        /// How the move was accomplished.
+        method: FileMoveMethod,
+    },
+
     /// File was not moved because the destination file already existed.
     ///
     /// This can be returned by [`move_file`] or [`move_file_with_progress`]
-    /// if `options.colliding_file_behaviour` is set to [`CollidingFileBehaviour::Skip`].
+    /// if `options.colliding_file_behaviour` is set to [`CollidingFileBehaviour::Skip`],
+    /// or to [`CollidingFileBehaviour::OverwriteIfNewer`] or [`CollidingFileBehaviour::OverwriteIfDifferent`]
+    /// and the destination file was not overwritten.
     ///
     /// Note that this means the source file still exists.
     Skipped,
@@ -133,6 +197,9 @@ pub enum FileMoveMethod {
 ///   then copying will be aborted with [`SourceAndDestinationAreTheSame`].
 /// - If the destination path has other issues (is a directory, does not have the correct permissions, etc.),
 ///   [`UnableToAccessDestinationFile`] will be returned.
+/// - If the file has to be copied, [`options.verify`] is enabled, and the destination file
+///   does not match the source file after copying, [`VerificationFailed`] will be returned
+///   (and the source file is left untouched).
 ///
 /// There do exist other failure points, mostly due to unavoidable
 /// [time-of-check time-of-use](https://en.wikipedia.org/wiki/Time-of-check_to_time-of-use)
@@ -171,12 +238,14 @@ pub enum FileMoveMethod {
 ///
 ///
 /// [`options.colliding_file_behaviour`]: FileMoveOptions::colliding_file_behaviour
+/// [`options.verify`]: FileMoveOptions::verify
 /// [`SourceFileNotFound`]: FileError::SourceFileNotFound
 /// [`SourcePathNotAFile`]: FileError::SourcePathNotAFile
 /// [`UnableToAccessSourceFile`]: FileError::UnableToAccessSourceFile
 /// [`DestinationPathAlreadyExists`]: FileError::DestinationPathAlreadyExists
 /// [`UnableToAccessDestinationFile`]: FileError::UnableToAccessDestinationFile
 /// [`SourceAndDestinationAreTheSame`]: FileError::SourceAndDestinationAreTheSame
+/// [`VerificationFailed`]: FileError::VerificationFailed
 /// [`OtherIoError`]: FileError::OtherIoError
 /// [^unix-mv]: Source for coreutils' `mv` is available
 ///   [here](https://github.com/coreutils/coreutils/blob/ccf47cad93bc0b85da0401b0a9d4b652e4c930e4/src/mv.c#L196-L244).
@@ -195,7 +264,7 @@ where
 
     let validated_source_path = validate_source_file_path(source_file_path)?;
 
-    let (validated_destination_file_path, destination_file_exists) =
+    let (validated_destination_file_path, destination_file_exists, is_alternative_path) =
         match validate_destination_file_path(
             &validated_source_path,
             destination_file_path,
@@ -205,7 +274,7 @@ where
                 return Ok(FileMoveFinished::Skipped);
             }
             DestinationValidationAction::Continue(info) => {
-                (info.destination_file_path, info.exists)
+                (info.destination_file_path, info.exists, info.is_alternative_path)
             }
         };
 
@@ -229,28 +298,58 @@ where
     };
 
     if fs::rename(source_file_path_to_rename, &validated_destination_file_path).is_ok() {
+        sync_directories_after_rename(
+            source_file_path_to_rename,
+            &validated_destination_file_path,
+            options.durability,
+        )?;
+
         // Get size of file that we just renamed.
         let target_file_path_metadata = fs::metadata(&validated_destination_file_path)
             .map_err(|error| FileError::OtherIoError { error })?;
 
-        match destination_file_exists {
-            true => Ok(FileMoveFinished::Overwritten {
-                bytes_copied: target_file_path_metadata.len(),
-                method: FileMoveMethod::Rename,
-            }),
-            false => Ok(FileMoveFinished::Created {
-                bytes_copied: target_file_path_metadata.len(),
-                method: FileMoveMethod::Rename,
-            }),
-        }
+        Ok(finished_move(
+            destination_file_exists,
+            is_alternative_path.then_some(validated_destination_file_path),
+            target_file_path_metadata.len(),
+            FileMoveMethod::Rename,
+        ))
     } else {
         // Copy to destination, then delete original file.
         // Special case: if the original was a symlink to a file, we need to
         // delete the symlink, not the file it points to.
 
-        let num_bytes_copied =
-            fs::copy(&validated_source_file_path, validated_destination_file_path)
-                .map_err(|error| FileError::OtherIoError { error })?;
+        // The copy must survive a power loss before we remove the source file.
+        // Its contents are synced while copying, before the preserved metadata
+        // can make it read-only (see `copy_and_verify_file`).
+        let num_bytes_copied = copy_and_verify_file(
+            &validated_source_file_path,
+            &validated_destination_file_path,
+            options.verify,
+            options.durability.syncs_files(),
+        )?;
+
+        apply_preserved_ownership(
+            &validated_source_file_path,
+            &validated_destination_file_path,
+            options.metadata_preservation,
+        )
+        .and_then(|()| {
+            apply_preserved_metadata(
+                &validated_source_file_path,
+                &validated_destination_file_path,
+                options.metadata_preservation,
+            )
+        })
+        .map_err(|error| FileError::UnableToPreserveMetadata {
+            path: validated_destination_file_path.clone(),
+            error,
+        })?;
+
+        options
+            .durability
+            .sync_parent_directory(&validated_destination_file_path)
+            .map_err(|error| FileError::OtherIoError { error })?;
 
         let source_file_path_to_remove = if source_file_was_symlink_to_file {
             // `source_file_path` instead of `validated_source_file_path` is intentional:
@@ -269,17 +368,65 @@ where
             FileRemoveError::OtherIoError { error } => FileError::OtherIoError { error },
         })?;
 
+        options
+            .durability
+            .sync_parent_directory(source_file_path_to_remove)
+            .map_err(|error| FileError::OtherIoError { error })?;
 
-        match destination_file_exists {
-            true => Ok(FileMoveFinished::Overwritten {
-                bytes_copied: num_bytes_copied,
-                method: FileMoveMethod::CopyAndDelete,
-            }),
-            false => Ok(FileMoveFinished::Created {
-                bytes_copied: num_bytes_copied,
-                method: FileMoveMethod::CopyAndDelete,
-            }),
-        }
+
+        Ok(finished_move(
+            destination_file_exists,
+            is_alternative_path.then_some(validated_destination_file_path),
+            num_bytes_copied,
+            FileMoveMethod::CopyAndDelete,
+        ))
+    }
+}
+
+
+
+/// Syncs the directories that contained the source file and now contain the destination file,
+/// if `durability` requires it.
+fn sync_directories_after_rename(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    durability: DurabilityLevel,
+) -> Result<(), FileError> {
+    durability
+        .sync_parent_directory(destination_file_path)
+        .and_then(
+            |_| match source_file_path.parent() == destination_file_path.parent() {
+                true => Ok(()),
+                false => durability.sync_parent_directory(source_file_path),
+            },
+        )
+        .map_err(|error| FileError::OtherIoError { error })
+}
+
+
+fn finished_move(
+    destination_file_existed: bool,
+    alternative_destination_file_path: Option<PathBuf>,
+    bytes_copied: u64,
+    method: FileMoveMethod,
+) -> FileMoveFinished {
+    if let Some(destination_file_path) = alternative_destination_file_path {
+        return FileMoveFinished::CreatedAtAlternativePath {
+            destination_file_path,
+            bytes_copied,
+            method,
+        };
+    }
+
+    match destination_file_existed {
+        true => FileMoveFinished::Overwritten {
+            bytes_copied,
+            method,
+        },
+        false => FileMoveFinished::Created {
+            bytes_copied,
+            method,
+        },
     }
 }
 
@@ -314,19 +461,55 @@ pub struct FileMoveWithProgressOptions {
     ///
     /// [`copy_file_with_progress`]: super::copy_file_with_progress
     pub progress_update_byte_interval: u64,
+
+    /// Which metadata of the source file to apply to the destination file
+    /// if the file has to be copied (see [`FileMoveMethod::CopyAndDelete`]).
+    /// A renamed file always keeps all of its metadata.
+    ///
+    /// See [`MetadataPreservation`] for more details.
+    ///
+    /// Defaults to [`MetadataPreservation::NONE`].
+    pub metadata_preservation: MetadataPreservation,
+
+    /// Whether to verify the destination file if the file has to be copied
+    /// (see [`FileMoveMethod::CopyAndDelete`]). The source file is only removed
+    /// once the copy has been verified.
+    ///
+    /// See [`CopyVerification`] for more details.
+    ///
+    /// Defaults to [`CopyVerification::DISABLED`].
+    pub verify: CopyVerification,
+
+    /// How much of the move to sync to disk before returning.
+    ///
+    /// If the file has to be copied, the copy is synced before the source file is removed.
+    /// A renamed file has no new contents to sync, so renames are only affected by
+    /// [`DurabilityLevel::FilesAndDirectories`], which syncs the directories
+    /// that contained the source file and now contain the destination file.
+    ///
+    /// See [`DurabilityLevel`] for more details.
+    ///
+    /// Defaults to [`DurabilityLevel::None`].
+    pub durability: DurabilityLevel,
 }
 
 impl Default for FileMoveWithProgressOptions {
     /// Constructs a default [`FileMoveOptions`]:
     /// - existing destination files will not be overwritten, and will cause an error ([`CollidingFileBehaviour::Abort`]),
     /// - read and write buffers with be 64 KiB large,
-    /// - the progress report closure interval will be 512 KiB.
+    /// - the progress report closure interval will be 512 KiB,
+    /// - no metadata is preserved if the file has to be copied ([`MetadataPreservation::NONE`]),
+    /// - the file is not verified if it has to be copied ([`CopyVerification::DISABLED`]),
+    /// - nothing is synced to disk ([`DurabilityLevel::None`]).
     fn default() -> Self {
         Self {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
             read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
             write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
             progress_update_byte_interval: DEFAULT_PROGRESS_UPDATE_BYTE_INTERVAL,
+            metadata_preservation: MetadataPreservation::NONE,
+            verify: CopyVerification::DISABLED,
+            durability: DurabilityLevel::None,
         }
     }
 }
@@ -376,6 +559,17 @@ impl Default for FileMoveWithProgressOptions {
 /// which is `file_size / progress_update_byte_interval`.
 ///
 ///
+/// ## Cancellation
+/// The `progress_handler` closure can return either `()`, a [`ProgressAction`],
+/// or a [`ControlFlow<()>`](std::ops::ControlFlow).
+/// Returning [`ProgressAction::Abort`] while the file is being copied stops the move
+/// as soon as possible: the partially written destination file is removed,
+/// the source file is left untouched, and [`Cancelled`] is returned.
+///
+/// If the file was moved by renaming it, the only progress report is emitted
+/// after the move has already completed, so there is nothing left to cancel.
+///
+///
 /// # Errors
 /// If the file cannot be moved to the destination, a [`FileError`] is returned;
 /// see its documentation for more details. Here is a non-exhaustive list of error causes:
@@ -389,6 +583,10 @@ impl Default for FileMoveWithProgressOptions {
 ///   then copying will be aborted with [`SourceAndDestinationAreTheSame`].
 /// - If the destination path has other issues (is a directory, does not have the correct permissions, etc.),
 ///   [`UnableToAccessDestinationFile`] will be returned.
+/// - If the file has to be copied, [`options.verify`] is enabled, and the destination file
+///   does not match the source file after copying, [`VerificationFailed`] will be returned
+///   (and the source file is left untouched).
+/// - If the progress handler cancels the move, [`Cancelled`] will be returned.
 ///
 /// There do exist other failure points, mostly due to unavoidable
 /// [time-of-check time-of-use](https://en.wikipedia.org/wiki/Time-of-check_to_time-of-use)
@@ -428,16 +626,19 @@ impl Default for FileMoveWithProgressOptions {
 ///
 /// [`options.progress_update_byte_interval`]: FileMoveWithProgressOptions::progress_update_byte_interval
 /// [`options.colliding_file_behaviour`]: FileMoveWithProgressOptions::colliding_file_behaviour
+/// [`options.verify`]: FileMoveWithProgressOptions::verify
 /// [`SourceFileNotFound`]: FileError::SourceFileNotFound
 /// [`SourcePathNotAFile`]: FileError::SourcePathNotAFile
 /// [`UnableToAccessSourceFile`]: FileError::UnableToAccessSourceFile
 /// [`DestinationPathAlreadyExists`]: FileError::DestinationPathAlreadyExists
 /// [`UnableToAccessDestinationFile`]: FileError::UnableToAccessDestinationFile
 /// [`SourceAndDestinationAreTheSame`]: FileError::SourceAndDestinationAreTheSame
+/// [`VerificationFailed`]: FileError::VerificationFailed
+/// [`Cancelled`]: FileError::Cancelled
 /// [`OtherIoError`]: FileError::OtherIoError
 /// [^unix-mv]: Source for coreutils' `mv` is available
 ///   [here](https://github.com/coreutils/coreutils/blob/ccf47cad93bc0b85da0401b0a9d4b652e4c930e4/src/mv.c#L196-L244).
-pub fn move_file_with_progress<S, D, P>(
+pub fn move_file_with_progress<S, D, P, R>(
     source_file_path: S,
     destination_file_path: D,
     options: FileMoveWithProgressOptions,
@@ -446,7 +647,8 @@ pub fn move_file_with_progress<S, D, P>(
 where
     S: AsRef<Path>,
     D: AsRef<Path>,
-    P: FnMut(&FileProgress),
+    P: FnMut(&FileProgress) -> R,
+    R: Into<ProgressAction>,
 {
     let source_file_path = source_file_path.as_ref();
     let destination_file_path = destination_file_path.as_ref();
@@ -454,7 +656,7 @@ where
 
     let validated_source_path = validate_source_file_path(source_file_path)?;
 
-    let (validated_destination_file_path, destination_file_exists) =
+    let (validated_destination_file_path, destination_file_exists, is_alternative_path) =
         match validate_destination_file_path(
             &validated_source_path,
             destination_file_path,
@@ -464,7 +666,7 @@ where
                 return Ok(FileMoveFinished::Skipped);
             }
             DestinationValidationAction::Continue(info) => {
-                (info.destination_file_path, info.exists)
+                (info.destination_file_path, info.exists, info.is_alternative_path)
             }
         };
 
@@ -488,33 +690,44 @@ where
     };
 
     if fs::rename(source_file_path_to_rename, &validated_destination_file_path).is_ok() {
+        sync_directories_after_rename(
+            source_file_path_to_rename,
+            &validated_destination_file_path,
+            options.durability,
+        )?;
+
         // Get size of file that we just renamed, emit one progress report, and return.
 
         let target_file_path_size_bytes = fs::metadata(&validated_destination_file_path)
             .map_err(|error| FileError::OtherIoError { error })?
             .len();
 
-        progress_handler(&FileProgress {
+        // The file has already been moved, so the returned action is irrelevant.
+        let _ = progress_handler(&FileProgress {
             bytes_finished: target_file_path_size_bytes,
+            bytes_written: 0,
             bytes_total: target_file_path_size_bytes,
+            phase: FileProgressPhase::Copying,
         });
 
 
-        match destination_file_exists {
-            true => Ok(FileMoveFinished::Overwritten {
-                bytes_copied: target_file_path_size_bytes,
-                method: FileMoveMethod::Rename,
-            }),
-            false => Ok(FileMoveFinished::Created {
-                bytes_copied: target_file_path_size_bytes,
-                method: FileMoveMethod::Rename,
-            }),
-        }
+        Ok(finished_move(
+            destination_file_exists,
+            is_alternative_path.then_some(validated_destination_file_path),
+            target_file_path_size_bytes,
+            FileMoveMethod::Rename,
+        ))
     } else {
         // It's impossible for us to just rename the file,
         // so we need to copy and delete the original.
-
-        let bytes_written = copy_file_with_progress_unchecked(
+        // If the copy is cancelled, we return early and the original is left untouched.
+
+        // Reflinks are only possible within a single filesystem,
+        // in which case the rename above would have succeeded.
+        let CopiedFileContents {
+            bytes_copied: bytes_written,
+            ..
+        } = copy_file_with_progress_unchecked(
             &validated_source_file_path,
             &validated_destination_file_path,
             FileCopyWithProgressOptions {
@@ -522,10 +735,25 @@ where
                 read_buffer_size: options.read_buffer_size,
                 write_buffer_size: options.write_buffer_size,
                 progress_update_byte_interval: options.progress_update_byte_interval,
+                reflink_behaviour: ReflinkBehaviour::Never,
+                metadata_preservation: options.metadata_preservation,
+                extended_attributes: ExtendedAttributeFilter::NONE,
+                sparse_behaviour: SparseFileBehaviour::Never,
+                verify: options.verify,
+                resume_behaviour: ResumeBehaviour::Never,
+                atomic: false,
+                // The copy must survive a power loss before we remove the source file;
+                // the directory containing it is synced below.
+                durability: options.durability.without_directories(),
             },
-            progress_handler,
+            |progress| progress_handler(progress).into(),
         )?;
 
+        options
+            .durability
+            .sync_parent_directory(&validated_destination_file_path)
+            .map_err(|error| FileError::OtherIoError { error })?;
+
 
         let source_file_path_to_remove = if source_file_was_symlink_to_file {
             // `source_file_path` instead of `validated_source_file_path` is intentional:
@@ -544,16 +772,17 @@ where
             FileRemoveError::OtherIoError { error } => FileError::OtherIoError { error },
         })?;
 
+        options
+            .durability
+            .sync_parent_directory(source_file_path_to_remove)
+            .map_err(|error| FileError::OtherIoError { error })?;
+
 
-        match destination_file_exists {
-            true => Ok(FileMoveFinished::Overwritten {
-                bytes_copied: bytes_written,
-                method: FileMoveMethod::CopyAndDelete,
-            }),
-            false => Ok(FileMoveFinished::Created {
-                bytes_copied: bytes_written,
-                method: FileMoveMethod::CopyAndDelete,
-            }),
-        }
+        Ok(finished_move(
+            destination_file_exists,
+            is_alternative_path.then_some(validated_destination_file_path),
+            bytes_written,
+            FileMoveMethod::CopyAndDelete,
+        ))
     }
 }
@@ -1,5 +1,7 @@
 use std::io::Write;
 
+use crate::ProgressAction;
+
 /// File copying or moving progress.
 ///
 /// Primarily used in [`copy_file_with_progress`] and [`move_file_with_progress`].
@@ -10,19 +12,61 @@ use std::io::Write;
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct FileProgress {
     /// Current number of bytes copied or moved to the destination.
+    ///
+    /// This counts the logical size of the file, meaning holes that are skipped
+    /// when copying sparse files (see [`SparseFileBehaviour`]) count towards it as well.
+    ///
+    ///
+    /// [`SparseFileBehaviour`]: super::SparseFileBehaviour
     pub bytes_finished: u64,
 
+    /// Current number of bytes physically written to the destination.
+    ///
+    /// This equals [`bytes_finished`][Self::bytes_finished], unless holes in a sparse file
+    /// have been skipped, or the file was cloned or renamed (in which case no data
+    /// has been written at all and this is zero).
+    pub bytes_written: u64,
+
     /// Total number of bytes that must be copied or moved to the destination
     /// to complete the copy or move operation.
     ///
     /// This value is always smaller or at most equal to
     /// the  [`bytes_finished`][Self::bytes_finished] field.
     pub bytes_total: u64,
+
+    /// Whether the file is still being copied, or is being verified.
+    pub phase: FileProgressPhase,
+}
+
+
+/// Phase of a file copy or move, see [`FileProgress::phase`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FileProgressPhase {
+    /// The contents of the file are being copied.
+    Copying,
+
+    /// The file has been copied, and the destination file is being read back
+    /// to verify it (see [`CopyVerification`]).
+    ///
+    /// Only reported if [`CopyVerification::reports_progress`] is `true`.
+    ///
+    ///
+    /// [`CopyVerification`]: super::CopyVerification
+    /// [`CopyVerification::reports_progress`]: super::CopyVerification::reports_progress
+    Verifying {
+        /// Number of bytes of the destination file that have been verified so far.
+        bytes_verified: u64,
+    },
 }
 
 
 /// A file write progress handler that implements `Write` and just passes data through.
-pub(crate) struct ProgressWriter<W: Write, F: FnMut(&FileProgress)> {
+///
+/// If the progress handler returns [`ProgressAction::Abort`], the writer is marked as cancelled
+/// and all subsequent writes (including the one that triggered the progress report)
+/// return an error, which stops e.g. [`std::io::copy`]. Callers should check
+/// [`Self::is_cancelled`] when they encounter a write error.
+pub(crate) struct ProgressWriter<W: Write, F: FnMut(&FileProgress) -> ProgressAction> {
     /// Current file copying or moving progress.
     progress: FileProgress,
 
@@ -37,9 +81,12 @@ pub(crate) struct ProgressWriter<W: Write, F: FnMut(&FileProgress)> {
 
     /// Progress report handler.
     handler: F,
+
+    /// Set to `true` once the progress handler returns [`ProgressAction::Abort`].
+    cancelled: bool,
 }
 
-impl<W: Write, F: FnMut(&FileProgress)> ProgressWriter<W, F> {
+impl<W: Write, F: FnMut(&FileProgress) -> ProgressAction> ProgressWriter<W, F> {
     /// Initialize a new `ProgressWriter` by providing:
     /// - a writer,
     /// - your progress handler,
@@ -49,33 +96,63 @@ impl<W: Write, F: FnMut(&FileProgress)> ProgressWriter<W, F> {
         Self {
             progress: FileProgress {
                 bytes_finished: 0,
+                bytes_written: 0,
                 bytes_total,
+                phase: FileProgressPhase::Copying,
             },
             inner,
             progress_report_byte_interval: progress_update_byte_interval,
             bytes_written_since_last_progress_report: 0,
             handler,
+            cancelled: false,
         }
     }
 
+    /// Makes progress start at `bytes_finished` instead of zero,
+    /// for when the first `bytes_finished` bytes have already been copied.
+    pub fn resuming_from(mut self, bytes_finished: u64) -> Self {
+        self.progress.bytes_finished = bytes_finished;
+        self
+    }
+
+    /// Returns `true` if the progress handler requested the operation to be cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Returns the last known progress.
+    pub fn progress(&self) -> &FileProgress {
+        &self.progress
+    }
+
     /// Consumes `self` and returns the inner writer, the last known progress and the progress report closure.
     pub fn into_inner(self) -> (W, FileProgress, F) {
         (self.inner, self.progress, self.handler)
     }
 }
 
-impl<W: Write, F: FnMut(&FileProgress)> Write for ProgressWriter<W, F> {
+impl<W: Write, F: FnMut(&FileProgress) -> ProgressAction> Write for ProgressWriter<W, F> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.cancelled {
+            return Err(operation_cancelled_io_error());
+        }
+
         let inner_write_result = self.inner.write(buf);
 
         if let Ok(bytes_written) = &inner_write_result {
             self.progress.bytes_finished += *bytes_written as u64;
+            self.progress.bytes_written += *bytes_written as u64;
             self.bytes_written_since_last_progress_report += *bytes_written as u64;
         }
 
         if self.bytes_written_since_last_progress_report > self.progress_report_byte_interval {
-            (self.handler)(&self.progress);
+            let action = (self.handler)(&self.progress);
             self.bytes_written_since_last_progress_report = 0;
+
+            if action.is_abort() {
+                self.cancelled = true;
+                return Err(operation_cancelled_io_error());
+            }
         }
 
         inner_write_result
@@ -85,3 +162,12 @@ impl<W: Write, F: FnMut(&FileProgress)> Write for ProgressWriter<W, F> {
         self.inner.flush()
     }
 }
+
+
+/// Constructs the IO error [`ProgressWriter`] returns after the operation has been cancelled.
+///
+/// Note that this must not be [`std::io::ErrorKind::Interrupted`],
+/// as e.g. [`std::io::copy`] would simply retry the write.
+fn operation_cancelled_io_error() -> std::io::Error {
+    std::io::Error::other("operation was cancelled by the progress handler")
+}
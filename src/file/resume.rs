@@ -0,0 +1,187 @@
+use std::{
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use_enabled_fs_module!();
+
+
+/// Number of blocks compared by [`ResumeBehaviour::VerifySampled`].
+const SAMPLE_COUNT: u64 = 16;
+
+/// Size of each block compared by [`ResumeBehaviour::VerifySampled`].
+const SAMPLE_SIZE_BYTES: u64 = 64 * 1024;
+
+/// Size of the buffers used when comparing the contents of two files.
+const COMPARISON_BUFFER_SIZE: usize = 64 * 1024;
+
+
+/// Controls whether a partially copied destination file
+/// (e.g. one left behind by an interrupted or cancelled copy) is resumed
+/// instead of being copied again from the start.
+///
+/// A destination file is considered to be partially copied if it is not larger than the
+/// source file, and if its contents match the start (prefix) of the source file.
+/// How thoroughly the prefix is compared depends on the chosen behaviour.
+///
+/// The colliding file behaviour is applied first, and only a destination file
+/// that it would overwrite can be resumed. If such a file can be resumed,
+/// the copy continues from the last byte of the destination file instead of overwriting it.
+///
+/// Unless this is [`ResumeBehaviour::Never`], cancelling a copy leaves the partially
+/// written destination file in place, so that the copy can be resumed later.
+///
+/// A resumed copy is reported as [`FileCopyFinished::Resumed`], and is always
+/// copied through buffers in user space.
+///
+///
+/// [`FileCopyFinished::Resumed`]: super::FileCopyFinished::Resumed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResumeBehaviour {
+    /// Partially copied destination files are never resumed.
+    ///
+    /// This is the default.
+    Never,
+
+    /// Partially copied destination files are resumed if their sizes are compatible
+    /// and 16 evenly spaced blocks of 64 KiB (including the first and last block)
+    /// match the source file. Smaller files are compared in full.
+    ///
+    /// This is fast even for very large files, but will not detect corruption
+    /// in between the sampled blocks.
+    VerifySampled,
+
+    /// Partially copied destination files are resumed if their entire contents
+    /// match the start of the source file.
+    ///
+    /// This reads the entire destination file (and the same amount of the source file)
+    /// before resuming.
+    VerifyFull,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ResumeBehaviour {
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+
+/// Returns the number of bytes the copy can be resumed from, or `None` if the destination file
+/// is not a partial copy of the source file (or if `resume_behaviour` is [`ResumeBehaviour::Never`]).
+pub(super) fn resumable_prefix_length(
+    source_file_path: &Path,
+    destination_file_path: &Path,
+    resume_behaviour: ResumeBehaviour,
+) -> io::Result<Option<u64>> {
+    let source_file_size = fs::metadata(source_file_path)?.len();
+    let prefix_length = fs::metadata(destination_file_path)?.len();
+
+    if prefix_length > source_file_size {
+        return Ok(None);
+    }
+
+
+    let ranges_to_compare = match resume_behaviour {
+        ResumeBehaviour::Never => return Ok(None),
+        ResumeBehaviour::VerifySampled => sampled_ranges(prefix_length),
+        ResumeBehaviour::VerifyFull => vec![(0, prefix_length)],
+    };
+
+    let mut source_file = fs::File::open(source_file_path)?;
+    let mut destination_file = fs::File::open(destination_file_path)?;
+
+    for (offset, length) in ranges_to_compare {
+        if !ranges_match(&mut source_file, &mut destination_file, offset, length)? {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(prefix_length))
+}
+
+
+/// Returns the `(offset, length)` ranges of a prefix of `prefix_length` bytes
+/// that are compared by [`ResumeBehaviour::VerifySampled`].
+fn sampled_ranges(prefix_length: u64) -> Vec<(u64, u64)> {
+    if prefix_length <= SAMPLE_COUNT * SAMPLE_SIZE_BYTES {
+        return vec![(0, prefix_length)];
+    }
+
+    // The samples are spread out evenly, so the first one starts at the beginning
+    // of the prefix, and the last one ends at its end (where an interrupted write is most likely).
+    let last_sample_offset = prefix_length - SAMPLE_SIZE_BYTES;
+
+    (0..SAMPLE_COUNT)
+        .map(|sample_index| {
+            (
+                last_sample_offset * sample_index / (SAMPLE_COUNT - 1),
+                SAMPLE_SIZE_BYTES,
+            )
+        })
+        .collect()
+}
+
+
+/// Returns `true` if `length` bytes starting at `offset` are the same in both files.
+fn ranges_match(
+    first_file: &mut fs::File,
+    second_file: &mut fs::File,
+    offset: u64,
+    length: u64,
+) -> io::Result<bool> {
+    first_file.seek(SeekFrom::Start(offset))?;
+    second_file.seek(SeekFrom::Start(offset))?;
+
+    let mut first_buffer = vec![0u8; COMPARISON_BUFFER_SIZE];
+    let mut second_buffer = vec![0u8; COMPARISON_BUFFER_SIZE];
+
+    let mut bytes_remaining = length;
+    while bytes_remaining > 0 {
+        let chunk_size = bytes_remaining.min(COMPARISON_BUFFER_SIZE as u64) as usize;
+
+        first_file.read_exact(&mut first_buffer[..chunk_size])?;
+        second_file.read_exact(&mut second_buffer[..chunk_size])?;
+
+        if first_buffer[..chunk_size] != second_buffer[..chunk_size] {
+            return Ok(false);
+        }
+
+        bytes_remaining -= chunk_size as u64;
+    }
+
+    Ok(true)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_prefixes_are_compared_in_full() {
+        assert_eq!(sampled_ranges(0), vec![(0, 0)]);
+        assert_eq!(sampled_ranges(1000), vec![(0, 1000)]);
+        assert_eq!(
+            sampled_ranges(SAMPLE_COUNT * SAMPLE_SIZE_BYTES),
+            vec![(0, SAMPLE_COUNT * SAMPLE_SIZE_BYTES)]
+        );
+    }
+
+    #[test]
+    fn samples_of_large_prefixes_cover_both_ends() {
+        let prefix_length = 10 * 1024 * 1024 * 1024 + 17;
+        let ranges = sampled_ranges(prefix_length);
+
+        assert_eq!(ranges.len() as u64, SAMPLE_COUNT);
+        assert_eq!(ranges.first(), Some(&(0, SAMPLE_SIZE_BYTES)));
+        assert_eq!(
+            ranges.last(),
+            Some(&(prefix_length - SAMPLE_SIZE_BYTES, SAMPLE_SIZE_BYTES))
+        );
+
+        assert!(ranges
+            .windows(2)
+            .all(|window| window[0].0 + window[0].1 <= window[1].0));
+    }
+}
@@ -0,0 +1,410 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use_enabled_fs_module!();
+
+use super::{
+    verify::{hash_zeroes, Hasher},
+    FileCopyWithProgressOptions,
+    FileProgress,
+    FileProgressPhase,
+};
+use crate::ProgressAction;
+
+
+/// Size of the blocks that are checked for zeroes when detecting holes by their contents.
+///
+/// This matches the block size of most filesystems; smaller runs of zeroes
+/// could not be turned into holes anyway.
+const ZERO_DETECTION_BLOCK_SIZE: usize = 4096;
+
+
+/// Controls whether holes in sparse files are preserved when copying them.
+///
+/// A sparse file (e.g. a virtual machine image or a database file) contains ranges that
+/// have never been written to, called *holes*, which read as zeroes, but take up no disk space.
+/// When copied naively, holes are turned into real zeroes, inflating the copy to the full
+/// logical size of the file.
+///
+/// When sparse copying is enabled, holes are skipped instead of written, so the destination
+/// file stays sparse. How many bytes have physically been written is reported as
+/// [`FileProgress::bytes_written`], next to the logical [`FileProgress::bytes_finished`].
+///
+/// On Linux, holes in the source file are found with `lseek`'s `SEEK_DATA` and `SEEK_HOLE`.
+/// If the source filesystem does not support that (or on other platforms), blocks
+/// that contain only zeroes are treated as holes instead.
+///
+/// Whether a file has been copied sparsely is reported as [`FileCopyMethod::Sparse`].
+///
+///
+/// [`FileCopyMethod::Sparse`]: super::FileCopyMethod::Sparse
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SparseFileBehaviour {
+    /// Holes are not preserved; the full logical size of the file is written.
+    ///
+    /// This is the default.
+    Never,
+
+    /// Holes in the source file are preserved, but data is copied as-is,
+    /// even if it contains blocks of zeroes.
+    ///
+    /// If holes can not be detected directly, blocks of zeroes are turned into holes instead.
+    Auto,
+
+    /// Holes in the source file are preserved, and blocks of zeroes in the data
+    /// are turned into holes as well, similar to `cp --sparse=always`.
+    ///
+    /// Use this to make a copy of a file that is sparser than the original.
+    Always,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for SparseFileBehaviour {
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+
+/// Outcome of [`copy_file_contents_sparsely_with_progress`].
+pub(super) enum SparseCopyOutcome {
+    /// The file has been fully copied.
+    Finished {
+        /// Logical number of bytes copied (i.e. the size of the destination file).
+        bytes_copied: u64,
+
+        /// Number of bytes physically written to the destination file.
+        bytes_written: u64,
+    },
+
+    /// The progress handler cancelled the copy.
+    ///
+    /// The destination file is left as-is; removing it is up to the caller.
+    Cancelled {
+        /// Logical number of bytes copied before the copy was cancelled.
+        bytes_finished: u64,
+    },
+}
+
+
+/// A range of the source file that is known to contain data.
+struct DataSegment {
+    start: u64,
+    end: u64,
+
+    /// Whether blocks of zeroes in this segment should be turned into holes.
+    detect_zero_blocks: bool,
+}
+
+
+/// Copies the contents of `source_file` into the (empty) `destination_file`, skipping holes
+/// as requested by `options.sparse_behaviour`, which must not be [`SparseFileBehaviour::Never`].
+///
+/// Data is read in chunks of `options.read_buffer_size` bytes. `progress_handler` is called whenever
+/// at least `options.progress_update_byte_interval` (logical) bytes have been copied
+/// since the last report, and once more after the file has been copied.
+///
+/// If a `source_hasher` is provided, the full logical contents of the source file
+/// (including the zeroes in holes) are fed into it.
+pub(super) fn copy_file_contents_sparsely_with_progress<F>(
+    source_file: &mut fs::File,
+    destination_file: &mut fs::File,
+    bytes_total: u64,
+    options: &FileCopyWithProgressOptions,
+    mut source_hasher: Option<&mut Box<dyn Hasher>>,
+    progress_handler: &mut F,
+) -> io::Result<SparseCopyOutcome>
+where
+    F: FnMut(&FileProgress) -> ProgressAction,
+{
+    let mut buffer = vec![0u8; options.read_buffer_size.max(ZERO_DETECTION_BLOCK_SIZE)];
+
+    let mut progress = FileProgress {
+        bytes_finished: 0,
+        bytes_written: 0,
+        bytes_total,
+        phase: FileProgressPhase::Copying,
+    };
+    let mut bytes_finished_at_last_report = 0;
+
+    // Reports progress if enough bytes have been copied since the last report.
+    // Returns `true` if the progress handler cancelled the copy.
+    let mut report_progress = |progress: &FileProgress, progress_handler: &mut F| {
+        if progress.bytes_finished >= progress.bytes_total
+            || progress.bytes_finished - bytes_finished_at_last_report
+                < options.progress_update_byte_interval
+        {
+            return false;
+        }
+
+        bytes_finished_at_last_report = progress.bytes_finished;
+        progress_handler(progress).is_abort()
+    };
+
+
+    while progress.bytes_finished < bytes_total {
+        let Some(segment) = next_data_segment(
+            source_file,
+            progress.bytes_finished,
+            bytes_total,
+            options.sparse_behaviour,
+        )?
+        else {
+            // Only a hole remains; it is created by extending the file below.
+            break;
+        };
+
+        // Skip the hole in front of the segment.
+        if let Some(hasher) = source_hasher.as_mut() {
+            hash_zeroes(hasher.as_mut(), segment.start - progress.bytes_finished);
+        }
+
+        progress.bytes_finished = segment.start;
+        if report_progress(&progress, progress_handler) {
+            return Ok(SparseCopyOutcome::Cancelled {
+                bytes_finished: progress.bytes_finished,
+            });
+        }
+
+        source_file.seek(SeekFrom::Start(segment.start))?;
+
+        while progress.bytes_finished < segment.end {
+            let chunk_size =
+                (segment.end - progress.bytes_finished).min(buffer.len() as u64) as usize;
+
+            let bytes_read = match source_file.read(&mut buffer[..chunk_size]) {
+                Ok(bytes_read) => bytes_read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+
+            if bytes_read == 0 {
+                // The source file has been truncated since we started copying it.
+                return finish_sparse_copy(destination_file, &mut progress, progress_handler);
+            }
+
+            let chunk = &buffer[..bytes_read];
+            let chunk_offset = progress.bytes_finished;
+
+            if let Some(hasher) = source_hasher.as_mut() {
+                hasher.update(chunk);
+            }
+
+            if segment.detect_zero_blocks {
+                for (run_start, run) in non_zero_runs(chunk) {
+                    write_at(destination_file, chunk_offset + run_start as u64, run)?;
+                    progress.bytes_written += run.len() as u64;
+                }
+            } else {
+                write_at(destination_file, chunk_offset, chunk)?;
+                progress.bytes_written += chunk.len() as u64;
+            }
+
+            progress.bytes_finished += bytes_read as u64;
+
+            if report_progress(&progress, progress_handler) {
+                return Ok(SparseCopyOutcome::Cancelled {
+                    bytes_finished: progress.bytes_finished,
+                });
+            }
+        }
+    }
+
+    if let Some(hasher) = source_hasher {
+        hash_zeroes(hasher.as_mut(), bytes_total - progress.bytes_finished);
+    }
+
+    progress.bytes_finished = bytes_total;
+    finish_sparse_copy(destination_file, &mut progress, progress_handler)
+}
+
+
+/// Extends the destination file to its full logical size (creating any trailing hole)
+/// and performs the final progress report.
+fn finish_sparse_copy<F>(
+    destination_file: &mut fs::File,
+    progress: &mut FileProgress,
+    progress_handler: &mut F,
+) -> io::Result<SparseCopyOutcome>
+where
+    F: FnMut(&FileProgress) -> ProgressAction,
+{
+    destination_file.set_len(progress.bytes_finished)?;
+    destination_file.flush()?;
+
+    // The file has been fully copied, so there is nothing left to cancel.
+    progress.bytes_total = progress.bytes_finished;
+    let _ = progress_handler(progress);
+
+    Ok(SparseCopyOutcome::Finished {
+        bytes_copied: progress.bytes_finished,
+        bytes_written: progress.bytes_written,
+    })
+}
+
+
+fn write_at(destination_file: &mut fs::File, offset: u64, data: &[u8]) -> io::Result<()> {
+    destination_file.seek(SeekFrom::Start(offset))?;
+    destination_file.write_all(data)
+}
+
+
+/// Returns the next range of the source file (at or after `offset`) that must be copied,
+/// or `None` if the rest of the file is a hole.
+#[cfg(target_os = "linux")]
+fn next_data_segment(
+    source_file: &fs::File,
+    offset: u64,
+    bytes_total: u64,
+    sparse_behaviour: SparseFileBehaviour,
+) -> io::Result<Option<DataSegment>> {
+    match linux::find_data_segment(source_file, offset, bytes_total) {
+        Ok(Some((start, end))) => Ok(Some(DataSegment {
+            start,
+            end,
+            detect_zero_blocks: sparse_behaviour == SparseFileBehaviour::Always,
+        })),
+        Ok(None) => Ok(None),
+        // The filesystem can't tell us where the holes are, so we look for zeroes instead.
+        Err(error) if linux::is_unsupported_error(&error) => Ok(Some(DataSegment {
+            start: offset,
+            end: bytes_total,
+            detect_zero_blocks: true,
+        })),
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns the next range of the source file (at or after `offset`) that must be copied,
+/// or `None` if the rest of the file is a hole.
+///
+/// Holes can not be detected directly on this platform, so we look for zeroes instead.
+#[cfg(not(target_os = "linux"))]
+fn next_data_segment(
+    _source_file: &fs::File,
+    offset: u64,
+    bytes_total: u64,
+    _sparse_behaviour: SparseFileBehaviour,
+) -> io::Result<Option<DataSegment>> {
+    Ok(Some(DataSegment {
+        start: offset,
+        end: bytes_total,
+        detect_zero_blocks: true,
+    }))
+}
+
+
+/// Splits `chunk` into blocks of [`ZERO_DETECTION_BLOCK_SIZE`] bytes and returns
+/// the runs of consecutive blocks that are not entirely zero, along with their offsets in `chunk`.
+fn non_zero_runs(chunk: &[u8]) -> Vec<(usize, &[u8])> {
+    let mut runs = Vec::new();
+    let mut run_start: Option<usize> = None;
+
+    for (block_index, block) in chunk.chunks(ZERO_DETECTION_BLOCK_SIZE).enumerate() {
+        let block_start = block_index * ZERO_DETECTION_BLOCK_SIZE;
+        let is_zero = block.iter().all(|byte| *byte == 0);
+
+        match (run_start, is_zero) {
+            (None, false) => run_start = Some(block_start),
+            (Some(start), true) => {
+                runs.push((start, &chunk[start..block_start]));
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = run_start {
+        runs.push((start, &chunk[start..]));
+    }
+
+    runs
+}
+
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{io, os::fd::AsRawFd};
+
+    /// Returns the first range of data at or after `offset` (and before `bytes_total`),
+    /// or `None` if there is no more data.
+    pub(super) fn find_data_segment<F>(
+        file: &F,
+        offset: u64,
+        bytes_total: u64,
+    ) -> io::Result<Option<(u64, u64)>>
+    where
+        F: AsRawFd,
+    {
+        let start = match seek(file, offset, libc::SEEK_DATA) {
+            Ok(start) => start,
+            // There is no data past the offset.
+            Err(error) if error.raw_os_error() == Some(libc::ENXIO) => return Ok(None),
+            Err(error) => return Err(error),
+        };
+
+        if start >= bytes_total {
+            return Ok(None);
+        }
+
+        // Every file ends with an implicit hole, so this only fails if the file is truncated.
+        let end = match seek(file, start, libc::SEEK_HOLE) {
+            Ok(end) => end,
+            Err(error) if error.raw_os_error() == Some(libc::ENXIO) => bytes_total,
+            Err(error) => return Err(error),
+        };
+
+        Ok(Some((start, end.min(bytes_total))))
+    }
+
+    /// Returns `true` if the error indicates that `SEEK_DATA` and `SEEK_HOLE` are not supported.
+    pub(super) fn is_unsupported_error(error: &io::Error) -> bool {
+        matches!(error.raw_os_error(), Some(libc::EINVAL | libc::EOPNOTSUPP))
+    }
+
+    fn seek<F>(file: &F, offset: u64, whence: libc::c_int) -> io::Result<u64>
+    where
+        F: AsRawFd,
+    {
+        // SAFETY: The file descriptor is valid for the duration of the call.
+        let result = unsafe { libc::lseek64(file.as_raw_fd(), offset as libc::off64_t, whence) };
+
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(result as u64)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_zero_runs_skip_zero_blocks() {
+        let mut chunk = vec![0u8; ZERO_DETECTION_BLOCK_SIZE * 5 + 100];
+        chunk[10] = 1;
+        chunk[ZERO_DETECTION_BLOCK_SIZE + 20] = 2;
+        chunk[ZERO_DETECTION_BLOCK_SIZE * 5 + 50] = 3;
+
+        let runs = non_zero_runs(&chunk)
+            .into_iter()
+            .map(|(start, run)| (start, run.len()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            runs,
+            [
+                (0, ZERO_DETECTION_BLOCK_SIZE * 2),
+                (ZERO_DETECTION_BLOCK_SIZE * 5, 100)
+            ]
+        );
+    }
+
+    #[test]
+    fn non_zero_runs_of_zeroes_are_empty() {
+        assert!(non_zero_runs(&[0u8; ZERO_DETECTION_BLOCK_SIZE * 3]).is_empty());
+        assert!(non_zero_runs(&[]).is_empty());
+    }
+}
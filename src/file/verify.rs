@@ -0,0 +1,507 @@
+use std::{
+    io::{self, Read},
+    path::Path,
+};
+
+use_enabled_fs_module!();
+
+use crate::DEFAULT_READ_BUFFER_SIZE;
+
+
+/// Computes a digest of file contents, which is used to verify copied files.
+///
+/// Implement this trait to verify copies using a hash function of your choosing
+/// (e.g. SHA-256), and pass a function constructing your hasher to [`CopyVerification::with_hasher`].
+/// A new hasher is constructed for each file that is hashed.
+///
+/// The built-in hasher is [`Xxh64Hasher`].
+pub trait Hasher {
+    /// Feeds the next chunk of file contents into the hasher.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the hasher and returns the digest of all data fed into it.
+    fn finish(self: Box<Self>) -> Vec<u8>;
+}
+
+
+/// Constructs a new hasher; see [`CopyVerification::with_hasher`].
+pub type HasherConstructor = fn() -> Box<dyn Hasher>;
+
+
+/// Wraps a [`HasherConstructor`], so that [`CopyVerification`] can implement
+/// [`PartialEq`] and [`Debug`] like the rest of the options.
+#[derive(Clone, Copy)]
+struct HasherConstructorFn(HasherConstructor);
+
+impl PartialEq for HasherConstructorFn {
+    fn eq(&self, other: &Self) -> bool {
+        // Function addresses are not guaranteed to be unique, but that is fine
+        // for comparing options; at worst, equal options compare as different.
+        self.0 as usize == other.0 as usize
+    }
+}
+
+impl Eq for HasherConstructorFn {}
+
+impl std::fmt::Debug for HasherConstructorFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("HasherConstructor")
+            .field(&(self.0 as usize as *const ()))
+            .finish()
+    }
+}
+
+
+
+/// Controls whether copied files are verified.
+///
+/// When verification is enabled, a hash of the source file is computed while it is being copied
+/// (or by reading it again when it is cloned, as its contents then never pass through user space),
+/// after which the destination file is read back and hashed as well.
+/// If the two hashes differ, the destination file is removed and
+/// [`FileError::VerificationFailed`] is returned. When moving a file by copying it,
+/// the source file is only removed once the copy has been verified.
+///
+/// Note that verification disables the kernel-accelerated copies on Linux,
+/// as the data must be read in user space in order to hash it.
+///
+/// By default, files are hashed with [`Xxh64Hasher`], a fast non-cryptographic hash
+/// that is well suited for catching corruption. Use [`CopyVerification::with_hasher`]
+/// to hash files with your own [`Hasher`] instead.
+///
+/// The default does not verify copies.
+///
+///
+/// [`FileError::VerificationFailed`]: crate::error::FileError::VerificationFailed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CopyVerification {
+    hasher_constructor: Option<HasherConstructorFn>,
+    report_progress: bool,
+}
+
+impl CopyVerification {
+    /// Copies are not verified. This is the default.
+    pub const DISABLED: Self = Self {
+        hasher_constructor: None,
+        report_progress: false,
+    };
+
+    /// Copies are verified using the built-in [`Xxh64Hasher`].
+    pub const ENABLED: Self = Self::with_hasher(new_xxh64_hasher);
+
+    /// Copies are verified using hashers constructed by `hasher_constructor`.
+    pub const fn with_hasher(hasher_constructor: HasherConstructor) -> Self {
+        Self {
+            hasher_constructor: Some(HasherConstructorFn(hasher_constructor)),
+            report_progress: false,
+        }
+    }
+
+    /// Returns a copy of these options that sets whether verification is reported
+    /// as a separate progress phase (see [`FileProgressPhase::Verifying`]).
+    ///
+    /// This only has an effect on functions that report progress. Verification is not reported by default.
+    ///
+    ///
+    /// [`FileProgressPhase::Verifying`]: super::FileProgressPhase::Verifying
+    pub const fn reporting_progress(mut self, report_progress: bool) -> Self {
+        self.report_progress = report_progress;
+        self
+    }
+
+    /// Returns `true` if copies are verified.
+    pub const fn is_enabled(&self) -> bool {
+        self.hasher_constructor.is_some()
+    }
+
+    /// Returns `true` if verification is reported as a separate progress phase.
+    pub const fn reports_progress(&self) -> bool {
+        self.report_progress
+    }
+
+    /// Constructs a new hasher, or returns `None` if copies are not verified.
+    pub(crate) fn new_hasher(&self) -> Option<Box<dyn Hasher>> {
+        self.hasher_constructor
+            .map(|HasherConstructorFn(constructor)| constructor())
+    }
+}
+
+impl Default for CopyVerification {
+    /// Constructs [`CopyVerification::DISABLED`]: copies are not verified.
+    fn default() -> Self {
+        Self::DISABLED
+    }
+}
+
+
+fn new_xxh64_hasher() -> Box<dyn Hasher> {
+    Box::new(Xxh64Hasher::new())
+}
+
+
+
+const XXH64_PRIME_1: u64 = 0x9E37_79B1_85EB_CA87;
+const XXH64_PRIME_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const XXH64_PRIME_3: u64 = 0x1656_67B1_9E37_79F9;
+const XXH64_PRIME_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const XXH64_PRIME_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+/// Number of bytes XXH64 consumes at once.
+const XXH64_STRIPE_SIZE: usize = 32;
+
+
+/// The built-in [`Hasher`]: a streaming implementation of the 64-bit xxHash (XXH64, with a seed of zero).
+///
+/// xxHash is a fast non-cryptographic hash function; it reliably detects accidental corruption,
+/// but must not be relied upon to detect deliberate tampering. The digest is the 8-byte
+/// big-endian representation of the hash (its canonical form).
+#[derive(Clone, Debug)]
+pub struct Xxh64Hasher {
+    accumulators: [u64; 4],
+
+    /// Bytes that did not yet fill up a whole stripe.
+    buffer: [u8; XXH64_STRIPE_SIZE],
+    buffer_length: usize,
+
+    total_length: u64,
+}
+
+impl Xxh64Hasher {
+    /// Constructs a new hasher.
+    pub const fn new() -> Self {
+        Self {
+            accumulators: [
+                XXH64_PRIME_1.wrapping_add(XXH64_PRIME_2),
+                XXH64_PRIME_2,
+                0,
+                0u64.wrapping_sub(XXH64_PRIME_1),
+            ],
+            buffer: [0; XXH64_STRIPE_SIZE],
+            buffer_length: 0,
+            total_length: 0,
+        }
+    }
+
+    /// Returns the hash of all data fed into the hasher so far.
+    pub fn digest(&self) -> u64 {
+        let mut hash = if self.total_length >= XXH64_STRIPE_SIZE as u64 {
+            let [a, b, c, d] = self.accumulators;
+
+            let mut hash = a
+                .rotate_left(1)
+                .wrapping_add(b.rotate_left(7))
+                .wrapping_add(c.rotate_left(12))
+                .wrapping_add(d.rotate_left(18));
+
+            for accumulator in self.accumulators {
+                hash = xxh64_merge_round(hash, accumulator);
+            }
+
+            hash
+        } else {
+            XXH64_PRIME_5
+        };
+
+        hash = hash.wrapping_add(self.total_length);
+
+
+        let mut remaining = &self.buffer[..self.buffer_length];
+
+        while remaining.len() >= 8 {
+            let (lane, rest) = remaining.split_at(8);
+            remaining = rest;
+
+            hash ^= xxh64_round(0, read_u64_le(lane));
+            hash = hash
+                .rotate_left(27)
+                .wrapping_mul(XXH64_PRIME_1)
+                .wrapping_add(XXH64_PRIME_4);
+        }
+
+        if remaining.len() >= 4 {
+            let (lane, rest) = remaining.split_at(4);
+            remaining = rest;
+
+            // PANIC SAFETY: The lane is exactly four bytes long.
+            let lane = u32::from_le_bytes(lane.try_into().expect("lane to be four bytes"));
+
+            hash ^= (lane as u64).wrapping_mul(XXH64_PRIME_1);
+            hash = hash
+                .rotate_left(23)
+                .wrapping_mul(XXH64_PRIME_2)
+                .wrapping_add(XXH64_PRIME_3);
+        }
+
+        for byte in remaining {
+            hash ^= (*byte as u64).wrapping_mul(XXH64_PRIME_5);
+            hash = hash.rotate_left(11).wrapping_mul(XXH64_PRIME_1);
+        }
+
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(XXH64_PRIME_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(XXH64_PRIME_3);
+        hash ^= hash >> 32;
+
+        hash
+    }
+
+    fn consume_stripe(&mut self, stripe: &[u8]) {
+        for (accumulator, lane) in self.accumulators.iter_mut().zip(stripe.chunks_exact(8)) {
+            *accumulator = xxh64_round(*accumulator, read_u64_le(lane));
+        }
+    }
+}
+
+impl Default for Xxh64Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Xxh64Hasher {
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_length += data.len() as u64;
+
+        // Fill up and consume a partially filled stripe first.
+        if self.buffer_length > 0 {
+            let bytes_to_buffer = (XXH64_STRIPE_SIZE - self.buffer_length).min(data.len());
+
+            self.buffer[self.buffer_length..self.buffer_length + bytes_to_buffer]
+                .copy_from_slice(&data[..bytes_to_buffer]);
+            self.buffer_length += bytes_to_buffer;
+            data = &data[bytes_to_buffer..];
+
+            if self.buffer_length < XXH64_STRIPE_SIZE {
+                return;
+            }
+
+            let stripe = self.buffer;
+            self.consume_stripe(&stripe);
+            self.buffer_length = 0;
+        }
+
+        let mut stripes = data.chunks_exact(XXH64_STRIPE_SIZE);
+        for stripe in stripes.by_ref() {
+            self.consume_stripe(stripe);
+        }
+
+        let remainder = stripes.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_length = remainder.len();
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        self.digest().to_be_bytes().to_vec()
+    }
+}
+
+
+fn xxh64_round(accumulator: u64, lane: u64) -> u64 {
+    accumulator
+        .wrapping_add(lane.wrapping_mul(XXH64_PRIME_2))
+        .rotate_left(31)
+        .wrapping_mul(XXH64_PRIME_1)
+}
+
+fn xxh64_merge_round(hash: u64, accumulator: u64) -> u64 {
+    (hash ^ xxh64_round(0, accumulator))
+        .wrapping_mul(XXH64_PRIME_1)
+        .wrapping_add(XXH64_PRIME_4)
+}
+
+fn read_u64_le(lane: &[u8]) -> u64 {
+    // PANIC SAFETY: Callers always pass exactly eight bytes.
+    u64::from_le_bytes(lane.try_into().expect("lane to be eight bytes"))
+}
+
+
+
+/// A reader that feeds everything it reads into a [`Hasher`] (if there is one).
+pub(crate) struct HashingReader<'h, R: Read> {
+    inner: R,
+    hasher: Option<&'h mut Box<dyn Hasher>>,
+}
+
+impl<'h, R: Read> HashingReader<'h, R> {
+    pub(crate) fn new(inner: R, hasher: Option<&'h mut Box<dyn Hasher>>) -> Self {
+        Self { inner, hasher }
+    }
+}
+
+impl<R: Read> Read for HashingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes_read = self.inner.read(buf)?;
+
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(&buf[..bytes_read]);
+        }
+
+        Ok(bytes_read)
+    }
+}
+
+
+/// Feeds `length` zero bytes into the hasher (e.g. for a hole in a sparse file).
+pub(crate) fn hash_zeroes(hasher: &mut dyn Hasher, mut length: u64) {
+    const ZEROES: [u8; 8192] = [0; 8192];
+
+    while length > 0 {
+        let chunk_length = length.min(ZEROES.len() as u64) as usize;
+        hasher.update(&ZEROES[..chunk_length]);
+        length -= chunk_length as u64;
+    }
+}
+
+
+/// Reads `reader` to its end, feeding its contents into `hasher`.
+///
+/// `progress_handler` is called with the number of bytes hashed so far whenever
+/// at least `progress_update_byte_interval` bytes have been hashed since the last call.
+/// If it returns `false`, hashing stops and `Ok(None)` is returned.
+/// Otherwise, the total number of bytes hashed is returned.
+pub(crate) fn hash_reader<R, F>(
+    reader: &mut R,
+    hasher: &mut dyn Hasher,
+    buffer_size: usize,
+    progress_update_byte_interval: u64,
+    mut progress_handler: F,
+) -> io::Result<Option<u64>>
+where
+    R: Read,
+    F: FnMut(u64) -> bool,
+{
+    let mut buffer = vec![0u8; buffer_size.max(1)];
+
+    let mut bytes_hashed = 0;
+    let mut bytes_hashed_at_last_report = 0;
+
+    loop {
+        let bytes_read = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(Some(bytes_hashed)),
+            Ok(bytes_read) => bytes_read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+
+        hasher.update(&buffer[..bytes_read]);
+        bytes_hashed += bytes_read as u64;
+
+        if bytes_hashed - bytes_hashed_at_last_report >= progress_update_byte_interval {
+            bytes_hashed_at_last_report = bytes_hashed;
+
+            if !progress_handler(bytes_hashed) {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+
+/// An error encountered while comparing the contents of two files (see [`have_same_contents`]).
+#[derive(Debug)]
+pub(crate) enum FileComparisonError {
+    /// The first file could not be opened or read.
+    UnableToReadFirstFile(io::Error),
+
+    /// The second file could not be opened or read.
+    UnableToReadSecondFile(io::Error),
+}
+
+impl From<FileComparisonError> for io::Error {
+    fn from(error: FileComparisonError) -> Self {
+        match error {
+            FileComparisonError::UnableToReadFirstFile(error) => error,
+            FileComparisonError::UnableToReadSecondFile(error) => error,
+        }
+    }
+}
+
+
+/// Returns `true` if the two files have the same contents.
+///
+/// Both files are read chunk by chunk, and reading stops at the first chunk that differs.
+pub(crate) fn have_same_contents(
+    first_file_path: &Path,
+    second_file_path: &Path,
+) -> Result<bool, FileComparisonError> {
+    let mut first_file =
+        fs::File::open(first_file_path).map_err(FileComparisonError::UnableToReadFirstFile)?;
+    let mut second_file =
+        fs::File::open(second_file_path).map_err(FileComparisonError::UnableToReadSecondFile)?;
+
+    let mut first_buffer = vec![0u8; DEFAULT_READ_BUFFER_SIZE];
+    let mut second_buffer = vec![0u8; DEFAULT_READ_BUFFER_SIZE];
+
+    loop {
+        let first_chunk_length = read_chunk(&mut first_file, &mut first_buffer)
+            .map_err(FileComparisonError::UnableToReadFirstFile)?;
+        let second_chunk_length = read_chunk(&mut second_file, &mut second_buffer)
+            .map_err(FileComparisonError::UnableToReadSecondFile)?;
+
+        if first_buffer[..first_chunk_length] != second_buffer[..second_chunk_length] {
+            return Ok(false);
+        }
+
+        if first_chunk_length == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+
+/// Reads from `reader` until `buffer` is full or the end of the reader is reached,
+/// returning the number of bytes read.
+fn read_chunk<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut total_bytes_read = 0;
+
+    while total_bytes_read < buffer.len() {
+        match reader.read(&mut buffer[total_bytes_read..]) {
+            Ok(0) => break,
+            Ok(bytes_read) => total_bytes_read += bytes_read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(total_bytes_read)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xxh64(data: &[u8]) -> u64 {
+        let mut hasher = Xxh64Hasher::new();
+        hasher.update(data);
+        hasher.digest()
+    }
+
+    #[test]
+    fn xxh64_matches_reference_values() {
+        assert_eq!(xxh64(b""), 0xEF46_DB37_51D8_E999);
+        assert_eq!(xxh64(b"a"), 0xD24E_C4F1_A98C_6E5B);
+        assert_eq!(xxh64(b"abc"), 0x44BC_2CF5_AD77_0999);
+        assert_eq!(
+            xxh64(b"The quick brown fox jumps over the lazy dog"),
+            0x0B24_2D36_1FDA_71BC
+        );
+    }
+
+    #[test]
+    fn xxh64_does_not_depend_on_chunking() {
+        let data = (0..1000u32)
+            .map(|value| (value * 7) as u8)
+            .collect::<Vec<_>>();
+
+        let mut chunked_hasher = Xxh64Hasher::new();
+        for chunk in data.chunks(13) {
+            chunked_hasher.update(chunk);
+        }
+
+        assert_eq!(chunked_hasher.digest(), xxh64(&data));
+        assert_ne!(xxh64(&data[1..]), xxh64(&data));
+    }
+}
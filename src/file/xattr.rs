@@ -0,0 +1,347 @@
+use std::{ffi::OsString, io, path::Path};
+
+
+/// A namespace of extended attributes.
+///
+/// The namespace is the part of an attribute name before the first dot,
+/// e.g. `user` in `user.checksum` or `security` in `security.selinux`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExtendedAttributeNamespace {
+    /// The `user.*` namespace, used for arbitrary user-defined attributes.
+    User,
+
+    /// The `trusted.*` namespace, which is only visible to privileged processes.
+    Trusted,
+
+    /// The `security.*` namespace, used by security modules (e.g. for SELinux labels).
+    Security,
+
+    /// The `system.*` namespace, which also contains POSIX access control lists
+    /// (`system.posix_acl_access` and `system.posix_acl_default`).
+    System,
+}
+
+impl ExtendedAttributeNamespace {
+    /// Returns the namespace of the given attribute name, or `None` if it is not a known namespace.
+    fn of_attribute(attribute_name: &[u8]) -> Option<Self> {
+        let dot_index = attribute_name.iter().position(|byte| *byte == b'.')?;
+
+        match &attribute_name[..dot_index] {
+            b"user" => Some(Self::User),
+            b"trusted" => Some(Self::Trusted),
+            b"security" => Some(Self::Security),
+            b"system" => Some(Self::System),
+            _ => None,
+        }
+    }
+}
+
+
+/// Controls which extended attributes (including access control lists) are copied.
+///
+/// Extended attributes are copied for files, directories and symbolic links.
+/// Attributes are selected by their namespace (see [`ExtendedAttributeNamespace`]);
+/// start from [`ExtendedAttributeFilter::ALL`] or [`ExtendedAttributeFilter::NONE`]
+/// and use [`including`] and [`excluding`] to adjust the selection.
+///
+/// Copying extended attributes is currently only supported on Linux;
+/// on other platforms, this has no effect.
+///
+/// If an attribute can not be copied (for example, because setting `trusted.*` attributes
+/// requires elevated privileges, or because the destination filesystem does not support
+/// extended attributes), the copy fails with an error naming the entry and the attribute,
+/// instead of silently dropping it. Exclude the namespace to skip such attributes.
+///
+/// The default copies no extended attributes.
+///
+///
+/// [`including`]: ExtendedAttributeFilter::including
+/// [`excluding`]: ExtendedAttributeFilter::excluding
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExtendedAttributeFilter {
+    user: bool,
+    trusted: bool,
+    security: bool,
+    system: bool,
+}
+
+impl ExtendedAttributeFilter {
+    /// Copies no extended attributes. This is the default.
+    pub const NONE: Self = Self {
+        user: false,
+        trusted: false,
+        security: false,
+        system: false,
+    };
+
+    /// Copies extended attributes in all namespaces.
+    pub const ALL: Self = Self {
+        user: true,
+        trusted: true,
+        security: true,
+        system: true,
+    };
+
+    /// Returns a copy of this filter that also copies attributes in the given namespace.
+    pub const fn including(mut self, namespace: ExtendedAttributeNamespace) -> Self {
+        match namespace {
+            ExtendedAttributeNamespace::User => self.user = true,
+            ExtendedAttributeNamespace::Trusted => self.trusted = true,
+            ExtendedAttributeNamespace::Security => self.security = true,
+            ExtendedAttributeNamespace::System => self.system = true,
+        }
+
+        self
+    }
+
+    /// Returns a copy of this filter that does not copy attributes in the given namespace.
+    pub const fn excluding(mut self, namespace: ExtendedAttributeNamespace) -> Self {
+        match namespace {
+            ExtendedAttributeNamespace::User => self.user = false,
+            ExtendedAttributeNamespace::Trusted => self.trusted = false,
+            ExtendedAttributeNamespace::Security => self.security = false,
+            ExtendedAttributeNamespace::System => self.system = false,
+        }
+
+        self
+    }
+
+    /// Returns `true` if attributes in the given namespace are copied.
+    pub const fn includes_namespace(&self, namespace: ExtendedAttributeNamespace) -> bool {
+        match namespace {
+            ExtendedAttributeNamespace::User => self.user,
+            ExtendedAttributeNamespace::Trusted => self.trusted,
+            ExtendedAttributeNamespace::Security => self.security,
+            ExtendedAttributeNamespace::System => self.system,
+        }
+    }
+
+    /// Returns `true` if no extended attributes are copied.
+    pub(crate) const fn is_none(&self) -> bool {
+        !self.user && !self.trusted && !self.security && !self.system
+    }
+
+    /// Returns `true` if the attribute with the given name is copied.
+    /// Attributes outside of the known namespaces are never copied.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn includes_attribute(&self, attribute_name: &[u8]) -> bool {
+        ExtendedAttributeNamespace::of_attribute(attribute_name)
+            .is_some_and(|namespace| self.includes_namespace(namespace))
+    }
+}
+
+impl Default for ExtendedAttributeFilter {
+    /// Constructs [`ExtendedAttributeFilter::NONE`]: no extended attributes are copied.
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+
+
+/// Describes why the extended attributes of an entry could not be copied.
+pub(crate) struct ExtendedAttributeCopyError {
+    /// The attribute that could not be copied,
+    /// or `None` if the attributes of the source could not be listed.
+    pub(crate) attribute_name: Option<OsString>,
+
+    /// The underlying IO error.
+    pub(crate) error: io::Error,
+}
+
+
+/// Copies the extended attributes accepted by `filter` from `source_path` to `destination_path`.
+///
+/// Symbolic links are not followed: if the paths are symbolic links,
+/// the attributes of the links themselves are copied.
+#[cfg(target_os = "linux")]
+pub(crate) fn copy_extended_attributes(
+    source_path: &Path,
+    destination_path: &Path,
+    filter: ExtendedAttributeFilter,
+) -> Result<(), ExtendedAttributeCopyError> {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    if filter.is_none() {
+        return Ok(());
+    }
+
+    let list_error = |error| ExtendedAttributeCopyError {
+        attribute_name: None,
+        error,
+    };
+
+    let source_path = linux::path_to_c_string(source_path).map_err(list_error)?;
+    let destination_path = linux::path_to_c_string(destination_path).map_err(list_error)?;
+
+    let attribute_names = match linux::list_attributes(&source_path) {
+        Ok(attribute_names) => attribute_names,
+        // The source filesystem does not support extended attributes, so there is nothing to copy.
+        Err(error) if error.raw_os_error() == Some(libc::EOPNOTSUPP) => return Ok(()),
+        Err(error) => return Err(list_error(error)),
+    };
+
+    for attribute_name in attribute_names
+        .split(|byte| *byte == 0)
+        .filter(|name| !name.is_empty())
+    {
+        if !filter.includes_attribute(attribute_name) {
+            continue;
+        }
+
+        let attribute_error = |error| ExtendedAttributeCopyError {
+            attribute_name: Some(OsStr::from_bytes(attribute_name).to_os_string()),
+            error,
+        };
+
+        // PANIC SAFETY: Names were split on NUL bytes, so they can not contain any.
+        let attribute_name_c = std::ffi::CString::new(attribute_name)
+            .expect("attribute names to not contain NUL bytes");
+
+        let value = match linux::get_attribute(&source_path, &attribute_name_c) {
+            Ok(value) => value,
+            // The attribute has been removed since we listed it.
+            Err(error) if error.raw_os_error() == Some(libc::ENODATA) => continue,
+            Err(error) => return Err(attribute_error(error)),
+        };
+
+        linux::set_attribute(&destination_path, &attribute_name_c, &value)
+            .map_err(attribute_error)?;
+    }
+
+    Ok(())
+}
+
+/// Copies the extended attributes accepted by `filter` from `source_path` to `destination_path`.
+///
+/// Extended attributes are only supported on Linux, so this does nothing.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn copy_extended_attributes(
+    _source_path: &Path,
+    _destination_path: &Path,
+    _filter: ExtendedAttributeFilter,
+) -> Result<(), ExtendedAttributeCopyError> {
+    Ok(())
+}
+
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        ffi::{CStr, CString},
+        io,
+        os::unix::ffi::OsStrExt,
+        path::Path,
+    };
+
+    pub(super) fn path_to_c_string(path: &Path) -> io::Result<CString> {
+        Ok(CString::new(path.as_os_str().as_bytes())?)
+    }
+
+    /// Returns the NUL-separated list of extended attribute names of the given path.
+    pub(super) fn list_attributes(path: &CStr) -> io::Result<Vec<u8>> {
+        read_into_buffer(|buffer, buffer_size| {
+            // SAFETY: The path is a valid NUL-terminated string, and the buffer
+            // is either null (with a size of zero) or valid for `buffer_size` bytes.
+            unsafe { libc::llistxattr(path.as_ptr(), buffer.cast(), buffer_size) }
+        })
+    }
+
+    /// Returns the value of the given extended attribute of the given path.
+    pub(super) fn get_attribute(path: &CStr, attribute_name: &CStr) -> io::Result<Vec<u8>> {
+        read_into_buffer(|buffer, buffer_size| {
+            // SAFETY: Both strings are valid and NUL-terminated, and the buffer
+            // is either null (with a size of zero) or valid for `buffer_size` bytes.
+            unsafe {
+                libc::lgetxattr(path.as_ptr(), attribute_name.as_ptr(), buffer.cast(), buffer_size)
+            }
+        })
+    }
+
+    /// Sets (creating or replacing) the given extended attribute of the given path.
+    pub(super) fn set_attribute(
+        path: &CStr,
+        attribute_name: &CStr,
+        value: &[u8],
+    ) -> io::Result<()> {
+        // SAFETY: Both strings are valid and NUL-terminated,
+        // and the value is valid for `value.len()` bytes.
+        let result = unsafe {
+            libc::lsetxattr(
+                path.as_ptr(),
+                attribute_name.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+            )
+        };
+
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Calls `read` once to query the required buffer size, then again to fill the buffer,
+    /// retrying if the value grew in between the two calls.
+    fn read_into_buffer<F>(mut read: F) -> io::Result<Vec<u8>>
+    where
+        F: FnMut(*mut u8, usize) -> isize,
+    {
+        loop {
+            let required_size = read(std::ptr::null_mut(), 0);
+            if required_size == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            if required_size == 0 {
+                return Ok(Vec::new());
+            }
+
+            let mut buffer = vec![0u8; required_size as usize];
+
+            let read_size = read(buffer.as_mut_ptr(), buffer.len());
+            if read_size == -1 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() == Some(libc::ERANGE) {
+                    continue;
+                }
+
+                return Err(error);
+            }
+
+            buffer.truncate(read_size as usize);
+            return Ok(buffer);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_selects_attributes_by_namespace() {
+        let filter = ExtendedAttributeFilter::ALL.excluding(ExtendedAttributeNamespace::Trusted);
+
+        assert!(filter.includes_attribute(b"user.checksum"));
+        assert!(filter.includes_attribute(b"security.selinux"));
+        assert!(filter.includes_attribute(b"system.posix_acl_access"));
+        assert!(!filter.includes_attribute(b"trusted.overlay.opaque"));
+
+        // Attributes outside of the known namespaces are never copied.
+        assert!(!filter.includes_attribute(b"unknown.attribute"));
+        assert!(!filter.includes_attribute(b"user"));
+    }
+
+    #[test]
+    fn empty_filter_includes_nothing() {
+        assert!(ExtendedAttributeFilter::default().is_none());
+        assert!(!ExtendedAttributeFilter::NONE
+            .including(ExtendedAttributeNamespace::User)
+            .is_none());
+        assert!(!ExtendedAttributeFilter::NONE.includes_attribute(b"user.checksum"));
+    }
+}
@@ -100,10 +100,10 @@
 //! )?;
 //!
 //! match finished_copy {
-//!     FileCopyFinished::Created { bytes_copied } => {
+//!     FileCopyFinished::Created { bytes_copied, .. } => {
 //!         println!("Copied {bytes_copied} bytes!");
 //!     }
-//!     FileCopyFinished::Overwritten { bytes_copied } => {
+//!     FileCopyFinished::Overwritten { bytes_copied, .. } => {
 //!         println!("Copied {bytes_copied} bytes over an existing file!");
 //!     }
 //!     // ...
@@ -215,3 +215,6 @@ mod macros;
 pub mod directory;
 pub mod error;
 pub mod file;
+mod progress;
+
+pub use progress::ProgressAction;
@@ -0,0 +1,56 @@
+use std::ops::ControlFlow;
+
+
+/// Instructs an ongoing operation whether to continue or to stop.
+///
+/// This is the value progress handler closures can return in all functions
+/// that report progress (e.g. [`copy_file_with_progress`] or [`copy_directory_with_progress`]).
+///
+/// Handlers are not required to return this type directly:
+/// any return type that implements `Into<ProgressAction>` is accepted. This includes:
+/// - `()`, which always maps to [`ProgressAction::Continue`] (this means existing
+///   closures that return nothing keep working as-is), and
+/// - [`ControlFlow<()>`], where [`ControlFlow::Continue`] maps to [`ProgressAction::Continue`]
+///   and [`ControlFlow::Break`] maps to [`ProgressAction::Abort`].
+///
+///
+/// [`copy_file_with_progress`]: crate::file::copy_file_with_progress
+/// [`copy_directory_with_progress`]: crate::directory::copy_directory_with_progress
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ProgressAction {
+    /// The operation should continue normally.
+    #[default]
+    Continue,
+
+    /// The operation should be cancelled as soon as possible.
+    ///
+    /// The function performing the operation will stop cleanly, removing any partially written
+    /// destination file, and return a `Cancelled` error variant that describes how far the
+    /// operation got before it was stopped.
+    Abort,
+}
+
+impl ProgressAction {
+    /// Returns `true` if this is [`ProgressAction::Abort`].
+    #[inline]
+    pub fn is_abort(&self) -> bool {
+        matches!(self, Self::Abort)
+    }
+}
+
+impl From<()> for ProgressAction {
+    #[inline]
+    fn from(_: ()) -> Self {
+        Self::Continue
+    }
+}
+
+impl From<ControlFlow<()>> for ProgressAction {
+    #[inline]
+    fn from(value: ControlFlow<()>) -> Self {
+        match value {
+            ControlFlow::Continue(_) => Self::Continue,
+            ControlFlow::Break(_) => Self::Abort,
+        }
+    }
+}
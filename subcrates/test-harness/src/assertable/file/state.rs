@@ -1,7 +1,7 @@
 use std::{
     fmt::Debug,
     fs::{self, OpenOptions},
-    io::Read,
+    io::{BufReader, Read},
     path::{Path, PathBuf},
 };
 
@@ -188,7 +188,7 @@ impl CapturedFileState {
                     .open(&self.file_path)
                     .expect("failed to open file");
 
-                if file.bytes().next().is_some() {
+                if BufReader::new(file).bytes().next().is_some() {
                     panic!(
                         "captured state is Empty, but file \"{}\" is not empty",
                         self.file_path.display()
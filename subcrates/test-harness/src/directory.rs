@@ -24,6 +24,7 @@ pub fn collect_directory_statistics_via_scan(
             maximum_scan_depth: DirectoryScanDepthLimit::Unlimited,
             follow_symbolic_links: false,
             follow_base_directory_symbolic_link: true,
+            ..Default::default()
         },
     )
     .into_iter();
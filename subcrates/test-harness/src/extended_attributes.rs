@@ -0,0 +1,60 @@
+use std::{ffi::CString, io, os::unix::ffi::OsStrExt, path::Path};
+
+
+/// Sets the extended attribute `name` of the given path (without following symbolic links).
+///
+/// Returns `false` if the filesystem does not support extended attributes,
+/// or if we are not permitted to set the attribute (e.g. `security.capability` without privileges).
+///
+/// # Panics
+/// Panics on any other IO error.
+pub fn set_extended_attribute_if_supported(path: &Path, name: &str, value: &[u8]) -> bool {
+    let path = CString::new(path.as_os_str().as_bytes()).expect("path to not contain NUL bytes");
+    let name = CString::new(name).expect("attribute name to not contain NUL bytes");
+
+    // SAFETY: Both strings are NUL-terminated, and the value is valid for `value.len()` bytes.
+    let result = unsafe {
+        libc::lsetxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0)
+    };
+
+    if result == -1 {
+        let error = io::Error::last_os_error();
+        if matches!(error.raw_os_error(), Some(libc::EOPNOTSUPP | libc::EPERM)) {
+            return false;
+        }
+
+        panic!("failed to set extended attribute: {error}");
+    }
+
+    true
+}
+
+
+/// Returns the value of the extended attribute `name` of the given path
+/// (without following symbolic links), or `None` if it is not set.
+///
+/// # Panics
+/// Panics on any IO error other than the attribute not existing.
+pub fn get_extended_attribute(path: &Path, name: &str) -> Option<Vec<u8>> {
+    let path = CString::new(path.as_os_str().as_bytes()).expect("path to not contain NUL bytes");
+    let name = CString::new(name).expect("attribute name to not contain NUL bytes");
+
+    let mut buffer = vec![0u8; 4096];
+
+    // SAFETY: Both strings are NUL-terminated, and the buffer is valid for `buffer.len()` bytes.
+    let result = unsafe {
+        libc::lgetxattr(path.as_ptr(), name.as_ptr(), buffer.as_mut_ptr().cast(), buffer.len())
+    };
+
+    if result == -1 {
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() == Some(libc::ENODATA) {
+            return None;
+        }
+
+        panic!("failed to get extended attribute: {error}");
+    }
+
+    buffer.truncate(result as usize);
+    Some(buffer)
+}
@@ -4,11 +4,15 @@ pub mod prelude;
 
 mod case_sensitivity;
 mod directory;
+#[cfg(target_os = "linux")]
+mod extended_attributes;
 mod path_comparison;
 
 pub use assert_matches::{assert_matches, debug_assert_matches};
 pub use case_sensitivity::*;
 pub use directory::*;
+#[cfg(target_os = "linux")]
+pub use extended_attributes::*;
 pub use path_comparison::*;
 
 
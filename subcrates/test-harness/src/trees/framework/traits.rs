@@ -1,6 +1,6 @@
 use std::{
     fs::{self, OpenOptions},
-    io::prelude::Read,
+    io::{prelude::Read, BufReader},
     path::{Path, PathBuf},
 };
 
@@ -121,7 +121,7 @@ pub trait AssertableInitialFileCapture: AsInitialFileStateRef {
                     .open(self.as_path())
                     .expect("failed to open file");
 
-                if file.bytes().next().is_some() {
+                if BufReader::new(file).bytes().next().is_some() {
                     panic!(
                         "initial state is Empty, but file \"{}\" is not empty",
                         self.as_path().display()
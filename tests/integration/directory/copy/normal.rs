@@ -1,21 +1,49 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
 use fs_more::{
     directory::{
         BrokenSymlinkBehaviour,
         CollidingSubDirectoryBehaviour,
         DestinationDirectoryRule,
+        DirectoryCollisionDecision,
+        DirectoryCollisionResolver,
         DirectoryCopyDepthLimit,
+        DirectoryCopyOperation,
         DirectoryCopyOptions,
+        DirectoryCopyParallelism,
+        DirectoryCopyWithProgressOptions,
+        DirectoryEntryFilter,
         DirectoryScanDepthLimit,
         DirectoryScanOptions,
+        HardLinkBehaviour,
+        PathMatcher,
+        RollbackBehaviour,
         SymlinkBehaviour,
     },
     error::{
         CopyDirectoryError,
+        CopyDirectoryExecutionError,
         CopyDirectoryPreparationError,
         DestinationDirectoryPathValidationError,
         DirectoryExecutionPlanError,
+        FileError,
+    },
+    file::{
+        AlternativeFileNaming,
+        CollidingFileBehaviour,
+        CopyVerification,
+        DurabilityLevel,
+        FileCopyOptions,
+        MetadataPreservation,
+        ReflinkBehaviour,
     },
-    file::{CollidingFileBehaviour, FileCopyOptions},
+    ProgressAction,
 };
 use fs_more_test_harness::{
     collect_directory_statistics_via_scan,
@@ -254,6 +282,7 @@ fn copy_directory_errors_when_destination_file_collides_and_its_behaviour_is_abo
             &empty_harness_colliding_file_path,
             FileCopyOptions {
                 colliding_file_behaviour: CollidingFileBehaviour::Abort,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -663,6 +692,7 @@ fn copy_directory_preemptively_checks_for_file_collisions() {
             &remapped_path,
             FileCopyOptions {
                 colliding_file_behaviour: CollidingFileBehaviour::Abort,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -1142,3 +1172,954 @@ fn copy_directory_preserves_source_directory_symbolic_link_when_behaviour_set_to
     copy_destination_tree.destroy();
     simple_tree.destroy();
 }
+
+
+
+#[test]
+fn copy_directory_with_rollback_enabled_removes_backups_of_overwritten_files_after_success() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let colliding_file_path = empty_harness.child_path("a.bin");
+    colliding_file_path.assert_not_exists();
+    std::fs::write(&colliding_file_path, b"this will be overwritten").unwrap();
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            rollback_behaviour: RollbackBehaviour::RollBackOnFailure,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.files_copied, 4);
+
+    // If any backup of the overwritten file was left behind, the directories wouldn't match.
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_skips_entries_excluded_by_entry_filter() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    // Directory sizes (as reported by their metadata) count towards the total as well.
+    let expected_bytes_copied = deep_harness.a_bin.as_path().metadata().unwrap().len()
+        + deep_harness.foo.as_path().metadata().unwrap().len()
+        + deep_harness.foo.b_bin.as_path().metadata().unwrap().len();
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            entry_filter: DirectoryEntryFilter::new()
+                .exclude(PathMatcher::new(["foo/bar"]).unwrap()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.total_bytes_copied, expected_bytes_copied);
+    assert_eq!(finished_copy.files_copied, 2);
+    assert_eq!(finished_copy.directories_created, 1);
+
+
+    empty_harness
+        .child_path(deep_harness.a_bin.as_path_relative_to_harness_root())
+        .assert_is_file_and_not_symlink();
+
+    empty_harness
+        .child_path(deep_harness.foo.b_bin.as_path_relative_to_harness_root())
+        .assert_is_file_and_not_symlink();
+
+    empty_harness
+        .child_path(deep_harness.foo.bar.as_path_relative_to_harness_root())
+        .assert_not_exists();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_skips_entries_ignored_by_ignore_files() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let gitignore_path = deep_harness.child_path(".gitignore");
+    std::fs::write(&gitignore_path, "*.bin\n!d.bin\n").unwrap();
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            entry_filter: DirectoryEntryFilter::new().respect_ignore_files(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    // Only `.gitignore` and `d.bin` are copied.
+    assert_eq!(finished_copy.files_copied, 2);
+
+    empty_harness
+        .child_path(".gitignore")
+        .assert_is_file_and_not_symlink();
+
+    empty_harness
+        .child_path(deep_harness.a_bin.as_path_relative_to_harness_root())
+        .assert_not_exists();
+
+    empty_harness
+        .child_path(
+            deep_harness
+                .foo
+                .bar
+                .c_bin
+                .as_path_relative_to_harness_root(),
+        )
+        .assert_not_exists();
+
+    empty_harness
+        .child_path(
+            deep_harness
+                .foo
+                .bar
+                .hello
+                .world
+                .d_bin
+                .as_path_relative_to_harness_root(),
+        )
+        .assert_is_file_and_not_symlink();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_in_parallel_creates_an_identical_copy() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            parallelism: DirectoryCopyParallelism::Parallel {
+                worker_threads: NonZeroUsize::new(3).unwrap(),
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.total_bytes_copied, deep_harness_stats.total_bytes);
+
+    assert_eq!(finished_copy.files_copied, deep_harness_stats.total_files);
+
+    assert_eq!(
+        finished_copy.directories_created,
+        deep_harness_stats.total_directories
+    );
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_reflink_always_either_clones_every_file_or_fails() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let copy_result = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            reflink_behaviour: ReflinkBehaviour::Always,
+            ..Default::default()
+        },
+    );
+
+
+    // Whether files can be cloned depends on the filesystem the tests are run on.
+    match copy_result {
+        Ok(finished_copy) => {
+            assert_eq!(finished_copy.files_copied, 4);
+
+            empty_harness
+                .assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+        }
+        Err(error) => {
+            assert_matches!(
+                error,
+                CopyDirectoryError::ExecutionError(CopyDirectoryExecutionError::FileCopyError {
+                    error: FileError::UnableToReflink { .. },
+                    ..
+                })
+            );
+        }
+    }
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+
+#[test]
+#[cfg(unix)]
+fn copy_directory_preserves_directory_metadata_after_copying_contents_on_unix() {
+    use std::os::unix::fs::PermissionsExt;
+
+
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+    std::fs::set_permissions(
+        deep_harness.foo.bar.as_path(),
+        std::fs::Permissions::from_mode(0o750),
+    )
+    .unwrap();
+
+    let copy_destination_path = empty_harness.child_path("copy");
+    copy_destination_path.assert_not_exists();
+
+
+    fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        &copy_destination_path,
+        DirectoryCopyOptions {
+            metadata_preservation: MetadataPreservation::ALL,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    copy_destination_path
+        .assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+    for relative_directory_path in ["", "foo", "foo/bar", "foo/bar/hello", "foo/bar/hello/world"] {
+        let source_metadata =
+            std::fs::metadata(deep_harness.as_path().join(relative_directory_path)).unwrap();
+        let destination_metadata =
+            std::fs::metadata(copy_destination_path.join(relative_directory_path)).unwrap();
+
+        assert_eq!(
+            destination_metadata.permissions().mode(),
+            source_metadata.permissions().mode()
+        );
+
+        // Directory timestamps must be applied after their contents have been copied,
+        // otherwise copying the contents would have updated them.
+        assert_eq!(
+            destination_metadata.modified().unwrap(),
+            source_metadata.modified().unwrap()
+        );
+    }
+
+    let copied_bar_metadata = std::fs::metadata(copy_destination_path.join("foo/bar")).unwrap();
+    assert_eq!(copied_bar_metadata.permissions().mode() & 0o7777, 0o750);
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+
+#[test]
+#[cfg(target_os = "linux")]
+fn copy_directory_copies_extended_attributes_of_files_and_directories_on_linux() {
+    use fs_more::file::{ExtendedAttributeFilter, ExtendedAttributeNamespace};
+    use fs_more_test_harness::{get_extended_attribute, set_extended_attribute_if_supported};
+
+
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+    let attributes_supported = set_extended_attribute_if_supported(
+        deep_harness.foo.bar.as_path(),
+        "user.fs_more_directory",
+        b"bar",
+    ) && set_extended_attribute_if_supported(
+        deep_harness.foo.bar.c_bin.as_path(),
+        "user.fs_more_file",
+        b"c",
+    );
+
+    if !attributes_supported {
+        // The filesystem the tests are run on does not support extended attributes.
+        deep_harness.destroy();
+        empty_harness.destroy();
+        return;
+    }
+
+
+    fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            extended_attributes: ExtendedAttributeFilter::NONE
+                .including(ExtendedAttributeNamespace::User),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+    assert_eq!(
+        get_extended_attribute(&empty_harness.child_path("foo/bar"), "user.fs_more_directory")
+            .as_deref(),
+        Some(b"bar".as_slice())
+    );
+    assert_eq!(
+        get_extended_attribute(&empty_harness.child_path("foo/bar/c.bin"), "user.fs_more_file")
+            .as_deref(),
+        Some(b"c".as_slice())
+    );
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_verification_creates_an_identical_copy() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            verify: CopyVerification::ENABLED,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.total_bytes_copied, deep_harness_stats.total_bytes);
+    assert_eq!(finished_copy.files_copied, deep_harness_stats.total_files);
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_resume_journal_copies_again_files_that_changed_since_the_interrupted_copy() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+    let journal_harness = EmptyTree::initialize();
+
+
+    let journal_path = journal_harness.child_path("copy-journal");
+
+
+    // Interrupt a copy once two files have been copied.
+    let mut first_copied_file_path = None;
+
+    let interrupted_copy_result = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            resume_journal_path: Some(journal_path.clone()),
+            ..Default::default()
+        },
+        |progress| {
+            if let DirectoryCopyOperation::CopyingFile {
+                destination_file_path,
+                ..
+            } = progress.current_operation
+            {
+                first_copied_file_path.get_or_insert_with(|| destination_file_path.clone());
+            }
+
+            match progress.files_copied {
+                2 => ProgressAction::Abort,
+                _ => ProgressAction::Continue,
+            }
+        },
+    );
+
+    assert_matches!(
+        interrupted_copy_result.unwrap_err(),
+        CopyDirectoryError::ExecutionError(CopyDirectoryExecutionError::Cancelled { .. })
+    );
+
+
+    // A journal can only be used to resume the copy it was created for.
+    let other_empty_harness = EmptyTree::initialize();
+
+    assert_matches!(
+        fs_more::directory::copy_directory(
+            deep_harness.as_path(),
+            other_empty_harness.as_path(),
+            DirectoryCopyOptions {
+                resume_journal_path: Some(journal_path.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap_err(),
+        CopyDirectoryError::PreparationError(
+            CopyDirectoryPreparationError::ResumeJournalMismatch { .. }
+        )
+    );
+
+    other_empty_harness.destroy();
+
+
+    // Change one of the copied files. Even though the destination directory is no longer empty,
+    // the copy can be resumed, since its contents were created by the interrupted copy.
+    let first_copied_file_path = first_copied_file_path.unwrap();
+    std::fs::write(&first_copied_file_path, b"changed since the copy").unwrap();
+
+
+    fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            resume_journal_path: Some(journal_path.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+    journal_path.assert_not_exists();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+    journal_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_atomically_creates_an_identical_copy_without_temporary_files() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            parallelism: DirectoryCopyParallelism::Parallel {
+                worker_threads: NonZeroUsize::new(3).unwrap(),
+            },
+            atomic: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.total_bytes_copied, deep_harness_stats.total_bytes);
+    assert_eq!(finished_copy.files_copied, deep_harness_stats.total_files);
+
+    // Any temporary file left behind would make the directories differ.
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_durability_creates_an_identical_copy() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            durability: DurabilityLevel::FilesAndDirectories,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.total_bytes_copied, deep_harness_stats.total_bytes);
+    assert_eq!(finished_copy.files_copied, deep_harness_stats.total_files);
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_copies_colliding_files_to_alternative_paths_when_behaviour_is_keep_both() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let colliding_file_path = empty_harness.child_path("a.bin");
+    colliding_file_path.assert_not_exists();
+    std::fs::write(&colliding_file_path, b"this will be kept").unwrap();
+
+    let alternative_file_path = empty_harness.child_path("a (1).bin");
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::KeepBoth {
+                    naming: AlternativeFileNaming::NumberInParentheses,
+                },
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.files_copied, 4);
+    assert_eq!(
+        finished_copy.files_copied_to_alternative_paths,
+        vec![alternative_file_path.clone()]
+    );
+
+    assert_eq!(std::fs::read(&colliding_file_path).unwrap(), b"this will be kept");
+    deep_harness
+        .a_bin
+        .assert_initial_state_matches_other_file(&alternative_file_path);
+
+    deep_harness
+        .foo
+        .b_bin
+        .assert_initial_state_matches_other_file(empty_harness.child_path("foo/b.bin"));
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+#[cfg(unix)]
+fn copy_directory_keeps_colliding_symlinks_when_behaviour_is_keep_both_on_unix() {
+    use std::path::Path;
+
+
+    let source_tree = SimpleTree::initialize();
+    let destination_tree = EmptyTree::initialize();
+
+    source_tree
+        .child_path("symlink-to-empty.txt")
+        .symlink_to_file(Path::new("./empty.txt"));
+
+    std::fs::write(destination_tree.child_path("other.txt"), b"other").unwrap();
+
+    let colliding_symlink_path = destination_tree.child_path("symlink-to-empty.txt");
+    colliding_symlink_path.symlink_to_file(Path::new("./other.txt"));
+
+    let alternative_symlink_path = destination_tree.child_path("symlink-to-empty (1).txt");
+
+
+    let finished_copy = fs_more::directory::copy_directory(
+        source_tree.as_path(),
+        destination_tree.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::KeepBoth {
+                    naming: AlternativeFileNaming::NumberInParentheses,
+                },
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            symlink_behaviour: SymlinkBehaviour::Keep,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(
+        finished_copy.files_copied_to_alternative_paths,
+        vec![alternative_symlink_path.clone()]
+    );
+
+    assert_eq!(
+        std::fs::read_link(&colliding_symlink_path).unwrap(),
+        Path::new("./other.txt")
+    );
+    assert_eq!(
+        std::fs::read_link(&alternative_symlink_path).unwrap(),
+        Path::new("./empty.txt")
+    );
+
+
+    source_tree.destroy();
+    destination_tree.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_only_copies_files_that_differ_when_behaviour_is_overwrite_if_different() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions::default(),
+    )
+    .unwrap();
+
+    std::fs::write(deep_harness.foo.b_bin.as_path(), b"changed contents").unwrap();
+
+
+    let finished_copy = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::OverwriteIfDifferent,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            ..Default::default()
+        },
+        |_| ProgressAction::Continue,
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.files_copied, 1);
+    assert_eq!(finished_copy.total_bytes_copied, 16);
+    assert!(finished_copy.files_copied_to_alternative_paths.is_empty());
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_resolves_each_collision_with_collision_resolver() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let renamed_colliding_file_path = empty_harness.child_path("a.bin");
+    std::fs::write(&renamed_colliding_file_path, b"this will be kept").unwrap();
+
+    let skipped_colliding_file_path = empty_harness.child_path("foo/b.bin");
+    std::fs::create_dir(empty_harness.child_path("foo")).unwrap();
+    std::fs::write(&skipped_colliding_file_path, b"this will be kept as well").unwrap();
+
+    let alternative_file_path = empty_harness.child_path("a-1.bin");
+
+
+    let resolver_calls = Arc::new(AtomicUsize::new(0));
+    let resolver_calls_in_resolver = resolver_calls.clone();
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Abort,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            collision_resolver: Some(DirectoryCollisionResolver::new(move |collision| {
+                resolver_calls_in_resolver.fetch_add(1, Ordering::SeqCst);
+
+                assert!(collision.destination_metadata.is_file());
+
+                if collision.source_path.ends_with("a.bin") {
+                    DirectoryCollisionDecision::Rename {
+                        naming: AlternativeFileNaming::NumberAfterHyphen,
+                    }
+                } else {
+                    DirectoryCollisionDecision::Skip
+                }
+            })),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(resolver_calls.load(Ordering::SeqCst), 2);
+
+    assert_eq!(finished_copy.files_copied, 3);
+    assert_eq!(
+        finished_copy.files_copied_to_alternative_paths,
+        vec![alternative_file_path.clone()]
+    );
+
+    assert_eq!(
+        std::fs::read(&renamed_colliding_file_path).unwrap(),
+        b"this will be kept"
+    );
+    assert_eq!(
+        std::fs::read(&skipped_colliding_file_path).unwrap(),
+        b"this will be kept as well"
+    );
+
+    deep_harness
+        .a_bin
+        .assert_initial_state_matches_other_file(&alternative_file_path);
+    deep_harness
+        .foo
+        .bar
+        .c_bin
+        .assert_initial_state_matches_other_file(empty_harness.child_path("foo/bar/c.bin"));
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_accepts_stateful_collision_resolver() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let first_colliding_file_path = empty_harness.child_path("a.bin");
+    std::fs::write(&first_colliding_file_path, b"colliding").unwrap();
+
+    let second_colliding_file_path = empty_harness.child_path("foo/b.bin");
+    std::fs::create_dir(empty_harness.child_path("foo")).unwrap();
+    std::fs::write(&second_colliding_file_path, b"colliding").unwrap();
+
+
+    // Only the first collision is overwritten, which requires the resolver to keep state.
+    let mut has_overwritten_a_file = false;
+
+    let finished_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Abort,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            collision_resolver: Some(DirectoryCollisionResolver::new(move |_| {
+                if has_overwritten_a_file {
+                    DirectoryCollisionDecision::Skip
+                } else {
+                    has_overwritten_a_file = true;
+                    DirectoryCollisionDecision::Overwrite
+                }
+            })),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.files_copied, 3);
+
+    let kept_colliding_files = [&first_colliding_file_path, &second_colliding_file_path]
+        .into_iter()
+        .filter(|file_path| std::fs::read(file_path).unwrap() == b"colliding")
+        .count();
+
+    assert_eq!(kept_colliding_files, 1);
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_errors_before_copying_when_collision_resolver_aborts() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let colliding_file_path = empty_harness.child_path("a.bin");
+    std::fs::write(&colliding_file_path, b"this will be kept").unwrap();
+
+
+    let copy_result = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            collision_resolver: Some(DirectoryCollisionResolver::new(|_| {
+                DirectoryCollisionDecision::Abort
+            })),
+            ..Default::default()
+        },
+    );
+
+
+    assert_matches!(
+        copy_result.unwrap_err(),
+        CopyDirectoryError::PreparationError(
+            CopyDirectoryPreparationError::CopyPlanningError(
+                DirectoryExecutionPlanError::DestinationItemAlreadyExists { path }
+            )
+        ) if paths_equal_no_unc(&path, &colliding_file_path)
+    );
+
+    assert_eq!(std::fs::read(&colliding_file_path).unwrap(), b"this will be kept");
+    empty_harness.child_path("foo").assert_not_exists();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+#[cfg(unix)]
+fn copy_directory_preserves_hard_links_only_when_enabled_on_unix() {
+    use std::os::unix::fs::MetadataExt;
+
+
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+    let source_hard_link_path = deep_harness.child_path("foo/a-link.bin");
+    std::fs::hard_link(deep_harness.a_bin.as_path(), &source_hard_link_path).unwrap();
+
+    let separate_copy_path = empty_harness.child_path("separate");
+    let preserved_copy_path = empty_harness.child_path("preserved");
+
+
+    let separate_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        &separate_copy_path,
+        DirectoryCopyOptions::default(),
+    )
+    .unwrap();
+
+    let preserved_copy = fs_more::directory::copy_directory(
+        deep_harness.as_path(),
+        &preserved_copy_path,
+        DirectoryCopyOptions {
+            hard_link_behaviour: HardLinkBehaviour::Preserve,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    let a_bin_size_bytes = std::fs::metadata(deep_harness.a_bin.as_path())
+        .unwrap()
+        .len();
+
+    assert_eq!(separate_copy.hard_links_created, 0);
+    assert_eq!(preserved_copy.hard_links_created, 1);
+    assert_eq!(preserved_copy.files_copied, separate_copy.files_copied - 1);
+    assert_eq!(
+        preserved_copy.total_bytes_copied,
+        separate_copy.total_bytes_copied - a_bin_size_bytes
+    );
+
+    let inode_of = |path: std::path::PathBuf| std::fs::metadata(path).unwrap().ino();
+
+    assert_ne!(
+        inode_of(separate_copy_path.join("a.bin")),
+        inode_of(separate_copy_path.join("foo/a-link.bin"))
+    );
+    assert_eq!(
+        inode_of(preserved_copy_path.join("a.bin")),
+        inode_of(preserved_copy_path.join("foo/a-link.bin"))
+    );
+
+    preserved_copy_path
+        .assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
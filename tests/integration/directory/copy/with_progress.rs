@@ -1,3 +1,5 @@
+use std::num::NonZeroUsize;
+
 use fs_more::{
     directory::{
         BrokenSymlinkBehaviour,
@@ -5,19 +7,24 @@ use fs_more::{
         DestinationDirectoryRule,
         DirectoryCopyDepthLimit,
         DirectoryCopyOperation,
+        DirectoryCopyParallelism,
         DirectoryCopyProgress,
         DirectoryCopyWithProgressOptions,
         DirectoryScanDepthLimit,
         DirectoryScanOptions,
+        HardLinkBehaviour,
+        RollbackBehaviour,
         SymlinkBehaviour,
     },
     error::{
         CopyDirectoryError,
+        CopyDirectoryExecutionError,
         CopyDirectoryPreparationError,
         DestinationDirectoryPathValidationError,
         DirectoryExecutionPlanError,
     },
     file::{CollidingFileBehaviour, FileCopyOptions},
+    ProgressAction,
 };
 use fs_more_test_harness::{
     collect_directory_statistics_via_scan,
@@ -112,13 +119,15 @@ fn copy_directory_with_progress_creates_an_identical_copy() {
                     let previous_path = match &previous_report.current_operation {
                         DirectoryCopyOperation::CreatingDirectory { destination_directory_path } => destination_directory_path.as_path(),
                         DirectoryCopyOperation::CopyingFile { destination_file_path, .. } => destination_file_path.as_path(),
-                        DirectoryCopyOperation::CreatingSymbolicLink { destination_symbolic_link_file_path } => destination_symbolic_link_file_path.as_path()
+                        DirectoryCopyOperation::CreatingSymbolicLink { destination_symbolic_link_file_path } => destination_symbolic_link_file_path.as_path(),
+                        DirectoryCopyOperation::CreatingHardLink { destination_hard_link_path } => destination_hard_link_path.as_path()
                     };
 
                     let current_path = match &progress.current_operation {
                         DirectoryCopyOperation::CreatingDirectory { destination_directory_path } => destination_directory_path.as_path(),
                         DirectoryCopyOperation::CopyingFile { destination_file_path, .. } => destination_file_path.as_path(),
-                        DirectoryCopyOperation::CreatingSymbolicLink { destination_symbolic_link_file_path } => destination_symbolic_link_file_path.as_path()
+                        DirectoryCopyOperation::CreatingSymbolicLink { destination_symbolic_link_file_path } => destination_symbolic_link_file_path.as_path(),
+                        DirectoryCopyOperation::CreatingHardLink { destination_hard_link_path } => destination_hard_link_path.as_path()
                     };
 
                     if previous_path != current_path {
@@ -367,6 +376,7 @@ fn copy_directory_with_progress_errors_when_destination_file_collides_and_its_be
             &empty_harness_colliding_file_path,
             FileCopyOptions {
                 colliding_file_behaviour: CollidingFileBehaviour::Abort,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -782,6 +792,7 @@ fn copy_directory_with_progress_preemptively_checks_for_file_collisions() {
             &remapped_path,
             FileCopyOptions {
                 colliding_file_behaviour: CollidingFileBehaviour::Abort,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -1281,3 +1292,486 @@ fn copy_directory_with_progress_preserves_source_directory_symbolic_link_when_be
     copy_destination_tree.destroy();
     simple_tree.destroy();
 }
+
+
+
+#[test]
+fn copy_directory_with_progress_stops_without_leaving_partial_files_when_cancelled() {
+    let deep_harness = DeepTree::initialize();
+    let deep_harness_untouched = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let mut has_cancelled = false;
+    let mut last_progress_report: Option<DirectoryCopyProgress> = None;
+
+    let copy_result = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            read_buffer_size: 1024,
+            write_buffer_size: 1024,
+            progress_update_byte_interval: 1024,
+            ..Default::default()
+        },
+        |progress| {
+            if has_cancelled {
+                panic!("invalid progress report: progress was reported after cancelling");
+            }
+
+            last_progress_report = Some(progress.to_owned_progress());
+
+            // Cancel in the middle of copying the second file.
+            if let DirectoryCopyOperation::CopyingFile {
+                progress: file_progress,
+                ..
+            } = progress.current_operation
+            {
+                if progress.files_copied == 1
+                    && file_progress.bytes_finished > 0
+                    && file_progress.bytes_finished < file_progress.bytes_total
+                {
+                    has_cancelled = true;
+                    return ProgressAction::Abort;
+                }
+            }
+
+            ProgressAction::Continue
+        },
+    );
+
+
+    assert!(has_cancelled);
+
+    let last_progress_report = last_progress_report.unwrap();
+    let DirectoryCopyOperation::CopyingFile {
+        destination_file_path: cancelled_file_path,
+        progress: cancelled_file_progress,
+    } = &last_progress_report.current_operation
+    else {
+        panic!("expected the last progress report to be a file copy");
+    };
+
+    assert_matches!(
+        copy_result.unwrap_err(),
+        CopyDirectoryError::ExecutionError(CopyDirectoryExecutionError::Cancelled {
+            bytes_finished,
+            bytes_total,
+            files_copied,
+            ..
+        })
+        if files_copied == 1
+            && bytes_total == last_progress_report.bytes_total
+            && bytes_finished
+                == last_progress_report.bytes_finished - cancelled_file_progress.bytes_finished
+    );
+
+
+    // The partially copied file must have been removed,
+    // and the source directory must be left untouched.
+    cancelled_file_path.assert_not_exists();
+
+    deep_harness_untouched
+        .assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    deep_harness_untouched.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_progress_resumes_a_cancelled_copy_when_using_a_resume_journal() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+    let journal_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+    let journal_path = journal_harness.child_path("copy-journal");
+
+    let options = DirectoryCopyWithProgressOptions {
+        destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+        read_buffer_size: 1024,
+        write_buffer_size: 1024,
+        progress_update_byte_interval: 1024,
+        resume_journal_path: Some(journal_path.clone()),
+        ..Default::default()
+    };
+
+
+    let mut last_progress_report: Option<DirectoryCopyProgress> = None;
+
+    let cancelled_copy_result = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        options.clone(),
+        |progress| {
+            last_progress_report = Some(progress.to_owned_progress());
+
+            // Cancel in the middle of copying the second file.
+            if let DirectoryCopyOperation::CopyingFile {
+                progress: file_progress,
+                ..
+            } = progress.current_operation
+            {
+                if progress.files_copied == 1
+                    && file_progress.bytes_finished > 0
+                    && file_progress.bytes_finished < file_progress.bytes_total
+                {
+                    return ProgressAction::Abort;
+                }
+            }
+
+            ProgressAction::Continue
+        },
+    );
+
+    assert_matches!(
+        cancelled_copy_result.unwrap_err(),
+        CopyDirectoryError::ExecutionError(CopyDirectoryExecutionError::Cancelled {
+            files_copied: 1,
+            ..
+        })
+    );
+
+
+    // The partially copied file must have been kept, so that it can be resumed.
+    let DirectoryCopyOperation::CopyingFile {
+        destination_file_path: cancelled_file_path,
+        ..
+    } = &last_progress_report.unwrap().current_operation
+    else {
+        panic!("expected the last progress report to be a file copy");
+    };
+
+    cancelled_file_path.assert_is_file_and_not_symlink();
+    journal_path.assert_is_file_and_not_symlink();
+
+
+    let mut first_progress_report: Option<DirectoryCopyProgress> = None;
+
+    let finished_copy = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        options,
+        |progress| {
+            if first_progress_report.is_none() {
+                first_progress_report = Some(progress.to_owned_progress());
+            }
+        },
+    )
+    .unwrap();
+
+
+    // The file that had been copied before cancelling must not have been copied again.
+    assert_eq!(first_progress_report.unwrap().files_copied, 1);
+
+    assert_eq!(finished_copy.total_bytes_copied, deep_harness_stats.total_bytes);
+    assert_eq!(finished_copy.files_copied, deep_harness_stats.total_files);
+    assert_eq!(
+        finished_copy.directories_created,
+        deep_harness_stats.total_directories
+    );
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+    // The journal must be removed once the copy has completed.
+    journal_path.assert_not_exists();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+    journal_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_progress_rolls_back_all_changes_when_execution_fails_and_rollback_is_enabled(
+) {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let mut has_injected_colliding_file = false;
+
+    let copy_result = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            rollback_behaviour: RollbackBehaviour::RollBackOnFailure,
+            ..Default::default()
+        },
+        |progress| {
+            // Simulates another process creating the file right before we copy it.
+            if let DirectoryCopyOperation::CopyingFile {
+                destination_file_path,
+                ..
+            } = progress.current_operation
+            {
+                if !has_injected_colliding_file && destination_file_path.ends_with("d.bin") {
+                    std::fs::write(destination_file_path, b"created externally").unwrap();
+                    has_injected_colliding_file = true;
+                }
+            }
+        },
+    );
+
+
+    assert!(has_injected_colliding_file);
+
+    let copy_error = copy_result.unwrap_err();
+
+    let CopyDirectoryError::ExecutionError(CopyDirectoryExecutionError::RolledBack {
+        error,
+        rollback_report,
+    }) = copy_error
+    else {
+        panic!("expected a rolled back execution error, got {:?}", copy_error);
+    };
+
+    assert_matches!(*error, CopyDirectoryExecutionError::FileCopyError { .. });
+
+    assert!(rollback_report.is_complete());
+    assert_eq!(rollback_report.files_removed, 4);
+    assert_eq!(rollback_report.directories_removed, 4);
+    assert_eq!(rollback_report.files_restored, 0);
+
+
+    empty_harness.assert_is_directory_and_empty();
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_progress_restores_overwritten_files_when_cancelled_and_rollback_is_enabled()
+{
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let colliding_file_path = empty_harness.child_path("a.bin");
+    colliding_file_path.assert_not_exists();
+    std::fs::write(&colliding_file_path, b"this must be restored").unwrap();
+
+
+    let copy_result = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            rollback_behaviour: RollbackBehaviour::RollBackOnFailure,
+            ..Default::default()
+        },
+        |progress| {
+            // Cancel once the last file has started copying.
+            if progress.files_copied == 3 {
+                ProgressAction::Abort
+            } else {
+                ProgressAction::Continue
+            }
+        },
+    );
+
+
+    assert_matches!(
+        copy_result.unwrap_err(),
+        CopyDirectoryError::ExecutionError(CopyDirectoryExecutionError::RolledBack {
+            error,
+            rollback_report,
+        })
+        if matches!(*error, CopyDirectoryExecutionError::Cancelled { files_copied: 3, .. })
+            && rollback_report.is_complete()
+            && rollback_report.files_restored == 1
+    );
+
+
+    // Only the original file must be left, with its original contents.
+    assert_eq!(std::fs::read_dir(empty_harness.as_path()).unwrap().count(), 1);
+    assert_eq!(std::fs::read(&colliding_file_path).unwrap(), b"this must be restored");
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_progress_in_parallel_creates_an_identical_copy() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let mut last_progress_report: Option<DirectoryCopyProgress> = None;
+
+    let finished_copy = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            parallelism: DirectoryCopyParallelism::Parallel {
+                worker_threads: NonZeroUsize::new(3).unwrap(),
+            },
+            ..Default::default()
+        },
+        |progress| {
+            if let Some(previous_report) = &last_progress_report {
+                assert!(progress.bytes_finished >= previous_report.bytes_finished);
+                assert!(progress.files_copied >= previous_report.files_copied);
+            }
+
+            last_progress_report = Some(progress.to_owned_progress());
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_copy.total_bytes_copied, deep_harness_stats.total_bytes);
+
+    assert_eq!(finished_copy.files_copied, deep_harness_stats.total_files);
+
+    assert_eq!(
+        finished_copy.directories_created,
+        deep_harness_stats.total_directories
+    );
+
+
+    let last_progress_report = last_progress_report.unwrap();
+
+    assert_eq!(last_progress_report.bytes_total, deep_harness_stats.total_bytes);
+    assert_eq!(last_progress_report.bytes_finished, deep_harness_stats.total_bytes);
+    assert_eq!(last_progress_report.files_copied, deep_harness_stats.total_files);
+
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn copy_directory_with_progress_in_parallel_rolls_back_all_changes_when_cancelled() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let copy_result = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            rollback_behaviour: RollbackBehaviour::RollBackOnFailure,
+            parallelism: DirectoryCopyParallelism::Parallel {
+                worker_threads: NonZeroUsize::new(2).unwrap(),
+            },
+            ..Default::default()
+        },
+        |progress| {
+            // Cancel as soon as any of the workers starts copying a file.
+            if matches!(progress.current_operation, DirectoryCopyOperation::CopyingFile { .. }) {
+                ProgressAction::Abort
+            } else {
+                ProgressAction::Continue
+            }
+        },
+    );
+
+
+    assert_matches!(
+        copy_result.unwrap_err(),
+        CopyDirectoryError::ExecutionError(CopyDirectoryExecutionError::RolledBack {
+            error,
+            rollback_report,
+        })
+        if matches!(*error, CopyDirectoryExecutionError::Cancelled { .. })
+            && rollback_report.is_complete()
+    );
+
+    empty_harness.assert_is_directory_and_empty();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+#[cfg(unix)]
+fn copy_directory_with_progress_preserves_hard_links_when_copying_in_parallel_on_unix() {
+    use std::os::unix::fs::MetadataExt;
+
+
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+    let source_hard_link_path = deep_harness.child_path("foo/bar/b-link.bin");
+    std::fs::hard_link(deep_harness.foo.b_bin.as_path(), &source_hard_link_path).unwrap();
+
+
+    let mut reported_hard_link_paths = Vec::new();
+
+    let finished_copy = fs_more::directory::copy_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            parallelism: DirectoryCopyParallelism::Parallel {
+                worker_threads: NonZeroUsize::new(3).unwrap(),
+            },
+            hard_link_behaviour: HardLinkBehaviour::Preserve,
+            ..Default::default()
+        },
+        |progress| {
+            if let DirectoryCopyOperation::CreatingHardLink {
+                destination_hard_link_path,
+            } = &progress.current_operation
+            {
+                if !reported_hard_link_paths.contains(destination_hard_link_path) {
+                    reported_hard_link_paths.push(destination_hard_link_path.clone());
+                }
+            }
+
+            ProgressAction::Continue
+        },
+    )
+    .unwrap();
+
+
+    let destination_hard_link_path = empty_harness.child_path("foo/bar/b-link.bin");
+
+    assert_eq!(finished_copy.hard_links_created, 1);
+    assert_eq!(reported_hard_link_paths, vec![destination_hard_link_path.clone()]);
+    assert_eq!(
+        std::fs::metadata(empty_harness.child_path("foo/b.bin"))
+            .unwrap()
+            .ino(),
+        std::fs::metadata(&destination_hard_link_path)
+            .unwrap()
+            .ino()
+    );
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
@@ -1,4 +1,8 @@
 pub mod copy;
 pub mod is_empty;
 pub mod r#move;
+pub mod plan;
+pub mod remove;
 pub mod scan;
+pub mod size;
+pub mod sync;
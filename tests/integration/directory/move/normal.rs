@@ -3,12 +3,16 @@ use fs_more::{
         BrokenSymlinkBehaviour,
         CollidingSubDirectoryBehaviour,
         DestinationDirectoryRule,
+        DirectoryCollisionDecision,
+        DirectoryCollisionResolver,
         DirectoryCopyDepthLimit,
         DirectoryCopyOptions,
+        DirectoryEntryFilter,
         DirectoryMoveAllowedStrategies,
         DirectoryMoveByCopyOptions,
         DirectoryMoveOptions,
         DirectoryMoveStrategy,
+        PathMatcher,
         SymlinkBehaviour,
     },
     error::{
@@ -17,7 +21,7 @@ use fs_more::{
         MoveDirectoryError,
         MoveDirectoryPreparationError,
     },
-    file::CollidingFileBehaviour,
+    file::{CollidingFileBehaviour, DurabilityLevel},
 };
 use fs_more_test_harness::{
     collect_directory_statistics_via_scan,
@@ -179,6 +183,7 @@ fn move_directory_preserves_symlinks_on_non_empty_destination_directory_with_onl
                 colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Abort,
             },
             allowed_strategies: DirectoryMoveAllowedStrategies::OnlyRename,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -262,6 +267,7 @@ fn move_directory_does_not_preserve_symlinks_on_empty_destination_directory_with
                     ..Default::default()
                 },
             },
+            ..Default::default()
         },
     )
     .unwrap();
@@ -439,6 +445,7 @@ fn move_directory_preserves_source_directory_symlink_when_using_rename_strategy(
         DirectoryMoveOptions {
             destination_directory_rule: DestinationDirectoryRule::DisallowExisting,
             allowed_strategies: DirectoryMoveAllowedStrategies::OnlyRename,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -473,6 +480,7 @@ fn move_directory_aborts_on_broken_symlink_when_using_copy_and_delete_strategy_a
                 options: DirectoryMoveByCopyOptions {
                     symlink_behaviour: SymlinkBehaviour::Keep,
                     broken_symlink_behaviour: BrokenSymlinkBehaviour::Abort,
+                    ..Default::default()
                 },
             },
             ..Default::default()
@@ -495,3 +503,209 @@ fn move_directory_aborts_on_broken_symlink_when_using_copy_and_delete_strategy_a
     destination_tree.destroy();
     broken_symlink_tree.destroy();
 }
+
+
+
+#[test]
+fn move_directory_leaves_entries_excluded_by_entry_filter_in_source_directory() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let finished_move = fs_more::directory::move_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryMoveOptions {
+            allowed_strategies: DirectoryMoveAllowedStrategies::Either {
+                copy_and_delete_options: DirectoryMoveByCopyOptions {
+                    entry_filter: DirectoryEntryFilter::new()
+                        .exclude(PathMatcher::new(["c.bin"]).unwrap()),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    // A rename would have moved the excluded file as well.
+    assert_eq!(finished_move.strategy_used, DirectoryMoveStrategy::CopyAndDelete);
+    assert_eq!(finished_move.files_moved, 3);
+
+
+    // The excluded file and its ancestors are left in the source directory.
+    deep_harness.foo.bar.c_bin.assert_is_file_and_not_symlink();
+    deep_harness.a_bin.assert_not_exists();
+    deep_harness.foo.b_bin.assert_not_exists();
+    deep_harness.foo.bar.hello.assert_not_exists();
+
+    empty_harness
+        .child_path(
+            deep_harness
+                .foo
+                .bar
+                .c_bin
+                .as_path_relative_to_harness_root(),
+        )
+        .assert_not_exists();
+
+    empty_harness
+        .child_path(
+            deep_harness
+                .foo
+                .bar
+                .hello
+                .world
+                .d_bin
+                .as_path_relative_to_harness_root(),
+        )
+        .assert_is_file_and_not_symlink();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+
+#[test]
+#[cfg(target_os = "linux")]
+fn move_directory_copies_extended_attributes_when_moving_by_copy_and_delete_on_linux() {
+    use fs_more::file::ExtendedAttributeFilter;
+    use fs_more_test_harness::{get_extended_attribute, set_extended_attribute_if_supported};
+
+
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+    if !set_extended_attribute_if_supported(
+        deep_harness.foo.b_bin.as_path(),
+        "user.fs_more_file",
+        b"b",
+    ) {
+        // The filesystem the tests are run on does not support extended attributes.
+        deep_harness.destroy();
+        empty_harness.destroy();
+        return;
+    }
+
+
+    let finished_move = fs_more::directory::move_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryMoveOptions {
+            allowed_strategies: DirectoryMoveAllowedStrategies::OnlyCopyAndDelete {
+                options: DirectoryMoveByCopyOptions {
+                    extended_attributes: ExtendedAttributeFilter::ALL,
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(finished_move.strategy_used, DirectoryMoveStrategy::CopyAndDelete);
+
+
+    deep_harness.assert_not_exists();
+    assert_eq!(
+        get_extended_attribute(&empty_harness.child_path("foo/b.bin"), "user.fs_more_file")
+            .as_deref(),
+        Some(b"b".as_slice())
+    );
+
+
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn move_directory_with_durability_moves_all_files_and_subdirectories_with_either_strategy() {
+    for allowed_strategies in [
+        DirectoryMoveAllowedStrategies::OnlyRename,
+        DirectoryMoveAllowedStrategies::OnlyCopyAndDelete {
+            options: DirectoryMoveByCopyOptions::default(),
+        },
+    ] {
+        let deep_harness = DeepTree::initialize();
+        let deep_harness_untouched = DeepTree::initialize();
+        let empty_harness = EmptyTree::initialize();
+
+
+        fs_more::directory::move_directory(
+            deep_harness.as_path(),
+            empty_harness.as_path(),
+            DirectoryMoveOptions {
+                destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+                allowed_strategies,
+                durability: DurabilityLevel::FilesAndDirectories,
+            },
+        )
+        .unwrap();
+
+
+        deep_harness.assert_not_exists();
+
+        deep_harness_untouched
+            .assert_is_directory_and_fully_matches_secondary_directory(empty_harness.as_path());
+
+
+        deep_harness.destroy();
+        deep_harness_untouched.destroy();
+        empty_harness.destroy();
+    }
+}
+
+
+
+#[test]
+fn move_directory_keeps_source_files_skipped_by_collision_resolver() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let colliding_file_path = empty_harness.child_path("a.bin");
+    std::fs::write(&colliding_file_path, b"this will be kept").unwrap();
+
+
+    let finished_move = fs_more::directory::move_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryMoveOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Abort,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            allowed_strategies: DirectoryMoveAllowedStrategies::OnlyCopyAndDelete {
+                options: DirectoryMoveByCopyOptions {
+                    collision_resolver: Some(DirectoryCollisionResolver::new(|_| {
+                        DirectoryCollisionDecision::Skip
+                    })),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(finished_move.strategy_used, DirectoryMoveStrategy::CopyAndDelete);
+
+
+    assert_eq!(std::fs::read(&colliding_file_path).unwrap(), b"this will be kept");
+    deep_harness.a_bin.assert_unchanged_from_initial_state();
+
+    deep_harness.foo.assert_not_exists();
+    deep_harness
+        .foo
+        .b_bin
+        .assert_initial_state_matches_other_file(empty_harness.child_path("foo/b.bin"));
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
@@ -11,15 +11,19 @@ use fs_more::{
         DirectoryMoveWithProgressAllowedStrategies,
         DirectoryMoveWithProgressByCopyOptions,
         DirectoryMoveWithProgressOptions,
+        RollbackBehaviour,
         SymlinkBehaviour,
     },
     error::{
+        CopyDirectoryExecutionError,
         DestinationDirectoryPathValidationError,
         DirectoryExecutionPlanError,
         MoveDirectoryError,
+        MoveDirectoryExecutionError,
         MoveDirectoryPreparationError,
     },
     file::CollidingFileBehaviour,
+    ProgressAction,
 };
 use fs_more_test_harness::{
     collect_directory_statistics_via_scan,
@@ -150,6 +154,17 @@ fn move_directory_with_progress_moves_all_files_and_subdirectories() {
                             assert_eq!(destination_symbolic_link_file_path, previous_destination_symbolic_link_file_path);
                         }
 
+                        DirectoryMoveOperation::CreatingHardLink { destination_hard_link_path } => {
+                            let DirectoryMoveOperation::CreatingHardLink { destination_hard_link_path: previous_destination_hard_link_path } = &previous_report.current_operation else {
+                                panic!(
+                                    "invalid progress reported: current_operation changed variant \
+                                    without incrementing current_operation_index"
+                                );
+                            };
+
+                            assert_eq!(destination_hard_link_path, previous_destination_hard_link_path);
+                        }
+
                         DirectoryMoveOperation::RemovingSourceDirectory => {
                             if previous_report.current_operation
                                 != DirectoryMoveOperation::RemovingSourceDirectory
@@ -311,6 +326,7 @@ fn move_directory_with_progress_preserves_symlinks_on_non_empty_destination_dire
                 colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Abort,
             },
             allowed_strategies: DirectoryMoveWithProgressAllowedStrategies::OnlyRename,
+            ..Default::default()
         },
         |_| {},
     )
@@ -396,6 +412,7 @@ fn move_directory_does_not_preserve_symlinks_on_empty_destination_directory_with
                     ..Default::default()
                 },
             },
+            ..Default::default()
         },
         |_| {},
     )
@@ -578,6 +595,7 @@ fn move_directory_with_progress_preserves_source_directory_symlink_when_using_re
         DirectoryMoveWithProgressOptions {
             destination_directory_rule: DestinationDirectoryRule::DisallowExisting,
             allowed_strategies: DirectoryMoveWithProgressAllowedStrategies::OnlyRename,
+            ..Default::default()
         },
         |_| {},
     )
@@ -638,3 +656,118 @@ fn move_directory_with_progress_aborts_on_broken_symlink_when_using_copy_and_del
     destination_tree.destroy();
     broken_symlink_tree.destroy();
 }
+
+
+
+#[test]
+fn move_directory_with_progress_leaves_source_untouched_when_cancelled_while_copying() {
+    let deep_harness = DeepTree::initialize();
+    let deep_harness_untouched = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let mut has_cancelled = false;
+
+    let move_result = fs_more::directory::move_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryMoveWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            allowed_strategies: DirectoryMoveWithProgressAllowedStrategies::OnlyCopyAndDelete {
+                options: DirectoryMoveWithProgressByCopyOptions::default(),
+            },
+            ..Default::default()
+        },
+        |progress| {
+            if has_cancelled {
+                panic!("invalid progress report: progress was reported after cancelling");
+            }
+
+            if progress.files_moved == 2 {
+                has_cancelled = true;
+                return ProgressAction::Abort;
+            }
+
+            ProgressAction::Continue
+        },
+    );
+
+
+    assert!(has_cancelled);
+
+    assert_matches!(
+        move_result.unwrap_err(),
+        MoveDirectoryError::ExecutionError(MoveDirectoryExecutionError::Cancelled {
+            files_moved,
+            bytes_finished,
+            bytes_total,
+            ..
+        })
+        if files_moved == 2 && bytes_finished < bytes_total
+    );
+
+
+    deep_harness_untouched
+        .assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    deep_harness_untouched.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn move_directory_with_progress_rolls_back_copied_entries_when_cancelled_and_rollback_is_enabled() {
+    let deep_harness = DeepTree::initialize();
+    let deep_harness_untouched = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let move_result = fs_more::directory::move_directory_with_progress(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryMoveWithProgressOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowEmpty,
+            allowed_strategies: DirectoryMoveWithProgressAllowedStrategies::OnlyCopyAndDelete {
+                options: DirectoryMoveWithProgressByCopyOptions {
+                    rollback_behaviour: RollbackBehaviour::RollBackOnFailure,
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        },
+        |progress| {
+            if progress.files_moved == 2 {
+                ProgressAction::Abort
+            } else {
+                ProgressAction::Continue
+            }
+        },
+    );
+
+
+    assert_matches!(
+        move_result.unwrap_err(),
+        MoveDirectoryError::ExecutionError(MoveDirectoryExecutionError::CopyDirectoryError(
+            CopyDirectoryExecutionError::RolledBack {
+                error,
+                rollback_report,
+            }
+        ))
+        if matches!(*error, CopyDirectoryExecutionError::Cancelled { .. })
+            && rollback_report.is_complete()
+    );
+
+
+    empty_harness.assert_is_directory_and_empty();
+
+    deep_harness_untouched
+        .assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    deep_harness_untouched.destroy();
+    empty_harness.destroy();
+}
@@ -0,0 +1,297 @@
+use fs_more::{
+    directory::{
+        CollidingSubDirectoryBehaviour,
+        DestinationDirectoryRule,
+        DirectoryCopyOptions,
+        DirectoryMoveOptions,
+        DirectoryMoveStrategy,
+        PlannedDirectoryCopyOperation,
+    },
+    error::{CopyDirectoryError, CopyDirectoryPreparationError, DirectoryExecutionPlanError},
+    file::CollidingFileBehaviour,
+};
+use fs_more_test_harness::{
+    collect_directory_statistics_via_scan,
+    prelude::*,
+    trees::structures::{deep::DeepTree, empty::EmptyTree},
+};
+
+
+
+#[test]
+fn plan_directory_copy_does_not_modify_destination_until_executed() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let copy_plan = fs_more::directory::plan_directory_copy(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions::default(),
+    )
+    .unwrap();
+
+
+    empty_harness.assert_is_directory_and_empty();
+
+    let plan_summary = copy_plan.summary();
+
+    assert_eq!(plan_summary.total_bytes, deep_harness_stats.total_bytes);
+    assert_eq!(plan_summary.files_to_copy, deep_harness_stats.total_files);
+    assert_eq!(plan_summary.files_to_overwrite, 0);
+    assert_eq!(
+        plan_summary.directories_to_create,
+        deep_harness_stats.total_directories
+    );
+    assert_eq!(plan_summary.existing_directories, 0);
+
+    assert_eq!(copy_plan.operations().len(), copy_plan.len());
+    assert!(copy_plan
+        .operations()
+        .all(|operation| !operation.destination_exists()));
+
+
+    let finished_copy = copy_plan.execute().unwrap();
+
+    assert_eq!(finished_copy.total_bytes_copied, deep_harness_stats.total_bytes);
+    assert_eq!(finished_copy.files_copied, deep_harness_stats.total_files);
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn plan_directory_copy_reports_files_that_will_be_overwritten() {
+    let deep_harness = DeepTree::initialize();
+    let destination_deep_harness = DeepTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let copy_plan = fs_more::directory::plan_directory_copy(
+        deep_harness.as_path(),
+        destination_deep_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    let plan_summary = copy_plan.summary();
+
+    assert_eq!(plan_summary.files_to_copy, deep_harness_stats.total_files);
+    assert_eq!(plan_summary.files_to_overwrite, deep_harness_stats.total_files);
+    assert_eq!(plan_summary.directories_to_create, 0);
+    assert_eq!(
+        plan_summary.existing_directories,
+        deep_harness_stats.total_directories
+    );
+
+
+    let overwritten_a_bin = copy_plan
+        .operations()
+        .find_map(|operation| match operation {
+            PlannedDirectoryCopyOperation::CopyFile {
+                destination_file_path,
+                size_bytes,
+                destination_exists,
+                ..
+            } if paths_equal_no_unc(
+                destination_file_path,
+                destination_deep_harness.a_bin.as_path(),
+            ) =>
+            {
+                Some((size_bytes, destination_exists))
+            }
+            _ => None,
+        });
+
+    assert_eq!(overwritten_a_bin, Some((32768, true)));
+
+
+    deep_harness.destroy();
+    destination_deep_harness.destroy();
+}
+
+
+
+#[test]
+fn plan_directory_copy_execute_errors_when_destination_file_appears_after_planning() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let copy_plan = fs_more::directory::plan_directory_copy(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            destination_directory_rule: DestinationDirectoryRule::AllowNonEmpty {
+                colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+                colliding_subdirectory_behaviour: CollidingSubDirectoryBehaviour::Continue,
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    let unexpected_file_path = empty_harness.child_path("a.bin");
+    unexpected_file_path.assert_not_exists();
+
+    std::fs::write(&unexpected_file_path, b"not part of the plan").unwrap();
+
+
+    let execution_result = copy_plan.execute();
+
+    assert_matches!(
+        execution_result.unwrap_err(),
+        CopyDirectoryError::PreparationError(CopyDirectoryPreparationError::CopyPlanningError(
+            DirectoryExecutionPlanError::DestinationChangedAfterPlanning { path }
+        )) if paths_equal_no_unc(&path, &unexpected_file_path)
+    );
+
+
+    // Nothing must have been copied.
+    assert_eq!(std::fs::read(&unexpected_file_path).unwrap(), b"not part of the plan");
+    empty_harness.child_path("foo").assert_not_exists();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn plan_directory_copy_rejects_resume_journal() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+    let journal_harness = EmptyTree::initialize();
+
+    let journal_path = journal_harness.child_path("copy-journal");
+
+
+    let plan_result = fs_more::directory::plan_directory_copy(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryCopyOptions {
+            resume_journal_path: Some(journal_path.clone()),
+            ..Default::default()
+        },
+    );
+
+    assert_matches!(
+        plan_result.unwrap_err(),
+        CopyDirectoryPreparationError::ResumeJournalNotSupportedForPlans { journal_path: rejected_journal_path }
+        if rejected_journal_path == journal_path
+    );
+
+    journal_path.assert_not_exists();
+    empty_harness.assert_is_directory_and_empty();
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+    journal_harness.destroy();
+}
+
+
+
+#[test]
+fn plan_directory_move_executes_planned_move() {
+    let deep_harness = DeepTree::initialize();
+    let deep_harness_untouched = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let move_plan = fs_more::directory::plan_directory_move(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectoryMoveOptions::default(),
+    )
+    .unwrap();
+
+
+    deep_harness.assert_is_directory_and_not_symlink();
+    empty_harness.assert_is_directory_and_empty();
+
+    assert_eq!(move_plan.total_bytes(), deep_harness_stats.total_bytes);
+    assert!(move_plan.will_attempt_rename());
+
+    let copy_plan_summary = move_plan.copy_plan().unwrap().summary();
+    assert_eq!(copy_plan_summary.files_to_copy, deep_harness_stats.total_files);
+
+
+    let finished_move = move_plan.execute().unwrap();
+
+    assert_eq!(finished_move.total_bytes_moved, deep_harness_stats.total_bytes);
+    assert_eq!(finished_move.files_moved, deep_harness_stats.total_files);
+
+    // Both harnesses live in the same temporary directory, so the rename must succeed.
+    assert_eq!(finished_move.strategy_used, DirectoryMoveStrategy::Rename);
+
+
+    deep_harness.assert_not_exists();
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(
+        deep_harness_untouched.as_path(),
+    );
+
+
+    deep_harness.destroy();
+    deep_harness_untouched.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn plan_directory_copy_orders_operations_by_file_name() {
+    let source_harness = EmptyTree::initialize();
+    let destination_harness = EmptyTree::initialize();
+
+    for file_name in ["c.bin", "a.bin", "b.bin"] {
+        std::fs::write(source_harness.child_path(file_name), b"").unwrap();
+    }
+
+
+    let copy_plan = fs_more::directory::plan_directory_copy(
+        source_harness.as_path(),
+        destination_harness.as_path(),
+        DirectoryCopyOptions::default(),
+    )
+    .unwrap();
+
+
+    let planned_file_names = copy_plan
+        .operations()
+        .filter_map(|operation| match operation {
+            PlannedDirectoryCopyOperation::CopyFile {
+                source_file_path, ..
+            } => Some(source_file_path.file_name().unwrap().to_os_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(planned_file_names, ["a.bin", "b.bin", "c.bin"]);
+
+
+    source_harness.destroy();
+    destination_harness.destroy();
+}
@@ -0,0 +1,243 @@
+use fs_more::{
+    directory::{DirectoryRemoveDepthLimit, DirectoryRemoveOptions},
+    error::{RemoveDirectoryError, RemoveDirectoryPreparationError},
+};
+use fs_more_test_harness::{
+    collect_directory_statistics_via_scan,
+    prelude::*,
+    trees::structures::{
+        deep::DeepTree,
+        empty::EmptyTree,
+        simple::SimpleTree,
+        symlinked::SymlinkedTree,
+    },
+};
+
+
+
+#[test]
+fn remove_directory_removes_all_files_and_subdirectories() {
+    let deep_harness = DeepTree::initialize();
+
+
+    let source_harness_stats =
+        collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+    let source_harness_size_bytes =
+        fs_more::directory::directory_size_in_bytes(deep_harness.as_path()).unwrap();
+
+
+    let finished_removal = fs_more::directory::remove_directory(
+        deep_harness.as_path(),
+        DirectoryRemoveOptions::default(),
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_removal.files_removed, source_harness_stats.total_files);
+    assert_eq!(finished_removal.symlinks_removed, 0);
+
+    // The base directory is removed as well.
+    assert_eq!(
+        finished_removal.directories_removed,
+        source_harness_stats.total_directories + 1
+    );
+
+    assert_eq!(finished_removal.total_bytes_removed, source_harness_size_bytes);
+
+
+    deep_harness.assert_not_exists();
+
+
+    deep_harness.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_removes_symlinks_without_following_them() {
+    let symlinked_harness = SymlinkedTree::initialize();
+    let simple_harness = SimpleTree::initialize();
+    let simple_harness_untouched = SimpleTree::initialize();
+
+
+    let symlink_to_outside_directory = symlinked_harness.child_path("symlink-to-simple");
+    symlink_to_outside_directory.assert_not_exists();
+    symlink_to_outside_directory.symlink_to_directory(simple_harness.as_path());
+
+    let symlink_to_outside_file = symlinked_harness.child_path("symlink-to-empty.txt");
+    symlink_to_outside_file.assert_not_exists();
+    symlink_to_outside_file.symlink_to_file(simple_harness.empty_txt.as_path());
+
+
+    let finished_removal = fs_more::directory::remove_directory(
+        symlinked_harness.as_path(),
+        DirectoryRemoveOptions::default(),
+    )
+    .unwrap();
+
+
+    // Two symlinks are part of the tree, and we added two more.
+    assert_eq!(finished_removal.symlinks_removed, 4);
+    assert_eq!(finished_removal.files_removed, 4);
+
+
+    symlinked_harness.assert_not_exists();
+
+    simple_harness.assert_is_directory_and_not_symlink();
+    simple_harness_untouched
+        .assert_is_directory_and_fully_matches_secondary_directory(simple_harness.as_path());
+
+
+    symlinked_harness.destroy();
+    simple_harness.destroy();
+    simple_harness_untouched.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_removes_only_the_symlink_when_base_path_is_symlink_to_directory() {
+    let deep_harness = DeepTree::initialize();
+    let deep_harness_untouched = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let symlink_path = empty_harness.child_path("symlink-to-deep");
+    symlink_path.assert_not_exists();
+    symlink_path.symlink_to_directory(deep_harness.as_path());
+
+
+    let finished_removal =
+        fs_more::directory::remove_directory(&symlink_path, DirectoryRemoveOptions::default())
+            .unwrap();
+
+
+    assert_eq!(finished_removal.symlinks_removed, 1);
+    assert_eq!(finished_removal.files_removed, 0);
+    assert_eq!(finished_removal.directories_removed, 0);
+
+
+    symlink_path.assert_not_exists();
+    empty_harness.assert_is_directory_and_empty();
+
+    deep_harness_untouched
+        .assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    deep_harness_untouched.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_errors_when_directory_does_not_exist() {
+    let empty_harness = EmptyTree::initialize();
+
+
+    let non_existent_directory = empty_harness.child_path("hello-world");
+    non_existent_directory.assert_not_exists();
+
+
+    let removal_result = fs_more::directory::remove_directory(
+        &non_existent_directory,
+        DirectoryRemoveOptions::default(),
+    );
+
+
+    assert_matches!(
+        removal_result.unwrap_err(),
+        RemoveDirectoryError::PreparationError(
+            RemoveDirectoryPreparationError::NotFound { directory_path }
+        ) if paths_equal_no_unc(&directory_path, &non_existent_directory)
+    );
+
+
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_errors_when_path_is_a_file() {
+    let simple_harness = SimpleTree::initialize();
+
+
+    let removal_result = fs_more::directory::remove_directory(
+        simple_harness.empty_txt.as_path(),
+        DirectoryRemoveOptions::default(),
+    );
+
+
+    assert_matches!(
+        removal_result.unwrap_err(),
+        RemoveDirectoryError::PreparationError(
+            RemoveDirectoryPreparationError::NotADirectory { path }
+        ) if paths_equal_no_unc(&path, simple_harness.empty_txt.as_path())
+    );
+
+    simple_harness.empty_txt.assert_is_file_and_not_symlink();
+
+
+    simple_harness.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_errors_without_removing_anything_when_depth_limit_is_exceeded() {
+    let deep_harness = DeepTree::initialize();
+    let deep_harness_untouched = DeepTree::initialize();
+
+
+    let removal_result = fs_more::directory::remove_directory(
+        deep_harness.as_path(),
+        DirectoryRemoveOptions {
+            remove_depth_limit: DirectoryRemoveDepthLimit::Limited { maximum_depth: 1 },
+        },
+    );
+
+
+    assert_matches!(
+        removal_result.unwrap_err(),
+        RemoveDirectoryError::PreparationError(
+            RemoveDirectoryPreparationError::DepthLimitExceeded { directory_path }
+        ) if paths_equal_no_unc(&directory_path, deep_harness.foo.bar.as_path())
+    );
+
+
+    deep_harness_untouched
+        .assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    deep_harness_untouched.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_removes_tree_that_fits_inside_depth_limit() {
+    let simple_harness = SimpleTree::initialize();
+
+
+    let finished_removal = fs_more::directory::remove_directory(
+        simple_harness.as_path(),
+        DirectoryRemoveOptions {
+            remove_depth_limit: DirectoryRemoveDepthLimit::Limited { maximum_depth: 1 },
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_removal.files_removed, 3);
+    assert_eq!(finished_removal.directories_removed, 2);
+
+
+    simple_harness.assert_not_exists();
+
+
+    simple_harness.destroy();
+}
@@ -0,0 +1,251 @@
+use std::ops::ControlFlow;
+
+use fs_more::{
+    directory::{DirectoryRemoveOperation, DirectoryRemoveOptions, DirectoryRemoveProgress},
+    error::{RemoveDirectoryError, RemoveDirectoryExecutionError, RemoveDirectoryPreparationError},
+};
+use fs_more_test_harness::{
+    collect_directory_statistics_via_scan,
+    prelude::*,
+    trees::structures::{deep::DeepTree, empty::EmptyTree, symlinked::SymlinkedTree},
+};
+
+
+
+#[test]
+fn remove_directory_with_progress_removes_all_files_and_subdirectories() {
+    let deep_harness = DeepTree::initialize();
+
+
+    let source_harness_stats =
+        collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+
+
+    let mut last_progress_report: Option<DirectoryRemoveProgress> = None;
+
+    let finished_removal = fs_more::directory::remove_directory_with_progress(
+        deep_harness.as_path(),
+        DirectoryRemoveOptions::default(),
+        |progress| {
+            if let Some(previous_report) = &last_progress_report {
+                assert_eq!(
+                    previous_report.bytes_total, progress.bytes_total,
+                    "invalid progress reported: bytes_total must never change"
+                );
+
+                assert_eq!(
+                    previous_report.total_operations, progress.total_operations,
+                    "invalid progress reported: total_operations must never change"
+                );
+
+                assert!(
+                    previous_report.bytes_finished <= progress.bytes_finished,
+                    "invalid progress reported: bytes_finished must never decrease"
+                );
+
+                assert!(
+                    previous_report.files_removed <= progress.files_removed,
+                    "invalid progress reported: files_removed must never decrease"
+                );
+
+                assert!(
+                    previous_report.directories_removed <= progress.directories_removed,
+                    "invalid progress reported: directories_removed must never decrease"
+                );
+
+                if previous_report.current_operation_index != progress.current_operation_index {
+                    assert_eq!(
+                        previous_report.current_operation_index + 1,
+                        progress.current_operation_index,
+                        "invalid progress reported: current_operation_index must always increase by one"
+                    );
+                } else {
+                    assert_eq!(
+                        previous_report.current_operation, progress.current_operation,
+                        "invalid progress reported: current_operation changed \
+                        without incrementing current_operation_index"
+                    );
+                }
+            }
+
+
+            // Contents must always be removed before the directory itself.
+            if let DirectoryRemoveOperation::RemovingDirectory { directory_path } =
+                &progress.current_operation
+            {
+                // The final progress report is emitted after the base directory has been removed.
+                if directory_path.exists() {
+                    directory_path.assert_is_directory_and_empty();
+                }
+            }
+
+
+            last_progress_report = Some(progress.clone());
+        },
+    )
+    .unwrap();
+
+
+    let last_progress_report = last_progress_report.unwrap();
+
+    assert_eq!(last_progress_report.bytes_total, last_progress_report.bytes_finished);
+    assert_eq!(
+        last_progress_report.current_operation_index + 1,
+        last_progress_report.total_operations
+    );
+
+    assert_eq!(finished_removal.total_bytes_removed, last_progress_report.bytes_total);
+    assert_eq!(finished_removal.files_removed, source_harness_stats.total_files);
+    assert_eq!(
+        finished_removal.directories_removed,
+        source_harness_stats.total_directories + 1
+    );
+
+
+    deep_harness.assert_not_exists();
+
+
+    deep_harness.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_with_progress_reports_symlinks_as_symlinks() {
+    let symlinked_harness = SymlinkedTree::initialize();
+
+
+    let mut removed_symlink_paths = Vec::new();
+
+    let finished_removal = fs_more::directory::remove_directory_with_progress(
+        symlinked_harness.as_path(),
+        DirectoryRemoveOptions::default(),
+        |progress| {
+            if let DirectoryRemoveOperation::RemovingSymbolicLink { symlink_path } =
+                &progress.current_operation
+            {
+                if !removed_symlink_paths.contains(symlink_path) {
+                    removed_symlink_paths.push(symlink_path.clone());
+                }
+            }
+        },
+    )
+    .unwrap();
+
+
+    assert_eq!(finished_removal.symlinks_removed, 2);
+    assert_eq!(removed_symlink_paths.len(), 2);
+
+    assert!(removed_symlink_paths
+        .iter()
+        .any(|path| paths_equal_no_unc(path, symlinked_harness.foo.symlink_to_hello.as_path())));
+    assert!(removed_symlink_paths
+        .iter()
+        .any(|path| paths_equal_no_unc(path, symlinked_harness.foo.symlink_to_d_bin.as_path())));
+
+
+    symlinked_harness.assert_not_exists();
+
+
+    symlinked_harness.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_with_progress_errors_when_directory_does_not_exist() {
+    let empty_harness = EmptyTree::initialize();
+
+
+    let non_existent_directory = empty_harness.child_path("hello-world");
+    non_existent_directory.assert_not_exists();
+
+
+    let mut num_progress_reports = 0;
+
+    let removal_result = fs_more::directory::remove_directory_with_progress(
+        &non_existent_directory,
+        DirectoryRemoveOptions::default(),
+        |_| {
+            num_progress_reports += 1;
+        },
+    );
+
+
+    assert_matches!(
+        removal_result.unwrap_err(),
+        RemoveDirectoryError::PreparationError(
+            RemoveDirectoryPreparationError::NotFound { directory_path }
+        ) if paths_equal_no_unc(&directory_path, &non_existent_directory)
+    );
+
+    assert_eq!(num_progress_reports, 0);
+
+
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn remove_directory_with_progress_stops_before_next_entry_when_cancelled() {
+    let deep_harness = DeepTree::initialize();
+
+
+    let mut removed_entry_paths = Vec::new();
+    let mut has_cancelled = false;
+
+    let removal_result = fs_more::directory::remove_directory_with_progress(
+        deep_harness.as_path(),
+        DirectoryRemoveOptions::default(),
+        |progress| {
+            if has_cancelled {
+                panic!("invalid progress report: progress was reported after cancelling");
+            }
+
+            if progress.current_operation_index == 2 {
+                has_cancelled = true;
+                return ControlFlow::Break(());
+            }
+
+            let entry_path = match &progress.current_operation {
+                DirectoryRemoveOperation::RemovingFile { file_path } => file_path,
+                DirectoryRemoveOperation::RemovingSymbolicLink { symlink_path } => symlink_path,
+                DirectoryRemoveOperation::RemovingDirectory { directory_path } => directory_path,
+            };
+
+            removed_entry_paths.push(entry_path.clone());
+
+            ControlFlow::Continue(())
+        },
+    );
+
+
+    assert!(has_cancelled);
+
+    assert_matches!(
+        removal_result.unwrap_err(),
+        RemoveDirectoryError::ExecutionError(RemoveDirectoryExecutionError::Cancelled {
+            files_removed,
+            directories_removed,
+            bytes_finished,
+            bytes_total,
+            ..
+        })
+        if files_removed + directories_removed == 2 && bytes_finished < bytes_total
+    );
+
+
+    // Only the first two entries have been removed.
+    assert_eq!(removed_entry_paths.len(), 2);
+
+    for removed_entry_path in removed_entry_paths {
+        removed_entry_path.assert_not_exists();
+    }
+
+    deep_harness.assert_is_directory_and_not_symlink();
+    deep_harness.a_bin.assert_is_file_and_not_symlink();
+
+
+    deep_harness.destroy();
+}
@@ -1,5 +1,15 @@
+use std::path::PathBuf;
+
 use fs_more::{
-    directory::{DirectoryScanDepthLimit, DirectoryScanOptions, DirectoryScanner},
+    directory::{
+        DepthFirstOrder,
+        DirectoryEntryFilter,
+        DirectoryScanDepthLimit,
+        DirectoryScanOptions,
+        DirectoryScanner,
+        PathMatcher,
+        ScanEntryOrdering,
+    },
     error::DirectoryScanError,
 };
 use fs_more_test_harness::{
@@ -494,3 +504,423 @@ fn scanner_iter_errors_on_symbolic_link_cycle() {
 
     cyclical_tree.destroy();
 }
+
+
+
+#[test]
+fn scanner_does_not_descend_into_directories_excluded_by_entry_filter() {
+    let deep_tree = DeepTree::initialize();
+
+    let scanner = DirectoryScanner::new(
+        deep_tree.as_path(),
+        DirectoryScanOptions {
+            entry_filter: DirectoryEntryFilter::new().exclude(PathMatcher::new(["bar"]).unwrap()),
+            ..Default::default()
+        },
+    )
+    .into_iter();
+
+
+    let scanned_paths = scanner
+        .map(|entry_result| entry_result.map(|entry| entry.into_path()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_path_list_fully_matches_set(
+        scanned_paths,
+        [
+            deep_tree.as_path(),
+            deep_tree.a_bin.as_path(),
+            deep_tree.foo.as_path(),
+            deep_tree.foo.b_bin.as_path(),
+        ],
+    );
+
+
+    deep_tree.destroy();
+}
+
+
+
+#[test]
+fn scanner_skips_entries_rejected_by_entry_filter_predicate() {
+    let deep_tree = DeepTree::initialize();
+
+    let scanner = DirectoryScanner::new(
+        deep_tree.as_path(),
+        DirectoryScanOptions {
+            entry_filter: DirectoryEntryFilter::new().with_predicate(
+                |relative_path, _, metadata| {
+                    metadata.is_dir() || relative_path.extension().is_some_and(|ext| ext != "bin")
+                },
+            ),
+            ..Default::default()
+        },
+    )
+    .into_iter();
+
+
+    let scanned_paths = scanner
+        .map(|entry_result| entry_result.map(|entry| entry.into_path()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_path_list_fully_matches_set(
+        scanned_paths,
+        [
+            deep_tree.as_path(),
+            deep_tree.foo.as_path(),
+            deep_tree.foo.bar.as_path(),
+            deep_tree.foo.bar.hello.as_path(),
+            deep_tree.foo.bar.hello.world.as_path(),
+        ],
+    );
+
+
+    deep_tree.destroy();
+}
+
+
+
+#[test]
+fn scanner_respects_nested_ignore_files_when_enabled() {
+    let empty_tree = EmptyTree::initialize();
+
+    let write_file = |relative_path: &str, contents: &str| {
+        let file_path = empty_tree.child_path(relative_path);
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        std::fs::write(file_path, contents).unwrap();
+    };
+
+    write_file(".gitignore", "# Logs\n*.log\n!keep.log\nbuild/\n");
+    write_file("debug.log", "");
+    write_file("keep.log", "");
+    write_file("build/output.bin", "");
+    write_file("src/build", "");
+    write_file("src/.ignore", "secret.txt\n");
+    write_file("src/secret.txt", "");
+    write_file("src/main.rs", "");
+    write_file("other/secret.txt", "");
+
+
+    let scanner = DirectoryScanner::new(
+        empty_tree.as_path(),
+        DirectoryScanOptions {
+            yield_base_directory: false,
+            entry_filter: DirectoryEntryFilter::new().respect_ignore_files(),
+            ..Default::default()
+        },
+    )
+    .into_iter();
+
+    let scanned_paths = scanner
+        .map(|entry_result| entry_result.map(|entry| entry.into_path()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_path_list_fully_matches_set(
+        scanned_paths,
+        [
+            empty_tree.child_path(".gitignore"),
+            empty_tree.child_path("keep.log"),
+            empty_tree.child_path("src"),
+            // Not ignored, since `build/` only matches directories.
+            empty_tree.child_path("src/build"),
+            empty_tree.child_path("src/.ignore"),
+            empty_tree.child_path("src/main.rs"),
+            empty_tree.child_path("other"),
+            // Not ignored, since the `.ignore` file only applies to the `src` directory.
+            empty_tree.child_path("other/secret.txt"),
+        ],
+    );
+
+
+    empty_tree.destroy();
+}
+
+
+
+#[test]
+fn scanner_iter_does_not_descend_into_skipped_directory() {
+    let deep_tree = DeepTree::initialize();
+
+    let mut scanner =
+        DirectoryScanner::new(deep_tree.as_path(), DirectoryScanOptions::default()).into_iter();
+
+
+    let mut scanned_paths = Vec::new();
+
+    while let Some(entry_result) = scanner.next() {
+        let entry = entry_result.unwrap();
+
+        if paths_equal_no_unc(entry.path(), deep_tree.foo.as_path()) {
+            scanner.skip_current_directory();
+        }
+
+        scanned_paths.push(entry.into_path());
+    }
+
+
+    assert_path_list_fully_matches_set(
+        scanned_paths,
+        [
+            deep_tree.as_path(),
+            deep_tree.a_bin.as_path(),
+            deep_tree.foo.as_path(),
+        ],
+    );
+
+
+    deep_tree.destroy();
+}
+
+
+
+#[test]
+fn scanner_iter_yields_only_base_directory_if_skipped_immediately() {
+    let deep_tree = DeepTree::initialize();
+
+    let mut scanner =
+        DirectoryScanner::new(deep_tree.as_path(), DirectoryScanOptions::default()).into_iter();
+
+
+    let base_directory_entry = scanner.next().unwrap().unwrap();
+    assert!(paths_equal_no_unc(base_directory_entry.path(), deep_tree.as_path()));
+
+    scanner.skip_current_directory();
+
+    assert!(scanner.next().is_none());
+
+
+    deep_tree.destroy();
+}
+
+
+#[test]
+fn scanner_iter_sorts_entries_according_to_entry_ordering() {
+    let empty_tree = EmptyTree::initialize();
+
+    for file_name in ["file10", "file2", "b.txt", "a.txt"] {
+        std::fs::write(empty_tree.child_path(file_name), b"").unwrap();
+    }
+
+    std::fs::create_dir(empty_tree.child_path("z-directory")).unwrap();
+
+
+    let scan_file_names = |entry_ordering: ScanEntryOrdering| {
+        DirectoryScanner::new(
+            empty_tree.as_path(),
+            DirectoryScanOptions {
+                yield_base_directory: false,
+                entry_ordering,
+                ..Default::default()
+            },
+        )
+        .into_iter()
+        .map(|entry_result| {
+            let entry = entry_result.unwrap();
+            entry
+                .path()
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+    };
+
+
+    assert_eq!(
+        scan_file_names(ScanEntryOrdering::ByFileName),
+        ["a.txt", "b.txt", "file10", "file2", "z-directory"]
+    );
+
+    assert_eq!(
+        scan_file_names(ScanEntryOrdering::NaturalByFileName),
+        ["a.txt", "b.txt", "file2", "file10", "z-directory"]
+    );
+
+    assert_eq!(
+        scan_file_names(ScanEntryOrdering::DirectoriesFirst),
+        ["z-directory", "a.txt", "b.txt", "file10", "file2"]
+    );
+
+    assert_eq!(
+        scan_file_names(ScanEntryOrdering::custom(|first, second| {
+            second.path().file_name().cmp(&first.path().file_name())
+        })),
+        ["z-directory", "file2", "file10", "b.txt", "a.txt"]
+    );
+
+
+    empty_tree.destroy();
+}
+
+
+/// Asserts that every directory in `scanned_paths` is immediately followed (in pre-order)
+/// or preceded (in post-order) by all of its descendants, and by nothing else.
+fn assert_paths_are_in_depth_first_order(scanned_paths: &[PathBuf], order: DepthFirstOrder) {
+    for (index, path) in scanned_paths.iter().enumerate() {
+        let descendant_count = scanned_paths
+            .iter()
+            .filter(|other_path| *other_path != path && other_path.starts_with(path))
+            .count();
+
+        let subtree_block = match order {
+            DepthFirstOrder::PreOrder => &scanned_paths[index + 1..index + 1 + descendant_count],
+            DepthFirstOrder::PostOrder => &scanned_paths[index - descendant_count..index],
+        };
+
+        assert!(
+            subtree_block
+                .iter()
+                .all(|block_path| block_path.starts_with(path)),
+            "descendants of {} are not contiguous: {:?}",
+            path.display(),
+            scanned_paths
+        );
+    }
+}
+
+
+
+#[test]
+fn depth_first_scanner_yields_directories_before_their_contents_in_pre_order() {
+    let deep_tree = DeepTree::initialize();
+
+    let scanned_paths = DirectoryScanner::new(deep_tree.as_path(), DirectoryScanOptions::default())
+        .into_depth_first_iter(DepthFirstOrder::PreOrder)
+        .map(|entry_result| entry_result.map(|entry| entry.into_path()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+
+    assert_eq!(scanned_paths.first().unwrap(), deep_tree.as_path());
+    assert_paths_are_in_depth_first_order(&scanned_paths, DepthFirstOrder::PreOrder);
+
+    assert_path_list_fully_matches_set(
+        scanned_paths,
+        [
+            deep_tree.as_path(),
+            deep_tree.a_bin.as_path(),
+            deep_tree.foo.as_path(),
+            deep_tree.foo.b_bin.as_path(),
+            deep_tree.foo.bar.as_path(),
+            deep_tree.foo.bar.c_bin.as_path(),
+            deep_tree.foo.bar.hello.as_path(),
+            deep_tree.foo.bar.hello.world.as_path(),
+            deep_tree.foo.bar.hello.world.d_bin.as_path(),
+        ],
+    );
+
+
+    deep_tree.destroy();
+}
+
+
+
+#[test]
+fn depth_first_scanner_yields_directories_after_their_contents_in_post_order() {
+    let deep_tree = DeepTree::initialize();
+
+    let scanned_paths = DirectoryScanner::new(
+        deep_tree.as_path(),
+        DirectoryScanOptions {
+            maximum_scan_depth: DirectoryScanDepthLimit::Limited { maximum_depth: 1 },
+            ..Default::default()
+        },
+    )
+    .into_depth_first_iter(DepthFirstOrder::PostOrder)
+    .map(|entry_result| entry_result.map(|entry| entry.into_path()))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+
+
+    assert_eq!(scanned_paths.last().unwrap(), deep_tree.as_path());
+    assert_paths_are_in_depth_first_order(&scanned_paths, DepthFirstOrder::PostOrder);
+
+    assert_path_list_fully_matches_set(
+        scanned_paths,
+        [
+            deep_tree.as_path(),
+            deep_tree.a_bin.as_path(),
+            deep_tree.foo.as_path(),
+            deep_tree.foo.b_bin.as_path(),
+            deep_tree.foo.bar.as_path(),
+        ],
+    );
+
+
+    deep_tree.destroy();
+}
+
+
+
+#[test]
+fn depth_first_scanner_does_not_descend_into_skipped_directory() {
+    let deep_tree = DeepTree::initialize();
+
+    let mut scanner = DirectoryScanner::new(deep_tree.as_path(), DirectoryScanOptions::default())
+        .into_depth_first_iter(DepthFirstOrder::PreOrder);
+
+
+    let mut scanned_paths = Vec::new();
+
+    while let Some(entry_result) = scanner.next() {
+        let entry = entry_result.unwrap();
+
+        if paths_equal_no_unc(entry.path(), deep_tree.foo.bar.as_path()) {
+            scanner.skip_current_directory();
+        }
+
+        scanned_paths.push(entry.into_path());
+    }
+
+
+    assert_path_list_fully_matches_set(
+        scanned_paths,
+        [
+            deep_tree.as_path(),
+            deep_tree.a_bin.as_path(),
+            deep_tree.foo.as_path(),
+            deep_tree.foo.b_bin.as_path(),
+            deep_tree.foo.bar.as_path(),
+        ],
+    );
+
+
+    deep_tree.destroy();
+}
+
+
+
+#[test]
+fn depth_first_scanner_errors_on_symbolic_link_cycle() {
+    let cyclical_tree = SymlinkCycleTree::initialize();
+
+
+    let scanner = DirectoryScanner::new(
+        cyclical_tree.as_path(),
+        DirectoryScanOptions {
+            maximum_scan_depth: DirectoryScanDepthLimit::Unlimited,
+            follow_symbolic_links: true,
+            ..Default::default()
+        },
+    )
+    .into_depth_first_iter(DepthFirstOrder::PreOrder);
+
+
+    let cycle_errors = scanner
+        .filter_map(Result::err)
+        .map(|error| match error {
+            DirectoryScanError::SymlinkCycleEncountered { directory_path } => directory_path,
+            other_error => panic!("unexpected error: {}", other_error),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(cycle_errors.len(), 1);
+    assert!(paths_equal_no_unc(&cycle_errors[0], cyclical_tree.foo.as_path()));
+
+
+    cyclical_tree.destroy();
+}
@@ -0,0 +1,30 @@
+use fs_more::directory::{DirectoryEntryFilter, DirectorySizeOptions, PathMatcher};
+use fs_more_test_harness::{prelude::*, trees::structures::deep::DeepTree};
+
+
+
+#[test]
+fn directory_size_in_bytes_with_options_skips_excluded_subtrees() {
+    let deep_harness = DeepTree::initialize();
+
+
+    let full_size = fs_more::directory::directory_size_in_bytes(deep_harness.as_path()).unwrap();
+
+    let bar_subtree_size =
+        fs_more::directory::directory_size_in_bytes(deep_harness.foo.bar.as_path()).unwrap();
+
+
+    let size_without_bar = fs_more::directory::directory_size_in_bytes_with_options(
+        deep_harness.as_path(),
+        DirectorySizeOptions {
+            entry_filter: DirectoryEntryFilter::new()
+                .exclude(PathMatcher::new(["foo/bar/"]).unwrap()),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(size_without_bar, full_size - bar_subtree_size);
+
+
+    deep_harness.destroy();
+}
@@ -0,0 +1,251 @@
+use std::path::PathBuf;
+
+use fs_more::{
+    directory::{DirectorySyncComparison, DirectorySyncOptions},
+    error::SyncDirectoryError,
+};
+use fs_more_test_harness::{
+    collect_directory_statistics_via_scan,
+    prelude::*,
+    trees::structures::{deep::DeepTree, empty::EmptyTree},
+};
+
+
+
+#[test]
+fn sync_directory_copies_only_new_entries_and_leaves_unchanged_ones_untouched() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let deep_harness_stats = collect_directory_statistics_via_scan(deep_harness.as_path()).unwrap();
+    let total_entries = deep_harness_stats.total_files + deep_harness_stats.total_directories;
+
+
+    let first_sync = fs_more::directory::sync_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectorySyncOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(first_sync.added.len(), total_entries);
+    assert!(first_sync.updated.is_empty());
+    assert!(first_sync.deleted.is_empty());
+    assert!(first_sync.unchanged.is_empty());
+    assert_eq!(first_sync.total_bytes_copied, deep_harness_stats.total_bytes);
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    let second_sync = fs_more::directory::sync_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectorySyncOptions::default(),
+    )
+    .unwrap();
+
+    assert!(second_sync.added.is_empty());
+    assert!(second_sync.updated.is_empty());
+    assert!(second_sync.deleted.is_empty());
+    assert_eq!(second_sync.unchanged.len(), total_entries);
+    assert_eq!(second_sync.total_bytes_copied, 0);
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn sync_directory_updates_changed_files_and_deletes_extraneous_entries() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    fs_more::directory::sync_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectorySyncOptions::default(),
+    )
+    .unwrap();
+
+
+    std::fs::write(deep_harness.foo.b_bin.as_path(), b"changed contents").unwrap();
+
+    let extraneous_file_path = empty_harness.child_path("extraneous.txt");
+    std::fs::write(&extraneous_file_path, b"not in the source").unwrap();
+
+    let extraneous_directory_path = empty_harness.child_path("foo/extraneous");
+    std::fs::create_dir(&extraneous_directory_path).unwrap();
+    std::fs::write(extraneous_directory_path.join("nested.txt"), b"nested").unwrap();
+
+
+    let finished_sync = fs_more::directory::sync_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectorySyncOptions {
+            comparison: DirectorySyncComparison::Contents,
+            delete_extraneous_entries: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert!(finished_sync.added.is_empty());
+    assert_eq!(finished_sync.updated, vec![PathBuf::from("foo/b.bin")]);
+    assert_eq!(
+        finished_sync.deleted,
+        vec![
+            PathBuf::from("extraneous.txt"),
+            PathBuf::from("foo/extraneous")
+        ]
+    );
+    assert_eq!(finished_sync.total_bytes_copied, 16);
+
+    extraneous_file_path.assert_not_exists();
+    extraneous_directory_path.assert_not_exists();
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn sync_directory_errors_on_entry_of_different_type_unless_deletion_is_allowed() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let colliding_directory_path = empty_harness.child_path("a.bin");
+    std::fs::create_dir(&colliding_directory_path).unwrap();
+
+
+    let sync_result = fs_more::directory::sync_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectorySyncOptions::default(),
+    );
+
+    assert_matches!(
+        sync_result.unwrap_err(),
+        SyncDirectoryError::DestinationEntryTypeMismatch { path }
+        if path == colliding_directory_path
+    );
+
+    colliding_directory_path.assert_is_directory_and_empty();
+    empty_harness.child_path("foo").assert_not_exists();
+
+
+    let finished_sync = fs_more::directory::sync_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectorySyncOptions {
+            delete_extraneous_entries: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(finished_sync.updated, vec![PathBuf::from("a.bin")]);
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn sync_directory_replaces_destination_file_with_source_directory_when_deletion_is_allowed() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+
+
+    let colliding_file_path = empty_harness.child_path("foo");
+    std::fs::write(&colliding_file_path, b"not a directory").unwrap();
+
+
+    let finished_sync = fs_more::directory::sync_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectorySyncOptions {
+            delete_extraneous_entries: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(finished_sync.updated, vec![PathBuf::from("foo")]);
+    assert!(finished_sync.added.contains(&PathBuf::from("foo/b.bin")));
+    assert!(finished_sync.deleted.is_empty());
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+}
+
+
+
+#[test]
+fn sync_directory_replaces_destination_symlink_with_source_directory_without_following_it() {
+    let deep_harness = DeepTree::initialize();
+    let empty_harness = EmptyTree::initialize();
+    let symlink_target_harness = EmptyTree::initialize();
+
+
+    // The symbolic link target contains an up-to-date copy of a source file
+    // and an entry that is extraneous from the point of view of the source directory.
+    let symlink_target_file_path = symlink_target_harness.child_path("b.bin");
+    std::fs::copy(deep_harness.foo.b_bin.as_path(), &symlink_target_file_path).unwrap();
+
+    let symlink_target_extraneous_file_path = symlink_target_harness.child_path("extraneous.txt");
+    std::fs::write(&symlink_target_extraneous_file_path, b"not in the source").unwrap();
+
+    let colliding_symlink_path = empty_harness.child_path("foo");
+    colliding_symlink_path.symlink_to_directory(symlink_target_harness.as_path());
+
+
+    let finished_sync = fs_more::directory::sync_directory(
+        deep_harness.as_path(),
+        empty_harness.as_path(),
+        DirectorySyncOptions {
+            comparison: DirectorySyncComparison::Contents,
+            delete_extraneous_entries: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(finished_sync.updated, vec![PathBuf::from("foo")]);
+    assert!(finished_sync.added.contains(&PathBuf::from("foo/b.bin")));
+    assert!(finished_sync.deleted.is_empty());
+
+    empty_harness.assert_is_directory_and_fully_matches_secondary_directory(deep_harness.as_path());
+
+    // Nothing inside the symbolic link target must have been touched.
+    deep_harness
+        .foo
+        .b_bin
+        .assert_initial_state_matches_other_file(&symlink_target_file_path);
+    assert_eq!(
+        std::fs::read(&symlink_target_extraneous_file_path).unwrap(),
+        b"not in the source"
+    );
+
+
+    deep_harness.destroy();
+    empty_harness.destroy();
+    symlink_target_harness.destroy();
+}
@@ -1,6 +1,23 @@
+use std::{
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
 use fs_more::{
     error::FileError,
-    file::{CollidingFileBehaviour, FileCopyFinished, FileCopyOptions},
+    file::{
+        AlternativeFileNaming,
+        CollidingFileBehaviour,
+        CopyVerification,
+        DurabilityLevel,
+        FileCopyFinished,
+        FileCopyMethod,
+        FileCopyOptions,
+        Hasher,
+        MetadataPreservation,
+        ReflinkBehaviour,
+    },
 };
 use fs_more_test_harness::{prelude::*, trees::structures::simple::SimpleTree};
 
@@ -24,12 +41,13 @@ fn copy_file_creates_an_identical_copy() {
         &destination_file_path,
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
     assert_matches!(
         copy_result.unwrap(),
-        FileCopyFinished::Created { bytes_copied }
+        FileCopyFinished::Created { bytes_copied, .. }
         if bytes_copied == source_file_size_bytes
     );
 
@@ -56,6 +74,7 @@ fn copy_file_errors_when_trying_to_copy_into_self() {
         harness.yes.no_bin.as_path(),
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            ..Default::default()
         },
     );
 
@@ -110,6 +129,7 @@ fn copy_file_handles_case_insensitivity_properly() {
         &hello_world_uppercased_file_path,
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
@@ -186,6 +206,7 @@ fn copy_file_errors_when_trying_to_copy_into_self_even_when_more_complicated() {
         &destination_file_path,
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
@@ -193,7 +214,7 @@ fn copy_file_errors_when_trying_to_copy_into_self_even_when_more_complicated() {
     if is_fs_case_sensitive {
         assert_matches!(
             copy_result.unwrap(),
-            FileCopyFinished::Created { bytes_copied }
+            FileCopyFinished::Created { bytes_copied, .. }
             if bytes_copied == source_file_size_bytes
         );
     } else {
@@ -235,13 +256,14 @@ fn copy_file_overwrites_destination_file_when_behaviour_is_overwrite() {
         harness.yes.hello_world_txt.as_path(),
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            ..Default::default()
         },
     );
 
 
     assert_matches!(
         copy_result.unwrap(),
-        FileCopyFinished::Overwritten { bytes_copied }
+        FileCopyFinished::Overwritten { bytes_copied, .. }
         if bytes_copied == source_file_size_bytes
     );
 
@@ -269,6 +291,7 @@ fn copy_file_errors_on_existing_destination_file_when_behaviour_is_abort() {
         harness.yes.hello_world_txt.as_path(),
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
@@ -301,6 +324,7 @@ fn copy_file_skips_existing_destination_file_when_behaviour_is_skip() {
         harness.yes.no_bin.as_path(),
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Skip,
+            ..Default::default()
         },
     );
 
@@ -319,6 +343,197 @@ fn copy_file_skips_existing_destination_file_when_behaviour_is_skip() {
 }
 
 
+#[test]
+fn copy_file_overwrites_destination_file_only_if_source_is_newer_when_behaviour_is_overwrite_if_newer(
+) {
+    let harness = SimpleTree::initialize();
+
+    let options = FileCopyOptions {
+        colliding_file_behaviour: CollidingFileBehaviour::OverwriteIfNewer,
+        ..Default::default()
+    };
+
+
+    // Make sure the modification times differ, even on file systems with coarse timestamps.
+    std::thread::sleep(Duration::from_millis(50));
+    std::fs::write(harness.yes.hello_world_txt.as_path(), b"Newer destination.").unwrap();
+
+
+    let copy_result = fs_more::file::copy_file(
+        harness.yes.no_bin.as_path(),
+        harness.yes.hello_world_txt.as_path(),
+        options,
+    );
+
+    assert_matches!(copy_result.unwrap(), FileCopyFinished::Skipped);
+
+    assert_eq!(
+        std::fs::read(harness.yes.hello_world_txt.as_path()).unwrap(),
+        b"Newer destination."
+    );
+
+
+    std::thread::sleep(Duration::from_millis(50));
+    std::fs::write(harness.yes.no_bin.as_path(), b"Newer source.").unwrap();
+
+
+    let copy_result = fs_more::file::copy_file(
+        harness.yes.no_bin.as_path(),
+        harness.yes.hello_world_txt.as_path(),
+        options,
+    );
+
+    assert_matches!(
+        copy_result.unwrap(),
+        FileCopyFinished::Overwritten { bytes_copied, .. }
+        if bytes_copied == 13
+    );
+
+    assert_eq!(
+        std::fs::read(harness.yes.hello_world_txt.as_path()).unwrap(),
+        b"Newer source."
+    );
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_overwrites_destination_file_only_if_it_differs_when_behaviour_is_overwrite_if_different(
+) {
+    let harness = SimpleTree::initialize();
+
+    let source_file_size_bytes = harness.yes.no_bin.size_in_bytes();
+
+    let options = FileCopyOptions {
+        colliding_file_behaviour: CollidingFileBehaviour::OverwriteIfDifferent,
+        ..Default::default()
+    };
+
+
+    let first_copy_result = fs_more::file::copy_file(
+        harness.yes.no_bin.as_path(),
+        harness.yes.hello_world_txt.as_path(),
+        options,
+    );
+
+    assert_matches!(
+        first_copy_result.unwrap(),
+        FileCopyFinished::Overwritten { bytes_copied, .. }
+        if bytes_copied == source_file_size_bytes
+    );
+
+
+    let second_copy_result = fs_more::file::copy_file(
+        harness.yes.no_bin.as_path(),
+        harness.yes.hello_world_txt.as_path(),
+        options,
+    );
+
+    assert_matches!(second_copy_result.unwrap(), FileCopyFinished::Skipped);
+
+
+    harness.yes.no_bin.assert_unchanged_from_initial_state();
+    harness
+        .yes
+        .no_bin
+        .assert_initial_state_matches_other_file(harness.yes.hello_world_txt.as_path());
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_overwrites_destination_file_of_same_size_when_behaviour_is_overwrite_if_different() {
+    let harness = SimpleTree::initialize();
+
+    // Larger than a single comparison chunk, and only the last byte differs.
+    let source_contents = (0..200_000u32)
+        .map(|value| (value % 251) as u8)
+        .collect::<Vec<_>>();
+
+    let mut destination_contents = source_contents.clone();
+    *destination_contents.last_mut().unwrap() ^= 1;
+
+    let source_file_path = harness.child_path("source.bin");
+    std::fs::write(&source_file_path, &source_contents).unwrap();
+
+    let destination_file_path = harness.child_path("destination.bin");
+    std::fs::write(&destination_file_path, &destination_contents).unwrap();
+
+
+    let copy_result = fs_more::file::copy_file(
+        &source_file_path,
+        &destination_file_path,
+        FileCopyOptions {
+            colliding_file_behaviour: CollidingFileBehaviour::OverwriteIfDifferent,
+            ..Default::default()
+        },
+    );
+
+    assert_matches!(copy_result.unwrap(), FileCopyFinished::Overwritten { .. });
+
+    assert_eq!(std::fs::read(&destination_file_path).unwrap(), source_contents);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_copies_to_alternative_path_when_behaviour_is_keep_both() {
+    let harness = SimpleTree::initialize();
+
+    let options = FileCopyOptions {
+        colliding_file_behaviour: CollidingFileBehaviour::KeepBoth {
+            naming: AlternativeFileNaming::NumberInParentheses,
+        },
+        ..Default::default()
+    };
+
+
+    let first_alternative_file_path = harness.child_path("yes/hello-world (1).txt");
+    let second_alternative_file_path = harness.child_path("yes/hello-world (2).txt");
+
+    for expected_destination_file_path in
+        [&first_alternative_file_path, &second_alternative_file_path]
+    {
+        let copy_result = fs_more::file::copy_file(
+            harness.yes.no_bin.as_path(),
+            harness.yes.hello_world_txt.as_path(),
+            options,
+        );
+
+        assert_matches!(
+            copy_result.unwrap(),
+            FileCopyFinished::CreatedAtAlternativePath { destination_file_path, .. }
+            if &destination_file_path == expected_destination_file_path
+        );
+
+        harness
+            .yes
+            .no_bin
+            .assert_initial_state_matches_other_file(expected_destination_file_path);
+    }
+
+
+    harness.yes.no_bin.assert_unchanged_from_initial_state();
+    harness
+        .yes
+        .hello_world_txt
+        .assert_unchanged_from_initial_state();
+
+
+    harness.destroy();
+}
+
+
+
 /// Tests behaviour when copying "symlink to file A" to "A",
 /// even when the overwriting behaviour is set. This operation must fail.
 #[test]
@@ -336,6 +551,7 @@ fn copy_file_errors_when_source_path_is_symlink_to_destination_file() {
         harness.yes.hello_world_txt.as_path(),
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            ..Default::default()
         },
     );
 
@@ -376,13 +592,14 @@ fn copy_file_does_not_preserve_symlinks() {
         &copy_destination_path,
         FileCopyOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
 
     assert_matches!(
         copy_result.unwrap(),
-        FileCopyFinished::Created { bytes_copied }
+        FileCopyFinished::Created { bytes_copied, .. }
         if bytes_copied == symlink_destination_file_size_bytes
     );
 
@@ -399,3 +616,569 @@ fn copy_file_does_not_preserve_symlinks() {
 
     harness.destroy();
 }
+
+
+
+#[test]
+fn copy_file_with_reflink_attempt_creates_an_identical_copy() {
+    let harness = SimpleTree::initialize();
+
+
+    let destination_file_path = harness.child_path("test-file.txt");
+    destination_file_path.assert_not_exists();
+
+    let source_file_size_bytes = harness.yes.hello_world_txt.size_in_bytes();
+
+
+    let copy_result = fs_more::file::copy_file(
+        harness.yes.hello_world_txt.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            reflink_behaviour: ReflinkBehaviour::Auto,
+            ..Default::default()
+        },
+    );
+
+
+    // Whether the file can be cloned depends on the filesystem the tests are run on.
+    assert_matches!(
+        copy_result.unwrap(),
+        FileCopyFinished::Created { bytes_copied, method }
+        if bytes_copied == source_file_size_bytes
+            && matches!(method, FileCopyMethod::Reflink | FileCopyMethod::StandardLibrary)
+    );
+
+    harness
+        .yes
+        .hello_world_txt
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_reflink_always_leaves_existing_destination_untouched_if_unable_to_clone() {
+    let harness = SimpleTree::initialize();
+
+
+    let destination_file_path = harness.child_path("test-file.txt");
+    std::fs::write(&destination_file_path, b"this must not be lost").unwrap();
+
+
+    let copy_result = fs_more::file::copy_file(
+        harness.yes.hello_world_txt.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            reflink_behaviour: ReflinkBehaviour::Always,
+            ..Default::default()
+        },
+    );
+
+
+    // Whether the file can be cloned depends on the filesystem the tests are run on.
+    match copy_result {
+        Ok(finished_copy) => {
+            assert_matches!(
+                finished_copy,
+                FileCopyFinished::Overwritten {
+                    method: FileCopyMethod::Reflink,
+                    ..
+                }
+            );
+
+            harness
+                .yes
+                .hello_world_txt
+                .assert_initial_state_matches_other_file(&destination_file_path);
+        }
+        Err(error) => {
+            assert_matches!(
+                error,
+                FileError::UnableToReflink { path, .. }
+                if path == destination_file_path
+            );
+
+            assert_eq!(
+                std::fs::read(&destination_file_path).unwrap(),
+                b"this must not be lost"
+            );
+        }
+    }
+
+
+    harness.destroy();
+}
+
+
+
+
+#[test]
+fn copy_file_with_verification_creates_an_identical_copy() {
+    let harness = SimpleTree::initialize();
+
+
+    let destination_file_path = harness.child_path("test-file.bin");
+    destination_file_path.assert_not_exists();
+
+    let source_file_size_bytes = harness.yes.no_bin.size_in_bytes();
+
+
+    let finished_copy = fs_more::file::copy_file(
+        harness.yes.no_bin.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            verify: CopyVerification::ENABLED,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_matches!(
+        finished_copy,
+        FileCopyFinished::Created { bytes_copied, .. }
+        if bytes_copied == source_file_size_bytes
+    );
+
+    harness
+        .yes
+        .no_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+    harness.destroy();
+}
+
+
+
+/// Total number of bytes fed into all [`CountingHasher`]s.
+static BYTES_HASHED: AtomicU64 = AtomicU64::new(0);
+
+/// A hasher that counts the bytes fed into it (see [`BYTES_HASHED`]), and always returns the same digest.
+struct CountingHasher;
+
+impl Hasher for CountingHasher {
+    fn update(&mut self, data: &[u8]) {
+        BYTES_HASHED.fetch_add(data.len() as u64, Ordering::Relaxed);
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+fn new_counting_hasher() -> Box<dyn Hasher> {
+    Box::new(CountingHasher)
+}
+
+
+#[test]
+fn copy_file_with_verification_reads_source_file_only_once() {
+    let harness = SimpleTree::initialize();
+
+
+    let destination_file_path = harness.child_path("test-file.bin");
+    destination_file_path.assert_not_exists();
+
+    let source_file_size_bytes = harness.yes.no_bin.size_in_bytes();
+
+
+    fs_more::file::copy_file(
+        harness.yes.no_bin.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            verify: CopyVerification::with_hasher(new_counting_hasher),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    // The source file is hashed while copying, and the destination file is read back once.
+    assert_eq!(BYTES_HASHED.load(Ordering::Relaxed), source_file_size_bytes * 2);
+
+    harness
+        .yes
+        .no_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_preserves_timestamps_of_read_only_files_when_requested() {
+    let harness = SimpleTree::initialize();
+
+    let source_modified_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+
+    let source_file = std::fs::File::options()
+        .write(true)
+        .open(harness.yes.no_bin.as_path())
+        .unwrap();
+    source_file.set_modified(source_modified_at).unwrap();
+    drop(source_file);
+
+    let mut read_only_permissions = std::fs::metadata(harness.yes.no_bin.as_path())
+        .unwrap()
+        .permissions();
+    read_only_permissions.set_readonly(true);
+    std::fs::set_permissions(harness.yes.no_bin.as_path(), read_only_permissions).unwrap();
+
+
+    let destination_file_path = harness.child_path("test-file.bin");
+    destination_file_path.assert_not_exists();
+
+
+    fs_more::file::copy_file(
+        harness.yes.no_bin.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            metadata_preservation: MetadataPreservation {
+                permissions: true,
+                timestamps: true,
+                ownership: false,
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    let destination_metadata = std::fs::metadata(&destination_file_path).unwrap();
+
+    assert!(destination_metadata.permissions().readonly());
+    assert_eq!(destination_metadata.modified().unwrap(), source_modified_at);
+
+
+    // Read-only files can't be removed on some platforms.
+    for file_path in [
+        harness.yes.no_bin.as_path(),
+        destination_file_path.as_path(),
+    ] {
+        let mut permissions = std::fs::metadata(file_path).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        std::fs::set_permissions(file_path, permissions).unwrap();
+    }
+
+    harness.destroy();
+}
+
+
+
+#[test]
+#[cfg(target_os = "linux")]
+fn copy_file_copies_extended_attributes_in_included_namespaces_on_linux() {
+    use fs_more::file::{ExtendedAttributeFilter, ExtendedAttributeNamespace};
+    use fs_more_test_harness::{get_extended_attribute, set_extended_attribute_if_supported};
+
+
+    let harness = SimpleTree::initialize();
+
+    if !set_extended_attribute_if_supported(
+        harness.yes.hello_world_txt.as_path(),
+        "user.fs_more_test",
+        b"hello",
+    ) {
+        // The filesystem the tests are run on does not support extended attributes.
+        harness.destroy();
+        return;
+    }
+
+
+    let destination_file_path = harness.child_path("test-file.txt");
+    destination_file_path.assert_not_exists();
+
+
+    fs_more::file::copy_file(
+        harness.yes.hello_world_txt.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            extended_attributes: ExtendedAttributeFilter::NONE
+                .including(ExtendedAttributeNamespace::User),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    harness
+        .yes
+        .hello_world_txt
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+    assert_eq!(
+        get_extended_attribute(&destination_file_path, "user.fs_more_test").as_deref(),
+        Some(b"hello".as_slice())
+    );
+
+
+    harness.destroy();
+}
+
+
+
+
+#[test]
+#[cfg(target_os = "linux")]
+fn copy_file_does_not_copy_extended_attributes_in_excluded_namespaces_on_linux() {
+    use fs_more::file::{ExtendedAttributeFilter, ExtendedAttributeNamespace};
+    use fs_more_test_harness::{get_extended_attribute, set_extended_attribute_if_supported};
+
+
+    let harness = SimpleTree::initialize();
+
+    if !set_extended_attribute_if_supported(
+        harness.yes.hello_world_txt.as_path(),
+        "user.fs_more_test",
+        b"hello",
+    ) {
+        // The filesystem the tests are run on does not support extended attributes.
+        harness.destroy();
+        return;
+    }
+
+
+    let destination_file_path = harness.child_path("test-file.txt");
+    destination_file_path.assert_not_exists();
+
+
+    fs_more::file::copy_file(
+        harness.yes.hello_world_txt.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            extended_attributes: ExtendedAttributeFilter::ALL
+                .excluding(ExtendedAttributeNamespace::User),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    harness
+        .yes
+        .hello_world_txt
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+    assert_eq!(
+        get_extended_attribute(&destination_file_path, "user.fs_more_test"),
+        None
+    );
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+#[cfg(target_os = "linux")]
+fn copy_file_keeps_file_capabilities_when_preserving_ownership_on_linux() {
+    use fs_more::file::{ExtendedAttributeFilter, MetadataPreservation};
+    use fs_more_test_harness::{get_extended_attribute, set_extended_attribute_if_supported};
+
+
+    let harness = SimpleTree::initialize();
+
+    // A version 2 capability set with `CAP_NET_BIND_SERVICE` permitted and effective.
+    let capability: &[u8] = &[
+        0x01, 0x00, 0x00, 0x02, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    if !set_extended_attribute_if_supported(
+        harness.yes.hello_world_txt.as_path(),
+        "security.capability",
+        capability,
+    ) {
+        // Setting file capabilities requires privileges and filesystem support.
+        harness.destroy();
+        return;
+    }
+
+
+    let destination_file_path = harness.child_path("test-file.txt");
+    destination_file_path.assert_not_exists();
+
+
+    fs_more::file::copy_file(
+        harness.yes.hello_world_txt.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            extended_attributes: ExtendedAttributeFilter::ALL,
+            metadata_preservation: MetadataPreservation::ALL,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    // Changing the owner clears file capabilities, so they must be copied afterwards.
+    assert_eq!(
+        get_extended_attribute(&destination_file_path, "security.capability").as_deref(),
+        Some(capability)
+    );
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_atomically_overwrites_destination_file_without_leaving_temporary_files() {
+    let harness = SimpleTree::initialize();
+
+
+    let destination_file_path = harness.child_path("test-file.txt");
+    std::fs::write(&destination_file_path, b"previous contents").unwrap();
+
+    let source_file_size_bytes = harness.yes.hello_world_txt.size_in_bytes();
+
+
+    let finished_copy = fs_more::file::copy_file(
+        harness.yes.hello_world_txt.as_path(),
+        &destination_file_path,
+        FileCopyOptions {
+            colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            atomic: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+
+    assert_matches!(
+        finished_copy,
+        FileCopyFinished::Overwritten { bytes_copied, .. }
+        if bytes_copied == source_file_size_bytes
+    );
+
+    harness
+        .yes
+        .hello_world_txt
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+    harness
+        .child_path(".test-file.txt.fs-more-tmp")
+        .assert_not_exists();
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_durability_creates_an_identical_copy() {
+    let harness = SimpleTree::initialize();
+
+
+    for durability in [DurabilityLevel::Files, DurabilityLevel::FilesAndDirectories] {
+        for atomic in [false, true] {
+            let destination_file_path = harness.child_path("test-file.bin");
+            destination_file_path.assert_not_exists();
+
+
+            let finished_copy = fs_more::file::copy_file(
+                harness.yes.no_bin.as_path(),
+                &destination_file_path,
+                FileCopyOptions {
+                    atomic,
+                    durability,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            assert_matches!(finished_copy, FileCopyFinished::Created { .. });
+
+            harness
+                .yes
+                .no_bin
+                .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+            std::fs::remove_file(&destination_file_path).unwrap();
+        }
+    }
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_durability_syncs_read_only_files() {
+    let harness = SimpleTree::initialize();
+
+    let mut read_only_permissions = std::fs::metadata(harness.yes.no_bin.as_path())
+        .unwrap()
+        .permissions();
+    read_only_permissions.set_readonly(true);
+    std::fs::set_permissions(harness.yes.no_bin.as_path(), read_only_permissions).unwrap();
+
+    // Read-only files can't be removed on some platforms.
+    let make_writable = |file_path: &Path| {
+        let mut permissions = std::fs::metadata(file_path).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        std::fs::set_permissions(file_path, permissions).unwrap();
+    };
+
+
+    for (atomic, durability) in [
+        (true, DurabilityLevel::None),
+        (false, DurabilityLevel::Files),
+        (true, DurabilityLevel::FilesAndDirectories),
+    ] {
+        let destination_file_path = harness.child_path("test-file.bin");
+        destination_file_path.assert_not_exists();
+
+
+        let finished_copy = fs_more::file::copy_file(
+            harness.yes.no_bin.as_path(),
+            &destination_file_path,
+            FileCopyOptions {
+                metadata_preservation: MetadataPreservation {
+                    permissions: true,
+                    timestamps: true,
+                    ownership: false,
+                },
+                atomic,
+                durability,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_matches!(finished_copy, FileCopyFinished::Created { .. });
+
+        assert!(std::fs::metadata(&destination_file_path)
+            .unwrap()
+            .permissions()
+            .readonly());
+
+        harness
+            .yes
+            .no_bin
+            .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+        make_writable(&destination_file_path);
+        std::fs::remove_file(&destination_file_path).unwrap();
+    }
+
+
+    make_writable(harness.yes.no_bin.as_path());
+
+    harness.destroy();
+}
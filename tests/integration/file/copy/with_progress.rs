@@ -1,8 +1,31 @@
+use std::{
+    ops::ControlFlow,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
 use fs_more::{
     error::FileError,
-    file::{CollidingFileBehaviour, FileCopyFinished, FileCopyWithProgressOptions},
+    file::{
+        CollidingFileBehaviour,
+        CopyVerification,
+        DurabilityLevel,
+        FileCopyFinished,
+        FileCopyMethod,
+        FileCopyWithProgressOptions,
+        FileProgressPhase,
+        Hasher,
+        MetadataPreservation,
+        ReflinkBehaviour,
+        ResumeBehaviour,
+        SparseFileBehaviour,
+    },
+    ProgressAction,
+};
+use fs_more_test_harness::{
+    prelude::*,
+    trees::structures::{deep::DeepTree, empty::EmptyTree, simple::SimpleTree},
 };
-use fs_more_test_harness::{prelude::*, trees::structures::simple::SimpleTree};
 
 
 
@@ -58,7 +81,7 @@ fn copy_file_with_progress_creates_an_identical_copy_and_reports_sensible_progre
 
     assert_matches!(
         copy_result.unwrap(),
-        FileCopyFinished::Created { bytes_copied }
+        FileCopyFinished::Created { bytes_copied, .. }
         if bytes_copied == expected_final_file_size_bytes
     );
 
@@ -169,7 +192,7 @@ fn copy_file_with_progress_handles_case_insensitivity_properly() {
     if is_fs_case_sensitive {
         assert_matches!(
             copy_result.unwrap(),
-            FileCopyFinished::Created { bytes_copied }
+            FileCopyFinished::Created { bytes_copied, .. }
             if bytes_copied == source_file_size_bytes
         );
     } else {
@@ -246,7 +269,7 @@ fn copy_file_with_progress_errors_when_trying_to_copy_into_self_even_when_more_c
     if is_fs_case_sensitive {
         assert_matches!(
             copy_result.unwrap(),
-            FileCopyFinished::Created { bytes_copied }
+            FileCopyFinished::Created { bytes_copied, .. }
             if bytes_copied == source_file_size_bytes
         );
     } else {
@@ -296,7 +319,7 @@ fn copy_file_with_progress_overwrites_destination_file_when_behaviour_is_overwri
 
     assert_matches!(
         copy_result.unwrap(),
-        FileCopyFinished::Overwritten { bytes_copied }
+        FileCopyFinished::Overwritten { bytes_copied, .. }
         if bytes_copied == source_file_size_bytes
     );
 
@@ -451,7 +474,7 @@ fn copy_file_with_progress_does_not_preserve_symlinks() {
 
     assert_matches!(
         copy_result.unwrap(),
-        FileCopyFinished::Created { bytes_copied }
+        FileCopyFinished::Created { bytes_copied, .. }
         if bytes_copied == symlink_destination_file_size_bytes
     );
 
@@ -469,3 +492,891 @@ fn copy_file_with_progress_does_not_preserve_symlinks() {
 
     harness.destroy();
 }
+
+
+
+#[test]
+fn copy_file_with_progress_stops_and_removes_partial_file_when_cancelled() {
+    let harness = DeepTree::initialize();
+
+
+    let source_file_path = harness.foo.bar.hello.world.d_bin.as_path();
+    let source_file_size_bytes = harness.foo.bar.hello.world.d_bin.size_in_bytes();
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let mut has_cancelled = false;
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            read_buffer_size: 1024,
+            write_buffer_size: 1024,
+            progress_update_byte_interval: 1024,
+            ..Default::default()
+        },
+        |progress| {
+            if has_cancelled {
+                panic!("invalid progress report: progress was reported after cancelling");
+            }
+
+            if progress.bytes_finished >= 8192 {
+                has_cancelled = true;
+                ProgressAction::Abort
+            } else {
+                ProgressAction::Continue
+            }
+        },
+    );
+
+
+    assert_matches!(
+        copy_result.unwrap_err(),
+        FileError::Cancelled { bytes_finished, bytes_total }
+        if bytes_finished >= 8192
+            && bytes_finished < source_file_size_bytes
+            && bytes_total == source_file_size_bytes
+    );
+
+
+    destination_file_path.assert_not_exists();
+
+    harness
+        .foo
+        .bar
+        .hello
+        .world
+        .d_bin
+        .assert_unchanged_from_initial_state();
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_can_be_cancelled_with_control_flow() {
+    let harness = DeepTree::initialize();
+
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        harness.foo.bar.hello.world.d_bin.as_path(),
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            read_buffer_size: 1024,
+            write_buffer_size: 1024,
+            progress_update_byte_interval: 1024,
+            ..Default::default()
+        },
+        |_| ControlFlow::Break(()),
+    );
+
+
+    assert_matches!(copy_result.unwrap_err(), FileError::Cancelled { .. });
+
+    destination_file_path.assert_not_exists();
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+#[cfg(target_os = "linux")]
+fn copy_file_with_progress_copies_in_kernel_and_reports_progress_between_chunks_on_linux() {
+    let harness = DeepTree::initialize();
+
+
+    let source_file_size_bytes = harness.foo.bar.hello.world.d_bin.size_in_bytes();
+    assert!(source_file_size_bytes > 4096);
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let mut number_of_progress_reports = 0;
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        harness.foo.bar.hello.world.d_bin.as_path(),
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            progress_update_byte_interval: 1024,
+            ..Default::default()
+        },
+        |progress| {
+            assert!(progress.bytes_finished <= progress.bytes_total);
+            number_of_progress_reports += 1;
+        },
+    );
+
+
+    assert_matches!(
+        copy_result.unwrap(),
+        FileCopyFinished::Created { bytes_copied, method }
+        if bytes_copied == source_file_size_bytes
+            && matches!(method, FileCopyMethod::CopyFileRange | FileCopyMethod::SendFile)
+    );
+
+    assert!(number_of_progress_reports > 1);
+
+    harness
+        .foo
+        .bar
+        .hello
+        .world
+        .d_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_falls_back_to_regular_copy_when_reflink_is_unsupported() {
+    let harness = DeepTree::initialize();
+
+
+    let source_file_size_bytes = harness.foo.bar.hello.world.d_bin.size_in_bytes();
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let mut last_progress_report = None;
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        harness.foo.bar.hello.world.d_bin.as_path(),
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            reflink_behaviour: ReflinkBehaviour::Auto,
+            ..Default::default()
+        },
+        |progress| {
+            last_progress_report = Some(progress.clone());
+        },
+    );
+
+
+    // Whether the file can be cloned depends on the filesystem the tests are run on.
+    assert_matches!(
+        copy_result.unwrap(),
+        FileCopyFinished::Created { bytes_copied, .. }
+        if bytes_copied == source_file_size_bytes
+    );
+
+    let last_progress_report = last_progress_report.unwrap();
+    assert_eq!(last_progress_report.bytes_finished, source_file_size_bytes);
+    assert_eq!(last_progress_report.bytes_total, source_file_size_bytes);
+
+    harness
+        .foo
+        .bar
+        .hello
+        .world
+        .d_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_with_reflink_always_does_not_leave_new_destination_if_unable_to_clone() {
+    let harness = DeepTree::initialize();
+
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        harness.foo.bar.hello.world.d_bin.as_path(),
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            reflink_behaviour: ReflinkBehaviour::Always,
+            ..Default::default()
+        },
+        |_| {},
+    );
+
+
+    // Whether the file can be cloned depends on the filesystem the tests are run on.
+    match copy_result {
+        Ok(finished_copy) => {
+            assert_matches!(
+                finished_copy,
+                FileCopyFinished::Created {
+                    method: FileCopyMethod::Reflink,
+                    ..
+                }
+            );
+
+            harness
+                .foo
+                .bar
+                .hello
+                .world
+                .d_bin
+                .assert_initial_state_matches_other_file(&destination_file_path);
+        }
+        Err(error) => {
+            assert_matches!(error, FileError::UnableToReflink { .. });
+
+            destination_file_path.assert_not_exists();
+        }
+    }
+
+
+    harness.destroy();
+}
+
+
+
+
+#[test]
+#[cfg(unix)]
+fn copy_file_with_progress_preserves_permissions_and_timestamps_when_requested_on_unix() {
+    use std::os::unix::fs::PermissionsExt;
+
+
+    let harness = SimpleTree::initialize();
+
+    std::fs::set_permissions(harness.yes.no_bin.as_path(), std::fs::Permissions::from_mode(0o750))
+        .unwrap();
+
+
+    let destination_file_path = harness.child_path("test-file.bin");
+    destination_file_path.assert_not_exists();
+
+
+    fs_more::file::copy_file_with_progress(
+        harness.yes.no_bin.as_path(),
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            metadata_preservation: MetadataPreservation::ALL,
+            ..Default::default()
+        },
+        |_| {},
+    )
+    .unwrap();
+
+
+    harness
+        .yes
+        .no_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+    let source_metadata = std::fs::metadata(harness.yes.no_bin.as_path()).unwrap();
+    let destination_metadata = std::fs::metadata(&destination_file_path).unwrap();
+
+    assert_eq!(destination_metadata.permissions().mode() & 0o7777, 0o750);
+    assert_eq!(
+        destination_metadata.modified().unwrap(),
+        source_metadata.modified().unwrap()
+    );
+    assert_eq!(
+        destination_metadata.accessed().unwrap(),
+        source_metadata.accessed().unwrap()
+    );
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+#[cfg(target_os = "linux")]
+fn copy_file_with_progress_preserves_holes_in_sparse_files_on_linux() {
+    use std::{
+        io::{Seek, SeekFrom, Write},
+        os::unix::fs::MetadataExt,
+    };
+
+
+    let harness = EmptyTree::initialize();
+
+    let source_file_path = harness.child_path("sparse.img");
+    let source_file_size_bytes = 16 * 1024 * 1024;
+
+    {
+        let mut source_file = std::fs::File::create(&source_file_path).unwrap();
+        source_file.set_len(source_file_size_bytes).unwrap();
+        source_file.seek(SeekFrom::Start(8 * 1024 * 1024)).unwrap();
+        source_file.write_all(&[42u8; 4096]).unwrap();
+    }
+
+
+    let destination_file_path = harness.child_path("sparse-copy.img");
+    destination_file_path.assert_not_exists();
+
+
+    let mut last_progress_report = None;
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        &source_file_path,
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            sparse_behaviour: SparseFileBehaviour::Auto,
+            ..Default::default()
+        },
+        |progress| {
+            last_progress_report = Some(progress.clone());
+        },
+    );
+
+
+    assert_matches!(
+        copy_result.unwrap(),
+        FileCopyFinished::Created {
+            bytes_copied,
+            method: FileCopyMethod::Sparse
+        }
+        if bytes_copied == source_file_size_bytes
+    );
+
+    let last_progress_report = last_progress_report.unwrap();
+    assert_eq!(last_progress_report.bytes_finished, source_file_size_bytes);
+    assert!(last_progress_report.bytes_written < source_file_size_bytes);
+
+    assert_eq!(
+        std::fs::read(&source_file_path).unwrap(),
+        std::fs::read(&destination_file_path).unwrap()
+    );
+
+    // `st_blocks` is always counted in 512-byte units.
+    let destination_metadata = std::fs::metadata(&destination_file_path).unwrap();
+    assert_eq!(destination_metadata.len(), source_file_size_bytes);
+    assert!(destination_metadata.blocks() * 512 < source_file_size_bytes / 2);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_turns_zero_blocks_into_holes_when_sparse_behaviour_is_always() {
+    let harness = EmptyTree::initialize();
+
+    let source_file_path = harness.child_path("zeroes.bin");
+
+    let mut source_file_contents = vec![0u8; 1024 * 1024];
+    source_file_contents[512 * 1024..512 * 1024 + 4096].fill(42);
+    std::fs::write(&source_file_path, &source_file_contents).unwrap();
+
+
+    let destination_file_path = harness.child_path("zeroes-copy.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let mut last_progress_report = None;
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        &source_file_path,
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            sparse_behaviour: SparseFileBehaviour::Always,
+            ..Default::default()
+        },
+        |progress| {
+            last_progress_report = Some(progress.clone());
+        },
+    );
+
+
+    assert_matches!(
+        copy_result.unwrap(),
+        FileCopyFinished::Created {
+            bytes_copied: 1048576,
+            method: FileCopyMethod::Sparse
+        }
+    );
+
+    let last_progress_report = last_progress_report.unwrap();
+    assert_eq!(last_progress_report.bytes_finished, 1024 * 1024);
+    assert_eq!(last_progress_report.bytes_written, 4096);
+
+    assert_eq!(std::fs::read(&destination_file_path).unwrap(), source_file_contents);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_reports_verification_as_a_separate_phase() {
+    let harness = DeepTree::initialize();
+
+
+    let source_file_size_bytes = harness.foo.bar.hello.world.d_bin.size_in_bytes();
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let mut progress_reports = Vec::new();
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        harness.foo.bar.hello.world.d_bin.as_path(),
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            progress_update_byte_interval: 1024,
+            verify: CopyVerification::ENABLED.reporting_progress(true),
+            ..Default::default()
+        },
+        |progress| {
+            progress_reports.push(progress.clone());
+        },
+    );
+
+
+    // The file must be hashed while being copied, so the kernel can not copy it.
+    assert_matches!(
+        copy_result.unwrap(),
+        FileCopyFinished::Created {
+            bytes_copied,
+            method: FileCopyMethod::Buffered
+        }
+        if bytes_copied == source_file_size_bytes
+    );
+
+    let first_verification_report_index = progress_reports
+        .iter()
+        .position(|progress| progress.phase != FileProgressPhase::Copying)
+        .unwrap();
+
+    assert!(first_verification_report_index > 0);
+    assert!(progress_reports[first_verification_report_index..]
+        .iter()
+        .all(
+            |progress| matches!(progress.phase, FileProgressPhase::Verifying { .. })
+                && progress.bytes_finished == source_file_size_bytes
+        ));
+
+    assert_eq!(
+        progress_reports.last().unwrap().phase,
+        FileProgressPhase::Verifying {
+            bytes_verified: source_file_size_bytes
+        }
+    );
+
+    harness
+        .foo
+        .bar
+        .hello
+        .world
+        .d_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+    harness.destroy();
+}
+
+
+
+/// A hasher that returns a different digest for every instance, making every verification fail.
+struct MismatchingHasher {
+    instance_index: usize,
+}
+
+impl Hasher for MismatchingHasher {
+    fn update(&mut self, _data: &[u8]) {}
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        self.instance_index.to_le_bytes().to_vec()
+    }
+}
+
+fn new_mismatching_hasher() -> Box<dyn Hasher> {
+    static NEXT_INSTANCE_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+    Box::new(MismatchingHasher {
+        instance_index: NEXT_INSTANCE_INDEX.fetch_add(1, Ordering::Relaxed),
+    })
+}
+
+
+#[test]
+fn copy_file_with_progress_removes_destination_file_when_verification_fails() {
+    let harness = SimpleTree::initialize();
+
+
+    let destination_file_path = harness.child_path("test-file.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        harness.yes.no_bin.as_path(),
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            verify: CopyVerification::with_hasher(new_mismatching_hasher),
+            ..Default::default()
+        },
+        |_| {},
+    );
+
+
+    assert_matches!(
+        copy_result.unwrap_err(),
+        FileError::VerificationFailed { path }
+        if paths_equal_no_unc(&path, &destination_file_path)
+    );
+
+    destination_file_path.assert_not_exists();
+    harness.yes.no_bin.assert_unchanged_from_initial_state();
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_resumes_a_cancelled_copy() {
+    let harness = DeepTree::initialize();
+
+
+    let source_file_path = harness.foo.bar.hello.world.d_bin.as_path();
+    let source_file_size_bytes = harness.foo.bar.hello.world.d_bin.size_in_bytes();
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+    destination_file_path.assert_not_exists();
+
+
+    let options = FileCopyWithProgressOptions {
+        colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+        read_buffer_size: 1024,
+        write_buffer_size: 1024,
+        progress_update_byte_interval: 1024,
+        resume_behaviour: ResumeBehaviour::VerifySampled,
+        ..Default::default()
+    };
+
+    let cancelled_copy_result = fs_more::file::copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        options,
+        |progress| {
+            if progress.bytes_finished >= 2048 {
+                ProgressAction::Abort
+            } else {
+                ProgressAction::Continue
+            }
+        },
+    );
+
+    assert_matches!(cancelled_copy_result.unwrap_err(), FileError::Cancelled { .. });
+
+
+    // The partially copied file must be kept, so that the copy can be resumed.
+    destination_file_path.assert_is_file_and_not_symlink();
+
+    let partially_copied_bytes = destination_file_path.metadata().unwrap().len();
+    assert!(partially_copied_bytes > 0 && partially_copied_bytes < source_file_size_bytes);
+
+
+    let mut progress_reports = Vec::new();
+
+    let resumed_copy_result = fs_more::file::copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        options,
+        |progress| {
+            progress_reports.push(progress.clone());
+        },
+    );
+
+    assert_matches!(
+        resumed_copy_result.unwrap(),
+        FileCopyFinished::Resumed {
+            bytes_copied,
+            resumed_from,
+            method: FileCopyMethod::Buffered
+        }
+        if resumed_from == partially_copied_bytes
+            && bytes_copied == source_file_size_bytes - partially_copied_bytes
+    );
+
+    assert!(progress_reports
+        .iter()
+        .all(|progress| progress.bytes_finished >= partially_copied_bytes));
+
+    let last_progress_report = progress_reports.last().unwrap();
+    assert_eq!(last_progress_report.bytes_finished, source_file_size_bytes);
+    assert_eq!(
+        last_progress_report.bytes_written,
+        source_file_size_bytes - partially_copied_bytes
+    );
+
+
+    harness
+        .foo
+        .bar
+        .hello
+        .world
+        .d_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_does_not_resume_destination_file_when_aborting_on_collision() {
+    let harness = DeepTree::initialize();
+
+
+    let source_file_path = harness.foo.bar.hello.world.d_bin.as_path();
+
+    // An empty file is always a valid prefix of the source file.
+    let destination_file_path = harness.child_path("d-copy.bin");
+    std::fs::write(&destination_file_path, b"").unwrap();
+
+
+    let copy_result = fs_more::file::copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            resume_behaviour: ResumeBehaviour::VerifyFull,
+            ..Default::default()
+        },
+        |_| {},
+    );
+
+    assert_matches!(
+        copy_result.unwrap_err(),
+        FileError::DestinationPathAlreadyExists { path }
+        if paths_equal_no_unc(&path, &destination_file_path)
+    );
+
+    assert!(std::fs::read(&destination_file_path).unwrap().is_empty());
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_does_not_resume_destination_file_that_does_not_match_source() {
+    let harness = DeepTree::initialize();
+
+
+    let source_file_path = harness.foo.bar.hello.world.d_bin.as_path();
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+
+    // Same size as a partial copy, but one of the bytes differs from the source file.
+    let mut mismatching_contents = std::fs::read(source_file_path).unwrap();
+    mismatching_contents.truncate(1000);
+    mismatching_contents[500] = !mismatching_contents[500];
+
+    std::fs::write(&destination_file_path, &mismatching_contents).unwrap();
+
+
+    let aborted_copy_result = fs_more::file::copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            resume_behaviour: ResumeBehaviour::VerifyFull,
+            ..Default::default()
+        },
+        |_| {},
+    );
+
+    assert_matches!(
+        aborted_copy_result.unwrap_err(),
+        FileError::DestinationPathAlreadyExists { path }
+        if paths_equal_no_unc(&path, &destination_file_path)
+    );
+
+    assert_eq!(std::fs::read(&destination_file_path).unwrap(), mismatching_contents);
+
+
+    let overwriting_copy_result = fs_more::file::copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        FileCopyWithProgressOptions {
+            colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            resume_behaviour: ResumeBehaviour::VerifyFull,
+            ..Default::default()
+        },
+        |_| {},
+    );
+
+    assert_matches!(overwriting_copy_result.unwrap(), FileCopyFinished::Overwritten { .. });
+
+    harness
+        .foo
+        .bar
+        .hello
+        .world
+        .d_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_atomically_leaves_destination_file_untouched_until_finished() {
+    let harness = DeepTree::initialize();
+
+
+    let source_file_path = harness.foo.bar.hello.world.d_bin.as_path();
+
+    let destination_file_path = harness.child_path("d-copy.bin");
+    std::fs::write(&destination_file_path, b"previous contents").unwrap();
+
+    let temporary_file_path = harness.child_path(".d-copy.bin.fs-more-tmp");
+
+
+    let options = FileCopyWithProgressOptions {
+        colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+        read_buffer_size: 1024,
+        write_buffer_size: 1024,
+        progress_update_byte_interval: 1024,
+        resume_behaviour: ResumeBehaviour::VerifySampled,
+        atomic: true,
+        ..Default::default()
+    };
+
+    let cancelled_copy_result = fs_more::file::copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        options,
+        |progress| {
+            // While the file is being copied, only the temporary file is being written to.
+            assert_eq!(std::fs::read(&destination_file_path).unwrap(), b"previous contents");
+            temporary_file_path.assert_is_file_and_not_symlink();
+
+            if progress.bytes_finished >= 2048 {
+                ProgressAction::Abort
+            } else {
+                ProgressAction::Continue
+            }
+        },
+    );
+
+    assert_matches!(cancelled_copy_result.unwrap_err(), FileError::Cancelled { .. });
+
+    assert_eq!(std::fs::read(&destination_file_path).unwrap(), b"previous contents");
+    temporary_file_path.assert_not_exists();
+
+
+    // Atomic copies are never resumed, even though the resume behaviour would allow it.
+    let finished_copy = fs_more::file::copy_file_with_progress(
+        source_file_path,
+        &destination_file_path,
+        options,
+        |_| {},
+    )
+    .unwrap();
+
+    assert_matches!(finished_copy, FileCopyFinished::Overwritten { .. });
+
+    harness
+        .foo
+        .bar
+        .hello
+        .world
+        .d_bin
+        .assert_initial_state_matches_other_file(&destination_file_path);
+
+    temporary_file_path.assert_not_exists();
+
+
+    harness.destroy();
+}
+
+
+
+#[test]
+fn copy_file_with_progress_with_durability_syncs_read_only_files() {
+    let harness = SimpleTree::initialize();
+
+    let mut read_only_permissions = std::fs::metadata(harness.yes.no_bin.as_path())
+        .unwrap()
+        .permissions();
+    read_only_permissions.set_readonly(true);
+    std::fs::set_permissions(harness.yes.no_bin.as_path(), read_only_permissions).unwrap();
+
+    // Read-only files can't be removed on some platforms.
+    let make_writable = |file_path: &Path| {
+        let mut permissions = std::fs::metadata(file_path).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        std::fs::set_permissions(file_path, permissions).unwrap();
+    };
+
+
+    for (atomic, durability) in [
+        (true, DurabilityLevel::None),
+        (false, DurabilityLevel::Files),
+        (true, DurabilityLevel::FilesAndDirectories),
+    ] {
+        let destination_file_path = harness.child_path("test-file.bin");
+        destination_file_path.assert_not_exists();
+
+
+        let finished_copy = fs_more::file::copy_file_with_progress(
+            harness.yes.no_bin.as_path(),
+            &destination_file_path,
+            FileCopyWithProgressOptions {
+                metadata_preservation: MetadataPreservation {
+                    permissions: true,
+                    timestamps: true,
+                    ownership: false,
+                },
+                atomic,
+                durability,
+                ..Default::default()
+            },
+            |_| {},
+        )
+        .unwrap();
+
+        assert_matches!(finished_copy, FileCopyFinished::Created { .. });
+
+        assert!(std::fs::metadata(&destination_file_path)
+            .unwrap()
+            .permissions()
+            .readonly());
+
+        harness
+            .yes
+            .no_bin
+            .assert_initial_state_matches_other_file(&destination_file_path);
+
+
+        make_writable(&destination_file_path);
+        std::fs::remove_file(&destination_file_path).unwrap();
+    }
+
+
+    make_writable(harness.yes.no_bin.as_path());
+
+    harness.destroy();
+}
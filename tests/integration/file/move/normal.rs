@@ -1,6 +1,13 @@
 use fs_more::{
     error::FileError,
-    file::{CollidingFileBehaviour, FileMoveFinished, FileMoveMethod, FileMoveOptions},
+    file::{
+        AlternativeFileNaming,
+        CollidingFileBehaviour,
+        DurabilityLevel,
+        FileMoveFinished,
+        FileMoveMethod,
+        FileMoveOptions,
+    },
 };
 use fs_more_test_harness::{prelude::*, trees::structures::simple::SimpleTree};
 
@@ -22,6 +29,7 @@ fn move_file_correctly_moves_the_file() {
         &destination_file_path,
         FileMoveOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
@@ -51,6 +59,7 @@ fn move_file_errors_when_trying_to_copy_into_self() {
         harness.yes.no_bin.as_path(),
         FileMoveOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
@@ -82,6 +91,7 @@ fn move_file_errors_when_trying_to_copy_into_self_even_with_overwrite_behaviour(
         harness.yes.no_bin.as_path(),
         FileMoveOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            ..Default::default()
         },
     );
 
@@ -139,6 +149,7 @@ fn move_file_errors_when_trying_to_copy_into_case_insensitive_self() {
         &hello_world_uppercased_file_path,
         FileMoveOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
@@ -202,6 +213,7 @@ fn move_file_errors_when_source_is_symlink_to_destination() {
         harness.yes.hello_world_txt.as_path(),
         FileMoveOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            ..Default::default()
         },
     );
 
@@ -235,6 +247,7 @@ fn move_file_overwrites_destination_file_when_behaviour_is_overwrite() {
         harness.yes.no_bin.as_path(),
         FileMoveOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Overwrite,
+            ..Default::default()
         },
     );
 
@@ -254,6 +267,45 @@ fn move_file_overwrites_destination_file_when_behaviour_is_overwrite() {
 
 
 
+#[test]
+fn move_file_moves_to_alternative_path_when_behaviour_is_keep_both() {
+    let harness = SimpleTree::initialize();
+
+    let captured_source_file = harness.yes.hello_world_txt.capture_with_content();
+    let captured_destination_file = harness.yes.no_bin.capture_with_content();
+    let source_file_size = harness.yes.hello_world_txt.size_in_bytes();
+
+    let expected_destination_file_path = harness.child_path("yes/no-1.bin");
+
+
+    let move_result = fs_more::file::move_file(
+        harness.yes.hello_world_txt.as_path(),
+        harness.yes.no_bin.as_path(),
+        FileMoveOptions {
+            colliding_file_behaviour: CollidingFileBehaviour::KeepBoth {
+                naming: AlternativeFileNaming::NumberAfterHyphen,
+            },
+            ..Default::default()
+        },
+    );
+
+    assert_matches!(
+        move_result.unwrap(),
+        FileMoveFinished::CreatedAtAlternativePath { destination_file_path, bytes_copied, .. }
+        if destination_file_path == expected_destination_file_path && bytes_copied == source_file_size
+    );
+
+
+    harness.yes.hello_world_txt.assert_not_exists();
+    captured_source_file.assert_captured_state_matches_other_file(&expected_destination_file_path);
+    captured_destination_file.assert_unchanged();
+
+
+    harness.destroy();
+}
+
+
+
 #[test]
 fn move_file_errors_on_existing_destination_file_when_behaviour_is_abort() {
     let harness = SimpleTree::initialize();
@@ -267,6 +319,7 @@ fn move_file_errors_on_existing_destination_file_when_behaviour_is_abort() {
         harness.yes.no_bin.as_path(),
         FileMoveOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     );
 
@@ -313,6 +366,7 @@ fn move_file_may_preserve_symlinks_when_moving_by_rename() {
         &symlink_moved_file_path,
         FileMoveOptions {
             colliding_file_behaviour: CollidingFileBehaviour::Abort,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -347,3 +401,35 @@ fn move_file_may_preserve_symlinks_when_moving_by_rename() {
 
     harness.destroy();
 }
+
+
+
+#[test]
+fn move_file_with_durability_moves_the_file() {
+    let harness = SimpleTree::initialize();
+
+    let destination_file_path = harness.child_path("destination-file.txt");
+    destination_file_path.assert_not_exists();
+
+    let captured_before_move = harness.yes.no_bin.capture_with_content();
+
+
+    let finished_move = fs_more::file::move_file(
+        harness.yes.no_bin.as_path(),
+        &destination_file_path,
+        FileMoveOptions {
+            durability: DurabilityLevel::FilesAndDirectories,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_matches!(finished_move, FileMoveFinished::Created { .. });
+
+
+    harness.yes.no_bin.assert_not_exists();
+    captured_before_move.assert_captured_state_matches_other_file(destination_file_path.as_path());
+
+
+    harness.destroy();
+}